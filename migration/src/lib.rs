@@ -1,6 +1,26 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250820_010340_baseline;
+mod m20261019_090000_create_attachment;
+mod m20261019_100000_create_bill_template;
+mod m20261019_110000_create_notification_outbox;
+mod m20261019_120000_add_tenant_notification_channels;
+mod m20261019_130000_create_webhooks;
+mod m20261019_140000_create_audit_log;
+mod m20261019_150000_add_soft_delete;
+mod m20261019_160000_add_row_version;
+mod m20261019_170000_bill_status_and_notes;
+mod m20261019_180000_updated_at_triggers;
+mod m20261019_190000_money_minor_units;
+mod m20261019_200000_charge_catalog;
+mod m20261019_210000_bill_discounts;
+mod m20261019_220000_bill_paid_at;
+mod m20261019_230000_tenancy_history;
+mod m20261019_240000_consumption_analytics;
+mod m20261019_250000_multi_property;
+mod m20261019_260000_utc_timestamps;
+mod m20261019_270000_bill_amount_paid;
+mod m20261019_280000_legacy_receipt_attachments;
 
 pub struct Migrator;
 
@@ -9,6 +29,26 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20250820_010340_baseline::Migration),
+            Box::new(m20261019_090000_create_attachment::Migration),
+            Box::new(m20261019_100000_create_bill_template::Migration),
+            Box::new(m20261019_110000_create_notification_outbox::Migration),
+            Box::new(m20261019_120000_add_tenant_notification_channels::Migration),
+            Box::new(m20261019_130000_create_webhooks::Migration),
+            Box::new(m20261019_140000_create_audit_log::Migration),
+            Box::new(m20261019_150000_add_soft_delete::Migration),
+            Box::new(m20261019_160000_add_row_version::Migration),
+            Box::new(m20261019_170000_bill_status_and_notes::Migration),
+            Box::new(m20261019_180000_updated_at_triggers::Migration),
+            Box::new(m20261019_190000_money_minor_units::Migration),
+            Box::new(m20261019_200000_charge_catalog::Migration),
+            Box::new(m20261019_210000_bill_discounts::Migration),
+            Box::new(m20261019_220000_bill_paid_at::Migration),
+            Box::new(m20261019_230000_tenancy_history::Migration),
+            Box::new(m20261019_240000_consumption_analytics::Migration),
+            Box::new(m20261019_250000_multi_property::Migration),
+            Box::new(m20261019_260000_utc_timestamps::Migration),
+            Box::new(m20261019_270000_bill_amount_paid::Migration),
+            Box::new(m20261019_280000_legacy_receipt_attachments::Migration),
         ]
     }
 }
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Attachments
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attachment::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Attachment::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Attachment::ObjectKey).text().not_null())
+                    .col(ColumnDef::new(Attachment::OwnerType).text().not_null())
+                    .col(ColumnDef::new(Attachment::OwnerId).integer().not_null())
+                    .col(ColumnDef::new(Attachment::FileName).text())
+                    .col(ColumnDef::new(Attachment::ContentType).text().not_null())
+                    .col(ColumnDef::new(Attachment::SizeBytes).big_integer().not_null())
+                    .col(ColumnDef::new(Attachment::Checksum).text().not_null())
+                    .col(ColumnDef::new(Attachment::UploadedBy).text())
+                    .col(ColumnDef::new(Attachment::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Attachment::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .index(Index::create().unique().name("attachments_objectKey_key").col(Attachment::ObjectKey))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("attachments_owner_idx")
+                    .table(Attachment::Table)
+                    .col(Attachment::OwnerType)
+                    .col(Attachment::OwnerId)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Attachment::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Attachment { Table, Id, ObjectKey, OwnerType, OwnerId, FileName, ContentType, SizeBytes, Checksum, UploadedBy, CreatedAt, UpdatedAt }
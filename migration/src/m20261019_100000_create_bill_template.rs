@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bill Templates
+        manager
+            .create_table(
+                Table::create()
+                    .table(BillTemplate::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(BillTemplate::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(BillTemplate::Format).text().not_null())
+                    .col(ColumnDef::new(BillTemplate::Locale).text().not_null())
+                    .col(ColumnDef::new(BillTemplate::Body).text().not_null())
+                    .col(ColumnDef::new(BillTemplate::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(BillTemplate::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .index(Index::create().unique().name("bill_templates_format_locale_key").col(BillTemplate::Format).col(BillTemplate::Locale))
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(BillTemplate::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum BillTemplate { Table, Id, Format, Locale, Body, CreatedAt, UpdatedAt }
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Tenant email address
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .add_column(ColumnDef::new(Tenant::Email).text())
+                    .to_owned()
+            ).await?;
+
+        // Notification Outbox
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationOutbox::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(NotificationOutbox::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(NotificationOutbox::Event).text().not_null())
+                    .col(ColumnDef::new(NotificationOutbox::Channel).text().not_null())
+                    .col(ColumnDef::new(NotificationOutbox::Recipient).text().not_null())
+                    .col(ColumnDef::new(NotificationOutbox::TenantId).integer())
+                    .col(ColumnDef::new(NotificationOutbox::BillId).integer())
+                    .col(ColumnDef::new(NotificationOutbox::Subject).text())
+                    .col(ColumnDef::new(NotificationOutbox::Body).text())
+                    .col(ColumnDef::new(NotificationOutbox::Status).text().not_null().default("pending"))
+                    .col(ColumnDef::new(NotificationOutbox::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(NotificationOutbox::LastError).text())
+                    .col(ColumnDef::new(NotificationOutbox::NextAttemptAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(NotificationOutbox::SentAt).timestamp())
+                    .col(ColumnDef::new(NotificationOutbox::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(NotificationOutbox::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(NotificationOutbox::Table, NotificationOutbox::TenantId).to(Tenant::Table, Tenant::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::SetNull))
+                    .foreign_key(ForeignKey::create().from(NotificationOutbox::Table, NotificationOutbox::BillId).to(Bill::Table, Bill::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::SetNull))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("notification_outbox_status_next_attempt_idx")
+                    .table(NotificationOutbox::Table)
+                    .col(NotificationOutbox::Status)
+                    .col(NotificationOutbox::NextAttemptAt)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(NotificationOutbox::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .drop_column(Tenant::Email)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Tenant { Table, Id, Email }
+
+#[derive(DeriveIden)]
+enum Bill { Table, Id }
+
+#[derive(DeriveIden)]
+enum NotificationOutbox { Table, Id, Event, Channel, Recipient, TenantId, BillId, Subject, Body, Status, Attempts, LastError, NextAttemptAt, SentAt, CreatedAt, UpdatedAt }
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Tenant contact details and channel preferences
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .add_column(ColumnDef::new(Tenant::PhoneNumber).text())
+                    .add_column(ColumnDef::new(Tenant::ChatId).text())
+                    .add_column(ColumnDef::new(Tenant::NotificationChannels).text().not_null().default("email"))
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .drop_column(Tenant::PhoneNumber)
+                    .drop_column(Tenant::ChatId)
+                    .drop_column(Tenant::NotificationChannels)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Tenant { Table, PhoneNumber, ChatId, NotificationChannels }
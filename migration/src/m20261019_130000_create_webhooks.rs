@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Webhook Endpoint
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookEndpoint::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(WebhookEndpoint::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(WebhookEndpoint::Url).text().not_null())
+                    .col(ColumnDef::new(WebhookEndpoint::Secret).text().not_null())
+                    .col(ColumnDef::new(WebhookEndpoint::Events).text().not_null())
+                    .col(ColumnDef::new(WebhookEndpoint::Description).text())
+                    .col(ColumnDef::new(WebhookEndpoint::IsActive).boolean().not_null().default(true))
+                    .col(ColumnDef::new(WebhookEndpoint::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(WebhookEndpoint::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        // Webhook Delivery
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(WebhookDelivery::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(WebhookDelivery::EndpointId).integer().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Event).text().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Payload).json_binary().not_null())
+                    .col(ColumnDef::new(WebhookDelivery::Status).text().not_null().default("pending"))
+                    .col(ColumnDef::new(WebhookDelivery::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(WebhookDelivery::ResponseStatus).integer())
+                    .col(ColumnDef::new(WebhookDelivery::LastError).text())
+                    .col(ColumnDef::new(WebhookDelivery::NextAttemptAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(WebhookDelivery::DeliveredAt).timestamp())
+                    .col(ColumnDef::new(WebhookDelivery::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(WebhookDelivery::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(WebhookDelivery::Table, WebhookDelivery::EndpointId).to(WebhookEndpoint::Table, WebhookEndpoint::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("webhook_delivery_status_next_attempt_idx")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAt)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("webhook_delivery_endpoint_idx")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::EndpointId)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(WebhookDelivery::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(WebhookEndpoint::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum WebhookEndpoint { Table, Id, Url, Secret, Events, Description, IsActive, CreatedAt, UpdatedAt }
+
+#[derive(DeriveIden)]
+enum WebhookDelivery { Table, Id, EndpointId, Event, Payload, Status, Attempts, ResponseStatus, LastError, NextAttemptAt, DeliveredAt, CreatedAt, UpdatedAt }
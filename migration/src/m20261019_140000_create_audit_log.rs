@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Audit Log
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AuditLog::Id).big_integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(AuditLog::Actor).text().not_null())
+                    .col(ColumnDef::new(AuditLog::Entity).text().not_null())
+                    .col(ColumnDef::new(AuditLog::EntityId).integer().not_null())
+                    .col(ColumnDef::new(AuditLog::Action).text().not_null())
+                    .col(ColumnDef::new(AuditLog::Changes).json_binary().not_null())
+                    .col(ColumnDef::new(AuditLog::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("audit_log_entity_idx")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::Entity)
+                    .col(AuditLog::EntityId)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("audit_log_actor_idx")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::Actor)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuditLog::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum AuditLog { Table, Id, Actor, Entity, EntityId, Action, Changes, CreatedAt }
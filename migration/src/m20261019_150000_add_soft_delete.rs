@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [SoftDelete::Room, SoftDelete::Tenant, SoftDelete::ElectricityReading, SoftDelete::Bill] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(SoftDelete::DeletedAt).timestamp())
+                        .to_owned()
+                ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [SoftDelete::Room, SoftDelete::Tenant, SoftDelete::ElectricityReading, SoftDelete::Bill] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(SoftDelete::DeletedAt)
+                        .to_owned()
+                ).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden, Clone, Copy)]
+enum SoftDelete { Room, Tenant, ElectricityReading, Bill, DeletedAt }
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [RowVersion::Room, RowVersion::Tenant, RowVersion::Bill] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(ColumnDef::new(RowVersion::Version).integer().not_null().default(1))
+                        .to_owned()
+                ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [RowVersion::Room, RowVersion::Tenant, RowVersion::Bill] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(RowVersion::Version)
+                        .to_owned()
+                ).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden, Clone, Copy)]
+enum RowVersion { Room, Tenant, Bill, Version }
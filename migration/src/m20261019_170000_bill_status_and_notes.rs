@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bill lifecycle: draft -> issued -> paid | void
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .add_column(ColumnDef::new(Bill::Status).text().not_null().default("draft"))
+                    .add_column(ColumnDef::new(Bill::IssuedAt).timestamp())
+                    .to_owned()
+            ).await?;
+
+        // Existing bills have already been sent to tenants
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE bill SET status = CASE WHEN paid THEN 'paid' ELSE 'issued' END, issued_at = created_at",
+            )
+            .await?;
+
+        // Credit and debit notes
+        manager
+            .create_table(
+                Table::create()
+                    .table(BillNote::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(BillNote::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(BillNote::BillId).integer().not_null())
+                    .col(ColumnDef::new(BillNote::TenantId).integer().not_null())
+                    .col(ColumnDef::new(BillNote::Kind).text().not_null())
+                    .col(ColumnDef::new(BillNote::Amount).integer().not_null())
+                    .col(ColumnDef::new(BillNote::Reason).text().not_null())
+                    .col(ColumnDef::new(BillNote::CreatedBy).text().not_null())
+                    .col(ColumnDef::new(BillNote::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(BillNote::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(BillNote::Table, BillNote::BillId).to(Bill::Table, Bill::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(BillNote::Table, BillNote::TenantId).to(Tenant::Table, Tenant::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("bill_note_tenant_idx")
+                    .table(BillNote::Table)
+                    .col(BillNote::TenantId)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("bill_note_bill_idx")
+                    .table(BillNote::Table)
+                    .col(BillNote::BillId)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(BillNote::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .drop_column(Bill::Status)
+                    .drop_column(Bill::IssuedAt)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Bill { Table, Id, Status, IssuedAt }
+
+#[derive(DeriveIden)]
+enum Tenant { Table, Id }
+
+#[derive(DeriveIden)]
+enum BillNote { Table, Id, BillId, TenantId, Kind, Amount, Reason, CreatedBy, CreatedAt, UpdatedAt }
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables with an `updated_at` column. A trigger rather than `before_save` so that
+/// `update_many` and guarded `Entity::update` calls are covered too.
+const TABLES: &[&str] = &[
+    "room",
+    "tenant",
+    "electricity_reading",
+    "bill",
+    "additional_charge",
+    "bill_note",
+    "attachment",
+    "bill_template",
+    "notification_outbox",
+    "webhook_endpoint",
+    "webhook_delivery",
+];
+
+/// Tables served by the incremental sync endpoint
+const SYNCED_TABLES: &[&str] = &["room", "tenant", "electricity_reading", "bill", "bill_note"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "CREATE OR REPLACE FUNCTION set_updated_at() RETURNS trigger AS $$
+             BEGIN
+                 NEW.updated_at := now() AT TIME ZONE 'UTC';
+                 RETURN NEW;
+             END;
+             $$ LANGUAGE plpgsql",
+        )
+        .await?;
+
+        for table in TABLES {
+            db.execute_unprepared(&format!(
+                "DROP TRIGGER IF EXISTS {table}_set_updated_at ON {table};
+                 CREATE TRIGGER {table}_set_updated_at BEFORE UPDATE ON {table}
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()"
+            ))
+            .await?;
+        }
+
+        for table in SYNCED_TABLES {
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS {table}_updated_at_idx ON {table} (updated_at)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for table in SYNCED_TABLES {
+            db.execute_unprepared(&format!("DROP INDEX IF EXISTS {table}_updated_at_idx")).await?;
+        }
+        for table in TABLES {
+            db.execute_unprepared(&format!("DROP TRIGGER IF EXISTS {table}_set_updated_at ON {table}")).await?;
+        }
+        db.execute_unprepared("DROP FUNCTION IF EXISTS set_updated_at()").await?;
+
+        Ok(())
+    }
+}
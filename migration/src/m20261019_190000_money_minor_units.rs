@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Money columns converted from whole pesos in `integer` to minor units in `bigint`
+const AMOUNT_COLUMNS: &[(&str, &str)] = &[
+    ("room", "rent"),
+    ("bill", "room_charges"),
+    ("bill", "electric_charges"),
+    ("bill", "total_amount"),
+    ("additional_charge", "amount"),
+    ("bill_note", "amount"),
+];
+
+/// Tables that record the currency of their amounts
+const CURRENCY_TABLES: &[&str] = &["room", "bill"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, column) in AMOUNT_COLUMNS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ALTER COLUMN {column} TYPE bigint USING {column}::bigint * 100"
+            ))
+            .await?;
+        }
+
+        for table in CURRENCY_TABLES {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ADD COLUMN currency text NOT NULL DEFAULT 'PHP'"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for table in CURRENCY_TABLES {
+            db.execute_unprepared(&format!("ALTER TABLE {table} DROP COLUMN currency")).await?;
+        }
+
+        for (table, column) in AMOUNT_COLUMNS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ALTER COLUMN {column} TYPE integer USING ({column} / 100)::integer"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
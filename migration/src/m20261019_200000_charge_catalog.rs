@@ -0,0 +1,145 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Catalog seeded on install: (code, name)
+const DEFAULT_CHARGE_TYPES: &[(&str, &str)] = &[
+    ("water", "Water"),
+    ("internet", "Internet"),
+    ("maintenance", "Maintenance"),
+    ("penalty", "Penalty"),
+    ("discount", "Discount"),
+    ("parking", "Parking"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Charge catalog
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChargeType::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChargeType::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChargeType::Code).text().not_null().unique_key())
+                    .col(ColumnDef::new(ChargeType::Name).text().not_null())
+                    .col(ColumnDef::new(ChargeType::DefaultAmount).big_integer().not_null().default(0))
+                    .col(ColumnDef::new(ChargeType::IsActive).boolean().not_null().default(true))
+                    .col(ColumnDef::new(ChargeType::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(ChargeType::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        let mut seed = Query::insert()
+            .into_table(ChargeType::Table)
+            .columns([ChargeType::Code, ChargeType::Name])
+            .to_owned();
+        for (code, name) in DEFAULT_CHARGE_TYPES {
+            seed.values_panic([(*code).into(), (*name).into()]);
+        }
+        manager.exec_stmt(seed).await?;
+
+        // Category of each bill line; existing free-text lines stay uncategorized
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AdditionalCharge::Table)
+                    .add_column(ColumnDef::new(AdditionalCharge::ChargeTypeId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("additional_charge_charge_type_fk")
+                            .from_tbl(AdditionalCharge::Table)
+                            .from_col(AdditionalCharge::ChargeTypeId)
+                            .to_tbl(ChargeType::Table)
+                            .to_col(ChargeType::Id)
+                            .on_update(ForeignKeyAction::Cascade)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned()
+            ).await?;
+
+        // Charges added to every new bill of a tenant, or of whoever rents a room
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecurringCharge::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RecurringCharge::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(RecurringCharge::ChargeTypeId).integer().not_null())
+                    .col(ColumnDef::new(RecurringCharge::TenantId).integer())
+                    .col(ColumnDef::new(RecurringCharge::RoomId).integer())
+                    .col(ColumnDef::new(RecurringCharge::Amount).big_integer())
+                    .col(ColumnDef::new(RecurringCharge::Description).text())
+                    .col(ColumnDef::new(RecurringCharge::IsActive).boolean().not_null().default(true))
+                    .col(ColumnDef::new(RecurringCharge::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(RecurringCharge::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(RecurringCharge::Table, RecurringCharge::ChargeTypeId).to(ChargeType::Table, ChargeType::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(RecurringCharge::Table, RecurringCharge::TenantId).to(Tenant::Table, Tenant::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(RecurringCharge::Table, RecurringCharge::RoomId).to(Room::Table, Room::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .check(Expr::col(RecurringCharge::TenantId).is_not_null().or(Expr::col(RecurringCharge::RoomId).is_not_null()))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("recurring_charge_tenant_idx")
+                    .table(RecurringCharge::Table)
+                    .col(RecurringCharge::TenantId)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("recurring_charge_room_idx")
+                    .table(RecurringCharge::Table)
+                    .col(RecurringCharge::RoomId)
+                    .to_owned()
+            ).await?;
+
+        let db = manager.get_connection();
+        for table in ["charge_type", "recurring_charge"] {
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER {table}_set_updated_at BEFORE UPDATE ON {table}
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RecurringCharge::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AdditionalCharge::Table)
+                    .drop_foreign_key(Alias::new("additional_charge_charge_type_fk"))
+                    .drop_column(AdditionalCharge::ChargeTypeId)
+                    .to_owned()
+            ).await?;
+        manager.drop_table(Table::drop().table(ChargeType::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum ChargeType { Table, Id, Code, Name, DefaultAmount, IsActive, CreatedAt, UpdatedAt }
+
+#[derive(DeriveIden)]
+enum AdditionalCharge { Table, ChargeTypeId }
+
+#[derive(DeriveIden)]
+enum RecurringCharge { Table, Id, ChargeTypeId, TenantId, RoomId, Amount, Description, IsActive, CreatedAt, UpdatedAt }
+
+#[derive(DeriveIden)]
+enum Tenant { Table, Id }
+
+#[derive(DeriveIden)]
+enum Room { Table, Id }
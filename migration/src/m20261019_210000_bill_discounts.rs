@@ -0,0 +1,98 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BillDiscount::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(BillDiscount::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(BillDiscount::BillId).integer().not_null())
+                    .col(ColumnDef::new(BillDiscount::Kind).text().not_null())
+                    .col(ColumnDef::new(BillDiscount::Value).big_integer().not_null())
+                    .col(ColumnDef::new(BillDiscount::Amount).big_integer().not_null())
+                    .col(ColumnDef::new(BillDiscount::Reason).text().not_null())
+                    .col(ColumnDef::new(BillDiscount::ApprovedBy).text().not_null())
+                    .col(ColumnDef::new(BillDiscount::Position).integer().not_null().default(0))
+                    .col(ColumnDef::new(BillDiscount::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(BillDiscount::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(BillDiscount::Table, BillDiscount::BillId).to(Bill::Table, Bill::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("bill_discount_bill_idx")
+                    .table(BillDiscount::Table)
+                    .col(BillDiscount::BillId)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .add_column(ColumnDef::new(Bill::DiscountAmount).big_integer().not_null().default(0))
+                    .to_owned()
+            ).await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE TRIGGER bill_discount_set_updated_at BEFORE UPDATE ON bill_discount
+             FOR EACH ROW EXECUTE FUNCTION set_updated_at()",
+        )
+        .await?;
+
+        // Negative charges were the old way of giving a discount; turn them into discount lines.
+        // Bill totals already include them, so only the breakdown changes.
+        db.execute_unprepared(
+            "INSERT INTO bill_discount (bill_id, kind, value, amount, reason, approved_by, position)
+             SELECT bill_id, 'fixed', -amount, -amount, description, 'migration', 0
+             FROM additional_charge WHERE amount < 0;
+             UPDATE bill SET discount_amount = d.total
+             FROM (SELECT bill_id, SUM(amount)::bigint AS total FROM bill_discount GROUP BY bill_id) d
+             WHERE bill.id = d.bill_id;
+             DELETE FROM additional_charge WHERE amount < 0",
+        )
+        .await?;
+
+        // Discounts are explicit lines now, not a charge category
+        db.execute_unprepared("UPDATE charge_type SET is_active = false WHERE code = 'discount'").await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO additional_charge (bill_id, amount, description)
+             SELECT bill_id, -amount, reason FROM bill_discount;
+             UPDATE charge_type SET is_active = true WHERE code = 'discount'",
+        )
+        .await?;
+
+        manager.drop_table(Table::drop().table(BillDiscount::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .drop_column(Bill::DiscountAmount)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Bill { Table, Id, DiscountAmount }
+
+#[derive(DeriveIden)]
+enum BillDiscount { Table, Id, BillId, Kind, Value, Amount, Reason, ApprovedBy, Position, CreatedAt, UpdatedAt }
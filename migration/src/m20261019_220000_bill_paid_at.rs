@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .add_column(ColumnDef::new(Bill::PaidAt).timestamp().null())
+                    .to_owned()
+            ).await?;
+
+        // Best guess for bills paid before the column existed: the last time the row changed
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE bill SET paid_at = updated_at WHERE status = 'paid'")
+            .await?;
+
+        // Reports group and filter by these
+        manager
+            .create_index(
+                Index::create()
+                    .name("bill_issued_at_idx")
+                    .table(Bill::Table)
+                    .col(Bill::IssuedAt)
+                    .to_owned()
+            ).await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("bill_paid_at_idx")
+                    .table(Bill::Table)
+                    .col(Bill::PaidAt)
+                    .to_owned()
+            ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("bill_paid_at_idx").table(Bill::Table).to_owned()).await?;
+        manager.drop_index(Index::drop().name("bill_issued_at_idx").table(Bill::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .drop_column(Bill::PaidAt)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Bill { Table, IssuedAt, PaidAt }
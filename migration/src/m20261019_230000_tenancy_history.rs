@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tenancy::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Tenancy::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Tenancy::TenantId).integer().not_null())
+                    .col(ColumnDef::new(Tenancy::RoomId).integer().not_null())
+                    .col(ColumnDef::new(Tenancy::StartDate).timestamp().not_null())
+                    .col(ColumnDef::new(Tenancy::EndDate).timestamp().null())
+                    .col(ColumnDef::new(Tenancy::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Tenancy::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(Tenancy::Table, Tenancy::TenantId).to(Tenant::Table, Tenant::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(Tenancy::Table, Tenancy::RoomId).to(Room::Table, Room::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("tenancy_room_idx")
+                    .table(Tenancy::Table)
+                    .col(Tenancy::RoomId)
+                    .col(Tenancy::StartDate)
+                    .to_owned()
+            ).await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("tenancy_tenant_idx")
+                    .table(Tenancy::Table)
+                    .col(Tenancy::TenantId)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Room::Table)
+                    .add_column(ColumnDef::new(Room::UnderMaintenance).boolean().not_null().default(false))
+                    .to_owned()
+            ).await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE TRIGGER tenancy_set_updated_at BEFORE UPDATE ON tenancy
+             FOR EACH ROW EXECUTE FUNCTION set_updated_at()",
+        )
+        .await?;
+
+        // One tenancy per existing tenant; tenants that already left are assumed to have
+        // moved out when their row last changed.
+        db.execute_unprepared(
+            "INSERT INTO tenancy (tenant_id, room_id, start_date, end_date)
+             SELECT id, room_id, join_date,
+                    CASE WHEN is_active AND deleted_at IS NULL THEN NULL
+                         ELSE GREATEST(join_date, COALESCE(deleted_at, updated_at)) END
+             FROM tenant",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Tenancy::Table).to_owned()).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Room::Table)
+                    .drop_column(Room::UnderMaintenance)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Room { Table, Id, UnderMaintenance }
+
+#[derive(DeriveIden)]
+enum Tenant { Table, Id }
+
+#[derive(DeriveIden)]
+enum Tenancy { Table, Id, TenantId, RoomId, StartDate, EndDate, CreatedAt, UpdatedAt }
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ElectricityReading::Table)
+                    .add_column(ColumnDef::new(ElectricityReading::Flagged).boolean().not_null().default(false))
+                    .add_column(ColumnDef::new(ElectricityReading::FlagReason).text().null())
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("electricity_reading_room_created_idx")
+                    .table(ElectricityReading::Table)
+                    .col(ElectricityReading::RoomId)
+                    .col(ElectricityReading::CreatedAt)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MasterMeterReading::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MasterMeterReading::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(MasterMeterReading::ReadingDate).timestamp().not_null())
+                    .col(ColumnDef::new(MasterMeterReading::PrevReading).integer().not_null())
+                    .col(ColumnDef::new(MasterMeterReading::CurrReading).integer().not_null())
+                    .col(ColumnDef::new(MasterMeterReading::Consumption).integer().not_null())
+                    .col(ColumnDef::new(MasterMeterReading::Notes).text().null())
+                    .col(ColumnDef::new(MasterMeterReading::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(MasterMeterReading::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE TRIGGER master_meter_reading_set_updated_at BEFORE UPDATE ON master_meter_reading
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MasterMeterReading::Table).to_owned()).await?;
+        manager
+            .drop_index(Index::drop().name("electricity_reading_room_created_idx").table(ElectricityReading::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ElectricityReading::Table)
+                    .drop_column(ElectricityReading::Flagged)
+                    .drop_column(ElectricityReading::FlagReason)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum ElectricityReading { Table, RoomId, CreatedAt, Flagged, FlagReason }
+
+#[derive(DeriveIden)]
+enum MasterMeterReading { Table, Id, ReadingDate, PrevReading, CurrReading, Consumption, Notes, CreatedAt, UpdatedAt }
@@ -0,0 +1,131 @@
+use sea_orm_migration::prelude::*;
+use sea_query::Expr;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Property::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Property::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Property::Name).text().not_null().unique_key())
+                    .col(ColumnDef::new(Property::Address).text().null())
+                    .col(ColumnDef::new(Property::Contact).text().null())
+                    .col(ColumnDef::new(Property::LogoUrl).text().null())
+                    .col(ColumnDef::new(Property::AccentColor).text().null())
+                    .col(ColumnDef::new(Property::ElectricRate).big_integer().null())
+                    .col(ColumnDef::new(Property::BillDueDays).integer().null())
+                    .col(ColumnDef::new(Property::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Property::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminUser::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AdminUser::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(AdminUser::Username).text().not_null().unique_key())
+                    .col(ColumnDef::new(AdminUser::PasswordHash).text().not_null())
+                    .col(ColumnDef::new(AdminUser::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(AdminUser::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminUserProperty::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AdminUserProperty::AdminUserId).integer().not_null())
+                    .col(ColumnDef::new(AdminUserProperty::PropertyId).integer().not_null())
+                    .primary_key(Index::create().col(AdminUserProperty::AdminUserId).col(AdminUserProperty::PropertyId))
+                    .foreign_key(ForeignKey::create().from(AdminUserProperty::Table, AdminUserProperty::AdminUserId).to(AdminUser::Table, AdminUser::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(AdminUserProperty::Table, AdminUserProperty::PropertyId).to(Property::Table, Property::Id).on_update(ForeignKeyAction::Cascade).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned()
+            ).await?;
+
+        let db = manager.get_connection();
+        for table in ["property", "admin_user"] {
+            db.execute_unprepared(&format!(
+                "CREATE TRIGGER {t}_set_updated_at BEFORE UPDATE ON {t}
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()",
+                t = table
+            ))
+            .await?;
+        }
+
+        // Everything that exists today is the one building
+        db.execute_unprepared("INSERT INTO property (name) VALUES ('M18 Residences')").await?;
+        for table in ["room", "tenant", "master_meter_reading"] {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {t} ADD COLUMN property_id integer REFERENCES property(id) ON UPDATE CASCADE ON DELETE RESTRICT;
+                 UPDATE {t} SET property_id = (SELECT MIN(id) FROM property);
+                 ALTER TABLE {t} ALTER COLUMN property_id SET NOT NULL;",
+                t = table
+            ))
+            .await?;
+        }
+
+        // Names only need to be unique within a property
+        db.execute_unprepared(
+            "ALTER TABLE room DROP CONSTRAINT rooms_name_key;
+             ALTER TABLE room ADD CONSTRAINT room_property_name_key UNIQUE (property_id, name);
+             ALTER TABLE tenant DROP CONSTRAINT tenants_name_key;
+             ALTER TABLE tenant ADD CONSTRAINT tenant_property_name_key UNIQUE (property_id, name);",
+        )
+        .await?;
+
+        // A tenant belongs to the property of their room, whichever way the row is written
+        db.execute_unprepared(
+            "CREATE FUNCTION tenant_set_property() RETURNS trigger AS $$
+             BEGIN
+                 NEW.property_id := (SELECT property_id FROM room WHERE id = NEW.room_id);
+                 RETURN NEW;
+             END;
+             $$ LANGUAGE plpgsql;
+             CREATE TRIGGER tenant_set_property BEFORE INSERT OR UPDATE OF room_id ON tenant
+             FOR EACH ROW EXECUTE FUNCTION tenant_set_property();",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "DROP TRIGGER tenant_set_property ON tenant;
+             DROP FUNCTION tenant_set_property();
+             ALTER TABLE tenant DROP CONSTRAINT tenant_property_name_key;
+             ALTER TABLE tenant ADD CONSTRAINT tenants_name_key UNIQUE (name);
+             ALTER TABLE room DROP CONSTRAINT room_property_name_key;
+             ALTER TABLE room ADD CONSTRAINT rooms_name_key UNIQUE (name);
+             ALTER TABLE room DROP COLUMN property_id;
+             ALTER TABLE tenant DROP COLUMN property_id;
+             ALTER TABLE master_meter_reading DROP COLUMN property_id;",
+        )
+        .await?;
+
+        manager.drop_table(Table::drop().table(AdminUserProperty::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(AdminUser::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Property::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Property { Table, Id, Name, Address, Contact, LogoUrl, AccentColor, ElectricRate, BillDueDays, CreatedAt, UpdatedAt }
+
+#[derive(DeriveIden)]
+enum AdminUser { Table, Id, Username, PasswordHash, CreatedAt, UpdatedAt }
+
+#[derive(DeriveIden)]
+enum AdminUserProperty { Table, AdminUserId, PropertyId }
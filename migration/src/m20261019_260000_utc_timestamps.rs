@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables whose `updated_at` is kept by the `set_updated_at` trigger
+const TRIGGER_TABLES: &[&str] = &[
+    "room",
+    "tenant",
+    "electricity_reading",
+    "bill",
+    "additional_charge",
+    "bill_note",
+    "attachment",
+    "bill_template",
+    "notification_outbox",
+    "webhook_endpoint",
+    "webhook_delivery",
+    "charge_type",
+    "recurring_charge",
+    "bill_discount",
+    "tenancy",
+    "master_meter_reading",
+    "property",
+    "admin_user",
+];
+
+/// `CURRENT_TIMESTAMP` into a `timestamp` column is local time in the session's
+/// `TimeZone`, while the app and the triggers write UTC; insert defaults are UTC too.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for table in TRIGGER_TABLES {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table}
+                     ALTER COLUMN created_at SET DEFAULT timezone('utc', now()),
+                     ALTER COLUMN updated_at SET DEFAULT timezone('utc', now());
+                 DROP TRIGGER IF EXISTS {table}_set_updated_at ON {table};
+                 CREATE TRIGGER {table}_set_updated_at BEFORE INSERT OR UPDATE ON {table}
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()"
+            ))
+            .await?;
+        }
+        db.execute_unprepared("ALTER TABLE audit_log ALTER COLUMN created_at SET DEFAULT timezone('utc', now())")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("ALTER TABLE audit_log ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP")
+            .await?;
+        for table in TRIGGER_TABLES {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table}
+                     ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP,
+                     ALTER COLUMN updated_at SET DEFAULT CURRENT_TIMESTAMP;
+                 DROP TRIGGER IF EXISTS {table}_set_updated_at ON {table};
+                 CREATE TRIGGER {table}_set_updated_at BEFORE UPDATE ON {table}
+                 FOR EACH ROW EXECUTE FUNCTION set_updated_at()"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .add_column(ColumnDef::new(Bill::AmountPaid).big_integer().null())
+                    .to_owned()
+            ).await?;
+
+        // Paid bills were settled at the total after the notes they had by then
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE bill SET amount_paid = bill.total_amount + COALESCE((
+                     SELECT SUM(CASE WHEN n.kind = 'credit' THEN -n.amount ELSE n.amount END)
+                     FROM bill_note n
+                     WHERE n.bill_id = bill.id AND n.created_at <= COALESCE(bill.paid_at, bill.updated_at)
+                 ), 0)::bigint
+                 WHERE status = 'paid'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bill::Table)
+                    .drop_column(Bill::AmountPaid)
+                    .to_owned()
+            ).await?;
+        Ok(())
+    }
+}
+
+// ================== Identifier enums ==================
+#[derive(DeriveIden)]
+enum Bill { Table, AmountPaid }
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Receipts uploaded before attachments existed were stored under
+/// `receipts/{tenant name}/{unix seconds}-r{reading id}`, with only the last segment kept in
+/// `bill.receipt_url`. Files are now only served through attachment rows, so give each of
+/// them one. The tenant's current name stands in for the name at upload time; size and
+/// checksum were never recorded and are left empty.
+const LEGACY_RECEIPT: &str = "'^[0-9]+-r[0-9]+$'";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                "INSERT INTO attachment (object_key, owner_type, owner_id, file_name, content_type, size_bytes, checksum, uploaded_by)
+                 SELECT 'receipts/' || t.name || '/' || b.receipt_url, 'bill', b.id, b.receipt_url,
+                        'application/octet-stream', 0, '', 'migration'
+                 FROM bill b JOIN tenant t ON t.id = b.tenant_id
+                 WHERE b.receipt_url ~ {LEGACY_RECEIPT}
+                 ON CONFLICT (object_key) DO NOTHING;
+                 UPDATE bill b SET receipt_url = 'receipts/' || t.name || '/' || b.receipt_url
+                 FROM tenant t
+                 WHERE t.id = b.tenant_id AND b.receipt_url ~ {LEGACY_RECEIPT}"
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE bill b SET receipt_url = a.file_name
+                 FROM attachment a
+                 WHERE a.uploaded_by = 'migration' AND a.owner_type = 'bill' AND a.owner_id = b.id
+                   AND b.receipt_url = a.object_key;
+                 DELETE FROM attachment WHERE uploaded_by = 'migration' AND owner_type = 'bill'",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,809 @@
+//! Settings read once at startup from the environment and an optional TOML file.
+//!
+//! The file is `CONFIG_FILE`, else `config.toml` when it exists, with one table per
+//! section (`[server]`, `[database]`, `[auth]`, `[r2]`, `[log]`, `[upload]`, `[billing]`, ...);
+//! environment variables win over it. Every missing or invalid key is reported at once, and the loaded config prints
+//! with its secrets masked.
+
+use crate::money::Currency;
+use crate::services::template_service::Branding;
+use crate::services::upload_validation::{self, UploadLimits};
+use axum::http::HeaderValue;
+use lettre::message::Mailbox;
+use std::fmt;
+use std::sync::Arc;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const DEFAULT_PORT: u16 = 3001;
+/// sqlx logs every statement at `info`; `LOG_LEVEL=info,sqlx=info` shows them
+pub const DEFAULT_LOG_LEVEL: &str = "info,sqlx=warn";
+
+/// Each setting's environment variable and its `section.key` in the file
+const KEYS: &[(&str, &str)] = &[
+    ("PORT", "server.port"),
+    ("PRODUCTION_URL", "server.production_url"),
+    ("DATABASE_URL", "database.url"),
+    ("JWT_SECRET", "auth.jwt_secret"),
+    ("ADMIN_USERNAME", "auth.admin_username"),
+    ("ADMIN_PASSWORD", "auth.admin_password"),
+    ("R2_ENDPOINT", "r2.endpoint"),
+    ("R2_BUCKET_NAME", "r2.bucket_name"),
+    ("R2_ACCESS_KEY_ID", "r2.access_key_id"),
+    ("R2_SECRET_ACCESS_KEY", "r2.secret_access_key"),
+    ("LOG_LEVEL", "log.level"),
+    ("LOG_FORMAT", "log.format"),
+    ("UPLOAD_MAX_IMAGE_BYTES", "upload.max_image_bytes"),
+    ("UPLOAD_MAX_DOCUMENT_BYTES", "upload.max_document_bytes"),
+    ("DEFAULT_CURRENCY", "billing.default_currency"),
+    ("BILL_DUE_DAYS", "billing.due_days"),
+    ("BILL_REMINDER_DAYS_BEFORE", "billing.reminder_days_before"),
+    ("TEMPLATE_DIR", "templates.dir"),
+    ("BRAND_NAME", "branding.name"),
+    ("BRAND_ADDRESS", "branding.address"),
+    ("BRAND_CONTACT", "branding.contact"),
+    ("BRAND_LOGO_URL", "branding.logo_url"),
+    ("BRAND_ACCENT_COLOR", "branding.accent_color"),
+    ("NOTIFICATION_POLL_SECS", "notifications.poll_secs"),
+    ("SMTP_HOST", "smtp.host"),
+    ("SMTP_PORT", "smtp.port"),
+    ("SMTP_SECURITY", "smtp.security"),
+    ("SMTP_USERNAME", "smtp.username"),
+    ("SMTP_PASSWORD", "smtp.password"),
+    ("SMTP_FROM", "smtp.from"),
+    ("SMS_GATEWAY_URL", "sms.gateway_url"),
+    ("SMS_GATEWAY_API_KEY", "sms.api_key"),
+    ("SMS_SENDER_ID", "sms.sender_id"),
+    ("CHAT_WEBHOOK_URL", "chat.webhook_url"),
+    ("WEBHOOK_POLL_SECS", "webhooks.poll_secs"),
+    ("TRASH_RETENTION_DAYS", "trash.retention_days"),
+];
+
+const DEFAULT_SMTP_FROM: &str = "M18 Residences <no-reply@localhost>";
+
+/// A value kept out of logs and `Debug` output; `expose` it where it is used
+#[derive(Clone, PartialEq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Secret(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    /// `PORT`, 3001 when unset
+    pub port: u16,
+    /// `PRODUCTION_URL`, allowed by CORS next to the local frontend
+    pub production_url: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+    /// `DATABASE_URL`; may carry a password
+    pub url: Secret,
+}
+
+/// The admin from the environment, who sees every property
+#[derive(Clone, Debug)]
+pub struct AdminAccount {
+    pub username: String,
+    pub password: Secret,
+}
+
+#[derive(Clone, Debug)]
+pub struct AuthConfig {
+    /// `JWT_SECRET`, signs admin and tenant tokens
+    pub jwt_secret: Secret,
+    /// `ADMIN_USERNAME` and `ADMIN_PASSWORD`; only admin users from the database can log in without it
+    pub admin: Option<AdminAccount>,
+}
+
+impl AuthConfig {
+    pub fn admin_username(&self) -> Option<&str> {
+        self.admin.as_ref().map(|a| a.username.as_str())
+    }
+}
+
+/// Cloudflare R2 bucket and credentials
+#[derive(Clone, Debug)]
+pub struct R2Settings {
+    pub endpoint: String,
+    pub bucket_name: String,
+    pub access_key_id: Secret,
+    pub secret_access_key: Secret,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable lines for a terminal
+    Pretty,
+    /// One JSON object per event, for log collectors in production
+    Json,
+}
+
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogConfig {
+    /// `LOG_LEVEL`, an `EnvFilter` directive such as `info` or `debug,sqlx=info`
+    pub level: String,
+    /// `LOG_FORMAT`, `pretty` or `json`
+    pub format: LogFormat,
+}
+
+#[derive(Clone, Debug)]
+pub struct BillingConfig {
+    /// `DEFAULT_CURRENCY`, for rooms and bills created without one
+    pub default_currency: Currency,
+    /// `BILL_DUE_DAYS`, from issue to due date where the property sets none
+    pub due_days: i64,
+    /// `BILL_REMINDER_DAYS_BEFORE`, how long before the due date the reminder goes out
+    pub reminder_days_before: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct TemplateConfig {
+    /// `TEMPLATE_DIR`, searched after the database and before the built-in templates
+    pub dir: String,
+    /// `BRAND_*`, filling in whatever a property leaves unset
+    pub branding: Branding,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection, for local SMTP sinks
+    None,
+    StartTls,
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpSecurity::None => "none",
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::Tls => "tls",
+        }
+    }
+}
+
+/// `SMTP_*`; email is delivered when `SMTP_HOST` is set
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<Secret>,
+    pub from: String,
+    pub security: SmtpSecurity,
+}
+
+/// `SMS_GATEWAY_*`; texts are sent when `SMS_GATEWAY_URL` is set
+#[derive(Debug, Clone)]
+pub struct SmsSettings {
+    pub gateway_url: String,
+    pub api_key: Option<Secret>,
+    pub sender_id: Option<String>,
+}
+
+/// Outbox delivery and the channels it can use
+#[derive(Clone, Debug)]
+pub struct NotificationConfig {
+    /// `NOTIFICATION_POLL_SECS`
+    pub poll_secs: u64,
+    pub smtp: Option<SmtpSettings>,
+    pub sms: Option<SmsSettings>,
+    /// `CHAT_WEBHOOK_URL`; webhook paths usually carry a token
+    pub chat_webhook_url: Option<Secret>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    /// `WEBHOOK_POLL_SECS`
+    pub poll_secs: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrashConfig {
+    /// `TRASH_RETENTION_DAYS`, after which trashed rows are purged
+    pub retention_days: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub r2: R2Settings,
+    pub log: LogConfig,
+    /// `UPLOAD_MAX_IMAGE_BYTES` and `UPLOAD_MAX_DOCUMENT_BYTES`
+    pub upload: UploadLimits,
+    pub billing: BillingConfig,
+    pub templates: TemplateConfig,
+    pub notifications: NotificationConfig,
+    pub webhooks: WebhookConfig,
+    pub trash: TrashConfig,
+}
+
+/// Shared with handlers and middleware as an `Extension`
+pub type SharedConfig = Arc<Config>;
+
+/// One missing or invalid setting
+#[derive(Debug, PartialEq)]
+pub struct ConfigProblem {
+    /// e.g. `PORT (server.port)`, or the file that could not be read
+    pub key: String,
+    pub message: String,
+}
+
+/// Every problem found while loading, so they can be fixed in one go
+#[derive(Debug)]
+pub struct ConfigError {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} configuration problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}: {}", problem.key, problem.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Keeps the user name and host of a connection URL and masks its password
+pub fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return "***".into();
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    match authority.rsplit_once('@') {
+        Some((userinfo, host)) => match userinfo.split_once(':') {
+            Some((user, _)) => format!("{}://{}:***@{}{}", scheme, user, host, path),
+            None => url.to_string(),
+        },
+        None => url.to_string(),
+    }
+}
+
+impl fmt::Display for Config {
+    /// One `section.key = value` line per setting, secrets masked
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unset = || "(unset)".to_string();
+        let branding = &self.templates.branding;
+        let smtp = self.notifications.smtp.as_ref();
+        let sms = self.notifications.sms.as_ref();
+        let lines = [
+            ("server.port", self.server.port.to_string()),
+            ("server.production_url", self.server.production_url.clone().unwrap_or_else(unset)),
+            ("database.url", redact_url(self.database.url.expose())),
+            ("auth.jwt_secret", self.auth.jwt_secret.to_string()),
+            ("auth.admin_username", self.auth.admin_username().map(str::to_string).unwrap_or_else(unset)),
+            ("auth.admin_password", self.auth.admin.as_ref().map(|a| a.password.to_string()).unwrap_or_else(unset)),
+            ("r2.endpoint", self.r2.endpoint.clone()),
+            ("r2.bucket_name", self.r2.bucket_name.clone()),
+            ("r2.access_key_id", self.r2.access_key_id.to_string()),
+            ("r2.secret_access_key", self.r2.secret_access_key.to_string()),
+            ("log.level", self.log.level.clone()),
+            ("log.format", self.log.format.as_str().to_string()),
+            ("upload.max_image_bytes", self.upload.max_image_bytes.to_string()),
+            ("upload.max_document_bytes", self.upload.max_document_bytes.to_string()),
+            ("billing.default_currency", self.billing.default_currency.code().to_string()),
+            ("billing.due_days", self.billing.due_days.to_string()),
+            ("billing.reminder_days_before", self.billing.reminder_days_before.to_string()),
+            ("templates.dir", self.templates.dir.clone()),
+            ("branding.name", branding.name.clone()),
+            ("branding.address", branding.address.clone().unwrap_or_else(unset)),
+            ("branding.contact", branding.contact.clone().unwrap_or_else(unset)),
+            ("branding.logo_url", branding.logo_url.clone().unwrap_or_else(unset)),
+            ("branding.accent_color", branding.accent_color.clone()),
+            ("notifications.poll_secs", self.notifications.poll_secs.to_string()),
+            ("smtp.host", smtp.map(|s| s.host.clone()).unwrap_or_else(unset)),
+            ("smtp.port", smtp.map(|s| s.port.to_string()).unwrap_or_else(unset)),
+            ("smtp.security", smtp.map(|s| s.security.as_str().to_string()).unwrap_or_else(unset)),
+            ("smtp.username", smtp.and_then(|s| s.username.clone()).unwrap_or_else(unset)),
+            ("smtp.password", smtp.and_then(|s| s.password.as_ref()).map(Secret::to_string).unwrap_or_else(unset)),
+            ("smtp.from", smtp.map(|s| s.from.clone()).unwrap_or_else(unset)),
+            ("sms.gateway_url", sms.map(|s| s.gateway_url.clone()).unwrap_or_else(unset)),
+            ("sms.api_key", sms.and_then(|s| s.api_key.as_ref()).map(Secret::to_string).unwrap_or_else(unset)),
+            ("sms.sender_id", sms.and_then(|s| s.sender_id.clone()).unwrap_or_else(unset)),
+            ("chat.webhook_url", self.notifications.chat_webhook_url.as_ref().map(Secret::to_string).unwrap_or_else(unset)),
+            ("webhooks.poll_secs", self.webhooks.poll_secs.to_string()),
+            ("trash.retention_days", self.trash.retention_days.to_string()),
+        ];
+        for (i, (key, value)) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{} = {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks settings up in the environment, then the file, and collects what is wrong with them
+struct Sources<E> {
+    file: toml::Table,
+    env: E,
+    problems: Vec<ConfigProblem>,
+}
+
+impl<E> Sources<E>
+where
+    E: Fn(&str) -> Option<String>,
+{
+    fn label(env_key: &str) -> String {
+        match KEYS.iter().find(|(k, _)| *k == env_key) {
+            Some((k, path)) => format!("{} ({})", k, path),
+            None => env_key.to_string(),
+        }
+    }
+
+    fn problem(&mut self, env_key: &str, message: impl Into<String>) {
+        self.problems.push(ConfigProblem { key: Self::label(env_key), message: message.into() });
+    }
+
+    fn file_value(&self, path: &str) -> Option<&toml::Value> {
+        let (section, key) = path.split_once('.')?;
+        self.file.get(section)?.as_table()?.get(key)
+    }
+
+    /// Non-empty environment variable, else the file's value as text
+    fn get(&mut self, env_key: &str) -> Option<String> {
+        if let Some(value) = (self.env)(env_key).filter(|v| !v.is_empty()) {
+            return Some(value);
+        }
+        let path = KEYS.iter().find(|(k, _)| *k == env_key).map(|(_, p)| *p)?;
+        match self.file_value(path) {
+            None => None,
+            Some(toml::Value::String(s)) if s.is_empty() => None,
+            Some(toml::Value::String(s)) => Some(s.clone()),
+            Some(toml::Value::Integer(n)) => Some(n.to_string()),
+            Some(_) => {
+                self.problem(env_key, "must be a string or a number");
+                None
+            }
+        }
+    }
+
+    fn required(&mut self, env_key: &str) -> Option<String> {
+        let value = self.get(env_key);
+        if value.is_none() {
+            self.problem(env_key, "is required");
+        }
+        value
+    }
+
+    fn http_url(&mut self, env_key: &str, value: Option<String>) -> Option<String> {
+        let value = value?;
+        if !(value.starts_with("http://") || value.starts_with("https://")) {
+            self.problem(env_key, format!("'{}' must start with http:// or https://", value));
+            return None;
+        }
+        Some(value)
+    }
+
+    /// A whole number of at least `min`, `default` when unset
+    fn int(&mut self, env_key: &str, default: i64, min: i64) -> i64 {
+        let Some(value) = self.get(env_key) else {
+            return default;
+        };
+        match value.parse::<i64>() {
+            Ok(n) if n >= min => n,
+            _ => {
+                self.problem(env_key, format!("'{}' must be a whole number of at least {}", value, min));
+                default
+            }
+        }
+    }
+
+    /// Both or neither of a user name and its password
+    fn credentials(&mut self, user_key: &str, password_key: &str) -> Option<(String, Secret)> {
+        match (self.get(user_key), self.get(password_key)) {
+            (Some(user), Some(password)) => Some((user, Secret::new(password))),
+            (None, None) => None,
+            (Some(_), None) => {
+                self.problem(password_key, format!("is required when {} is set", user_key));
+                None
+            }
+            (None, Some(_)) => {
+                self.problem(user_key, format!("is required when {} is set", password_key));
+                None
+            }
+        }
+    }
+
+    /// Section or key names the file has but nothing reads, most likely typos
+    fn check_unknown_file_keys(&mut self) {
+        let mut unknown = Vec::new();
+        for (section, table) in &self.file {
+            match table.as_table() {
+                Some(table) => {
+                    for key in table.keys() {
+                        let path = format!("{}.{}", section, key);
+                        if !KEYS.iter().any(|(_, p)| *p == path) {
+                            unknown.push(path);
+                        }
+                    }
+                }
+                None => unknown.push(section.clone()),
+            }
+        }
+        for path in unknown {
+            self.problems.push(ConfigProblem { key: path, message: "is not a known setting".into() });
+        }
+    }
+}
+
+impl Config {
+    /// Load from the environment over `CONFIG_FILE` (or `config.toml` when present)
+    pub fn load() -> Result<Config, ConfigError> {
+        let path = std::env::var("CONFIG_FILE").ok().filter(|p| !p.is_empty());
+        let file = match &path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(text) => Some((path.as_str(), text)),
+                Err(err) => {
+                    return Err(ConfigError {
+                        problems: vec![ConfigProblem { key: path.clone(), message: format!("cannot be read: {}", err) }],
+                    });
+                }
+            },
+            None => std::fs::read_to_string(DEFAULT_CONFIG_FILE).ok().map(|text| (DEFAULT_CONFIG_FILE, text)),
+        };
+        Config::from_sources(file.as_ref().map(|(p, t)| (*p, t.as_str())), |key| std::env::var(key).ok())
+    }
+
+    /// Build from a file's `(path, contents)` and an environment lookup
+    pub fn from_sources<E>(file: Option<(&str, &str)>, env: E) -> Result<Config, ConfigError>
+    where
+        E: Fn(&str) -> Option<String>,
+    {
+        let mut sources = Sources { file: toml::Table::new(), env, problems: Vec::new() };
+        if let Some((path, text)) = file {
+            match text.parse::<toml::Table>() {
+                Ok(table) => sources.file = table,
+                Err(err) => sources.problems.push(ConfigProblem {
+                    key: path.to_string(),
+                    message: format!("is not valid TOML: {}", err.message()),
+                }),
+            }
+            sources.check_unknown_file_keys();
+        }
+
+        let port = match sources.get("PORT") {
+            None => Some(DEFAULT_PORT),
+            Some(value) => match value.parse::<u16>() {
+                Ok(port) if port > 0 => Some(port),
+                _ => {
+                    sources.problem("PORT", format!("'{}' must be a number from 1 to 65535", value));
+                    None
+                }
+            },
+        };
+        let production_url = sources.get("PRODUCTION_URL");
+        let production_url = sources.http_url("PRODUCTION_URL", production_url);
+        if production_url.as_deref().is_some_and(|url| HeaderValue::from_str(url).is_err()) {
+            sources.problem("PRODUCTION_URL", "must be a plain origin such as https://example.com");
+        }
+
+        let database_url = sources.required("DATABASE_URL");
+        if database_url.as_deref().is_some_and(|url| !url.starts_with("postgres://") && !url.starts_with("postgresql://")) {
+            sources.problem("DATABASE_URL", "must be a postgres:// or postgresql:// URL");
+        }
+
+        let jwt_secret = sources.required("JWT_SECRET");
+        let admin = sources
+            .credentials("ADMIN_USERNAME", "ADMIN_PASSWORD")
+            .map(|(username, password)| AdminAccount { username, password });
+
+        let endpoint = sources.required("R2_ENDPOINT");
+        let endpoint = sources.http_url("R2_ENDPOINT", endpoint);
+        let bucket_name = sources.required("R2_BUCKET_NAME");
+        let access_key_id = sources.required("R2_ACCESS_KEY_ID");
+        let secret_access_key = sources.required("R2_SECRET_ACCESS_KEY");
+
+        let level = sources.get("LOG_LEVEL").unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        if let Err(err) = tracing_subscriber::EnvFilter::try_new(&level) {
+            sources.problem("LOG_LEVEL", format!("'{}' is not a valid filter: {}", level, err));
+        }
+        let format = match sources.get("LOG_FORMAT").as_deref() {
+            None | Some("pretty") => Some(LogFormat::Pretty),
+            Some("json") => Some(LogFormat::Json),
+            Some(other) => {
+                sources.problem("LOG_FORMAT", format!("'{}' must be pretty or json", other));
+                None
+            }
+        };
+
+        let upload = UploadLimits {
+            max_image_bytes: sources.int("UPLOAD_MAX_IMAGE_BYTES", upload_validation::DEFAULT_MAX_IMAGE_BYTES as i64, 1) as usize,
+            max_document_bytes: sources.int("UPLOAD_MAX_DOCUMENT_BYTES", upload_validation::DEFAULT_MAX_DOCUMENT_BYTES as i64, 1)
+                as usize,
+        };
+
+        let default_currency = match sources.get("DEFAULT_CURRENCY") {
+            None => Currency::PHP,
+            Some(code) => Currency::parse(&code).unwrap_or_else(|| {
+                sources.problem("DEFAULT_CURRENCY", format!("'{}' is not a supported currency", code));
+                Currency::PHP
+            }),
+        };
+        let billing = BillingConfig {
+            default_currency,
+            due_days: sources.int("BILL_DUE_DAYS", 7, 0),
+            reminder_days_before: sources.int("BILL_REMINDER_DAYS_BEFORE", 2, 0),
+        };
+
+        let logo_url = sources.get("BRAND_LOGO_URL");
+        let logo_url = sources.http_url("BRAND_LOGO_URL", logo_url);
+        let default_branding = Branding::default();
+        let accent_color = sources.get("BRAND_ACCENT_COLOR").unwrap_or(default_branding.accent_color);
+        let hex = accent_color.strip_prefix('#').unwrap_or_default();
+        if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            sources.problem("BRAND_ACCENT_COLOR", format!("'{}' must be a hex color such as #1f4e79", accent_color));
+        }
+        let templates = TemplateConfig {
+            dir: sources.get("TEMPLATE_DIR").unwrap_or_else(|| "templates".to_string()),
+            branding: Branding {
+                name: sources.get("BRAND_NAME").unwrap_or(default_branding.name),
+                address: sources.get("BRAND_ADDRESS"),
+                contact: sources.get("BRAND_CONTACT"),
+                logo_url,
+                accent_color,
+            },
+        };
+
+        let security = match sources.get("SMTP_SECURITY").as_deref() {
+            None | Some("starttls") => SmtpSecurity::StartTls,
+            Some("tls") => SmtpSecurity::Tls,
+            Some("none") => SmtpSecurity::None,
+            Some(other) => {
+                sources.problem("SMTP_SECURITY", format!("'{}' must be starttls, tls or none", other));
+                SmtpSecurity::StartTls
+            }
+        };
+        let default_smtp_port = match security {
+            SmtpSecurity::None => 25,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+        };
+        let smtp_port = sources.int("SMTP_PORT", default_smtp_port, 1);
+        if smtp_port > u16::MAX as i64 {
+            sources.problem("SMTP_PORT", format!("'{}' must be a number from 1 to 65535", smtp_port));
+        }
+        let smtp_login = sources.credentials("SMTP_USERNAME", "SMTP_PASSWORD");
+        let smtp_from = sources.get("SMTP_FROM").unwrap_or_else(|| DEFAULT_SMTP_FROM.to_string());
+        if let Err(err) = smtp_from.parse::<Mailbox>() {
+            sources.problem("SMTP_FROM", format!("'{}' is not a valid address: {}", smtp_from, err));
+        }
+        let smtp = sources.get("SMTP_HOST").map(|host| {
+            let (username, password) = smtp_login.unzip();
+            SmtpSettings { host, port: smtp_port as u16, username, password, from: smtp_from, security }
+        });
+
+        let gateway_url = sources.get("SMS_GATEWAY_URL");
+        let sms = sources.http_url("SMS_GATEWAY_URL", gateway_url).map(|gateway_url| SmsSettings {
+            gateway_url,
+            api_key: sources.get("SMS_GATEWAY_API_KEY").map(Secret::new),
+            sender_id: sources.get("SMS_SENDER_ID"),
+        });
+        let chat_webhook_url = sources.get("CHAT_WEBHOOK_URL");
+        let chat_webhook_url = sources.http_url("CHAT_WEBHOOK_URL", chat_webhook_url).map(Secret::new);
+        let notifications = NotificationConfig {
+            poll_secs: sources.int("NOTIFICATION_POLL_SECS", 30, 1) as u64,
+            smtp,
+            sms,
+            chat_webhook_url,
+        };
+
+        let webhooks = WebhookConfig { poll_secs: sources.int("WEBHOOK_POLL_SECS", 10, 1) as u64 };
+        let trash = TrashConfig { retention_days: sources.int("TRASH_RETENTION_DAYS", 30, 0) };
+
+        match (port, database_url, jwt_secret, endpoint, bucket_name, access_key_id, secret_access_key, format) {
+            (Some(port), Some(url), Some(jwt_secret), Some(endpoint), Some(bucket_name), Some(key_id), Some(secret), Some(format))
+                if sources.problems.is_empty() =>
+            {
+                Ok(Config {
+                    server: ServerConfig { port, production_url },
+                    database: DatabaseConfig { url: Secret::new(url) },
+                    auth: AuthConfig { jwt_secret: Secret::new(jwt_secret), admin },
+                    r2: R2Settings {
+                        endpoint,
+                        bucket_name,
+                        access_key_id: Secret::new(key_id),
+                        secret_access_key: Secret::new(secret),
+                    },
+                    log: LogConfig { level, format },
+                    upload,
+                    billing,
+                    templates,
+                    notifications,
+                    webhooks,
+                    trash,
+                })
+            }
+            _ => Err(ConfigError { problems: sources.problems }),
+        }
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| map.get(key).cloned()
+    }
+
+    const FULL_ENV: &[(&str, &str)] = &[
+        ("DATABASE_URL", "postgres://app:hunter2@db:5432/m18"),
+        ("JWT_SECRET", "jwt-signing-key"),
+        ("ADMIN_USERNAME", "root"),
+        ("ADMIN_PASSWORD", "admin-pass"),
+        ("R2_ENDPOINT", "https://acct.r2.cloudflarestorage.com"),
+        ("R2_BUCKET_NAME", "receipts"),
+        ("R2_ACCESS_KEY_ID", "AKIAEXAMPLE"),
+        ("R2_SECRET_ACCESS_KEY", "r2-secret"),
+    ];
+
+    #[test]
+    fn test_lists_every_problem() {
+        let err = Config::from_sources(None, env(&[("PORT", "http"), ("ADMIN_USERNAME", "root")])).unwrap_err();
+        let keys: Vec<&str> = err.problems.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "PORT (server.port)",
+                "DATABASE_URL (database.url)",
+                "JWT_SECRET (auth.jwt_secret)",
+                "ADMIN_PASSWORD (auth.admin_password)",
+                "R2_ENDPOINT (r2.endpoint)",
+                "R2_BUCKET_NAME (r2.bucket_name)",
+                "R2_ACCESS_KEY_ID (r2.access_key_id)",
+                "R2_SECRET_ACCESS_KEY (r2.secret_access_key)",
+            ]
+        );
+        assert!(err.to_string().starts_with("8 configuration problem(s):\n  - PORT (server.port): 'http' must be"));
+    }
+
+    #[test]
+    fn test_env_wins_over_file() {
+        let file = r#"
+            [server]
+            port = 8080
+            production_url = "https://m18.example.com"
+
+            [database]
+            url = "postgres://file@db/m18"
+        "#;
+        let config = Config::from_sources(Some(("config.toml", file)), env(FULL_ENV)).unwrap();
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.production_url.as_deref(), Some("https://m18.example.com"));
+        assert_eq!(config.database.url.expose(), "postgres://app:hunter2@db:5432/m18");
+        assert_eq!(config.auth.admin_username(), Some("root"));
+
+        let config = Config::from_sources(None, env(&[FULL_ENV, &[("ADMIN_USERNAME", ""), ("ADMIN_PASSWORD", "")]].concat())).unwrap();
+        assert_eq!(config.server.port, DEFAULT_PORT);
+        assert!(config.auth.admin.is_none());
+    }
+
+    #[test]
+    fn test_file_typos_and_bad_toml() {
+        let err = Config::from_sources(Some(("config.toml", "[server]\nprot = 1\n")), env(FULL_ENV)).unwrap_err();
+        assert_eq!(err.problems, [ConfigProblem { key: "server.prot".into(), message: "is not a known setting".into() }]);
+
+        let err = Config::from_sources(Some(("m18.toml", "[server\n")), env(FULL_ENV)).unwrap_err();
+        assert_eq!(err.problems[0].key, "m18.toml");
+    }
+
+    #[test]
+    fn test_log_settings() {
+        let config = Config::from_sources(None, env(FULL_ENV)).unwrap();
+        assert_eq!(config.log.level, DEFAULT_LOG_LEVEL);
+        assert_eq!(config.log.format, LogFormat::Pretty);
+
+        let file = "[log]\nlevel = \"debug,sqlx=info\"\nformat = \"json\"\n";
+        let config = Config::from_sources(Some(("config.toml", file)), env(FULL_ENV)).unwrap();
+        assert_eq!(config.log.level, "debug,sqlx=info");
+        assert_eq!(config.log.format, LogFormat::Json);
+
+        let err = Config::from_sources(None, env(&[FULL_ENV, &[("LOG_LEVEL", "info,=["), ("LOG_FORMAT", "xml")]].concat())).unwrap_err();
+        let keys: Vec<&str> = err.problems.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, ["LOG_LEVEL (log.level)", "LOG_FORMAT (log.format)"]);
+    }
+
+    #[test]
+    fn test_service_settings() {
+        let config = Config::from_sources(None, env(FULL_ENV)).unwrap();
+        assert_eq!(config.upload.max_image_bytes, upload_validation::DEFAULT_MAX_IMAGE_BYTES);
+        assert_eq!(config.billing.default_currency, Currency::PHP);
+        assert_eq!((config.billing.due_days, config.billing.reminder_days_before), (7, 2));
+        assert_eq!(config.templates.branding.accent_color, Branding::default().accent_color);
+        assert!(config.notifications.smtp.is_none() && config.notifications.sms.is_none());
+        assert_eq!((config.notifications.poll_secs, config.webhooks.poll_secs), (30, 10));
+        assert_eq!(config.trash.retention_days, 30);
+
+        let file = "[smtp]\nhost = \"mail.example.com\"\nsecurity = \"tls\"\n\n[trash]\nretention_days = 7\n";
+        let config = Config::from_sources(Some(("config.toml", file)), env(&[FULL_ENV, &[("BILL_DUE_DAYS", "15")]].concat())).unwrap();
+        let smtp = config.notifications.smtp.unwrap();
+        assert_eq!((smtp.host.as_str(), smtp.port, smtp.security), ("mail.example.com", 465, SmtpSecurity::Tls));
+        assert_eq!(smtp.from, DEFAULT_SMTP_FROM);
+        assert_eq!(config.billing.due_days, 15);
+        assert_eq!(config.trash.retention_days, 7);
+
+        let bad = [
+            ("DEFAULT_CURRENCY", "XYZ"),
+            ("BILL_DUE_DAYS", "-1"),
+            ("BRAND_ACCENT_COLOR", "blue"),
+            ("SMTP_SECURITY", "ssl"),
+            ("SMTP_USERNAME", "mailer"),
+            ("CHAT_WEBHOOK_URL", "hooks.example.com/T000"),
+            ("WEBHOOK_POLL_SECS", "0"),
+        ];
+        let err = Config::from_sources(None, env(&[FULL_ENV, &bad].concat())).unwrap_err();
+        let keys: Vec<&str> = err.problems.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "DEFAULT_CURRENCY (billing.default_currency)",
+                "BILL_DUE_DAYS (billing.due_days)",
+                "BRAND_ACCENT_COLOR (branding.accent_color)",
+                "SMTP_SECURITY (smtp.security)",
+                "SMTP_PASSWORD (smtp.password)",
+                "CHAT_WEBHOOK_URL (chat.webhook_url)",
+                "WEBHOOK_POLL_SECS (webhooks.poll_secs)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_printing_masks_secrets() {
+        let channels = [
+            ("SMTP_HOST", "mail.example.com"),
+            ("SMTP_USERNAME", "mailer"),
+            ("SMTP_PASSWORD", "smtp-pass"),
+            ("SMS_GATEWAY_URL", "https://sms.example.com/send"),
+            ("SMS_GATEWAY_API_KEY", "sms-key"),
+            ("CHAT_WEBHOOK_URL", "https://hooks.example.com/T000/chat-token"),
+        ];
+        let config = Config::from_sources(None, env(&[FULL_ENV, &channels].concat())).unwrap();
+        let printed = format!("{}\n{:?}", config, config);
+        for secret in ["hunter2", "jwt-signing-key", "admin-pass", "AKIAEXAMPLE", "r2-secret", "smtp-pass", "sms-key", "chat-token"] {
+            assert!(!printed.contains(secret), "{} leaked", secret);
+        }
+        assert!(printed.contains("database.url = postgres://app:***@db:5432/m18"));
+        assert!(printed.contains("auth.admin_username = root"));
+        assert!(printed.contains("smtp.username = mailer"));
+        assert_eq!(redact_url("postgres://localhost/m18"), "postgres://localhost/m18");
+    }
+}
@@ -1,6 +1,6 @@
 use sea_orm::entity::prelude::*;
 use serde::Serialize;
-use crate::entities::bill;
+use crate::entities::{bill, charge_type};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "additional_charge")]
@@ -8,8 +8,12 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub bill_id: i32,
-    pub amount: i32,
+    /// Minor units, in the bill's currency
+    #[serde(with = "crate::money::decimal")]
+    pub amount: i64,
     pub description: String,
+    /// Catalog category; `None` for uncategorized lines
+    pub charge_type_id: Option<i32>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
@@ -18,6 +22,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(belongs_to = "bill::Entity", from = "Column::BillId", to = "bill::Column::Id")]
     Bill,
+    #[sea_orm(belongs_to = "charge_type::Entity", from = "Column::ChargeTypeId", to = "charge_type::Column::Id")]
+    ChargeType,
 }
 
 impl Related<bill::Entity> for Entity {
@@ -26,4 +32,10 @@ impl Related<bill::Entity> for Entity {
     }
 }
 
+impl Related<charge_type::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChargeType.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
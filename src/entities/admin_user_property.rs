@@ -0,0 +1,17 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+/// Property an admin user may manage
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "admin_user_property")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub admin_user_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub property_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
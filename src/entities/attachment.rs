@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+/// Owner types an attachment can be linked to
+pub const OWNER_BILL: &str = "bill";
+pub const OWNER_TENANT: &str = "tenant";
+/// Meter photos backing a reading
+pub const OWNER_READING: &str = "electricity_reading";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "attachment")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub object_key: String,
+    pub owner_type: String,
+    pub owner_id: i32,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub uploaded_by: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
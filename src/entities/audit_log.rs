@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+/// Kinds of recorded mutations
+pub const ACTION_CREATE: &str = "create";
+pub const ACTION_UPDATE: &str = "update";
+pub const ACTION_DELETE: &str = "delete";
+pub const ACTION_RESTORE: &str = "restore";
+pub const ACTION_PURGE: &str = "purge";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// `Claims::actor()` of the caller, or `system` for background jobs
+    pub actor: String,
+    /// Table name of the changed row, e.g. `bill`
+    pub entity: String,
+    pub entity_id: i32,
+    pub action: String,
+    /// `{ field: { "before": .., "after": .. } }` for every changed field
+    pub changes: Json,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
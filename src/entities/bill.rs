@@ -1,6 +1,13 @@
 use sea_orm::entity::prelude::*;
 use serde::Serialize;
-use crate::entities::{tenant, electricity_reading, additional_charge};
+use crate::money::{Currency, Money};
+use crate::entities::{tenant, electricity_reading, additional_charge, bill_note};
+
+/// Bill lifecycle: `draft` -> `issued` -> `paid` | `void`. Only drafts can be edited.
+pub const STATUS_DRAFT: &str = "draft";
+pub const STATUS_ISSUED: &str = "issued";
+pub const STATUS_PAID: &str = "paid";
+pub const STATUS_VOID: &str = "void";
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "bill")]
@@ -10,13 +17,32 @@ pub struct Model {
     #[sea_orm(unique)]
     pub reading_id: i32,
     pub tenant_id: i32,
-    pub room_charges: i32,
-    pub electric_charges: i32,
-    pub total_amount: i32,
+    /// Amounts are minor units of `currency`
+    #[serde(with = "crate::money::decimal")]
+    pub room_charges: i64,
+    #[serde(with = "crate::money::decimal")]
+    pub electric_charges: i64,
+    /// Sum of the discount lines, already taken off `total_amount`
+    #[serde(with = "crate::money::decimal")]
+    pub discount_amount: i64,
+    #[serde(with = "crate::money::decimal")]
+    pub total_amount: i64,
+    pub currency: String,
     pub receipt_url: Option<String>,
+    /// Mirrors `status == paid`
     pub paid: bool,
+    /// What the tenant paid: the total after the notes the bill had when it was paid
+    #[serde(with = "crate::money::decimal_option")]
+    pub amount_paid: Option<i64>,
+    pub status: String,
+    pub issued_at: Option<chrono::NaiveDateTime>,
+    pub paid_at: Option<chrono::NaiveDateTime>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Set when the row is in the trash; purged after `TRASH_RETENTION_DAYS`
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    /// Bumped on every update and served as the `ETag`
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -27,6 +53,8 @@ pub enum Relation {
     Tenant,
     #[sea_orm(has_many = "additional_charge::Entity")]
     AdditionalCharges,
+    #[sea_orm(has_many = "bill_note::Entity")]
+    Notes,
 }
 
 impl Related<additional_charge::Entity> for Entity {
@@ -41,4 +69,29 @@ impl Related<electricity_reading::Entity> for Entity {
     }
 }
 
+impl Related<bill_note::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notes.def()
+    }
+}
+
+impl Model {
+    pub fn is_draft(&self) -> bool {
+        self.status == STATUS_DRAFT
+    }
+
+    pub fn currency(&self) -> Currency {
+        Currency::parse(&self.currency).unwrap_or(Currency::PHP)
+    }
+
+    pub fn total(&self) -> Money {
+        Money::new(self.total_amount, self.currency())
+    }
+
+    /// Zero until the bill is paid
+    pub fn amount_paid(&self) -> Money {
+        Money::new(self.amount_paid.unwrap_or(0), self.currency())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::bill;
+
+/// `value` is an amount in minor units
+pub const KIND_FIXED: &str = "fixed";
+/// `value` is hundredths of a percent of the bill's gross, e.g. `1250` is 12.5%
+pub const KIND_PERCENT: &str = "percent";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "bill_discount")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub bill_id: i32,
+    pub kind: String,
+    #[serde(with = "crate::money::decimal")]
+    pub value: i64,
+    /// Minor units taken off the bill; always positive
+    #[serde(with = "crate::money::decimal")]
+    pub amount: i64,
+    pub reason: String,
+    pub approved_by: String,
+    /// Order the discount was applied in
+    pub position: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "bill::Entity", from = "Column::BillId", to = "bill::Column::Id")]
+    Bill,
+}
+
+impl Related<bill::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
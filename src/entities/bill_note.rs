@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::bill;
+
+/// Credit notes reduce what the tenant owes, debit notes add to it
+pub const KIND_CREDIT: &str = "credit";
+pub const KIND_DEBIT: &str = "debit";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "bill_note")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub bill_id: i32,
+    pub tenant_id: i32,
+    pub kind: String,
+    /// Minor units in the bill's currency; always positive, `kind` gives the direction
+    #[serde(with = "crate::money::decimal")]
+    pub amount: i64,
+    pub reason: String,
+    pub created_by: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "bill::Entity", from = "Column::BillId", to = "bill::Column::Id")]
+    Bill,
+}
+
+impl Related<bill::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bill.def()
+    }
+}
+
+impl Model {
+    /// Effect on the tenant's balance
+    pub fn signed_amount(&self) -> i64 {
+        if self.kind == KIND_CREDIT { -self.amount } else { self.amount }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::{additional_charge, recurring_charge};
+
+/// Catalog entry for additional charges, e.g. water or parking
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "charge_type")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Stable key used in reports, e.g. `water`
+    pub code: String,
+    pub name: String,
+    /// Minor units used when a charge of this type is added without an amount
+    #[serde(with = "crate::money::decimal")]
+    pub default_amount: i64,
+    /// Retired types stay on old bills but cannot be added to new ones
+    pub is_active: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "additional_charge::Entity")]
+    AdditionalCharges,
+    #[sea_orm(has_many = "recurring_charge::Entity")]
+    RecurringCharges,
+}
+
+impl Related<additional_charge::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AdditionalCharges.def()
+    }
+}
+
+impl Related<recurring_charge::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecurringCharges.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,8 +12,13 @@ pub struct Model {
     pub prev_reading: i32,
     pub curr_reading: i32,
     pub consumption: i32,
+    /// Set when consumption strays far from the room's recent baseline
+    pub flagged: bool,
+    pub flag_reason: Option<String>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Set when the row is in the trash; purged after `TRASH_RETENTION_DAYS`
+    pub deleted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
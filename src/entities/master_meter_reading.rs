@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+/// The utility's meter for a whole property, read off its monthly bill
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "master_meter_reading")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub property_id: i32,
+    /// Counts towards the month it falls in
+    pub reading_date: chrono::NaiveDateTime,
+    pub prev_reading: i32,
+    pub curr_reading: i32,
+    pub consumption: i32,
+    pub notes: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -3,3 +3,18 @@ pub mod tenant;
 pub mod electricity_reading;
 pub mod bill;
 pub mod additional_charge;
+pub mod attachment;
+pub mod bill_template;
+pub mod notification_outbox;
+pub mod webhook_endpoint;
+pub mod webhook_delivery;
+pub mod audit_log;
+pub mod bill_note;
+pub mod charge_type;
+pub mod recurring_charge;
+pub mod bill_discount;
+pub mod tenancy;
+pub mod master_meter_reading;
+pub mod property;
+pub mod admin_user;
+pub mod admin_user_property;
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::{bill, tenant};
+
+/// Delivery states of an outbox message
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_SENT: &str = "sent";
+pub const STATUS_FAILED: &str = "failed";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "notification_outbox")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub event: String,
+    pub channel: String,
+    pub recipient: String,
+    pub tenant_id: Option<i32>,
+    pub bill_id: Option<i32>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: chrono::NaiveDateTime,
+    pub sent_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "tenant::Entity", from = "Column::TenantId", to = "tenant::Column::Id")]
+    Tenant,
+    #[sea_orm(belongs_to = "bill::Entity", from = "Column::BillId", to = "bill::Column::Id")]
+    Bill,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
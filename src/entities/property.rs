@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::room;
+
+/// A building with its own rooms, tariff, due dates and branding
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "property")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub address: Option<String>,
+    pub contact: Option<String>,
+    pub logo_url: Option<String>,
+    pub accent_color: Option<String>,
+    /// Minor units per kWh, used when a batch drafts bills without a rate
+    #[serde(with = "crate::money::decimal_option")]
+    pub electric_rate: Option<i64>,
+    /// Days after issue a bill falls due; `BILL_DUE_DAYS` when unset
+    pub bill_due_days: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "room::Entity")]
+    Rooms,
+}
+
+impl Related<room::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Rooms.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::charge_type;
+
+/// A charge added to every new bill of a tenant, or of whoever rents a room
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "recurring_charge")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub charge_type_id: i32,
+    /// At least one of `tenant_id` and `room_id` is set
+    pub tenant_id: Option<i32>,
+    pub room_id: Option<i32>,
+    /// Minor units; the charge type's default amount when unset
+    #[serde(with = "crate::money::decimal_option")]
+    pub amount: Option<i64>,
+    /// The charge type's name when unset
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(belongs_to = "charge_type::Entity", from = "Column::ChargeTypeId", to = "charge_type::Column::Id")]
+    ChargeType,
+}
+
+impl Related<charge_type::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChargeType.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,25 +1,50 @@
 use sea_orm::entity::prelude::*;
-use crate::entities::{tenant, electricity_reading};
+use crate::entities::{property, tenant, electricity_reading};
 use serde::Serialize; 
+use crate::money::{Currency, Money};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)] 
 #[sea_orm(table_name = "room")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    #[sea_orm(unique)]
+    pub property_id: i32,
+    /// Unique within the property
     pub name: String,
-    pub rent: i32,
+    /// Minor units of `currency`
+    #[serde(with = "crate::money::decimal")]
+    pub rent: i64,
+    pub currency: String,
+    /// Taken off the market; reported as `maintenance` regardless of tenants
+    pub under_maintenance: bool,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Set when the row is in the trash; purged after `TRASH_RETENTION_DAYS`
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    /// Bumped on every update and served as the `ETag`
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(belongs_to = "property::Entity", from = "Column::PropertyId", to = "property::Column::Id")]
+    Property,
     #[sea_orm(has_many = "tenant::Entity")]
     Tenants,
     #[sea_orm(has_many = "electricity_reading::Entity")]
     Readings,
 }
 
+impl Related<property::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Property.def()
+    }
+}
+
+impl Model {
+    pub fn rent(&self) -> Money {
+        Money::new(self.rent, Currency::parse(&self.currency).unwrap_or(Currency::PHP))
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -8,11 +8,23 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub room_id: i32,
+    /// The room's property, kept in step by a database trigger
+    pub property_id: i32,
+    /// Unique within the property
     pub name: String,
     pub is_active: bool,
     pub join_date: chrono::NaiveDateTime,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub chat_id: Option<String>,
+    /// Comma-separated notification channels, e.g. `email,sms`
+    pub notification_channels: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Set when the row is in the trash; purged after `TRASH_RETENTION_DAYS`
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    /// Bumped on every update and served as the `ETag`
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,4 +41,13 @@ impl Related<room::Entity> for Entity {
     }
 }
 
+impl Model {
+    pub fn channels(&self) -> impl Iterator<Item = &str> {
+        self.notification_channels
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use crate::entities::webhook_delivery;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "webhook_endpoint")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub url: String,
+    /// Shared secret for the `X-M18-Signature` HMAC; only returned when the endpoint is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Comma-separated subscribed events, e.g. `bill.created,bill.paid`; `*` for all
+    pub events: String,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "webhook_delivery::Entity")]
+    Deliveries,
+}
+
+impl Related<webhook_delivery::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Deliveries.def()
+    }
+}
+
+impl Model {
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.events
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+    }
+
+    pub fn is_subscribed(&self, event: &str) -> bool {
+        self.events().any(|e| e == "*" || e == event)
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
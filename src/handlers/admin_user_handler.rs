@@ -0,0 +1,107 @@
+use crate::config::SharedConfig;
+use crate::middleware::jwt::Claims;
+use crate::services::admin_user_service::{self, AdminUserChanges, AdminUserWithProperties};
+use crate::services::versioning::WriteError;
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
+
+#[derive(Deserialize)]
+pub struct AdminUserInput {
+    /// Required on create
+    pub username: Option<String>,
+    /// Required on create, at least 8 characters; left unchanged when omitted on update
+    pub password: Option<String>,
+    /// Properties the user may manage; left unchanged when omitted on update
+    pub property_ids: Option<Vec<i32>>,
+}
+
+impl From<AdminUserInput> for AdminUserChanges {
+    fn from(input: AdminUserInput) -> Self {
+        AdminUserChanges {
+            username: input.username.map(|u| u.trim().to_string()),
+            password: input.password,
+            property_ids: input.property_ids,
+        }
+    }
+}
+
+/// Admin users are managed by the `ADMIN_USERNAME` admin only
+fn require_superadmin(claims: &Claims) -> Result<(), ErrorResponse> {
+    if claims.is_superadmin() {
+        Ok(())
+    } else {
+        Err(error_json(StatusCode::FORBIDDEN, "Only the main admin can manage admin users"))
+    }
+}
+
+fn map_write_error(err: WriteError) -> ErrorResponse {
+    match err {
+        WriteError::NotFound => error_json(StatusCode::NOT_FOUND, "Admin user not found"),
+        WriteError::InvalidState(msg) => error_json(StatusCode::CONFLICT, &msg),
+        WriteError::Invalid(msg) => error_json(StatusCode::BAD_REQUEST, &msg),
+        _ => error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+    }
+}
+
+/// GET /admin/users
+pub async fn get_admin_users(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<AdminUserWithProperties>>, ErrorResponse> {
+    require_superadmin(&claims)?;
+    admin_user_service::get_admin_users(&db)
+        .await
+        .map(Json)
+        .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))
+}
+
+/// POST /admin/users
+pub async fn create_admin_user(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Extension(config): Extension<SharedConfig>,
+    Json(payload): Json<AdminUserInput>,
+) -> Result<(StatusCode, Json<AdminUserWithProperties>), ErrorResponse> {
+    require_superadmin(&claims)?;
+    admin_user_service::create_admin_user(&db, &claims.actor(), config.auth.admin_username(), payload.into())
+        .await
+        .map(|u| (StatusCode::CREATED, Json(u)))
+        .map_err(map_write_error)
+}
+
+/// PUT /admin/users/:id
+pub async fn update_admin_user(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Extension(config): Extension<SharedConfig>,
+    Json(payload): Json<AdminUserInput>,
+) -> Result<Json<AdminUserWithProperties>, ErrorResponse> {
+    require_superadmin(&claims)?;
+    admin_user_service::update_admin_user(&db, &claims.actor(), config.auth.admin_username(), id, payload.into())
+        .await
+        .map(Json)
+        .map_err(map_write_error)
+}
+
+/// DELETE /admin/users/:id
+pub async fn delete_admin_user(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, ErrorResponse> {
+    require_superadmin(&claims)?;
+    match admin_user_service::delete_admin_user(&db, &claims.actor(), id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(error_json(StatusCode::NOT_FOUND, "Admin user not found")),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error")),
+    }
+}
@@ -0,0 +1,38 @@
+use crate::entities::audit_log;
+use crate::repository::audit_log_repo::AuditLogFilter;
+use crate::services::audit_service;
+use axum::{Extension, Json, extract::Query, http::StatusCode};
+use chrono::NaiveDateTime;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    pub entity: Option<String>,
+    pub entity_id: Option<i32>,
+    pub actor: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+/// GET /admin/audit-log?entity=bill&entity_id=3&actor=admin:root&since=..&until=..&limit=50&offset=0
+pub async fn get_audit_log(
+    Query(query): Query<AuditLogQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<audit_log::Model>>, StatusCode> {
+    let filter = AuditLogFilter {
+        entity: query.entity,
+        entity_id: query.entity_id,
+        actor: query.actor,
+        since: query.since,
+        until: query.until,
+    };
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    audit_service::get_audit_logs(&db, &filter, limit, query.offset.unwrap_or(0))
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -4,6 +4,7 @@ use axum::{
     response::{IntoResponse},
 };
 use serde::{Deserialize, Serialize};
+use crate::config::SharedConfig;
 use crate::services::auth_service::{admin_login, tenant_login, validate_token, AuthError};
 
 #[derive(Deserialize)]
@@ -15,6 +16,8 @@ pub struct AdminLoginInput {
 #[derive(Deserialize)]
 pub struct TenantLoginInput {
     pub name: String,
+    /// Needed when tenants of several properties share the name
+    pub property_id: Option<i32>,
 }
 
 #[derive(Serialize)]
@@ -28,14 +31,19 @@ fn map_auth_error(err: AuthError) -> (StatusCode, String) {
     match err {
         AuthError::InvalidCredentials => (StatusCode::UNAUTHORIZED, "Invalid credentials".into()),
         AuthError::TenantNotFound => (StatusCode::NOT_FOUND, "Tenant not found".into()),
+        AuthError::TenantAmbiguous => (StatusCode::CONFLICT, "Several tenants have this name; give property_id".into()),
         AuthError::TokenMissing => (StatusCode::UNAUTHORIZED, "Missing token".into()),
         AuthError::TokenInvalid => (StatusCode::UNAUTHORIZED, "Invalid token".into()),
         AuthError::Other(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
     }
 }
 
-pub async fn admin_login_handler(Json(input): Json<AdminLoginInput>) -> impl IntoResponse {
-    match admin_login(&input.username, &input.password).await {
+pub async fn admin_login_handler(
+    Extension(db): Extension<sea_orm::DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Json(input): Json<AdminLoginInput>,
+) -> impl IntoResponse {
+    match admin_login(&db, &config.auth, &input.username, &input.password).await {
         Ok(token) => (
             StatusCode::OK,
             Json(TokenResponse {
@@ -54,9 +62,10 @@ pub async fn admin_login_handler(Json(input): Json<AdminLoginInput>) -> impl Int
 
 pub async fn tenant_login_handler(
     Extension(db): Extension<sea_orm::DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
     Json(input): Json<TenantLoginInput>,
 ) -> impl IntoResponse {
-    match tenant_login(&db, &input.name).await {
+    match tenant_login(&db, &config.auth, &input.name, input.property_id).await {
         Ok((token, tenant)) => (
             StatusCode::OK,
             Json(serde_json::json!({ "token": token, "tenant": tenant })),
@@ -69,10 +78,13 @@ pub async fn tenant_login_handler(
     }
 }
 
-pub async fn validate_token_handler(headers: HeaderMap) -> impl IntoResponse {
+pub async fn validate_token_handler(
+    Extension(config): Extension<SharedConfig>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let auth_header = headers.get("authorization").and_then(|h| h.to_str().ok());
 
-    match validate_token(auth_header) {
+    match validate_token(&config.auth, auth_header) {
         Ok(claims) => (StatusCode::OK, Json(serde_json::json!({ "user": claims }))),
         Err(err) => {
             let (status, msg) = map_auth_error(err);
@@ -1,33 +1,83 @@
+use crate::config::{Config, SharedConfig};
+use crate::middleware::jwt::Claims;
+use crate::entities::attachment::OWNER_BILL;
+use crate::handlers::etag::{self, ETagHeader};
+use crate::handlers::property_handler;
+use crate::money::{self, Currency};
+use crate::repository::bill_repo::BillFilter;
+use crate::repository::property_repo::PropertyOwner;
 use crate::services::{
-    bill_service::{self, AdditionalChargeInput, BillInput, BillWithChargesAndReading},
-    r2_service::{R2Config, upload_file},
-    tenant_service::get_tenant_by_id,
+    attachment_service::{self, NewAttachment, StoredFile},
+    bill_service::{
+        self, AdditionalChargeInput, BillInput, BillNoteInput, BillWithChargesAndReading, DiscountInput, TenantBalance,
+    },
+    document_service::{self, DocumentError, DocumentKind},
+    r2_service::{self, R2Config},
+    template_service::{self, RenderError, RenderFormat},
+    upload_validation::{self, UploadError, UploadLimits, ValidatedUpload},
+    versioning::WriteError,
 };
 use axum::{
     Extension, Json,
-    extract::{Path, multipart::Multipart},
-    http::StatusCode,
+    extract::{Path, Query, multipart::Multipart},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use chrono::Utc;
+use chrono::NaiveDateTime;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
-use mime_guess::MimeGuess;
+
+#[derive(Deserialize)]
+pub struct BillListQuery {
+    pub tenant_id: Option<i32>,
+    pub status: Option<String>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub property_id: Option<i32>,
+}
+
+impl BillListQuery {
+    /// The filter for this query within the caller's properties; 403 for someone else's property
+    pub fn filter(self, claims: &Claims) -> Result<BillFilter, StatusCode> {
+        let scope = property_handler::scope_for(claims, self.property_id)?;
+        Ok(BillFilter {
+            tenant_id: self.tenant_id,
+            status: self.status,
+            from: self.from,
+            to: self.to,
+            property_ids: scope.ids().map(<[i32]>::to_vec),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RenderQuery {
+    pub format: Option<String>,
+    pub locale: Option<String>,
+}
 
 #[derive(Deserialize)]
 pub struct BillPayload {
     pub tenant_id: i32,
     pub reading_id: i32,
-    pub room_charges: i32,
-    pub electric_charges: i32,
+    /// Defaults to `DEFAULT_CURRENCY`
+    pub currency: Option<Currency>,
+    #[serde(with = "money::decimal")]
+    pub room_charges: i64,
+    #[serde(with = "money::decimal")]
+    pub electric_charges: i64,
     pub additional_charges: Option<Vec<AdditionalChargeInput>>,
+    pub discounts: Option<Vec<DiscountInput>>,
     pub receipt_url: Option<String>,
 }
 
-/// GET /bills
+/// GET /bills?tenant_id=1&status=unpaid&from=..&to=..&property_id=1
 pub async fn get_bills(
+    Query(query): Query<BillListQuery>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<Json<Vec<BillWithChargesAndReading>>, StatusCode> {
-    bill_service::get_all_bills_with_details(&db)
+    bill_service::get_all_bills_with_details(&db, &query.filter(&claims)?)
         .await
         .map(Json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
@@ -37,9 +87,9 @@ pub async fn get_bills(
 pub async fn get_bill_by_tenant(
     Path(tenant_id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<Json<BillWithChargesAndReading>, StatusCode> {
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), StatusCode> {
     match bill_service::get_tenant_bill_with_details(&db, tenant_id).await {
-        Ok(Some(bill)) => Ok(Json(bill)),
+        Ok(Some(bill)) => Ok((etag::etag_header(bill.bill.version), Json(bill))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -59,127 +109,433 @@ pub async fn get_bills_by_tenant(
 /// POST /bills
 pub async fn create_bill_handler(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BillPayload>,
-) -> Result<(StatusCode, Json<BillWithChargesAndReading>), StatusCode> {
+) -> Result<(StatusCode, Json<BillWithChargesAndReading>), ErrorResponse> {
+    check_reading(&db, &claims, payload.reading_id).await?;
     let input = BillInput {
         tenant_id: payload.tenant_id,
         reading_id: payload.reading_id,
+        currency: payload.currency.unwrap_or(config.billing.default_currency),
         room_charges: payload.room_charges,
         electric_charges: payload.electric_charges,
         additional_charges: payload.additional_charges.unwrap_or_default(),
+        discounts: payload.discounts.unwrap_or_default(),
         receipt_url: None,
     };
 
-    bill_service::create_bill(&db, input)
+    bill_service::create_bill(&db, &claims.actor(), input)
         .await
         .map(|bill| (StatusCode::CREATED, Json(bill)))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(map_write_error)
 }
 
-/// PUT /bills/:id (JSON update)
+/// PUT /bills/:id (JSON update, requires If-Match)
 pub async fn update_bill_json_handler(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
     Json(payload): Json<BillPayload>,
-) -> Result<Json<BillWithChargesAndReading>, StatusCode> {
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    let expected_version = if_match(&headers)?;
+    check_reading(&db, &claims, payload.reading_id).await?;
     let input = BillInput {
         tenant_id: payload.tenant_id,
         reading_id: payload.reading_id,
+        currency: payload.currency.unwrap_or(config.billing.default_currency),
         room_charges: payload.room_charges,
         electric_charges: payload.electric_charges,
         additional_charges: payload.additional_charges.unwrap_or_default(),
+        discounts: payload.discounts.unwrap_or_default(),
         receipt_url: payload.receipt_url,
     };
 
-    match bill_service::update_bill(&db, id, input).await {
-        Ok(updated) => Ok(Json(updated)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    bill_service::update_bill(&db, &claims.actor(), id, expected_version, input, None)
+        .await
+        .map(with_etag)
+        .map_err(map_write_error)
+}
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, msg: impl Into<String>) -> ErrorResponse {
+    (status, Json(serde_json::json!({ "error": msg.into() })))
+}
+
+fn if_match(headers: &HeaderMap) -> Result<Option<i32>, ErrorResponse> {
+    etag::if_match(headers).map_err(|status| error_json(status, "Missing or invalid If-Match"))
+}
+
+/// Multipart amounts are decimal strings in major units, like JSON ones
+fn parse_amount(value: &str) -> Result<i64, ErrorResponse> {
+    money::parse_decimal(value).map_err(|err| error_json(StatusCode::UNPROCESSABLE_ENTITY, err.to_string()))
+}
+
+fn map_write_error(err: WriteError) -> ErrorResponse {
+    error_json(etag::write_error_status(&err), err.to_string())
+}
+
+/// The reading a bill is raised on must be in the caller's properties; unknown readings
+/// are left to the service to report
+async fn check_reading(db: &DatabaseConnection, claims: &Claims, reading_id: i32) -> Result<(), ErrorResponse> {
+    match property_handler::check_owner(db, claims, PropertyOwner::Reading(reading_id)).await {
+        Ok(()) | Err(StatusCode::NOT_FOUND) => Ok(()),
+        Err(status) => Err(error_json(status, "Reading is not in your properties")),
+    }
+}
+
+fn with_etag(bill: BillWithChargesAndReading) -> (ETagHeader, Json<BillWithChargesAndReading>) {
+    (etag::etag_header(bill.bill.version), Json(bill))
+}
+
+/// A receipt that passed validation and is waiting for the rest of the request
+struct PendingReceipt {
+    file_name: String,
+    upload: ValidatedUpload,
+}
+
+async fn validate_receipt(
+    limits: &UploadLimits,
+    file_name: String,
+    bytes: axum::body::Bytes,
+) -> Result<PendingReceipt, ErrorResponse> {
+    let upload = upload_validation::validate_upload(&file_name, bytes, limits)
+        .await
+        .map_err(map_upload_error)?;
+    Ok(PendingReceipt { file_name, upload })
+}
+
+/// Upload a receipt for the bill; its attachment row is written by the bill update itself
+async fn store_receipt(r2: &R2Config, id: i32, receipt: PendingReceipt) -> Result<StoredFile, ErrorResponse> {
+    let key = attachment_service::object_key(&format!("receipts/bills/{}", id), receipt.upload.kind.extension());
+    attachment_service::store_file(
+        r2,
+        NewAttachment {
+            object_key: key,
+            owner_type: OWNER_BILL.to_string(),
+            owner_id: id,
+            file_name: Some(receipt.file_name),
+            content_type: receipt.upload.kind.mime_type().to_string(),
+            bytes: receipt.upload.bytes,
+        },
+    )
+    .await
+    .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store file"))
+}
+
+/// Upload the receipt, if any, then run the bill write; a rejected write removes the upload again
+async fn write_with_receipt<F, Fut>(
+    r2: &R2Config,
+    id: i32,
+    receipt: Option<PendingReceipt>,
+    write: F,
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse>
+where
+    F: FnOnce(Option<StoredFile>) -> Fut,
+    Fut: std::future::Future<Output = Result<BillWithChargesAndReading, WriteError>>,
+{
+    let stored = match receipt {
+        Some(receipt) => Some(store_receipt(r2, id, receipt).await?),
+        None => None,
+    };
+    let result = write(stored.clone()).await;
+    if let (Err(_), Some(stored)) = (&result, &stored) {
+        attachment_service::discard(r2, stored).await;
     }
+    result.map(with_etag).map_err(map_write_error)
 }
 
-/// PUT /bills/:id (Multipart update with file upload)
+pub fn map_upload_error(err: UploadError) -> ErrorResponse {
+    let status = match err {
+        UploadError::UnsupportedType | UploadError::ExtensionMismatch { .. } => {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        }
+        UploadError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        UploadError::InvalidImage(_) => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    error_json(status, err.message())
+}
+
+/// PUT /bills/:id (Multipart update with file upload, requires If-Match)
 pub async fn update_bill_multipart_handler(
     Extension(db): Extension<DatabaseConnection>,
     Extension(r2): Extension<R2Config>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
     mut multipart: Multipart,
-) -> Result<Json<BillWithChargesAndReading>, StatusCode> {
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    // Checked before anything is uploaded; the receipt itself is only stored once the whole
+    // request has been read
+    let expected_version = if_match(&headers)?;
     let mut tenant_id: i32 = 0;
     let mut reading_id: i32 = 0;
-    let mut room_charges: i32 = 0;
-    let mut electric_charges: i32 = 0;
+    let mut currency = config.billing.default_currency;
+    let mut room_charges: i64 = 0;
+    let mut electric_charges: i64 = 0;
     let mut additional_charges: Vec<AdditionalChargeInput> = vec![];
+    let mut discounts: Vec<DiscountInput> = vec![];
     let mut receipt_url: Option<String> = None;
+    let mut receipt: Option<PendingReceipt> = None;
 
-    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| error_json(e.status(), e.body_text()))?
+    {
         let name = field.name().unwrap_or_default().to_string();
         let file_name = field.file_name().map(|s| s.to_string());
 
         // Handle file upload
         if name == "receipt_file" {
             if let Some(fname) = file_name {
-                let tenant_name = get_tenant_by_id(&db, tenant_id)
+                let bytes = field
+                    .bytes()
                     .await
-                    .ok()
-                    .flatten()
-                    .map(|t| t.name)
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                let ext = fname.rsplit('.').next().unwrap_or("bin");
-                let mime_type = MimeGuess::from_ext(ext).first_or_octet_stream().to_string();
-
-                let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                let key = format!("receipts/{}/{}-r{}", tenant_name, Utc::now().timestamp(), reading_id);
-
-                receipt_url = Some(
-                    upload_file(&r2, bytes, &key, &mime_type)
-                        .await
-                        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
-                );
+                    .map_err(|e| error_json(e.status(), e.body_text()))?;
+                receipt = Some(validate_receipt(&config.upload, fname, bytes).await?);
             }
             continue;
         }
 
         // Handle text fields
-        let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+        let bytes = field
+            .bytes()
+            .await
+            .map_err(|e| error_json(e.status(), e.body_text()))?;
         let value = String::from_utf8(bytes.to_vec()).unwrap_or_default();
 
         match name.as_str() {
             "tenant_id" => tenant_id = value.parse().unwrap_or_default(),
             "reading_id" => reading_id = value.parse().unwrap_or_default(),
-            "room_charges" => room_charges = value.parse().unwrap_or_default(),
-            "electric_charges" => electric_charges = value.parse().unwrap_or_default(),
+            "currency" => {
+                currency = Currency::parse(&value)
+                    .ok_or_else(|| error_json(StatusCode::UNPROCESSABLE_ENTITY, format!("unsupported currency '{}'", value)))?
+            }
+            "room_charges" => room_charges = parse_amount(&value)?,
+            "electric_charges" => electric_charges = parse_amount(&value)?,
             "additional_charges" => {
                 additional_charges = serde_json::from_slice(&bytes).unwrap_or_default()
             }
+            "discounts" => {
+                discounts = serde_json::from_slice(&bytes)
+                    .map_err(|e| error_json(StatusCode::UNPROCESSABLE_ENTITY, format!("discounts: {}", e)))?
+            }
             "receipt_url" => receipt_url = Some(value),
             _ => {}
         }
     }
 
+    check_reading(&db, &claims, reading_id).await?;
     let input = BillInput {
         tenant_id,
         reading_id,
+        currency,
         room_charges,
         electric_charges,
         additional_charges,
+        discounts,
         receipt_url,
     };
 
-    match bill_service::update_bill(&db, id, input).await {
-        Ok(bill) => Ok(Json(bill)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    let actor = claims.actor();
+    write_with_receipt(&r2, id, receipt, |stored| {
+        bill_service::update_bill(&db, &actor, id, expected_version, input, stored)
+    })
+    .await
+}
+
+/// POST /bills/:id/issue (requires If-Match)
+pub async fn issue_bill_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    let expected_version = if_match(&headers)?;
+    bill_service::issue_bill(&db, &claims.actor(), id, expected_version)
+        .await
+        .map(with_etag)
+        .map_err(map_write_error)
+}
+
+/// POST /bills/:id/pay (multipart with an optional `receipt_file`, requires If-Match)
+pub async fn pay_bill_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(r2): Extension<R2Config>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    let expected_version = if_match(&headers)?;
+    let mut receipt: Option<PendingReceipt> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| error_json(e.status(), e.body_text()))?
+    {
+        if field.name() != Some("receipt_file") {
+            continue;
+        }
+        if let Some(fname) = field.file_name().map(|s| s.to_string()) {
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| error_json(e.status(), e.body_text()))?;
+            receipt = Some(validate_receipt(&config.upload, fname, bytes).await?);
+        }
     }
+
+    let actor = claims.actor();
+    write_with_receipt(&r2, id, receipt, |stored| {
+        bill_service::pay_bill(&db, &actor, id, expected_version, stored)
+    })
+    .await
 }
 
-/// DELETE /bills/:id
+/// POST /bills/:id/void (requires If-Match)
+pub async fn void_bill_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<(ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    let expected_version = if_match(&headers)?;
+    bill_service::void_bill(&db, &claims.actor(), id, expected_version)
+        .await
+        .map(with_etag)
+        .map_err(map_write_error)
+}
+
+/// POST /bills/:id/notes { kind: "credit" | "debit", amount, reason }
+pub async fn add_bill_note_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<BillNoteInput>,
+) -> Result<(StatusCode, ETagHeader, Json<BillWithChargesAndReading>), ErrorResponse> {
+    bill_service::add_bill_note(&db, &claims.actor(), id, payload)
+        .await
+        .map(|bill| (StatusCode::CREATED, etag::etag_header(bill.bill.version), Json(bill)))
+        .map_err(map_write_error)
+}
+
+/// GET /bills/:tenant_id/balance
+pub async fn get_tenant_balance(
+    Path(tenant_id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+) -> Result<Json<TenantBalance>, ErrorResponse> {
+    bill_service::get_tenant_balance(&db, tenant_id, config.billing.default_currency)
+        .await
+        .map(Json)
+        .map_err(map_write_error)
+}
+
+/// DELETE /bills/:id (requires If-Match)
 pub async fn delete_bill(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<StatusCode, StatusCode> {
-    match bill_service::delete_bill_with_charges(&db, id).await {
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ErrorResponse> {
+    let expected_version = if_match(&headers)?;
+    match bill_service::delete_bill(&db, &claims.actor(), id, expected_version).await {
         Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(None) => Err(error_json(StatusCode::NOT_FOUND, "Bill not found")),
+        Err(err) => Err(map_write_error(err)),
+    }
+}
+
+fn map_document_error(err: DocumentError) -> ErrorResponse {
+    match err {
+        DocumentError::BillNotFound => error_json(StatusCode::NOT_FOUND, "Bill not found"),
+        DocumentError::BillNotPaid => {
+            error_json(StatusCode::CONFLICT, "A receipt can only be issued for a paid bill")
+        }
+        _ => error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate document"),
+    }
+}
+
+/// 404 unless the caller may see the bill: admins always, tenants only their own
+async fn check_bill_visible(db: &DatabaseConnection, claims: &Claims, id: i32) -> Result<(), ErrorResponse> {
+    match bill_service::get_bill_by_id(db, id).await {
+        Ok(Some(b)) if claims.is_admin() || claims.id == Some(b.tenant_id) => Ok(()),
+        Ok(_) => Err(error_json(StatusCode::NOT_FOUND, "Bill not found")),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load bill")),
+    }
+}
+
+async fn generate_document(
+    db: &DatabaseConnection,
+    r2: &R2Config,
+    config: &Config,
+    claims: &Claims,
+    bill_id: i32,
+    kind: DocumentKind,
+) -> Result<(StatusCode, Json<serde_json::Value>), ErrorResponse> {
+    check_bill_visible(db, claims, bill_id).await?;
+    let document =
+        document_service::generate_bill_document(db, r2, bill_id, kind, &config.templates.branding, Some(claims.actor()))
+            .await
+            .map_err(map_document_error)?;
+
+    let url = r2_service::get_signed_url(r2, &document.attachment.object_key, 600)
+        .await
+        .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to generate URL"))?;
+
+    Ok((
+        if document.created { StatusCode::CREATED } else { StatusCode::OK },
+        Json(serde_json::json!({ "attachment": document.attachment, "url": url })),
+    ))
+}
+
+/// POST /bills/:id/invoice
+pub async fn generate_invoice_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(r2): Extension<R2Config>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ErrorResponse> {
+    generate_document(&db, &r2, &config, &claims, id, DocumentKind::Invoice).await
+}
+
+/// POST /bills/:id/receipt
+pub async fn generate_receipt_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(r2): Extension<R2Config>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ErrorResponse> {
+    generate_document(&db, &r2, &config, &claims, id, DocumentKind::Receipt).await
+}
+
+/// GET /bills/:id/render?format=html|text&locale=en
+pub async fn render_bill_handler(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Query(query): Query<RenderQuery>,
+) -> Result<Response, ErrorResponse> {
+    check_bill_visible(&db, &claims, id).await?;
+    let format = RenderFormat::parse(query.format.as_deref().unwrap_or("html"))
+        .ok_or_else(|| error_json(StatusCode::BAD_REQUEST, "format must be 'html' or 'text'"))?;
+    let locale = query.locale.unwrap_or_else(|| "en".to_string());
+
+    match template_service::render_bill(&db, &config.templates, id, format, &locale).await {
+        Ok(body) => Ok(([(header::CONTENT_TYPE, format.content_type())], body).into_response()),
+        Err(RenderError::BillNotFound) => Err(error_json(StatusCode::NOT_FOUND, "Bill not found")),
+        Err(RenderError::Template(msg)) => Err(error_json(StatusCode::UNPROCESSABLE_ENTITY, msg)),
+        Err(RenderError::Db(_)) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to render bill")),
     }
 }
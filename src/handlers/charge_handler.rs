@@ -0,0 +1,217 @@
+use crate::entities::{charge_type, recurring_charge};
+use crate::middleware::jwt::Claims;
+use crate::money;
+use crate::services::charge_service;
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+    http::StatusCode,
+};
+use sea_orm::ActiveValue::{NotSet, Set};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
+
+fn map_db_error(err: DbErr, not_found: &str) -> ErrorResponse {
+    match err {
+        DbErr::RecordNotUpdated => error_json(StatusCode::NOT_FOUND, not_found),
+        // Unique code or foreign key violations
+        DbErr::Query(_) | DbErr::Exec(_) => error_json(StatusCode::CONFLICT, &err.to_string()),
+        _ => error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChargeTypeInput {
+    /// Lowercase key used in reports, e.g. `water`
+    pub code: String,
+    pub name: String,
+    #[serde(default, with = "money::decimal")]
+    pub default_amount: i64,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct RecurringChargeInput {
+    pub charge_type_id: i32,
+    pub tenant_id: Option<i32>,
+    pub room_id: Option<i32>,
+    /// Defaults to the charge type's amount
+    #[serde(default, with = "money::decimal_option")]
+    pub amount: Option<i64>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct RecurringChargeQuery {
+    pub tenant_id: Option<i32>,
+    pub room_id: Option<i32>,
+}
+
+fn validate_charge_type(payload: &ChargeTypeInput) -> Result<(), ErrorResponse> {
+    let code_ok = !payload.code.is_empty()
+        && payload.code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !code_ok || payload.code == charge_service::UNCATEGORIZED || payload.code == charge_service::DISCOUNTS {
+        return Err(error_json(StatusCode::BAD_REQUEST, "code must be lowercase letters, digits or '_'"));
+    }
+    if payload.name.trim().is_empty() {
+        return Err(error_json(StatusCode::BAD_REQUEST, "name is required"));
+    }
+    if payload.default_amount < 0 {
+        return Err(error_json(StatusCode::BAD_REQUEST, "default_amount cannot be negative"));
+    }
+    Ok(())
+}
+
+fn validate_recurring(payload: &RecurringChargeInput) -> Result<(), ErrorResponse> {
+    if payload.tenant_id.is_none() && payload.room_id.is_none() {
+        return Err(error_json(StatusCode::BAD_REQUEST, "tenant_id or room_id is required"));
+    }
+    if payload.amount.is_some_and(|a| a < 0) {
+        return Err(error_json(StatusCode::BAD_REQUEST, "amount cannot be negative"));
+    }
+    Ok(())
+}
+
+/// GET /admin/charge-types
+pub async fn get_charge_types(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<charge_type::Model>>, StatusCode> {
+    charge_service::get_charge_types(&db)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/charge-types
+pub async fn create_charge_type(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ChargeTypeInput>,
+) -> Result<(StatusCode, Json<charge_type::Model>), ErrorResponse> {
+    validate_charge_type(&payload)?;
+    let active_model = charge_type::ActiveModel {
+        code: Set(payload.code),
+        name: Set(payload.name.trim().to_string()),
+        default_amount: Set(payload.default_amount),
+        is_active: Set(payload.is_active.unwrap_or(true)),
+        ..Default::default()
+    };
+
+    charge_service::create_charge_type(&db, &claims.actor(), active_model)
+        .await
+        .map(|t| (StatusCode::CREATED, Json(t)))
+        .map_err(|e| map_db_error(e, "Charge type not found"))
+}
+
+/// PUT /admin/charge-types/:id
+pub async fn update_charge_type(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ChargeTypeInput>,
+) -> Result<Json<charge_type::Model>, ErrorResponse> {
+    validate_charge_type(&payload)?;
+    let active_model = charge_type::ActiveModel {
+        code: Set(payload.code),
+        name: Set(payload.name.trim().to_string()),
+        default_amount: Set(payload.default_amount),
+        is_active: payload.is_active.map_or(NotSet, Set),
+        ..Default::default()
+    };
+
+    charge_service::update_charge_type(&db, &claims.actor(), id, active_model)
+        .await
+        .map(Json)
+        .map_err(|e| map_db_error(e, "Charge type not found"))
+}
+
+/// DELETE /admin/charge-types/:id (retires the type; bills keep their category)
+pub async fn retire_charge_type(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<charge_type::Model>, ErrorResponse> {
+    charge_service::retire_charge_type(&db, &claims.actor(), id)
+        .await
+        .map(Json)
+        .map_err(|e| map_db_error(e, "Charge type not found"))
+}
+
+/// GET /admin/recurring-charges?tenant_id=&room_id=
+pub async fn get_recurring_charges(
+    Query(query): Query<RecurringChargeQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<recurring_charge::Model>>, StatusCode> {
+    charge_service::get_recurring_charges(&db, query.tenant_id, query.room_id)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/recurring-charges
+pub async fn create_recurring_charge(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<RecurringChargeInput>,
+) -> Result<(StatusCode, Json<recurring_charge::Model>), ErrorResponse> {
+    validate_recurring(&payload)?;
+    let active_model = recurring_charge::ActiveModel {
+        charge_type_id: Set(payload.charge_type_id),
+        tenant_id: Set(payload.tenant_id),
+        room_id: Set(payload.room_id),
+        amount: Set(payload.amount),
+        description: Set(payload.description),
+        is_active: Set(payload.is_active.unwrap_or(true)),
+        ..Default::default()
+    };
+
+    charge_service::create_recurring_charge(&db, &claims.actor(), active_model)
+        .await
+        .map(|r| (StatusCode::CREATED, Json(r)))
+        .map_err(|e| map_db_error(e, "Recurring charge not found"))
+}
+
+/// PUT /admin/recurring-charges/:id
+pub async fn update_recurring_charge(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<RecurringChargeInput>,
+) -> Result<Json<recurring_charge::Model>, ErrorResponse> {
+    validate_recurring(&payload)?;
+    let active_model = recurring_charge::ActiveModel {
+        charge_type_id: Set(payload.charge_type_id),
+        tenant_id: Set(payload.tenant_id),
+        room_id: Set(payload.room_id),
+        amount: Set(payload.amount),
+        description: Set(payload.description),
+        is_active: payload.is_active.map_or(NotSet, Set),
+        ..Default::default()
+    };
+
+    charge_service::update_recurring_charge(&db, &claims.actor(), id, active_model)
+        .await
+        .map(Json)
+        .map_err(|e| map_db_error(e, "Recurring charge not found"))
+}
+
+/// DELETE /admin/recurring-charges/:id
+pub async fn delete_recurring_charge(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, StatusCode> {
+    match charge_service::delete_recurring_charge(&db, &claims.actor(), id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
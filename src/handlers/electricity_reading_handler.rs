@@ -1,9 +1,28 @@
+use crate::config::SharedConfig;
+use crate::middleware::jwt::Claims;
+use crate::entities::attachment::{self, OWNER_READING};
 use crate::entities::electricity_reading;
+use crate::handlers::bill_handler::map_upload_error;
+use crate::handlers::property_handler;
+use crate::repository::electricity_reading_repo::ReadingFilter;
+use crate::services::attachment_service::{self, NewAttachment};
 use crate::services::electricity_reading_service;
-use axum::{Extension, Json, extract::Path, http::StatusCode};
+use crate::services::r2_service::R2Config;
+use crate::services::reading_batch_service::{self, BatchReadingInput, BatchResult};
+use crate::services::upload_validation::{self, FileCategory};
+use crate::services::versioning::WriteError;
+use axum::{Extension, Json, extract::{Multipart, Path, Query}, http::StatusCode};
+use chrono::NaiveDateTime;
 use sea_orm::ActiveValue::Set;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
 
 #[derive(Deserialize)]
 pub struct ReadingInput {
@@ -13,11 +32,38 @@ pub struct ReadingInput {
     pub curr_reading: i32,
 }
 
-/// GET /readings
+#[derive(Deserialize)]
+pub struct ReadingListQuery {
+    pub tenant_id: Option<i32>,
+    pub room_id: Option<i32>,
+    pub flagged: Option<bool>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub property_id: Option<i32>,
+}
+
+impl ReadingListQuery {
+    /// The filter for this query within the caller's properties; 403 for someone else's property
+    pub fn filter(self, claims: &Claims) -> Result<ReadingFilter, StatusCode> {
+        let scope = property_handler::scope_for(claims, self.property_id)?;
+        Ok(ReadingFilter {
+            tenant_id: self.tenant_id,
+            room_id: self.room_id,
+            flagged: self.flagged,
+            from: self.from,
+            to: self.to,
+            property_ids: scope.ids().map(<[i32]>::to_vec),
+        })
+    }
+}
+
+/// GET /readings?tenant_id=1&room_id=2&flagged=true&from=..&to=..&property_id=1
 pub async fn get_readings(
+    Query(query): Query<ReadingListQuery>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<Json<Vec<electricity_reading::Model>>, StatusCode> {
-    electricity_reading_service::get_all_readings(&db)
+    electricity_reading_service::get_all_readings(&db, &query.filter(&claims)?)
         .await
         .map(Json)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
@@ -38,8 +84,10 @@ pub async fn get_reading(
 /// POST /readings
 pub async fn create_reading(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<ReadingInput>,
 ) -> Result<(StatusCode, Json<electricity_reading::Model>), StatusCode> {
+    property_handler::check_room(&db, &claims, payload.room_id).await?;
     let active_model = electricity_reading::ActiveModel {
         tenant_id: Set(payload.tenant_id),
         room_id: Set(payload.room_id),
@@ -48,18 +96,40 @@ pub async fn create_reading(
         ..Default::default()
     };
 
-    electricity_reading_service::create_reading(&db, active_model)
+    electricity_reading_service::create_reading(&db, &claims.actor(), active_model)
         .await
         .map(|reading| (StatusCode::CREATED, Json(reading)))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+/// POST /readings/batch
+///
+/// Each reading is saved or rejected on its own; see `results` for which
+pub async fn create_reading_batch(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<BatchReadingInput>,
+) -> Result<Json<BatchResult>, ErrorResponse> {
+    for item in &payload.readings {
+        property_handler::check_room(&db, &claims, item.room_id)
+            .await
+            .map_err(|status| error_json(status, &format!("room {} is not in your properties", item.room_id)))?;
+    }
+    match reading_batch_service::create_batch(&db, &claims.actor(), payload).await {
+        Ok(result) => Ok(Json(result)),
+        Err(WriteError::Invalid(msg)) => Err(error_json(StatusCode::BAD_REQUEST, &msg)),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save readings")),
+    }
+}
+
 /// PUT /readings/:id
 pub async fn update_reading(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<ReadingInput>,
 ) -> Result<(StatusCode, Json<electricity_reading::Model>), StatusCode> {
+    property_handler::check_room(&db, &claims, payload.room_id).await?;
     let active_model = electricity_reading::ActiveModel {
         id: Set(id),
         tenant_id: Set(payload.tenant_id),
@@ -69,7 +139,7 @@ pub async fn update_reading(
         ..Default::default()
     };
 
-    match electricity_reading_service::update_reading(&db, id, active_model).await {
+    match electricity_reading_service::update_reading(&db, &claims.actor(), id, active_model).await {
         Ok(updated) => Ok((StatusCode::OK, Json(updated))),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -79,10 +149,89 @@ pub async fn update_reading(
 pub async fn delete_reading(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<StatusCode, StatusCode> {
-    match electricity_reading_service::delete_reading(&db, id).await {
+    match electricity_reading_service::delete_reading(&db, &claims.actor(), id).await {
         Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
+
+/// The reading, if the caller may see it: admins always, tenants only their own
+async fn visible_reading(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    id: i32,
+) -> Result<electricity_reading::Model, ErrorResponse> {
+    match electricity_reading_service::get_reading_by_id(db, id).await {
+        Ok(Some(r)) if claims.is_admin() || claims.id == Some(r.tenant_id) => Ok(r),
+        Ok(_) => Err(error_json(StatusCode::NOT_FOUND, "Reading not found")),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load reading")),
+    }
+}
+
+/// GET /readings/:id/photos; view one through `/api/signed-urls/attachments/:id`
+pub async fn get_reading_photos(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<attachment::Model>>, ErrorResponse> {
+    visible_reading(&db, &claims, id).await?;
+    attachment_service::get_reading_photos(&db, id)
+        .await
+        .map(Json)
+        .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load photos"))
+}
+
+/// POST /readings/:id/photos (admin only; multipart with a `photo` image of the meter)
+pub async fn upload_reading_photo(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(r2): Extension<R2Config>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<attachment::Model>), ErrorResponse> {
+    let reading = visible_reading(&db, &claims, id).await?;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| error_json(e.status(), &e.body_text()))?
+    {
+        if field.name() != Some("photo") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or("photo").to_string();
+        let bytes = field.bytes().await.map_err(|e| error_json(e.status(), &e.body_text()))?;
+
+        let upload = upload_validation::validate_upload(&file_name, bytes, &config.upload)
+            .await
+            .map_err(map_upload_error)?;
+        if upload.kind.category() != FileCategory::Image {
+            return Err(error_json(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Meter photos must be JPEG, PNG or WebP"));
+        }
+
+        let key = attachment_service::object_key(&format!("readings/{}", reading.id), upload.kind.extension());
+        let attachment = attachment_service::upload_attachment(
+            &db,
+            &r2,
+            NewAttachment {
+                object_key: key,
+                owner_type: OWNER_READING.to_string(),
+                owner_id: reading.id,
+                file_name: Some(file_name),
+                content_type: upload.kind.mime_type().to_string(),
+                bytes: upload.bytes,
+            },
+            Some(claims.actor()),
+        )
+        .await
+        .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store photo"))?;
+
+        return Ok((StatusCode::CREATED, Json(attachment)));
+    }
+
+    Err(error_json(StatusCode::BAD_REQUEST, "Missing photo"))
+}
@@ -0,0 +1,66 @@
+//! `ETag` / `If-Match` handling for versioned rows (see `services::versioning`)
+
+use crate::services::versioning::WriteError;
+use axum::http::{HeaderMap, HeaderName, StatusCode, header};
+
+pub type ETagHeader = [(HeaderName, String); 1];
+
+/// `ETag: "<version>"`
+pub fn etag_header(version: i32) -> ETagHeader {
+    [(header::ETAG, format!("\"{}\"", version))]
+}
+
+/// The version a write is conditioned on.
+/// A missing `If-Match` is 428, an unreadable one 412; `*` matches any version.
+pub fn if_match(headers: &HeaderMap) -> Result<Option<i32>, StatusCode> {
+    let value = headers
+        .get(header::IF_MATCH)
+        .ok_or(StatusCode::PRECONDITION_REQUIRED)?
+        .to_str()
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?
+        .trim();
+
+    if value == "*" {
+        return Ok(None);
+    }
+
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse()
+        .map(Some)
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)
+}
+
+pub fn write_error_status(err: &WriteError) -> StatusCode {
+    match err {
+        WriteError::NotFound => StatusCode::NOT_FOUND,
+        WriteError::VersionMismatch => StatusCode::PRECONDITION_FAILED,
+        WriteError::InvalidState(_) => StatusCode::CONFLICT,
+        WriteError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        WriteError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers_with(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_if_match() {
+        assert_eq!(if_match(&HeaderMap::new()), Err(StatusCode::PRECONDITION_REQUIRED));
+        assert_eq!(if_match(&headers_with("\"4\"")), Ok(Some(4)));
+        assert_eq!(if_match(&headers_with("W/\"4\"")), Ok(Some(4)));
+        assert_eq!(if_match(&headers_with("*")), Ok(None));
+        assert_eq!(if_match(&headers_with("\"abc\"")), Err(StatusCode::PRECONDITION_FAILED));
+        assert_eq!(etag_header(4)[0].1, "\"4\"");
+    }
+}
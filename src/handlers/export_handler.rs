@@ -0,0 +1,109 @@
+use crate::handlers::bill_handler::BillListQuery;
+use crate::handlers::electricity_reading_handler::ReadingListQuery;
+use crate::handlers::tenant_handler::TenantListQuery;
+use crate::middleware::jwt::Claims;
+use crate::services::export_service::{self, Export, ExportFormat};
+use axum::{
+    Extension, Json,
+    body::Body,
+    extract::Query,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
+
+fn forbidden(status: StatusCode) -> ErrorResponse {
+    error_json(status, "Not in your properties")
+}
+
+#[derive(Deserialize)]
+pub struct FormatQuery {
+    /// `csv` (default) or `xlsx`
+    pub format: Option<String>,
+}
+
+/// Stream CSV straight from the database; XLSX is assembled first since the
+/// zip container can't be sent until the sheet is complete
+async fn export(db: DatabaseConnection, format: FormatQuery, export: Export) -> Result<Response, ErrorResponse> {
+    let format = match format.format.as_deref() {
+        None => ExportFormat::Csv,
+        Some(value) => ExportFormat::parse(value)
+            .ok_or_else(|| error_json(StatusCode::BAD_REQUEST, "format must be csv or xlsx"))?,
+    };
+    let filename = format!(
+        "{}-{}.{}",
+        export.name(),
+        Utc::now().format("%Y%m%d"),
+        format.extension()
+    );
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+    ];
+
+    match format {
+        ExportFormat::Csv => {
+            let body = Body::from_stream(export_service::stream_csv(db, export));
+            Ok((headers, body).into_response())
+        }
+        ExportFormat::Xlsx => match export_service::build_xlsx(&db, &export).await {
+            Ok(bytes) => Ok((headers, bytes).into_response()),
+            Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build export")),
+        },
+    }
+}
+
+/// GET /admin/exports/bills?format=xlsx&tenant_id=1&status=unpaid&from=..&to=..&property_id=1
+pub async fn export_bills(
+    Query(format): Query<FormatQuery>,
+    Query(query): Query<BillListQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Response, ErrorResponse> {
+    let filter = query.filter(&claims).map_err(forbidden)?;
+    export(db, format, Export::Bills(filter)).await
+}
+
+/// GET /admin/exports/payments?format=csv&tenant_id=1&from=..&to=..
+///
+/// Paid bills, with `from`/`to` applied to the payment date
+pub async fn export_payments(
+    Query(format): Query<FormatQuery>,
+    Query(query): Query<BillListQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Response, ErrorResponse> {
+    let filter = query.filter(&claims).map_err(forbidden)?;
+    export(db, format, Export::Payments(filter)).await
+}
+
+/// GET /admin/exports/readings?format=csv&room_id=2&flagged=true&from=..&to=..
+pub async fn export_readings(
+    Query(format): Query<FormatQuery>,
+    Query(query): Query<ReadingListQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Response, ErrorResponse> {
+    let filter = query.filter(&claims).map_err(forbidden)?;
+    export(db, format, Export::Readings(filter)).await
+}
+
+/// GET /admin/exports/tenants?format=csv&room_id=2&is_active=true
+pub async fn export_tenants(
+    Query(format): Query<FormatQuery>,
+    Query(query): Query<TenantListQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Response, ErrorResponse> {
+    let filter = query.filter(&claims).map_err(forbidden)?;
+    export(db, format, Export::Tenants(filter)).await
+}
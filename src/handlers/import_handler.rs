@@ -0,0 +1,49 @@
+use crate::config::SharedConfig;
+use crate::handlers::property_handler;
+use crate::middleware::jwt::Claims;
+use crate::services::property_service;
+use crate::services::import_service::{self, ImportKind, ImportReport};
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query},
+    http::StatusCode,
+};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    /// Validate and roll back instead of saving
+    pub dry_run: Option<bool>,
+    /// The property names are looked up in; the caller's first one when unset
+    pub property_id: Option<i32>,
+}
+
+/// POST /admin/imports/:kind?dry_run=true&property_id=2 with a CSV body; `kind` is `rooms`, `tenants` or `readings`
+///
+/// `200` with the report when every row is valid, `422` with the per-row errors otherwise,
+/// `403` for a property outside the caller's
+pub async fn import_csv(
+    Path(kind): Path<String>,
+    Query(query): Query<ImportQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ImportReport>), StatusCode> {
+    let kind = ImportKind::parse(&kind).ok_or(StatusCode::NOT_FOUND)?;
+    let dry_run = query.dry_run.unwrap_or(false);
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    let property_id = match property_service::default_property_id(&db, &scope).await {
+        Ok(Some(id)) => id,
+        Ok(None) => return Err(StatusCode::UNPROCESSABLE_ENTITY),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match import_service::import_csv(&db, &claims.actor(), property_id, config.billing.default_currency, kind, &body, dry_run).await {
+        Ok(report) if report.errors.is_empty() => Ok((StatusCode::OK, Json(report))),
+        Ok(report) => Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(report))),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::entities::master_meter_reading;
+use crate::handlers::property_handler::{self, PropertyQuery};
+use crate::middleware::jwt::Claims;
+use crate::repository::property_repo::PropertyOwner;
+use crate::services::{consumption_service, property_service};
+use axum::{Extension, Json, extract::{Path, Query}, http::StatusCode};
+use chrono::NaiveDateTime;
+use sea_orm::ActiveValue::Set;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct MasterReadingInput {
+    pub reading_date: NaiveDateTime,
+    pub prev_reading: i32,
+    pub curr_reading: i32,
+    pub notes: Option<String>,
+    /// The building the meter is for; the caller's first property when unset
+    pub property_id: Option<i32>,
+}
+
+/// GET /admin/master-meter?property_id=
+pub async fn get_master_readings(
+    Query(query): Query<PropertyQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<master_meter_reading::Model>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    consumption_service::get_master_readings(&db, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/master-meter
+pub async fn create_master_reading(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<MasterReadingInput>,
+) -> Result<(StatusCode, Json<master_meter_reading::Model>), StatusCode> {
+    if payload.curr_reading < payload.prev_reading {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let scope = property_handler::scope_for(&claims, payload.property_id)?;
+    let property_id = property_service::default_property_id(&db, &scope)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let active_model = master_meter_reading::ActiveModel {
+        property_id: Set(property_id),
+        reading_date: Set(payload.reading_date),
+        prev_reading: Set(payload.prev_reading),
+        curr_reading: Set(payload.curr_reading),
+        notes: Set(payload.notes),
+        ..Default::default()
+    };
+
+    consumption_service::create_master_reading(&db, &claims.actor(), active_model)
+        .await
+        .map(|reading| (StatusCode::CREATED, Json(reading)))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// DELETE /admin/master-meter/:id
+pub async fn delete_master_reading(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, StatusCode> {
+    property_handler::check_owner(&db, &claims, PropertyOwner::MasterReading(id)).await?;
+    match consumption_service::delete_master_reading(&db, &claims.actor(), id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
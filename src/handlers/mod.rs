@@ -4,4 +4,18 @@ pub mod room_handler;
 pub mod tenant_handler;
 pub mod electricity_reading_handler;
 pub mod bill_handler;
+pub mod template_handler;
 
+
+pub mod webhook_handler;
+pub mod audit_handler;
+pub mod trash_handler;
+pub mod etag;
+pub mod sync_handler;
+pub mod charge_handler;
+pub mod report_handler;
+pub mod master_meter_handler;
+pub mod export_handler;
+pub mod import_handler;
+pub mod property_handler;
+pub mod admin_user_handler;
@@ -0,0 +1,179 @@
+use crate::entities::property;
+use crate::middleware::jwt::Claims;
+use crate::money;
+use crate::repository::property_repo::PropertyOwner;
+use crate::services::property_service::{self, PropertyScope};
+use crate::services::versioning::WriteError;
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
+
+/// `?property_id=` on lists, reports and exports
+#[derive(Deserialize)]
+pub struct PropertyQuery {
+    pub property_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct PropertyInput {
+    pub name: String,
+    pub address: Option<String>,
+    pub contact: Option<String>,
+    pub logo_url: Option<String>,
+    /// e.g. `#1f4e79`; `BRAND_ACCENT_COLOR` when unset
+    pub accent_color: Option<String>,
+    /// Price per kWh used when a reading batch drafts bills without a rate
+    #[serde(default, with = "money::decimal_option")]
+    pub electric_rate: Option<i64>,
+    /// `BILL_DUE_DAYS` when unset
+    pub bill_due_days: Option<i32>,
+}
+
+/// The caller's properties, narrowed to `property_id` when given; 403 if it is not theirs
+pub fn scope_for(claims: &Claims, property_id: Option<i32>) -> Result<PropertyScope, StatusCode> {
+    PropertyScope::for_claims(claims).narrow(property_id).ok_or(StatusCode::FORBIDDEN)
+}
+
+/// 404 if the row does not exist, 403 if it belongs to another property
+pub async fn check_owner(db: &DatabaseConnection, claims: &Claims, owner: PropertyOwner) -> Result<(), StatusCode> {
+    match property_service::owner_in_scope(db, &PropertyScope::for_claims(claims), owner).await {
+        Ok(Some(true)) => Ok(()),
+        Ok(Some(false)) => Err(StatusCode::FORBIDDEN),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// A room named in a request body must be in the caller's properties; unknown rooms are
+/// left to the foreign key or the service to report
+pub async fn check_room(db: &DatabaseConnection, claims: &Claims, room_id: i32) -> Result<(), StatusCode> {
+    if PropertyScope::for_claims(claims) == PropertyScope::All {
+        return Ok(());
+    }
+    match check_owner(db, claims, PropertyOwner::Room(room_id)).await {
+        Err(StatusCode::NOT_FOUND) => Ok(()),
+        other => other,
+    }
+}
+
+fn require_superadmin(claims: &Claims) -> Result<(), ErrorResponse> {
+    if claims.is_superadmin() {
+        Ok(())
+    } else {
+        Err(error_json(StatusCode::FORBIDDEN, "Only the main admin can manage properties"))
+    }
+}
+
+fn validate(payload: &PropertyInput) -> Result<(), ErrorResponse> {
+    if payload.name.trim().is_empty() {
+        return Err(error_json(StatusCode::BAD_REQUEST, "name is required"));
+    }
+    if payload.electric_rate.is_some_and(|r| r < 0) {
+        return Err(error_json(StatusCode::BAD_REQUEST, "electric_rate cannot be negative"));
+    }
+    if payload.bill_due_days.is_some_and(|d| d < 0) {
+        return Err(error_json(StatusCode::BAD_REQUEST, "bill_due_days cannot be negative"));
+    }
+    Ok(())
+}
+
+fn map_db_error(err: DbErr) -> ErrorResponse {
+    match err {
+        DbErr::RecordNotUpdated => error_json(StatusCode::NOT_FOUND, "Property not found"),
+        // Duplicate names
+        DbErr::Query(_) | DbErr::Exec(_) => error_json(StatusCode::CONFLICT, &err.to_string()),
+        _ => error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+    }
+}
+
+fn active_model(payload: PropertyInput) -> property::ActiveModel {
+    property::ActiveModel {
+        name: Set(payload.name.trim().to_string()),
+        address: Set(payload.address),
+        contact: Set(payload.contact),
+        logo_url: Set(payload.logo_url),
+        accent_color: Set(payload.accent_color),
+        electric_rate: Set(payload.electric_rate),
+        bill_due_days: Set(payload.bill_due_days),
+        ..Default::default()
+    }
+}
+
+/// GET /admin/properties, limited to the caller's properties
+pub async fn get_properties(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<property::Model>>, StatusCode> {
+    property_service::get_properties(&db, &PropertyScope::for_claims(&claims))
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/properties/:id
+pub async fn get_property(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<property::Model>, StatusCode> {
+    if !PropertyScope::for_claims(&claims).allows(id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    match property_service::get_property(&db, id).await {
+        Ok(Some(p)) => Ok(Json(p)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// POST /admin/properties
+pub async fn create_property(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PropertyInput>,
+) -> Result<(StatusCode, Json<property::Model>), ErrorResponse> {
+    require_superadmin(&claims)?;
+    validate(&payload)?;
+    property_service::create_property(&db, &claims.actor(), active_model(payload))
+        .await
+        .map(|p| (StatusCode::CREATED, Json(p)))
+        .map_err(map_db_error)
+}
+
+/// PUT /admin/properties/:id
+pub async fn update_property(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PropertyInput>,
+) -> Result<Json<property::Model>, ErrorResponse> {
+    require_superadmin(&claims)?;
+    validate(&payload)?;
+    property_service::update_property(&db, &claims.actor(), id, active_model(payload))
+        .await
+        .map(Json)
+        .map_err(map_db_error)
+}
+
+/// DELETE /admin/properties/:id; refused while the property has rooms
+pub async fn delete_property(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, ErrorResponse> {
+    require_superadmin(&claims)?;
+    match property_service::delete_property(&db, &claims.actor(), id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(WriteError::NotFound) => Err(error_json(StatusCode::NOT_FOUND, "Property not found")),
+        Err(WriteError::InvalidState(msg)) => Err(error_json(StatusCode::CONFLICT, &msg)),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Database error")),
+    }
+}
@@ -0,0 +1,155 @@
+use crate::entities::electricity_reading;
+use crate::handlers::property_handler;
+use crate::middleware::jwt::Claims;
+use crate::repository::report_repo::ConsumptionGroup;
+use crate::services::charge_service::{self, ChargeCategoryTotal};
+use crate::services::consumption_service::{self, BuildingConsumption, ConsumptionSeries};
+use crate::services::occupancy_service::{self, OccupancyReport};
+use crate::services::report_service::{self, ElectricityRevenue, MonthlyIncome, ReceivablesReport, RoomRevenue};
+use axum::{Extension, Json, extract::Query, http::StatusCode};
+use chrono::NaiveDateTime;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct PeriodQuery {
+    /// Inclusive start of the period; bills are dated by issue, payments by when they were made
+    pub from: Option<NaiveDateTime>,
+    /// Exclusive end of the period
+    pub to: Option<NaiveDateTime>,
+    /// Only this property; every property the caller manages when unset
+    pub property_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ConsumptionQuery {
+    /// `room` (default) or `tenant`
+    pub by: Option<String>,
+    /// Only this room or tenant
+    pub id: Option<i32>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub property_id: Option<i32>,
+}
+
+/// GET /admin/reports/charges?from=2026-10-01T00:00:00&to=2026-11-01T00:00:00&property_id=2
+pub async fn get_charge_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<ChargeCategoryTotal>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    charge_service::get_category_totals(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/income?from=&to=
+pub async fn get_income_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<MonthlyIncome>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    report_service::get_monthly_income(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/receivables?from=&to=
+pub async fn get_receivables_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ReceivablesReport>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    report_service::get_receivables(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/rooms?from=&to=
+pub async fn get_room_revenue_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<RoomRevenue>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    report_service::get_revenue_by_room(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/electricity?from=&to=
+pub async fn get_electricity_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<ElectricityRevenue>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    report_service::get_electricity_revenue(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/occupancy?from=&to= (defaults to the last 12 months)
+pub async fn get_occupancy_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<OccupancyReport>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    occupancy_service::get_occupancy_report(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/consumption?by=room|tenant&id=&from=&to=
+pub async fn get_consumption_report(
+    Query(query): Query<ConsumptionQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<ConsumptionSeries>>, StatusCode> {
+    let group = match query.by.as_deref() {
+        None | Some("room") => ConsumptionGroup::Room,
+        Some("tenant") => ConsumptionGroup::Tenant,
+        Some(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    consumption_service::get_consumption_series(&db, group, query.id, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/consumption/building?from=&to=
+pub async fn get_building_consumption_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<BuildingConsumption>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    consumption_service::get_building_consumption(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// GET /admin/reports/consumption/anomalies?from=&to=
+pub async fn get_anomaly_report(
+    Query(query): Query<PeriodQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<electricity_reading::Model>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    consumption_service::get_flagged_readings(&db, query.from, query.to, scope.ids())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
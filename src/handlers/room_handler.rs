@@ -1,6 +1,12 @@
+use crate::config::SharedConfig;
+use crate::middleware::jwt::Claims;
 use crate::entities::room;
-use crate::services::room_service;
-use axum::{Extension, Json, extract::Path, http::StatusCode};
+use crate::handlers::etag::{self, ETagHeader};
+use crate::handlers::property_handler::{self, PropertyQuery};
+use crate::money::{self, Currency};
+use crate::services::occupancy_service::{self, RoomWithStatus};
+use crate::services::{property_service, room_service};
+use axum::{Extension, Json, extract::{Path, Query}, http::{HeaderMap, StatusCode}};
 use sea_orm::ActiveValue::Set;
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
@@ -8,14 +14,24 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 pub struct RoomInput {
     pub name: String,
-    pub rent: i32,
+    #[serde(with = "money::decimal")]
+    pub rent: i64,
+    /// Defaults to `DEFAULT_CURRENCY` on create and is left unchanged on update
+    pub currency: Option<Currency>,
+    /// Defaults to `false` on create and is left unchanged on update
+    pub under_maintenance: Option<bool>,
+    /// Defaults to the caller's first property on create and is left unchanged on update
+    pub property_id: Option<i32>,
 }
 
-/// GET /rooms
+/// GET /rooms?property_id=, each with its occupancy status
 pub async fn get_rooms(
+    Query(query): Query<PropertyQuery>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<Json<Vec<room::Model>>, StatusCode> {
-    let rooms = room_service::get_all_rooms(&db)
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<RoomWithStatus>>, StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    let rooms = occupancy_service::get_rooms_with_status(&db, scope.ids())
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(Json(rooms))
@@ -25,9 +41,9 @@ pub async fn get_rooms(
 pub async fn get_room(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<(StatusCode, Json<room::Model>), StatusCode> {
+) -> Result<(StatusCode, ETagHeader, Json<room::Model>), StatusCode> {
     match room_service::get_room_by_id(&db, id).await {
-        Ok(Some(r)) => Ok((StatusCode::OK, Json(r))),
+        Ok(Some(r)) => Ok((StatusCode::OK, etag::etag_header(r.version), Json(r))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -36,47 +52,69 @@ pub async fn get_room(
 /// POST /rooms
 pub async fn create_room(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<RoomInput>,
 ) -> Result<(StatusCode, Json<room::Model>), StatusCode> {
+    let scope = property_handler::scope_for(&claims, payload.property_id)?;
+    let property_id = property_service::default_property_id(&db, &scope)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::BAD_REQUEST)?;
     let active_model = room::ActiveModel {
+        property_id: Set(property_id),
         name: Set(payload.name),
         rent: Set(payload.rent),
+        currency: Set(payload.currency.unwrap_or(config.billing.default_currency).code().to_string()),
+        under_maintenance: Set(payload.under_maintenance.unwrap_or(false)),
         ..Default::default()
     };
 
-    room_service::create_room(&db, active_model)
+    room_service::create_room(&db, &claims.actor(), active_model)
         .await
         .map(|room| (StatusCode::CREATED, Json(room)))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
-/// PUT /rooms/:id
+/// PUT /rooms/:id (requires If-Match)
 pub async fn update_room(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     Json(payload): Json<RoomInput>,
-) -> Result<(StatusCode, Json<room::Model>), StatusCode> {
+) -> Result<(StatusCode, ETagHeader, Json<room::Model>), StatusCode> {
+    let expected_version = etag::if_match(&headers)?;
+    if let Some(property_id) = payload.property_id {
+        property_handler::scope_for(&claims, Some(property_id))?;
+    }
     let active_model = room::ActiveModel {
         id: Set(id),
+        property_id: payload.property_id.map(Set).unwrap_or_default(),
         name: Set(payload.name),
         rent: Set(payload.rent),
+        currency: payload.currency.map(|c| Set(c.code().to_string())).unwrap_or_default(),
+        under_maintenance: payload.under_maintenance.map(Set).unwrap_or_default(),
         ..Default::default()
     };
 
-    match room_service::update_room(&db, id, active_model).await {
-        Ok(updated) => Ok((StatusCode::OK, Json(updated))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    match room_service::update_room(&db, &claims.actor(), id, expected_version, active_model).await {
+        Ok(updated) => Ok((StatusCode::OK, etag::etag_header(updated.version), Json(updated))),
+        Err(err) => Err(etag::write_error_status(&err)),
     }
 }
 
-/// DELETE /rooms/:id
+/// DELETE /rooms/:id (requires If-Match)
 pub async fn delete_room(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, StatusCode> {
-    match room_service::delete_room(&db, id).await {
+    let expected_version = etag::if_match(&headers)?;
+    match room_service::delete_room(&db, &claims.actor(), id, expected_version).await {
         Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
         Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(err) => Err(etag::write_error_status(&err)),
     }
 }
@@ -4,34 +4,56 @@ use axum::{
     response::IntoResponse,
     Json,
 };
+use sea_orm::DatabaseConnection;
+use crate::middleware::jwt::Claims;
+use crate::services::attachment_service;
 use crate::services::r2_service::get_signed_url;
 use crate::services::r2_service::R2Config;
 
-/// GET /api/files/receipts/:tenant_name/:filename
-pub async fn get_receipt_signed_url_handler(
-    Path((tenant_name, filename)): Path<(String, String)>,
+/// GET /api/signed-urls/attachments/:id
+pub async fn get_attachment_signed_url_handler(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
     Extension(r2): Extension<R2Config>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    let key = format!("receipts/{}/{}", tenant_name, filename);
+    let attachment = match attachment_service::get_attachment_by_id(&db, id).await {
+        Ok(Some(a)) => a,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Attachment not found" })),
+            )
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to load attachment" })),
+            )
+        }
+    };
 
-    match get_signed_url(&r2, &key, 600).await {
-        Ok(url) => (StatusCode::OK, Json(serde_json::json!({ "url": url }))),
-        Err(_) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": "Failed to generate URL" })),
-        )
+    match attachment_service::can_view(&db, &claims, &attachment).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": "Not allowed to view this attachment" })),
+            )
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Failed to load attachment" })),
+            )
+        }
     }
-}
 
-/// GET /api/files/payments/:filename
-pub async fn get_payment_signed_url_handler(
-    Path(filename): Path<String>,
-    Extension(r2): Extension<R2Config>,
-) -> impl IntoResponse {
-    let key = format!("payments/{}.png", filename);
-
-    match get_signed_url(&r2, &key, 600).await {
-        Ok(url) => (StatusCode::OK, Json(serde_json::json!({ "url": url }))),
+    match get_signed_url(&r2, &attachment.object_key, 600).await {
+        Ok(url) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "url": url, "attachment": attachment })),
+        ),
         Err(_) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": "Failed to generate URL" })),
@@ -0,0 +1,23 @@
+use crate::config::SharedConfig;
+use crate::services::sync_service::{self, SyncChanges};
+use axum::{Extension, Json, extract::Query, http::StatusCode};
+use chrono::NaiveDateTime;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct SyncQuery {
+    pub since: Option<NaiveDateTime>,
+}
+
+/// GET /admin/sync?since=2026-10-19T08:00:00 (omit `since` for a full snapshot)
+pub async fn get_changes(
+    Query(query): Query<SyncQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+) -> Result<Json<SyncChanges>, StatusCode> {
+    sync_service::get_changes(&db, query.since, config.trash.retention_days)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -0,0 +1,71 @@
+use crate::entities::bill_template;
+use crate::middleware::jwt::Claims;
+use crate::services::template_service::{self, RenderError, RenderFormat};
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct TemplateInput {
+    pub body: String,
+}
+
+fn parse_format(format: &str) -> Result<RenderFormat, (StatusCode, Json<serde_json::Value>)> {
+    RenderFormat::parse(format).ok_or((
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "error": "format must be 'html' or 'text'" })),
+    ))
+}
+
+/// GET /admin/templates
+pub async fn get_templates(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<bill_template::Model>>, StatusCode> {
+    template_service::get_all_templates(&db)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// PUT /admin/templates/:format/:locale
+pub async fn save_template(
+    Path((format, locale)): Path<(String, String)>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<TemplateInput>,
+) -> Result<Json<bill_template::Model>, (StatusCode, Json<serde_json::Value>)> {
+    let format = parse_format(&format)?;
+
+    match template_service::save_template(&db, &claims.actor(), format, &locale, payload.body).await {
+        Ok(t) => Ok(Json(t)),
+        Err(RenderError::Template(msg)) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({ "error": msg })),
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to save template" })),
+        )),
+    }
+}
+
+/// DELETE /admin/templates/:format/:locale
+pub async fn delete_template(
+    Path((format, locale)): Path<(String, String)>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    let format = parse_format(&format)?;
+
+    match template_service::delete_template(&db, &claims.actor(), format, &locale).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Template not found" })),
+        )),
+        Err(_) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": "Failed to delete template" })),
+        )),
+    }
+}
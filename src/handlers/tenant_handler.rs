@@ -1,8 +1,13 @@
+use crate::middleware::jwt::Claims;
 use crate::entities::tenant;
+use crate::handlers::etag::{self, ETagHeader};
+use crate::handlers::property_handler::{self, PropertyQuery};
+use crate::repository::tenant_repo::TenantFilter;
+use crate::services::notification_channel::ALL_CHANNELS;
 use crate::services::tenant_service;
-use axum::{Extension, Json, extract::Path, http::StatusCode};
+use axum::{Extension, Json, extract::{Path, Query}, http::{HeaderMap, StatusCode}};
 use chrono::NaiveDateTime;
-use sea_orm::ActiveValue::Set;
+use sea_orm::ActiveValue::{self, NotSet, Set};
 use sea_orm::DatabaseConnection;
 use serde::Deserialize;
 
@@ -12,13 +17,50 @@ pub struct TenantInput {
     pub room_id: i32,
     pub join_date: NaiveDateTime,
     pub is_active: Option<bool>,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub chat_id: Option<String>,
+    /// Preferred channels, e.g. `["sms", "email"]`; left unchanged when omitted
+    pub notification_channels: Option<Vec<String>>,
 }
 
-/// GET /tenants
+#[derive(Deserialize)]
+pub struct TenantListQuery {
+    pub room_id: Option<i32>,
+    pub is_active: Option<bool>,
+    pub property_id: Option<i32>,
+}
+
+impl TenantListQuery {
+    /// The filter for this query within the caller's properties; 403 for someone else's property
+    pub fn filter(self, claims: &Claims) -> Result<TenantFilter, StatusCode> {
+        let scope = property_handler::scope_for(claims, self.property_id)?;
+        Ok(TenantFilter {
+            room_id: self.room_id,
+            is_active: self.is_active,
+            property_ids: scope.ids().map(<[i32]>::to_vec),
+        })
+    }
+}
+
+/// Validate channel names and join them for storage
+fn channels_value(channels: Option<Vec<String>>) -> Result<ActiveValue<String>, StatusCode> {
+    let Some(channels) = channels else {
+        return Ok(NotSet);
+    };
+    if channels.iter().any(|c| !ALL_CHANNELS.contains(&c.as_str())) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(Set(channels.join(",")))
+}
+
+/// GET /tenants?room_id=2&is_active=true&property_id=1
 pub async fn get_tenants(
+    Query(query): Query<TenantListQuery>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
 ) -> Result<Json<Vec<tenant::Model>>, StatusCode> {
-    let tenants = tenant_service::get_all_tenants(&db)
+    let tenants = tenant_service::get_all_tenants(&db, &query.filter(&claims)?)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(Json(tenants))
@@ -28,74 +70,105 @@ pub async fn get_tenants(
 pub async fn get_tenant(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<(StatusCode, Json<tenant::Model>), StatusCode> {
+) -> Result<(StatusCode, ETagHeader, Json<tenant::Model>), StatusCode> {
     match tenant_service::get_tenant_by_id(&db, id).await {
-        Ok(Some(t)) => Ok((StatusCode::OK, Json(t))),
+        Ok(Some(t)) => Ok((StatusCode::OK, etag::etag_header(t.version), Json(t))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
-/// GET /tenants/by-name/:name
+/// GET /tenants/tenant/:name?property_id=1
+///
+/// 409 when tenants of several properties share the name and no property is given
 pub async fn get_tenant_by_name(
     Path(name): Path<String>,
+    Query(query): Query<PropertyQuery>,
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<(StatusCode, Json<tenant::Model>), StatusCode> {
-    match tenant_service::get_tenant_by_name(&db, &name).await {
-        Ok(Some(t)) => Ok((StatusCode::OK, Json(t))),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, ETagHeader, Json<tenant::Model>), StatusCode> {
+    let scope = property_handler::scope_for(&claims, query.property_id)?;
+    let mut tenants = tenant_service::get_tenants_by_name(&db, &name, query.property_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tenants.retain(|t| scope.allows(t.property_id));
+    match tenants.len() {
+        0 => Err(StatusCode::NOT_FOUND),
+        1 => {
+            let t = tenants.remove(0);
+            Ok((StatusCode::OK, etag::etag_header(t.version), Json(t)))
+        }
+        _ => Err(StatusCode::CONFLICT),
     }
 }
 
 /// POST /tenants
 pub async fn create_tenant(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<TenantInput>,
 ) -> Result<(StatusCode, Json<tenant::Model>), StatusCode> {
+    let notification_channels = channels_value(payload.notification_channels)?;
+    property_handler::check_room(&db, &claims, payload.room_id).await?;
     let active_model = tenant::ActiveModel {
         name: Set(payload.name),
         room_id: Set(payload.room_id),
         join_date: Set(payload.join_date),
         is_active: Set(payload.is_active.unwrap_or(true)),
+        email: Set(payload.email),
+        phone_number: Set(payload.phone_number),
+        chat_id: Set(payload.chat_id),
+        notification_channels,
         ..Default::default()
     };
 
-    tenant_service::create_tenant(&db, active_model)
+    tenant_service::create_tenant(&db, &claims.actor(), active_model)
         .await
         .map(|tenant| (StatusCode::CREATED, Json(tenant)))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
-/// PUT /tenants/:id
+/// PUT /tenants/:id (requires If-Match)
 pub async fn update_tenant(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     Json(payload): Json<TenantInput>,
-) -> Result<(StatusCode, Json<tenant::Model>), StatusCode> {
+) -> Result<(StatusCode, ETagHeader, Json<tenant::Model>), StatusCode> {
+    let expected_version = etag::if_match(&headers)?;
+    let notification_channels = channels_value(payload.notification_channels)?;
+    property_handler::check_room(&db, &claims, payload.room_id).await?;
     let active_model = tenant::ActiveModel {
         id: Set(id),
         name: Set(payload.name),
         room_id: Set(payload.room_id),
         join_date: Set(payload.join_date),
         is_active: Set(payload.is_active.unwrap_or(true)),
+        email: Set(payload.email),
+        phone_number: Set(payload.phone_number),
+        chat_id: Set(payload.chat_id),
+        notification_channels,
         ..Default::default()
     };
 
-    match tenant_service::update_tenant(&db, id, active_model).await {
-        Ok(updated) => Ok((StatusCode::OK, Json(updated))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    match tenant_service::update_tenant(&db, &claims.actor(), id, expected_version, active_model).await {
+        Ok(updated) => Ok((StatusCode::OK, etag::etag_header(updated.version), Json(updated))),
+        Err(err) => Err(etag::write_error_status(&err)),
     }
 }
 
-/// DELETE /tenants/:id
+/// DELETE /tenants/:id (requires If-Match)
 pub async fn delete_tenant(
     Path(id): Path<i32>,
     Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, StatusCode> {
-    match tenant_service::delete_tenant(&db, id).await {
+    let expected_version = etag::if_match(&headers)?;
+    match tenant_service::delete_tenant(&db, &claims.actor(), id, expected_version).await {
         Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
         Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(err) => Err(etag::write_error_status(&err)),
     }
 }
@@ -0,0 +1,52 @@
+use crate::config::SharedConfig;
+use crate::middleware::jwt::Claims;
+use crate::services::trash_service::{self, PurgeSummary, Trash, TrashEntity};
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use serde_json::{Value, json};
+
+type ErrorResponse = (StatusCode, Json<Value>);
+
+fn error_json(status: StatusCode, msg: impl Into<String>) -> ErrorResponse {
+    (status, Json(json!({ "error": msg.into() })))
+}
+
+/// GET /admin/trash
+pub async fn get_trash(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Trash>, StatusCode> {
+    trash_service::get_trash(&db)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/trash/:entity/:id/restore (entity: rooms, tenants, readings or bills)
+pub async fn restore_from_trash(
+    Path((entity, id)): Path<(String, i32)>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Value>, ErrorResponse> {
+    let entity = TrashEntity::parse(&entity).ok_or_else(|| {
+        error_json(StatusCode::BAD_REQUEST, "entity must be one of rooms, tenants, readings, bills")
+    })?;
+
+    match trash_service::restore(&db, &claims.actor(), entity, id).await {
+        Ok(Some(restored)) => Ok(Json(restored)),
+        Ok(None) => Err(error_json(StatusCode::NOT_FOUND, "Not found in the trash")),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to restore")),
+    }
+}
+
+/// POST /admin/trash/purge (removes rows older than the retention window now)
+pub async fn purge_trash(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(config): Extension<SharedConfig>,
+) -> Result<Json<PurgeSummary>, StatusCode> {
+    let cutoff = trash_service::retention_cutoff(Utc::now().naive_utc(), config.trash.retention_days);
+    trash_service::purge(&db, cutoff)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
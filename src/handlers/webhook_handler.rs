@@ -0,0 +1,151 @@
+use crate::entities::{webhook_delivery, webhook_endpoint};
+use crate::middleware::jwt::Claims;
+use crate::services::webhook_service;
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+    http::StatusCode,
+};
+use sea_orm::ActiveValue::{NotSet, Set};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use serde_json::json;
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_json(status: StatusCode, message: &str) -> ErrorResponse {
+    (status, Json(json!({ "error": message })))
+}
+
+#[derive(Deserialize)]
+pub struct WebhookInput {
+    pub url: String,
+    /// Event names from `webhook_service::ALL_EVENTS`, or `*`
+    pub events: Vec<String>,
+    /// Generated when omitted on create; left unchanged when omitted on update
+    pub secret: Option<String>,
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct DeliveryQuery {
+    pub limit: Option<u64>,
+}
+
+fn validate(payload: &WebhookInput) -> Result<(), ErrorResponse> {
+    if !(payload.url.starts_with("https://") || payload.url.starts_with("http://")) {
+        return Err(error_json(StatusCode::BAD_REQUEST, "url must be an http(s) URL"));
+    }
+    if payload.events.is_empty() || !payload.events.iter().all(|e| webhook_service::is_known_event(e)) {
+        return Err(error_json(
+            StatusCode::BAD_REQUEST,
+            &format!("events must be a non-empty list of: *, {}", webhook_service::ALL_EVENTS.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+/// GET /admin/webhooks
+pub async fn get_webhooks(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<webhook_endpoint::Model>>, StatusCode> {
+    webhook_service::get_all_endpoints(&db)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/webhooks
+/// The response is the only place the signing secret is returned.
+pub async fn create_webhook(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<WebhookInput>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ErrorResponse> {
+    validate(&payload)?;
+
+    let secret = payload
+        .secret
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(webhook_service::generate_secret);
+    let active_model = webhook_endpoint::ActiveModel {
+        url: Set(payload.url),
+        secret: Set(secret.clone()),
+        events: Set(payload.events.join(",")),
+        description: Set(payload.description),
+        is_active: Set(payload.is_active.unwrap_or(true)),
+        ..Default::default()
+    };
+
+    let endpoint = webhook_service::create_endpoint(&db, &claims.actor(), active_model)
+        .await
+        .map_err(|_| error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create webhook"))?;
+
+    let mut body = json!(endpoint);
+    body["secret"] = json!(secret);
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+/// PUT /admin/webhooks/:id
+pub async fn update_webhook(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<WebhookInput>,
+) -> Result<Json<webhook_endpoint::Model>, ErrorResponse> {
+    validate(&payload)?;
+
+    let active_model = webhook_endpoint::ActiveModel {
+        url: Set(payload.url),
+        secret: payload.secret.filter(|s| !s.is_empty()).map_or(NotSet, Set),
+        events: Set(payload.events.join(",")),
+        description: Set(payload.description),
+        is_active: Set(payload.is_active.unwrap_or(true)),
+        ..Default::default()
+    };
+
+    match webhook_service::update_endpoint(&db, &claims.actor(), id, active_model).await {
+        Ok(endpoint) => Ok(Json(endpoint)),
+        Err(sea_orm::DbErr::RecordNotUpdated) => Err(error_json(StatusCode::NOT_FOUND, "Webhook not found")),
+        Err(_) => Err(error_json(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update webhook")),
+    }
+}
+
+/// DELETE /admin/webhooks/:id
+pub async fn delete_webhook(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, StatusCode> {
+    match webhook_service::delete_endpoint(&db, &claims.actor(), id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+/// GET /admin/webhooks/:id/deliveries?limit=50
+pub async fn get_webhook_deliveries(
+    Path(id): Path<i32>,
+    Query(query): Query<DeliveryQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<webhook_delivery::Model>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    webhook_service::get_deliveries(&db, id, limit)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// POST /admin/webhooks/deliveries/:id/replay
+pub async fn replay_webhook_delivery(
+    Path(id): Path<i32>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<(StatusCode, Json<webhook_delivery::Model>), StatusCode> {
+    match webhook_service::replay_delivery(&db, id).await {
+        Ok(Some(delivery)) => Ok((StatusCode::ACCEPTED, Json(delivery))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -1,6 +1,8 @@
+pub mod config;
 pub mod entities;
 pub mod handlers;
 pub mod middleware;
+pub mod money;
 pub mod repository;
 pub mod routes;
 pub mod services;
@@ -1,66 +1,73 @@
 use m18_residences_server::services;
 use m18_residences_server::routes;
-use axum::{middleware::from_fn, response::Json, routing::get, Extension, Router};
-use m18_residences_server::middleware::{cors::cors_layer, db, jwt::require_auth};
-use std::{net::SocketAddr, time::Duration};
+use axum::Extension;
+use m18_residences_server::config::Config;
+use m18_residences_server::middleware::{cors::cors_layer, db, logging};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
 
+    // Load configuration, reporting every problem at once; logging is not set up yet
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            eprintln!("❌ Invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if std::env::args().any(|arg| arg == "--print-config") {
+        println!("{}", config);
+        return;
+    }
+    logging::init(&config.log);
+    tracing::info!("configuration loaded:\n{}", config);
+
     // Connect to database
-    let db = match db::connect().await {
+    let db = match db::connect(&config.database).await {
         Ok(conn) => {
-            println!("✅ Database connected successfully");
+            tracing::info!("database connected");
             conn
         }
         Err(err) => {
-            eprintln!("❌ Database connection failed: {}", err);
+            tracing::error!(error = %err, "database connection failed");
             std::process::exit(1);
         }
     };
 
     // Initialize R2 client
-    let r2 = services::r2_service::init_r2().await;
+    let r2 = services::r2_service::init_r2(&config.r2).await;
 
-    // Helper to apply JWT auth to a router
-    let protected = |router: Router| router.route_layer(from_fn(require_auth));
+    // Start notification outbox worker
+    services::notification_service::spawn_worker(db.clone(), config.clone());
 
-    // Build app
-    let app = Router::new()
-        // Public routes
-        .nest("/api/auth", routes::auth_routes::auth_routes())
-        .route("/", get(|| async { "API is up" }))
-        .route("/health", get(|| async { Json(serde_json::json!({ "status": "ok" })) }))
-        
-        // Protected routes
-        .nest("/api/signed-urls", protected(routes::signed_url_routes::signed_url_routes()))
-        .nest("/api/rooms", protected(routes::room_routes::room_routes()))
-        .nest("/api/tenants", protected(routes::tenant_routes::tenant_routes()))
-        .nest(
-            "/api/electricity-readings",
-            protected(routes::electricity_reading_routes::electricity_reading_routes()),
-        )
-        .nest("/api/bills", protected(routes::bill_routes::bill_routes()))
+    // Start webhook delivery worker
+    services::webhook_service::spawn_worker(db.clone(), &config.webhooks);
 
+    // Start trash purge worker
+    services::trash_service::spawn_purge_worker(db.clone(), &config.trash);
+
+    // Build app
+    let app = routes::api_routes(&config)
         // Global layers
-        .layer(cors_layer())
+        .layer(cors_layer(&config.server))
         .layer(Extension(db))
-        .layer(Extension(r2));
+        .layer(Extension(r2))
+        .layer(Extension(config.clone()));
+    // Outermost, so the request id and span cover everything above
+    let app = logging::request_tracing(app);
 
     // Server address
-    let addr = SocketAddr::from((
-        [0, 0, 0, 0],
-        std::env::var("PORT").unwrap_or("3001".to_string()).parse().unwrap(),
-    ));
-    println!("Server running on {}", addr);
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
+    tracing::info!(%addr, "server running");
 
     // Create handle for graceful shutdown
     let handle = axum_server::Handle::new();
     let graceful = handle.clone();
     tokio::spawn(async move {
         tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
-        println!("⚠️ Ctrl+C received, shutting down...");
+        tracing::warn!("ctrl+c received, shutting down");
         graceful.graceful_shutdown(Some(Duration::from_secs(5)));
     });
 
@@ -70,8 +77,8 @@ async fn main() {
         .serve(app.into_make_service())
         .await
     {
-        eprintln!("Server error: {}", err);
+        tracing::error!(error = %err, "server error");
     }
 
-    println!("Server has shut down gracefully");
+    tracing::info!("server has shut down gracefully");
 }
@@ -1,20 +1,21 @@
-use axum::http::{Method, header};
+use crate::config::ServerConfig;
+use axum::http::{HeaderName, Method, header};
 use tower_http::cors::{CorsLayer, AllowOrigin};
 
-pub fn cors_layer() -> CorsLayer {
-    let production_url = std::env::var("PRODUCTION_URL").unwrap_or_default();
-
+pub fn cors_layer(config: &ServerConfig) -> CorsLayer {
     let mut origins = vec![
         "http://localhost:45794".parse().unwrap(),
     ];
 
-    if !production_url.is_empty() {
+    // Checked to be a valid header value when the config was loaded
+    if let Some(production_url) = &config.production_url {
         origins.push(production_url.parse().unwrap());
     }
 
     CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::IF_MATCH])
+        .expose_headers([header::ETAG, HeaderName::from_static("x-request-id")])
         .allow_origin(AllowOrigin::list(origins))
         .allow_credentials(true)
 }
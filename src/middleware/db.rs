@@ -1,3 +1,4 @@
+use crate::config::DatabaseConfig;
 use sea_orm::Database;
 use sea_orm::DatabaseConnection;
 use migration::{Migrator, MigratorTrait, sea_orm::Database as MigrationDatabase, sea_orm::DatabaseConnection as MigrationDatabaseConnection};
@@ -9,7 +10,6 @@ pub async fn run_migrations(url: &str) {
     Migrator::up(&db, None).await.expect("Failed to run migrations");
 }
 
-pub async fn connect() -> Result<DatabaseConnection, sea_orm::DbErr> {
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    Database::connect(&database_url).await
+pub async fn connect(config: &DatabaseConfig) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    Database::connect(config.url.expose()).await
 }
\ No newline at end of file
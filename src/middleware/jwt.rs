@@ -1,10 +1,12 @@
 use axum::{
     body::Body,
+    extract::OriginalUri,
     http::{Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
+use crate::config::SharedConfig;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 
@@ -13,11 +15,30 @@ pub struct Claims {
     pub id: Option<i32>,
     pub name: Option<String>,
     pub role: Option<String>,
+    /// Properties a database admin user is limited to; absent for the `ADMIN_USERNAME` admin and tenants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<i32>>,
     pub exp: usize,
 }
 
-fn jwt_secret() -> String {
-    std::env::var("JWT_SECRET").unwrap_or_else(|_| "secret".into())
+impl Claims {
+    pub fn is_admin(&self) -> bool {
+        self.role.as_deref() == Some("admin")
+    }
+
+    /// The admin from the environment, who sees every property and manages admin users
+    pub fn is_superadmin(&self) -> bool {
+        self.is_admin() && self.properties.is_none()
+    }
+
+    /// Identifier of the caller, e.g. `admin:root` or `tenant:12`
+    pub fn actor(&self) -> String {
+        match (self.is_admin(), self.id) {
+            (true, _) => format!("admin:{}", self.name.as_deref().unwrap_or_default()),
+            (false, Some(id)) => format!("tenant:{}", id),
+            (false, None) => "unknown".to_string(),
+        }
+    }
 }
 
 fn error_response(status: StatusCode, msg: impl Into<String>) -> Response {
@@ -25,6 +46,9 @@ fn error_response(status: StatusCode, msg: impl Into<String>) -> Response {
 }
 
 pub async fn require_auth(mut req: Request<Body>, next: Next) -> Response {
+    let Some(config) = req.extensions().get::<SharedConfig>().cloned() else {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Configuration unavailable");
+    };
     let claims = req
         .headers()
         .get("authorization")
@@ -34,7 +58,7 @@ pub async fn require_auth(mut req: Request<Body>, next: Next) -> Response {
             let token = h.trim_start_matches("Bearer ");
             decode::<Claims>(
                 token,
-                &DecodingKey::from_secret(jwt_secret().as_bytes()),
+                &DecodingKey::from_secret(config.auth.jwt_secret.expose().as_bytes()),
                 &Validation::new(Algorithm::HS256),
             )
             .ok()
@@ -43,11 +67,15 @@ pub async fn require_auth(mut req: Request<Body>, next: Next) -> Response {
 
     match claims {
         Some(claims) => {
+            tracing::Span::current().record("actor", claims.actor());
             req.extensions_mut().insert(claims.clone());
 
-            if req.uri().path().starts_with("/api/admin")
-                && claims.role.as_deref() != Some("admin")
-            {
+            // `nest` strips its prefix from `req.uri()`, so match on the path as requested
+            let path = match req.extensions().get::<OriginalUri>() {
+                Some(OriginalUri(uri)) => uri.path(),
+                None => req.uri().path(),
+            };
+            if path.starts_with("/api/admin") && !claims.is_admin() {
                 return error_response(StatusCode::FORBIDDEN, "Admin access required");
             }
 
@@ -56,3 +84,19 @@ pub async fn require_auth(mut req: Request<Body>, next: Next) -> Response {
         None => error_response(StatusCode::UNAUTHORIZED, "Authentication required"),
     }
 }
+
+/// For admin-only routes on mounts tenants share; layered inside `require_auth`, which sets the claims
+pub async fn require_admin(req: Request<Body>, next: Next) -> Response {
+    match req.extensions().get::<Claims>() {
+        Some(claims) if claims.is_admin() => next.run(req).await,
+        _ => error_response(StatusCode::FORBIDDEN, "Admin access required"),
+    }
+}
+
+/// For settings shared by every property; layered inside `require_auth`, which sets the claims
+pub async fn require_superadmin(req: Request<Body>, next: Next) -> Response {
+    match req.extensions().get::<Claims>() {
+        Some(claims) if claims.is_superadmin() => next.run(req).await,
+        _ => error_response(StatusCode::FORBIDDEN, "Only the main admin can manage this"),
+    }
+}
@@ -1,3 +1,5 @@
 pub mod cors;
 pub mod db;
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod logging;
+pub mod property_access;
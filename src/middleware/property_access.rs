@@ -0,0 +1,97 @@
+//! Keeps admin users limited to some properties away from the rows of the others, and
+//! tenants away from every row but their own.
+//!
+//! Layered on the rooms, tenants, readings and bills routers: the first path segment,
+//! when numeric, names the row, and requests for rows outside the caller's properties
+//! are answered with 403. Tenants have no properties; they reach their own tenant record
+//! and the readings and bills raised for them. Lists and creates check their filters and
+//! bodies themselves.
+
+use crate::middleware::jwt::Claims;
+use crate::repository::property_repo::PropertyOwner;
+use crate::services::property_service::{self, PropertyScope};
+use axum::{
+    Json,
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sea_orm::DatabaseConnection;
+use tracing::error;
+
+fn error_response(status: StatusCode, msg: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": msg }))).into_response()
+}
+
+/// `/{id}/{action}` as the id and the action, if the path starts with a number
+fn split_path(path: &str) -> Option<(i32, Option<&str>)> {
+    let mut segments = path.trim_start_matches('/').split('/');
+    let id = segments.next()?.parse().ok()?;
+    Some((id, segments.next().filter(|s| !s.is_empty())))
+}
+
+async fn guard(req: Request<Body>, next: Next, owner: fn(i32, Option<&str>) -> PropertyOwner) -> Response {
+    let Some(claims) = req.extensions().get::<Claims>().cloned() else {
+        return next.run(req).await;
+    };
+    let scope = PropertyScope::for_claims(&claims);
+    let target = split_path(req.uri().path()).map(|(id, action)| owner(id, action));
+    let (PropertyScope::Only(_), Some(target)) = (&scope, target) else {
+        return next.run(req).await;
+    };
+    let Some(db) = req.extensions().get::<DatabaseConnection>().cloned() else {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database unavailable");
+    };
+
+    let allowed = if claims.is_admin() {
+        property_service::owner_in_scope(&db, &scope, target).await
+    } else {
+        property_service::owned_by_tenant(&db, &claims, target).await
+    };
+    match allowed {
+        // Missing rows are left to the handler's 404
+        Ok(Some(true)) | Ok(None) => next.run(req).await,
+        Ok(Some(false)) => error_response(StatusCode::FORBIDDEN, "Not in your properties"),
+        Err(err) => {
+            error!(?target, error = ?err, "error checking property access");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        }
+    }
+}
+
+pub async fn guard_rooms(req: Request<Body>, next: Next) -> Response {
+    guard(req, next, |id, _| PropertyOwner::Room(id)).await
+}
+
+pub async fn guard_tenants(req: Request<Body>, next: Next) -> Response {
+    guard(req, next, |id, _| PropertyOwner::Tenant(id)).await
+}
+
+pub async fn guard_readings(req: Request<Body>, next: Next) -> Response {
+    guard(req, next, |id, _| PropertyOwner::Reading(id)).await
+}
+
+/// `/:tenant_id/bill`, `/:tenant_id/bills` and `/:tenant_id/balance` are keyed by tenant
+pub async fn guard_bills(req: Request<Body>, next: Next) -> Response {
+    guard(req, next, |id, action| match action {
+        Some("bill" | "bills" | "balance") => PropertyOwner::Tenant(id),
+        _ => PropertyOwner::Bill(id),
+    })
+    .await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(split_path("/12"), Some((12, None)));
+        assert_eq!(split_path("/12/"), Some((12, None)));
+        assert_eq!(split_path("/3/balance"), Some((3, Some("balance"))));
+        assert_eq!(split_path("/batch"), None);
+        assert_eq!(split_path("/"), None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Money as integer minor units (hundredths) plus an ISO 4217 currency.
+//!
+//! Amounts are stored in `bigint` columns as minor units and travel over JSON as
+//! decimal strings, e.g. `"1500.00"`, so no float ever touches a balance.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Currencies we bill in; all of them have two decimal places
+const SUPPORTED: &[(&str, &str)] = &[
+    ("PHP", "₱"),
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("SGD", "S$"),
+    ("HKD", "HK$"),
+    ("AUD", "A$"),
+    ("CAD", "C$"),
+];
+
+const MINOR_PER_MAJOR: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency(&'static str);
+
+impl Currency {
+    pub const PHP: Currency = Currency("PHP");
+
+    pub fn parse(code: &str) -> Option<Currency> {
+        SUPPORTED
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(code.trim()))
+            .map(|(c, _)| Currency(c))
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        SUPPORTED
+            .iter()
+            .find(|(c, _)| *c == self.0)
+            .map(|(_, s)| *s)
+            .unwrap_or(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Currency, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Currency::parse(&code).ok_or_else(|| serde::de::Error::custom(format!("unsupported currency '{}'", code)))
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    Overflow,
+    CurrencyMismatch(Currency, Currency),
+    InvalidAmount(String),
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoneyError::Overflow => write!(f, "amount out of range"),
+            MoneyError::CurrencyMismatch(a, b) => write!(f, "cannot combine {} and {} amounts", a, b),
+            MoneyError::InvalidAmount(s) => write!(f, "invalid amount '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Money {
+    minor: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor: i64, currency: Currency) -> Money {
+        Money { minor, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Money {
+        Money::new(0, currency)
+    }
+
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, other.currency));
+        }
+        self.minor
+            .checked_add(other.minor)
+            .map(|minor| Money::new(minor, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, other.currency));
+        }
+        self.minor
+            .checked_sub(other.minor)
+            .map(|minor| Money::new(minor, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    /// Add up minor-unit amounts in one currency
+    pub fn sum<I>(currency: Currency, amounts: I) -> Result<Money, MoneyError>
+    where
+        I: IntoIterator<Item = i64>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), |acc, minor| acc.checked_add(Money::new(minor, currency)))
+    }
+
+    /// Amount in major units, for display only
+    pub fn to_major_f64(&self) -> f64 {
+        to_major_f64(self.minor)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.currency, to_decimal_string(self.minor))
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("Money", 2)?;
+        s.serialize_field("amount", &to_decimal_string(self.minor))?;
+        s.serialize_field("currency", self.currency.code())?;
+        s.end()
+    }
+}
+
+/// `123456` -> `1234.56`, for display only
+pub fn to_major_f64(minor: i64) -> f64 {
+    minor as f64 / MINOR_PER_MAJOR as f64
+}
+
+/// `123456` -> `"1234.56"`
+pub fn to_decimal_string(minor: i64) -> String {
+    let sign = if minor < 0 { "-" } else { "" };
+    let abs = minor.unsigned_abs();
+    let per = MINOR_PER_MAJOR as u64;
+    format!("{}{}.{:02}", sign, abs / per, abs % per)
+}
+
+/// `"1234.5"` -> `123450`; at most two decimal places
+pub fn parse_decimal(value: &str) -> Result<i64, MoneyError> {
+    let invalid = || MoneyError::InvalidAmount(value.to_string());
+    let trimmed = value.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() || frac.len() > 2 || !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let whole: i64 = whole.parse().map_err(|_| MoneyError::Overflow)?;
+    let frac: i64 = format!("{:0<2}", frac).parse().map_err(|_| invalid())?;
+    let minor = whole
+        .checked_mul(MINOR_PER_MAJOR)
+        .and_then(|m| m.checked_add(frac))
+        .ok_or(MoneyError::Overflow)?;
+    Ok(if negative { -minor } else { minor })
+}
+
+/// Serde adapter for minor-unit `i64` fields: written as a decimal string, read from
+/// a decimal string or a JSON number in major units (`1500`, `1500.5`, `"1500.50"`)
+pub mod decimal {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(minor: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_decimal_string(*minor))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let text = match &value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            _ => return Err(serde::de::Error::custom("amount must be a decimal string or number")),
+        };
+        parse_decimal(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// [`decimal`] for optional amounts; `null` or a missing field is `None`
+pub mod decimal_option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(minor: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match minor {
+            Some(minor) => decimal::serialize(minor, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapped(#[serde(with = "decimal")] i64);
+
+        Ok(Option::<Wrapped>::deserialize(deserializer)?.map(|Wrapped(minor)| minor))
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        assert_eq!(to_decimal_string(123456), "1234.56");
+        assert_eq!(to_decimal_string(-5), "-0.05");
+        assert_eq!(parse_decimal("1234.5"), Ok(123450));
+        assert_eq!(parse_decimal("-0.05"), Ok(-5));
+        assert_eq!(parse_decimal("1500"), Ok(150000));
+        assert!(parse_decimal("1.234").is_err());
+        assert!(parse_decimal("1e3").is_err());
+        assert_eq!(parse_decimal("99999999999999999999"), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let php = |m| Money::new(m, Currency::PHP);
+        let usd = Money::new(100, Currency::parse("usd").unwrap());
+
+        assert_eq!(php(150).checked_add(php(50)), Ok(php(200)));
+        assert_eq!(php(150).checked_sub(php(200)), Ok(php(-50)));
+        assert_eq!(php(i64::MAX).checked_add(php(1)), Err(MoneyError::Overflow));
+        assert!(matches!(php(1).checked_add(usd), Err(MoneyError::CurrencyMismatch(..))));
+        assert_eq!(Money::sum(Currency::PHP, [100, 250, -50]), Ok(php(300)));
+    }
+
+    #[test]
+    fn test_serialization() {
+        #[derive(Serialize, Deserialize)]
+        struct Row {
+            #[serde(with = "decimal")]
+            amount: i64,
+        }
+
+        let row: Row = serde_json::from_str(r#"{"amount": 1500}"#).unwrap();
+        assert_eq!(row.amount, 150000);
+        let row: Row = serde_json::from_str(r#"{"amount": "12.5"}"#).unwrap();
+        assert_eq!(serde_json::to_string(&row).unwrap(), r#"{"amount":"12.50"}"#);
+
+        let money = Money::new(-1050, Currency::PHP);
+        assert_eq!(serde_json::to_value(money).unwrap(), serde_json::json!({"amount": "-10.50", "currency": "PHP"}));
+        assert_eq!(money.to_string(), "PHP -10.50");
+
+        #[derive(Deserialize)]
+        struct Optional {
+            #[serde(default, with = "decimal_option")]
+            amount: Option<i64>,
+        }
+        let row: Optional = serde_json::from_str("{}").unwrap();
+        assert_eq!(row.amount, None);
+        let row: Optional = serde_json::from_str(r#"{"amount": "2.50"}"#).unwrap();
+        assert_eq!(row.amount, Some(250));
+
+        let currency: Currency = serde_json::from_str(r#""usd""#).unwrap();
+        assert_eq!(currency.code(), "USD");
+        assert!(serde_json::from_str::<Currency>(r#""XYZ""#).is_err());
+    }
+}
@@ -1,5 +1,17 @@
-use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, QueryOrder, TransactionTrait};
-use crate::entities::additional_charge;
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait, TransactionTrait};
+use sea_orm::sea_query::Expr;
+use chrono::NaiveDateTime;
+use crate::entities::{additional_charge, bill};
+use crate::repository::electricity_reading_repo;
+
+/// Charges on issued and paid bills, totalled per category and currency
+#[derive(Debug, FromQueryResult)]
+pub struct CategoryTotalRow {
+    pub charge_type_id: Option<i32>,
+    pub currency: String,
+    pub count: i64,
+    pub total: i64,
+}
 
 #[allow(dead_code)]
 pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<additional_charge::Model>, DbErr> {
@@ -40,6 +52,53 @@ pub async fn delete(db: &DatabaseConnection, id: i32) -> Result<(), DbErr> {
         .map(|_| ())
 }
 
+/// GET the charges of several bills at once
+pub async fn get_all_by_bill_ids(
+    db: &DatabaseConnection,
+    bill_ids: Vec<i32>,
+) -> Result<Vec<additional_charge::Model>, DbErr> {
+    additional_charge::Entity::find()
+        .filter(additional_charge::Column::BillId.is_in(bill_ids))
+        .order_by_asc(additional_charge::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// Totals per category of charges on bills issued in `[from, to)` in `property_ids` (all when `None`);
+/// drafts, void and trashed bills are left out
+pub async fn sum_by_category(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<CategoryTotalRow>, DbErr> {
+    let mut query = additional_charge::Entity::find()
+        .select_only()
+        .column(additional_charge::Column::ChargeTypeId)
+        .column(bill::Column::Currency)
+        .column_as(Expr::cust("COUNT(*)"), "count")
+        .column_as(Expr::cust("SUM(additional_charge.amount)::bigint"), "total")
+        .join(JoinType::InnerJoin, additional_charge::Relation::Bill.def())
+        .filter(bill::Column::Status.is_in([bill::STATUS_ISSUED, bill::STATUS_PAID]))
+        .filter(bill::Column::DeletedAt.is_null());
+    if let Some(from) = from {
+        query = query.filter(bill::Column::IssuedAt.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(bill::Column::IssuedAt.lt(to));
+    }
+    if let Some(ids) = property_ids {
+        query = query.filter(bill::Column::ReadingId.in_subquery(electricity_reading_repo::ids_in_properties(ids)));
+    }
+
+    query
+        .group_by(additional_charge::Column::ChargeTypeId)
+        .group_by(bill::Column::Currency)
+        .into_model::<CategoryTotalRow>()
+        .all(db)
+        .await
+}
+
 pub async fn delete_many_by_bill_id<C>(
     conn: &C,
     bill_id: i32,
@@ -77,6 +136,7 @@ mod tests {
     async fn setup_bill(db: &DatabaseConnection) -> bill::Model {
         // Create Room
         let room = room::ActiveModel {
+            property_id: Set(1),
             name: Set(format!("Test Room {}", Utc::now().timestamp())),
             rent: Set(1000),
             created_at: Set(Utc::now().naive_utc()),
@@ -285,4 +345,47 @@ mod tests {
         let by_bill = get_all_by_bill_id(&db, bill.id).await.unwrap();
         assert!(by_bill.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_sum_by_category() {
+        let db = get_test_db().await;
+        reset_tables_for_test(&db).await;
+
+        let bill = setup_bill(&db).await;
+        let water = crate::repository::charge_type_repo::get_by_code(&db, "water").await.unwrap().unwrap();
+
+        let txn = db.begin().await.unwrap();
+        for (amount, charge_type_id) in [(15000, Some(water.id)), (5000, Some(water.id)), (700, None)] {
+            create(
+                &txn,
+                additional_charge::ActiveModel {
+                    bill_id: Set(bill.id),
+                    amount: Set(amount),
+                    description: Set("Line".to_string()),
+                    charge_type_id: Set(charge_type_id),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        txn.commit().await.unwrap();
+
+        // Drafts are not reported
+        assert!(sum_by_category(&db, None, None, None).await.unwrap().is_empty());
+
+        let mut issued: bill::ActiveModel = bill.into();
+        issued.status = Set(bill::STATUS_ISSUED.to_string());
+        issued.issued_at = Set(Some(Utc::now().naive_utc()));
+        issued.update(&db).await.unwrap();
+
+        let rows = sum_by_category(&db, None, None, None).await.unwrap();
+        let water_row = rows.iter().find(|r| r.charge_type_id == Some(water.id)).unwrap();
+        assert_eq!((water_row.count, water_row.total, water_row.currency.as_str()), (2, 20000, "PHP"));
+        let other = rows.iter().find(|r| r.charge_type_id.is_none()).unwrap();
+        assert_eq!((other.count, other.total), (1, 700));
+
+        let tomorrow = Utc::now().naive_utc() + chrono::Duration::days(1);
+        assert!(sum_by_category(&db, Some(tomorrow), None, None).await.unwrap().is_empty());
+    }
 }
@@ -0,0 +1,96 @@
+use crate::entities::{admin_user, admin_user_property};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+/// GET all admin users
+pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<admin_user::Model>, DbErr> {
+    admin_user::Entity::find()
+        .order_by_asc(admin_user::Column::Username)
+        .all(db)
+        .await
+}
+
+/// GET admin user by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<admin_user::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    admin_user::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET admin user by username
+pub async fn get_by_username<C>(conn: &C, username: &str) -> Result<Option<admin_user::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    admin_user::Entity::find()
+        .filter(admin_user::Column::Username.eq(username))
+        .one(conn)
+        .await
+}
+
+/// GET the properties assigned to an admin user
+pub async fn get_property_ids<C>(conn: &C, admin_user_id: i32) -> Result<Vec<i32>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let rows = admin_user_property::Entity::find()
+        .filter(admin_user_property::Column::AdminUserId.eq(admin_user_id))
+        .order_by_asc(admin_user_property::Column::PropertyId)
+        .all(conn)
+        .await?;
+    Ok(rows.into_iter().map(|r| r.property_id).collect())
+}
+
+/// Replace the properties assigned to an admin user
+pub async fn set_property_ids<C>(conn: &C, admin_user_id: i32, property_ids: &[i32]) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    admin_user_property::Entity::delete_many()
+        .filter(admin_user_property::Column::AdminUserId.eq(admin_user_id))
+        .exec(conn)
+        .await?;
+    if property_ids.is_empty() {
+        return Ok(());
+    }
+    let rows = property_ids.iter().map(|&property_id| admin_user_property::ActiveModel {
+        admin_user_id: Set(admin_user_id),
+        property_id: Set(property_id),
+    });
+    admin_user_property::Entity::insert_many(rows).exec(conn).await?;
+    Ok(())
+}
+
+/// CREATE an admin user
+pub async fn create<C>(conn: &C, item: admin_user::ActiveModel) -> Result<admin_user::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// UPDATE an admin user
+pub async fn update<C>(conn: &C, id: i32, mut item: admin_user::ActiveModel) -> Result<admin_user::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.update(conn).await
+}
+
+/// DELETE an admin user along with their property assignments
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<admin_user::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    match get_by_id(conn, id).await? {
+        Some(model) => {
+            model.clone().delete(conn).await?;
+            Ok(Some(model))
+        }
+        None => Ok(None),
+    }
+}
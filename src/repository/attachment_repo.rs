@@ -0,0 +1,119 @@
+use crate::entities::attachment;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+
+/// GET attachment by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<attachment::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    attachment::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET all attachments for an owner entity
+pub async fn get_all_by_owner<C>(
+    conn: &C,
+    owner_type: &str,
+    owner_id: i32,
+) -> Result<Vec<attachment::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    attachment::Entity::find()
+        .filter(attachment::Column::OwnerType.eq(owner_type))
+        .filter(attachment::Column::OwnerId.eq(owner_id))
+        .order_by_asc(attachment::Column::CreatedAt)
+        .all(conn)
+        .await
+}
+
+/// CREATE an attachment record
+pub async fn create<C>(conn: &C, item: attachment::ActiveModel) -> Result<attachment::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// DELETE an attachment record
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<attachment::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = attachment::Entity::find_by_id(id).one(conn).await? {
+        let am: attachment::ActiveModel = model.clone().into();
+        am.delete(conn).await.map(|_| Some(model))
+    } else {
+        Ok(None)
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::attachment::{OWNER_BILL, OWNER_TENANT};
+    use crate::test_utils::{get_test_db, reset_table};
+    use sea_orm::Set;
+
+    fn new_attachment_model(key: &str, owner_type: &str, owner_id: i32) -> attachment::ActiveModel {
+        attachment::ActiveModel {
+            object_key: Set(key.to_string()),
+            owner_type: Set(owner_type.to_string()),
+            owner_id: Set(owner_id),
+            file_name: Set(Some("receipt.png".into())),
+            content_type: Set("image/png".into()),
+            size_bytes: Set(1024),
+            checksum: Set("abc123".into()),
+            uploaded_by: Set(Some("admin:admin".into())),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_attachment() {
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+
+        let created = create(&db, new_attachment_model("receipts/1/a.png", OWNER_BILL, 1))
+            .await
+            .unwrap();
+
+        let fetched = get_by_id(&db, created.id).await.unwrap().unwrap();
+        assert_eq!(fetched.object_key, "receipts/1/a.png");
+        assert_eq!(fetched.size_bytes, 1024);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_by_owner() {
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+
+        create(&db, new_attachment_model("receipts/1/a.png", OWNER_BILL, 1)).await.unwrap();
+        create(&db, new_attachment_model("receipts/1/b.png", OWNER_BILL, 1)).await.unwrap();
+        create(&db, new_attachment_model("tenants/1/id.png", OWNER_TENANT, 1)).await.unwrap();
+
+        let by_bill = get_all_by_owner(&db, OWNER_BILL, 1).await.unwrap();
+        assert_eq!(by_bill.len(), 2);
+
+        let by_tenant = get_all_by_owner(&db, OWNER_TENANT, 1).await.unwrap();
+        assert_eq!(by_tenant.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_attachment() {
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+
+        let created = create(&db, new_attachment_model("receipts/2/a.png", OWNER_BILL, 2))
+            .await
+            .unwrap();
+
+        let deleted = delete(&db, created.id).await.unwrap();
+        assert!(deleted.is_some());
+
+        let should_be_none = get_by_id(&db, created.id).await.unwrap();
+        assert!(should_be_none.is_none());
+    }
+}
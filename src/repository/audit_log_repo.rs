@@ -0,0 +1,102 @@
+use crate::entities::audit_log;
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// Filters for querying the audit log; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub entity: Option<String>,
+    pub entity_id: Option<i32>,
+    pub actor: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
+
+/// CREATE an audit entry
+pub async fn create<C>(conn: &C, item: audit_log::ActiveModel) -> Result<audit_log::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// GET audit entries matching the filter, newest first
+pub async fn find(
+    db: &DatabaseConnection,
+    filter: &AuditLogFilter,
+    limit: u64,
+    offset: u64,
+) -> Result<Vec<audit_log::Model>, DbErr> {
+    let mut query = audit_log::Entity::find();
+
+    if let Some(entity) = &filter.entity {
+        query = query.filter(audit_log::Column::Entity.eq(entity.as_str()));
+    }
+    if let Some(entity_id) = filter.entity_id {
+        query = query.filter(audit_log::Column::EntityId.eq(entity_id));
+    }
+    if let Some(actor) = &filter.actor {
+        query = query.filter(audit_log::Column::Actor.eq(actor.as_str()));
+    }
+    if let Some(since) = filter.since {
+        query = query.filter(audit_log::Column::CreatedAt.gte(since));
+    }
+    if let Some(until) = filter.until {
+        query = query.filter(audit_log::Column::CreatedAt.lt(until));
+    }
+
+    query
+        .order_by_desc(audit_log::Column::Id)
+        .limit(limit)
+        .offset(offset)
+        .all(db)
+        .await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+    use sea_orm::Set;
+    use serde_json::json;
+
+    fn new_entry(actor: &str, entity: &str, entity_id: i32) -> audit_log::ActiveModel {
+        audit_log::ActiveModel {
+            actor: Set(actor.into()),
+            entity: Set(entity.into()),
+            entity_id: Set(entity_id),
+            action: Set(audit_log::ACTION_UPDATE.into()),
+            changes: Set(json!({})),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_by_entity_and_actor() {
+        let db = get_test_db().await;
+        reset_table(&db, "audit_log").await;
+
+        create(&db, new_entry("admin:root", "bill", 1)).await.unwrap();
+        create(&db, new_entry("admin:root", "bill", 2)).await.unwrap();
+        create(&db, new_entry("tenant:3", "attachment", 9)).await.unwrap();
+
+        let bills = AuditLogFilter { entity: Some("bill".into()), ..Default::default() };
+        assert_eq!(find(&db, &bills, 10, 0).await.unwrap().len(), 2);
+
+        let one_bill = AuditLogFilter { entity_id: Some(2), ..bills.clone() };
+        let found = find(&db, &one_bill, 10, 0).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].entity_id, 2);
+
+        let by_tenant = AuditLogFilter { actor: Some("tenant:3".into()), ..Default::default() };
+        assert_eq!(find(&db, &by_tenant, 10, 0).await.unwrap()[0].entity, "attachment");
+
+        let all = find(&db, &AuditLogFilter::default(), 2, 0).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all[0].id > all[1].id);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::entities::{bill, bill_discount};
+use crate::repository::electricity_reading_repo;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+
+/// Discounts on issued and paid bills, totalled per currency
+#[derive(Debug, FromQueryResult)]
+pub struct DiscountTotalRow {
+    pub currency: String,
+    pub count: i64,
+    pub total: i64,
+}
+
+/// CREATE a discount line
+pub async fn create<C>(conn: &C, item: bill_discount::ActiveModel) -> Result<bill_discount::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// GET the discounts of a bill in the order they were applied
+pub async fn get_all_by_bill_id<C>(conn: &C, bill_id: i32) -> Result<Vec<bill_discount::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill_discount::Entity::find()
+        .filter(bill_discount::Column::BillId.eq(bill_id))
+        .order_by_asc(bill_discount::Column::Position)
+        .all(conn)
+        .await
+}
+
+/// GET the discounts of several bills at once
+pub async fn get_all_by_bill_ids(
+    db: &DatabaseConnection,
+    bill_ids: Vec<i32>,
+) -> Result<Vec<bill_discount::Model>, DbErr> {
+    bill_discount::Entity::find()
+        .filter(bill_discount::Column::BillId.is_in(bill_ids))
+        .order_by_asc(bill_discount::Column::BillId)
+        .order_by_asc(bill_discount::Column::Position)
+        .all(db)
+        .await
+}
+
+/// DELETE all discounts of a bill
+pub async fn delete_many_by_bill_id<C>(conn: &C, bill_id: i32) -> Result<u64, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let res = bill_discount::Entity::delete_many()
+        .filter(bill_discount::Column::BillId.eq(bill_id))
+        .exec(conn)
+        .await?;
+
+    Ok(res.rows_affected)
+}
+
+/// Totals per currency of discounts on bills issued in `[from, to)` in `property_ids` (all when `None`);
+/// drafts, void and trashed bills are left out
+pub async fn sum_by_currency(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<DiscountTotalRow>, DbErr> {
+    let mut query = bill_discount::Entity::find()
+        .select_only()
+        .column(bill::Column::Currency)
+        .column_as(Expr::cust("COUNT(*)"), "count")
+        .column_as(Expr::cust("SUM(bill_discount.amount)::bigint"), "total")
+        .join(JoinType::InnerJoin, bill_discount::Relation::Bill.def())
+        .filter(bill::Column::Status.is_in([bill::STATUS_ISSUED, bill::STATUS_PAID]))
+        .filter(bill::Column::DeletedAt.is_null());
+    if let Some(from) = from {
+        query = query.filter(bill::Column::IssuedAt.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(bill::Column::IssuedAt.lt(to));
+    }
+    if let Some(ids) = property_ids {
+        query = query.filter(bill::Column::ReadingId.in_subquery(electricity_reading_repo::ids_in_properties(ids)));
+    }
+
+    query
+        .group_by(bill::Column::Currency)
+        .into_model::<DiscountTotalRow>()
+        .all(db)
+        .await
+}
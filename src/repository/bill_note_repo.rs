@@ -0,0 +1,110 @@
+use crate::entities::bill_note;
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+
+/// CREATE a credit or debit note
+pub async fn create<C>(conn: &C, item: bill_note::ActiveModel) -> Result<bill_note::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// GET notes against a bill, oldest first
+pub async fn get_all_by_bill_id<C>(conn: &C, bill_id: i32) -> Result<Vec<bill_note::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill_note::Entity::find()
+        .filter(bill_note::Column::BillId.eq(bill_id))
+        .order_by_asc(bill_note::Column::CreatedAt)
+        .all(conn)
+        .await
+}
+
+/// GET all notes for a tenant, oldest first
+pub async fn get_all_by_tenant_id(
+    db: &DatabaseConnection,
+    tenant_id: i32,
+) -> Result<Vec<bill_note::Model>, DbErr> {
+    bill_note::Entity::find()
+        .filter(bill_note::Column::TenantId.eq(tenant_id))
+        .order_by_asc(bill_note::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// GET notes changed after `since`
+pub async fn get_changed_since(db: &DatabaseConnection, since: NaiveDateTime) -> Result<Vec<bill_note::Model>, DbErr> {
+    bill_note::Entity::find()
+        .filter(bill_note::Column::UpdatedAt.gt(since))
+        .order_by_asc(bill_note::Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// GET all notes, oldest first
+pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<bill_note::Model>, DbErr> {
+    bill_note::Entity::find()
+        .order_by_asc(bill_note::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::bill;
+    use crate::test_utils::{get_test_db, seed_reading};
+    use sea_orm::Set;
+
+    async fn setup_bill(db: &DatabaseConnection) -> bill::Model {
+        let (_, tenant, reading) = seed_reading(db).await;
+
+        bill::ActiveModel {
+            reading_id: Set(reading.id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(1000),
+            electric_charges: Set(500),
+            total_amount: Set(1500),
+            paid: Set(false),
+            status: Set(bill::STATUS_ISSUED.into()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    fn new_note(bill: &bill::Model, kind: &str, amount: i64) -> bill_note::ActiveModel {
+        bill_note::ActiveModel {
+            bill_id: Set(bill.id),
+            tenant_id: Set(bill.tenant_id),
+            kind: Set(kind.into()),
+            amount: Set(amount),
+            reason: Set("Meter misread".into()),
+            created_by: Set("admin:root".into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_notes() {
+        let db = get_test_db().await;
+        let bill = setup_bill(&db).await;
+
+        create(&db, new_note(&bill, bill_note::KIND_CREDIT, 200)).await.unwrap();
+        create(&db, new_note(&bill, bill_note::KIND_DEBIT, 50)).await.unwrap();
+
+        let by_bill = get_all_by_bill_id(&db, bill.id).await.unwrap();
+        assert_eq!(by_bill.len(), 2);
+        assert_eq!(by_bill.iter().map(|n| n.signed_amount()).sum::<i64>(), -150);
+
+        assert_eq!(get_all_by_tenant_id(&db, bill.tenant_id).await.unwrap().len(), 2);
+        assert!(get_all_by_tenant_id(&db, bill.tenant_id + 1).await.unwrap().is_empty());
+    }
+}
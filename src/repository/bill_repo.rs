@@ -1,20 +1,170 @@
 use crate::entities::bill;
+use crate::repository::electricity_reading_repo;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::Expr;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, QuerySelect, Select, SelectModel, Selector, Set
 };
 
+/// Filters shared by the bill list and its exports
+#[derive(Debug, Default, Clone)]
+pub struct BillFilter {
+    pub tenant_id: Option<i32>,
+    pub status: Option<String>,
+    /// Issued on or after; drafts have no issue date and drop out once a date is given
+    pub from: Option<NaiveDateTime>,
+    /// Issued before
+    pub to: Option<NaiveDateTime>,
+    /// Only bills on readings of rooms in these properties; `None` for all
+    pub property_ids: Option<Vec<i32>>,
+}
+
+/// A bill with its tenant, room and additional charges, flattened for spreadsheets
+#[derive(Debug, FromQueryResult)]
+pub struct BillExportRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub tenant_name: Option<String>,
+    pub room_name: Option<String>,
+    pub status: String,
+    pub currency: String,
+    pub issued_at: Option<NaiveDateTime>,
+    pub paid_at: Option<NaiveDateTime>,
+    pub room_charges: i64,
+    pub electric_charges: i64,
+    /// `[{"description": .., "amount": ..}]` in charge order, or NULL without charges
+    pub charges: Option<serde_json::Value>,
+    pub discount_amount: i64,
+    pub total_amount: i64,
+    pub receipt_url: Option<String>,
+}
+
+/// Bills belong to a property through the room of their reading
+fn in_properties(query: Select<bill::Entity>, filter: &BillFilter) -> Select<bill::Entity> {
+    let Some(ids) = &filter.property_ids else {
+        return query;
+    };
+    query.filter(bill::Column::ReadingId.in_subquery(electricity_reading_repo::ids_in_properties(ids)))
+}
+
+/// Bills matching `filter`, newest first
+fn filtered(filter: &BillFilter) -> Select<bill::Entity> {
+    let mut query = bill::Entity::find().filter(bill::Column::DeletedAt.is_null());
+    if let Some(tenant_id) = filter.tenant_id {
+        query = query.filter(bill::Column::TenantId.eq(tenant_id));
+    }
+    if let Some(status) = &filter.status {
+        query = query.filter(bill::Column::Status.eq(status.as_str()));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(bill::Column::IssuedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(bill::Column::IssuedAt.lt(to));
+    }
+    in_properties(query, filter).order_by_desc(bill::Column::CreatedAt).order_by_desc(bill::Column::Id)
+}
+
+fn with_export_columns(query: Select<bill::Entity>) -> Selector<SelectModel<BillExportRow>> {
+    query
+        .column_as(Expr::cust("(SELECT t.name FROM tenant t WHERE t.id = bill.tenant_id)"), "tenant_name")
+        .column_as(
+            Expr::cust("(SELECT r.name FROM electricity_reading er JOIN room r ON r.id = er.room_id WHERE er.id = bill.reading_id)"),
+            "room_name",
+        )
+        .column_as(
+            Expr::cust(
+                "(SELECT json_agg(json_build_object('description', ac.description, 'amount', ac.amount) ORDER BY ac.id)
+                  FROM additional_charge ac WHERE ac.bill_id = bill.id)",
+            ),
+            "charges",
+        )
+        .into_model::<BillExportRow>()
+}
+
+/// GET bills matching `filter`
+pub async fn get_filtered(db: &DatabaseConnection, filter: &BillFilter) -> Result<Vec<bill::Model>, DbErr> {
+    filtered(filter).all(db).await
+}
+
+/// Bills matching `filter` as export rows; `stream` it rather than loading every row
+pub fn export_rows(filter: &BillFilter) -> Selector<SelectModel<BillExportRow>> {
+    with_export_columns(filtered(filter))
+}
+
+/// Paid bills matching `filter` as export rows, dated by payment instead of issue, latest first
+pub fn payment_rows(filter: &BillFilter) -> Selector<SelectModel<BillExportRow>> {
+    let mut query = bill::Entity::find()
+        .filter(bill::Column::DeletedAt.is_null())
+        .filter(bill::Column::Status.eq(bill::STATUS_PAID));
+    if let Some(tenant_id) = filter.tenant_id {
+        query = query.filter(bill::Column::TenantId.eq(tenant_id));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(bill::Column::PaidAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(bill::Column::PaidAt.lt(to));
+    }
+    with_export_columns(in_properties(query, filter).order_by_desc(bill::Column::PaidAt).order_by_desc(bill::Column::Id))
+}
+
 /// GET all bills
 pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<bill::Model>, DbErr> {
     bill::Entity::find()
+        .filter(bill::Column::DeletedAt.is_null())
         .order_by_desc(bill::Column::CreatedAt)
         .all(db)
         .await
 }
 
 /// GET bill by id
-#[allow(dead_code)]
-pub async fn get_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<bill::Model>, DbErr> {
-    bill::Entity::find_by_id(id).one(db).await
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<bill::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill::Entity::find_by_id(id)
+        .filter(bill::Column::DeletedAt.is_null())
+        .one(conn)
+        .await
+}
+
+/// GET a bill and lock its row until the transaction ends, so status changes and notes
+/// on the same bill run one after the other
+pub async fn get_by_id_for_update<C>(conn: &C, id: i32) -> Result<Option<bill::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill::Entity::find_by_id(id)
+        .filter(bill::Column::DeletedAt.is_null())
+        .lock_exclusive()
+        .one(conn)
+        .await
+}
+
+/// GET all issued, unpaid bills, oldest first
+pub async fn get_unpaid(db: &DatabaseConnection) -> Result<Vec<bill::Model>, DbErr> {
+    bill::Entity::find()
+        .filter(bill::Column::Status.eq(bill::STATUS_ISSUED))
+        .filter(bill::Column::DeletedAt.is_null())
+        .order_by_asc(bill::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+/// UPDATE a bill still at `version`, bumping it; `RecordNotUpdated` if it has moved on
+pub async fn update<C>(conn: &C, id: i32, version: i32, mut item: bill::ActiveModel) -> Result<bill::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.version = Set(version + 1);
+    bill::Entity::update(item)
+        .filter(bill::Column::Version.eq(version))
+        .filter(bill::Column::DeletedAt.is_null())
+        .exec(conn)
+        .await
 }
 
 // Get latest bill for tenant
@@ -24,6 +174,7 @@ pub async fn get_latest_by_tenant_id(
 ) -> Result<Option<bill::Model>, DbErr> {
     bill::Entity::find()
         .filter(bill::Column::TenantId.eq(tenant_id))
+        .filter(bill::Column::DeletedAt.is_null())
         .order_by_desc(bill::Column::CreatedAt)
         .one(db)
         .await
@@ -36,30 +187,95 @@ pub async fn get_all_by_tenant_id(
 ) -> Result<Vec<bill::Model>, DbErr> {
     bill::Entity::find()
         .filter(bill::Column::TenantId.eq(tenant_id))
+        .filter(bill::Column::DeletedAt.is_null())
         .order_by_desc(bill::Column::CreatedAt)
         .all(db)
         .await
 }
 
-/// DELETE a bill by ID
+/// Move a bill to the trash; its charges stay attached for a restore
 pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<bill::Model>, DbErr>
 where
     C: ConnectionTrait,
 {
-    if let Some(model) = bill::Entity::find_by_id(id).one(conn).await? {
-        let am: bill::ActiveModel = model.clone().into();
-        am.delete(conn).await?;
-        Ok(Some(model))
+    if let Some(model) = get_by_id(conn, id).await? {
+        let mut am: bill::ActiveModel = model.into();
+        am.deleted_at = Set(Some(chrono::Utc::now().naive_utc()));
+        am.update(conn).await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// GET bills changed after `since`, trashed ones included so clients can drop them
+pub async fn get_changed_since(db: &DatabaseConnection, since: NaiveDateTime) -> Result<Vec<bill::Model>, DbErr> {
+    bill::Entity::find()
+        .filter(bill::Column::UpdatedAt.gt(since))
+        .order_by_asc(bill::Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// GET bills in the trash, most recently deleted first
+pub async fn get_deleted(db: &DatabaseConnection) -> Result<Vec<bill::Model>, DbErr> {
+    bill::Entity::find()
+        .filter(bill::Column::DeletedAt.is_not_null())
+        .order_by_desc(bill::Column::DeletedAt)
+        .all(db)
+        .await
+}
+
+/// GET a bill in the trash by ID
+pub async fn get_deleted_by_id<C>(conn: &C, id: i32) -> Result<Option<bill::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill::Entity::find_by_id(id)
+        .filter(bill::Column::DeletedAt.is_not_null())
+        .one(conn)
+        .await
+}
+
+/// Take a bill out of the trash
+pub async fn restore<C>(conn: &C, id: i32) -> Result<Option<bill::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_deleted_by_id(conn, id).await? {
+        let mut am: bill::ActiveModel = model.into();
+        am.deleted_at = Set(None);
+        am.update(conn).await.map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// Permanently remove bills deleted before `cutoff`; their charges cascade. Only drafts that
+/// were never issued and carry no notes go, so the record of anything a tenant was billed stays
+pub async fn purge_deleted_before<C>(conn: &C, cutoff: NaiveDateTime) -> Result<Vec<bill::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let purgeable = bill::Entity::find()
+        .filter(bill::Column::DeletedAt.lt(cutoff))
+        .filter(bill::Column::Status.eq(bill::STATUS_DRAFT))
+        .filter(bill::Column::IssuedAt.is_null())
+        .filter(Expr::cust("NOT EXISTS (SELECT 1 FROM bill_note WHERE bill_note.bill_id = bill.id)"))
+        .all(conn)
+        .await?;
+
+    bill::Entity::delete_many()
+        .filter(bill::Column::Id.is_in(purgeable.iter().map(|b| b.id)))
+        .exec(conn)
+        .await?;
+    Ok(purgeable)
+}
+
 // ---------------------- INLINE TESTS ----------------------
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::{get_test_db, reset_table};
+    use crate::test_utils::{add_reading, get_test_db, reset_table};
     use crate::entities::{room, tenant, electricity_reading, bill};
     use sea_orm::{ActiveModelTrait, Set};
     use chrono::Utc;
@@ -76,6 +292,7 @@ mod tests {
     async fn setup_dependencies(db: &DatabaseConnection) -> (room::Model, tenant::Model, electricity_reading::Model) {
         // Create Room
         let room = room::ActiveModel {
+            property_id: Set(1),
             name: Set(format!("Test Room {}", Utc::now().timestamp())),
             rent: Set(1000),
             created_at: Set(Utc::now().naive_utc()),
@@ -200,9 +417,9 @@ async fn test_get_all_by_tenant_id() {
         let _ = bill::ActiveModel {
             reading_id: Set(reading.id),
             tenant_id: Set(tenant.id),
-            room_charges: Set(1000 + i * 100),
-            electric_charges: Set(500 + i * 50),
-            total_amount: Set(1500 + i * 150),
+            room_charges: Set(1000 + i as i64 * 100),
+            electric_charges: Set(500 + i as i64 * 50),
+            total_amount: Set(1500 + i as i64 * 150),
             receipt_url: Set(None),
             paid: Set(false),
             created_at: Set(Utc::now().naive_utc()),
@@ -219,6 +436,36 @@ async fn test_get_all_by_tenant_id() {
 }
 
 
+    #[tokio::test]
+    async fn test_get_unpaid() {
+        let db = get_test_db().await;
+        reset_tables_for_test(&db).await;
+
+        let (_room, tenant, reading) = setup_dependencies(&db).await;
+        let other_reading = add_reading(&db, &tenant, 200, 250).await;
+
+        for (reading_id, paid) in [(reading.id, true), (other_reading.id, false)] {
+            bill::ActiveModel {
+                reading_id: Set(reading_id),
+                tenant_id: Set(tenant.id),
+                room_charges: Set(1000),
+                electric_charges: Set(500),
+                total_amount: Set(1500),
+                receipt_url: Set(None),
+                paid: Set(paid),
+                status: Set(if paid { bill::STATUS_PAID } else { bill::STATUS_ISSUED }.to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let unpaid = get_unpaid(&db).await.unwrap();
+        assert_eq!(unpaid.len(), 1);
+        assert_eq!(unpaid[0].reading_id, other_reading.id);
+    }
+
     #[tokio::test]
     async fn test_delete_bill() {
         let db = get_test_db().await;
@@ -248,4 +495,123 @@ async fn test_get_all_by_tenant_id() {
         let fetched = get_by_id(&db, bill.id).await.unwrap();
         assert!(fetched.is_none());
     }
+
+    #[tokio::test]
+    async fn test_purge_only_removes_drafts_never_issued() {
+        let db = get_test_db().await;
+        reset_tables_for_test(&db).await;
+        reset_table(&db, "bill_note").await;
+
+        let (_room, tenant, reading) = setup_dependencies(&db).await;
+        let trashed_bill = |reading_id: i32, status: &str, issued: bool| bill::ActiveModel {
+            reading_id: Set(reading_id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(1000),
+            electric_charges: Set(0),
+            total_amount: Set(1000),
+            status: Set(status.into()),
+            issued_at: Set(issued.then(|| Utc::now().naive_utc())),
+            deleted_at: Set(Some(Utc::now().naive_utc())),
+            ..Default::default()
+        };
+
+        let draft = trashed_bill(reading.id, bill::STATUS_DRAFT, false).insert(&db).await.unwrap();
+        let issued = trashed_bill(add_reading(&db, &tenant, 150, 160).await.id, bill::STATUS_ISSUED, true)
+            .insert(&db)
+            .await
+            .unwrap();
+        let noted = trashed_bill(add_reading(&db, &tenant, 160, 170).await.id, bill::STATUS_DRAFT, false)
+            .insert(&db)
+            .await
+            .unwrap();
+        crate::entities::bill_note::ActiveModel {
+            bill_id: Set(noted.id),
+            tenant_id: Set(tenant.id),
+            kind: Set(crate::entities::bill_note::KIND_CREDIT.into()),
+            amount: Set(100),
+            reason: Set("Goodwill".into()),
+            created_by: Set("admin:root".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let cutoff = Utc::now().naive_utc() + chrono::Duration::seconds(1);
+        let purged = purge_deleted_before(&db, cutoff).await.unwrap();
+        assert_eq!(purged.iter().map(|b| b.id).collect::<Vec<_>>(), vec![draft.id]);
+
+        let mut kept: Vec<i32> = get_deleted(&db).await.unwrap().iter().map(|b| b.id).collect();
+        kept.sort();
+        assert_eq!(kept, vec![issued.id, noted.id]);
+    }
+
+    #[tokio::test]
+    async fn test_export_rows_follow_filter() {
+        let db = get_test_db().await;
+        reset_tables_for_test(&db).await;
+
+        let (room, tenant, reading) = setup_dependencies(&db).await;
+        let paid_at = Utc::now().naive_utc();
+
+        let paid = bill::ActiveModel {
+            reading_id: Set(reading.id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(1000),
+            electric_charges: Set(500),
+            total_amount: Set(1750),
+            paid: Set(true),
+            status: Set(bill::STATUS_PAID.into()),
+            issued_at: Set(Some(paid_at)),
+            paid_at: Set(Some(paid_at)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        crate::entities::additional_charge::ActiveModel {
+            bill_id: Set(paid.id),
+            amount: Set(250),
+            description: Set("Water".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let next_reading = electricity_reading::ActiveModel {
+            tenant_id: Set(tenant.id),
+            room_id: Set(room.id),
+            prev_reading: Set(200),
+            curr_reading: Set(200),
+            consumption: Set(0),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        bill::ActiveModel {
+            reading_id: Set(next_reading.id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(1000),
+            electric_charges: Set(0),
+            total_amount: Set(1000),
+            status: Set(bill::STATUS_ISSUED.into()),
+            issued_at: Set(Some(paid_at)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let filter = BillFilter { status: Some(bill::STATUS_PAID.into()), ..Default::default() };
+        let rows = export_rows(&filter).all(&db).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].room_name.as_deref(), Some(room.name.as_str()));
+        assert_eq!(rows[0].tenant_name.as_deref(), Some(tenant.name.as_str()));
+        assert_eq!(rows[0].charges, Some(serde_json::json!([{ "description": "Water", "amount": 250 }])));
+
+        let payments = payment_rows(&BillFilter { to: Some(paid_at), ..Default::default() }).all(&db).await.unwrap();
+        assert!(payments.is_empty());
+        assert_eq!(get_filtered(&db, &BillFilter::default()).await.unwrap().len(), 2);
+    }
 }
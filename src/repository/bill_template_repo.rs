@@ -0,0 +1,126 @@
+use crate::entities::bill_template;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+
+/// GET all stored templates
+pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<bill_template::Model>, DbErr> {
+    bill_template::Entity::find()
+        .order_by_asc(bill_template::Column::Format)
+        .order_by_asc(bill_template::Column::Locale)
+        .all(db)
+        .await
+}
+
+/// GET template for a format and locale
+pub async fn get_by_format_and_locale<C>(
+    conn: &C,
+    format: &str,
+    locale: &str,
+) -> Result<Option<bill_template::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    bill_template::Entity::find()
+        .filter(bill_template::Column::Format.eq(format))
+        .filter(bill_template::Column::Locale.eq(locale))
+        .one(conn)
+        .await
+}
+
+/// CREATE or replace the template for a format and locale
+pub async fn upsert<C>(
+    conn: &C,
+    format: &str,
+    locale: &str,
+    body: String,
+) -> Result<bill_template::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    match get_by_format_and_locale(conn, format, locale).await? {
+        Some(existing) => {
+            let mut am: bill_template::ActiveModel = existing.into();
+            am.body = Set(body);
+            am.update(conn).await
+        }
+        None => {
+            bill_template::ActiveModel {
+                format: Set(format.to_string()),
+                locale: Set(locale.to_string()),
+                body: Set(body),
+                ..Default::default()
+            }
+            .insert(conn)
+            .await
+        }
+    }
+}
+
+/// DELETE the template for a format and locale
+pub async fn delete<C>(
+    conn: &C,
+    format: &str,
+    locale: &str,
+) -> Result<Option<bill_template::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_by_format_and_locale(conn, format, locale).await? {
+        let am: bill_template::ActiveModel = model.clone().into();
+        am.delete(conn).await.map(|_| Some(model))
+    } else {
+        Ok(None)
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+
+    #[tokio::test]
+    async fn test_upsert_and_get_template() {
+        let db = get_test_db().await;
+        reset_table(&db, "bill_template").await;
+
+        let created = upsert(&db, "html", "en", "<p>v1</p>".into()).await.unwrap();
+        let updated = upsert(&db, "html", "en", "<p>v2</p>".into()).await.unwrap();
+        assert_eq!(created.id, updated.id);
+
+        let fetched = get_by_format_and_locale(&db, "html", "en").await.unwrap().unwrap();
+        assert_eq!(fetched.body, "<p>v2</p>");
+
+        let missing = get_by_format_and_locale(&db, "text", "en").await.unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_all_templates() {
+        let db = get_test_db().await;
+        reset_table(&db, "bill_template").await;
+
+        upsert(&db, "text", "en", "v1".into()).await.unwrap();
+        upsert(&db, "html", "fil", "<p>v1</p>".into()).await.unwrap();
+
+        let all = get_all(&db).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].format, "html");
+    }
+
+    #[tokio::test]
+    async fn test_delete_template() {
+        let db = get_test_db().await;
+        reset_table(&db, "bill_template").await;
+
+        upsert(&db, "text", "en", "v1".into()).await.unwrap();
+
+        let deleted = delete(&db, "text", "en").await.unwrap();
+        assert!(deleted.is_some());
+
+        let should_be_none = get_by_format_and_locale(&db, "text", "en").await.unwrap();
+        assert!(should_be_none.is_none());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::entities::charge_type;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+/// GET the whole catalog, retired types included
+pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<charge_type::Model>, DbErr> {
+    charge_type::Entity::find()
+        .order_by_asc(charge_type::Column::Code)
+        .all(db)
+        .await
+}
+
+/// GET charge type by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<charge_type::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    charge_type::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET several charge types at once
+pub async fn get_by_ids<C>(conn: &C, ids: Vec<i32>) -> Result<Vec<charge_type::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    charge_type::Entity::find()
+        .filter(charge_type::Column::Id.is_in(ids))
+        .all(conn)
+        .await
+}
+
+/// GET charge type by its code
+pub async fn get_by_code<C>(conn: &C, code: &str) -> Result<Option<charge_type::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    charge_type::Entity::find()
+        .filter(charge_type::Column::Code.eq(code))
+        .one(conn)
+        .await
+}
+
+/// CREATE a charge type
+pub async fn create<C>(conn: &C, item: charge_type::ActiveModel) -> Result<charge_type::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// UPDATE a charge type
+pub async fn update<C>(conn: &C, id: i32, mut item: charge_type::ActiveModel) -> Result<charge_type::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.update(conn).await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_test_db;
+
+    #[tokio::test]
+    async fn test_seeded_catalog() {
+        let db = get_test_db().await;
+
+        let water = get_by_code(&db, "water").await.unwrap().expect("water is seeded");
+        assert_eq!(water.name, "Water");
+        assert!(get_all(&db).await.unwrap().iter().any(|t| t.code == "parking"));
+
+        let found = get_by_ids(&db, vec![water.id]).await.unwrap();
+        assert_eq!(found, vec![water]);
+    }
+}
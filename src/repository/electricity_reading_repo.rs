@@ -1,12 +1,102 @@
 use crate::entities::electricity_reading;
+use crate::repository::room_repo;
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::{Expr, Query, SelectStatement};
 use sea_orm::{
-    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryOrder,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Select, SelectModel, Selector, Set,
     TransactionTrait,
 };
 
+/// Filters shared by the reading list and its export
+#[derive(Debug, Default, Clone)]
+pub struct ReadingFilter {
+    pub tenant_id: Option<i32>,
+    pub room_id: Option<i32>,
+    pub flagged: Option<bool>,
+    /// Taken on or after
+    pub from: Option<NaiveDateTime>,
+    /// Taken before
+    pub to: Option<NaiveDateTime>,
+    /// Only readings of rooms in these properties; `None` for all
+    pub property_ids: Option<Vec<i32>>,
+}
+
+/// A reading with its tenant and room names, for the export
+#[derive(Debug, FromQueryResult)]
+pub struct ReadingExportRow {
+    pub id: i32,
+    pub tenant_id: i32,
+    pub tenant_name: Option<String>,
+    pub room_id: i32,
+    pub room_name: Option<String>,
+    pub prev_reading: i32,
+    pub curr_reading: i32,
+    pub consumption: i32,
+    pub flagged: bool,
+    pub flag_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// `SELECT id FROM electricity_reading` of rooms in the given properties, for filtering bills
+pub fn ids_in_properties(property_ids: &[i32]) -> SelectStatement {
+    Query::select()
+        .column(electricity_reading::Column::Id)
+        .from(electricity_reading::Entity)
+        .and_where(electricity_reading::Column::RoomId.in_subquery(room_repo::ids_in_properties(property_ids)))
+        .to_owned()
+}
+
+/// Readings matching `filter`, newest first
+fn filtered(filter: &ReadingFilter) -> Select<electricity_reading::Entity> {
+    let mut query = electricity_reading::Entity::find().filter(electricity_reading::Column::DeletedAt.is_null());
+    if let Some(tenant_id) = filter.tenant_id {
+        query = query.filter(electricity_reading::Column::TenantId.eq(tenant_id));
+    }
+    if let Some(room_id) = filter.room_id {
+        query = query.filter(electricity_reading::Column::RoomId.eq(room_id));
+    }
+    if let Some(flagged) = filter.flagged {
+        query = query.filter(electricity_reading::Column::Flagged.eq(flagged));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(electricity_reading::Column::CreatedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(electricity_reading::Column::CreatedAt.lt(to));
+    }
+    if let Some(ids) = &filter.property_ids {
+        query = query.filter(electricity_reading::Column::RoomId.in_subquery(room_repo::ids_in_properties(ids)));
+    }
+    query.order_by_desc(electricity_reading::Column::CreatedAt)
+}
+
+/// GET readings matching `filter`
+pub async fn get_filtered(
+    db: &DatabaseConnection,
+    filter: &ReadingFilter,
+) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    filtered(filter).all(db).await
+}
+
+/// Readings matching `filter` as export rows; `stream` it rather than loading every row
+pub fn export_rows(filter: &ReadingFilter) -> Selector<SelectModel<ReadingExportRow>> {
+    filtered(filter)
+        .column_as(
+            Expr::cust("(SELECT t.name FROM tenant t WHERE t.id = electricity_reading.tenant_id)"),
+            "tenant_name",
+        )
+        .column_as(
+            Expr::cust("(SELECT r.name FROM room r WHERE r.id = electricity_reading.room_id)"),
+            "room_name",
+        )
+        .into_model::<ReadingExportRow>()
+}
+
 /// GET all readings
 pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<electricity_reading::Model>, DbErr> {
     electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::DeletedAt.is_null())
         .order_by_desc(electricity_reading::Column::CreatedAt)
         .all(db)
         .await
@@ -20,38 +110,187 @@ pub async fn get_by_id<C>(
 where
     C: ConnectionTrait + TransactionTrait,
 {
-    electricity_reading::Entity::find_by_id(id).one(conn).await
+    electricity_reading::Entity::find_by_id(id)
+        .filter(electricity_reading::Column::DeletedAt.is_null())
+        .one(conn)
+        .await
 }
 
 /// CREATE a new reading
-pub async fn create(
-    db: &DatabaseConnection,
+pub async fn create<C>(
+    conn: &C,
     item: electricity_reading::ActiveModel,
-) -> Result<electricity_reading::Model, DbErr> {
-    item.insert(db).await
+) -> Result<electricity_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
 }
 
 /// UPDATE a reading
-pub async fn update(
-    db: &DatabaseConnection,
+pub async fn update<C>(
+    conn: &C,
     item: electricity_reading::ActiveModel,
-) -> Result<electricity_reading::Model, DbErr> {
-    item.update(db).await
+) -> Result<electricity_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.update(conn).await
 }
 
-/// DELETE a reading
-pub async fn delete(
-    db: &DatabaseConnection,
+/// Move a reading to the trash
+pub async fn delete<C>(
+    conn: &C,
     id: i32,
-) -> Result<Option<electricity_reading::Model>, DbErr> {
-    if let Some(model) = electricity_reading::Entity::find_by_id(id).one(db).await? {
-        let am: electricity_reading::ActiveModel = model.clone().into();
-        am.delete(db).await.map(|_| Some(model))
+) -> Result<Option<electricity_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let found = electricity_reading::Entity::find_by_id(id)
+        .filter(electricity_reading::Column::DeletedAt.is_null())
+        .one(conn)
+        .await?;
+    if let Some(model) = found {
+        let mut am: electricity_reading::ActiveModel = model.into();
+        am.deleted_at = Set(Some(chrono::Utc::now().naive_utc()));
+        am.update(conn).await.map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// GET readings changed after `since`, trashed ones included so clients can drop them
+pub async fn get_changed_since(db: &DatabaseConnection, since: NaiveDateTime) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::UpdatedAt.gt(since))
+        .order_by_asc(electricity_reading::Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// GET readings in the trash, most recently deleted first
+pub async fn get_deleted(db: &DatabaseConnection) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::DeletedAt.is_not_null())
+        .order_by_desc(electricity_reading::Column::DeletedAt)
+        .all(db)
+        .await
+}
+
+/// GET a reading in the trash by ID
+pub async fn get_deleted_by_id<C>(conn: &C, id: i32) -> Result<Option<electricity_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    electricity_reading::Entity::find_by_id(id)
+        .filter(electricity_reading::Column::DeletedAt.is_not_null())
+        .one(conn)
+        .await
+}
+
+/// Take a reading out of the trash
+pub async fn restore<C>(conn: &C, id: i32) -> Result<Option<electricity_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_deleted_by_id(conn, id).await? {
+        let mut am: electricity_reading::ActiveModel = model.into();
+        am.deleted_at = Set(None);
+        am.update(conn).await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Permanently remove readings deleted before `cutoff` that no bill still references
+pub async fn purge_deleted_before<C>(
+    conn: &C,
+    cutoff: NaiveDateTime,
+) -> Result<Vec<electricity_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let purgeable = electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::DeletedAt.lt(cutoff))
+        .filter(Expr::cust(
+            "NOT EXISTS (SELECT 1 FROM bill WHERE bill.reading_id = electricity_reading.id)",
+        ))
+        .all(conn)
+        .await?;
+
+    electricity_reading::Entity::delete_many()
+        .filter(electricity_reading::Column::Id.is_in(purgeable.iter().map(|r| r.id)))
+        .exec(conn)
+        .await?;
+    Ok(purgeable)
+}
+
+/// GET the latest readings of a room taken before `before`, newest first, leaving out `exclude_id`
+pub async fn get_recent_for_room<C>(
+    conn: &C,
+    room_id: i32,
+    before: NaiveDateTime,
+    exclude_id: i32,
+    limit: u64,
+) -> Result<Vec<electricity_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::RoomId.eq(room_id))
+        .filter(electricity_reading::Column::DeletedAt.is_null())
+        .filter(electricity_reading::Column::Id.ne(exclude_id))
+        .filter(electricity_reading::Column::CreatedAt.lt(before))
+        .order_by_desc(electricity_reading::Column::CreatedAt)
+        .limit(limit)
+        .all(conn)
+        .await
+}
+
+/// Record whether a reading looks anomalous
+pub async fn set_flag<C>(
+    conn: &C,
+    id: i32,
+    flag_reason: Option<String>,
+) -> Result<electricity_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    electricity_reading::ActiveModel {
+        id: Set(id),
+        flagged: Set(flag_reason.is_some()),
+        flag_reason: Set(flag_reason),
+        ..Default::default()
+    }
+    .update(conn)
+    .await
+}
+
+/// GET flagged readings taken in `[from, to)`, newest first
+pub async fn get_flagged(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    let mut query = electricity_reading::Entity::find()
+        .filter(electricity_reading::Column::Flagged.eq(true))
+        .filter(electricity_reading::Column::DeletedAt.is_null());
+    if let Some(from) = from {
+        query = query.filter(electricity_reading::Column::CreatedAt.gte(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(electricity_reading::Column::CreatedAt.lt(to));
+    }
+    if let Some(ids) = property_ids {
+        query = query.filter(electricity_reading::Column::RoomId.in_subquery(room_repo::ids_in_properties(ids)));
+    }
+    query
+        .order_by_desc(electricity_reading::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
 // ---------------------- INLINE TESTS ----------------------
 #[cfg(test)]
 mod tests {
@@ -72,6 +311,7 @@ mod tests {
         let room = room_repo::create(
             db,
             room::ActiveModel {
+                property_id: Set(1),
                 name: Set("Test Room".into()),
                 rent: Set(1000),
                 ..Default::default()
@@ -153,4 +393,30 @@ mod tests {
         let should_be_none = get_by_id(&db, reading.id).await.unwrap();
         assert!(should_be_none.is_none());
     }
+
+    #[tokio::test]
+    async fn test_recent_readings_and_flags() {
+        let db = get_test_db().await;
+        let (room, tenant) = setup_room_and_tenant(&db).await;
+
+        let now = Utc::now().naive_utc();
+        let mut ids = Vec::new();
+        for days_ago in [3, 2, 1] {
+            let mut reading = new_reading_model(tenant.id, room.id, 0, 100 + days_ago);
+            reading.created_at = Set(now - chrono::Duration::days(days_ago as i64));
+            ids.push(create(&db, reading).await.unwrap().id);
+        }
+
+        let recent = get_recent_for_room(&db, room.id, now, ids[2], 5).await.unwrap();
+        assert_eq!(recent.iter().map(|r| r.id).collect::<Vec<_>>(), vec![ids[1], ids[0]]);
+        assert_eq!(get_recent_for_room(&db, room.id, now, ids[2], 1).await.unwrap().len(), 1);
+
+        let flagged = set_flag(&db, ids[2], Some("spike".into())).await.unwrap();
+        assert!(flagged.flagged);
+        assert_eq!(get_flagged(&db, None, None, None).await.unwrap().len(), 1);
+        assert!(get_flagged(&db, Some(now), None, None).await.unwrap().is_empty());
+
+        let cleared = set_flag(&db, ids[2], None).await.unwrap();
+        assert!(!cleared.flagged && cleared.flag_reason.is_none());
+    }
 }
@@ -0,0 +1,53 @@
+use crate::entities::master_meter_reading;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder,
+};
+
+/// GET master meter readings of the given properties, all of them when `property_ids` is `None`; newest first
+pub async fn get_all(
+    db: &DatabaseConnection,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<master_meter_reading::Model>, DbErr> {
+    let mut query = master_meter_reading::Entity::find();
+    if let Some(ids) = property_ids {
+        query = query.filter(master_meter_reading::Column::PropertyId.is_in(ids.to_vec()));
+    }
+    query
+        .order_by_desc(master_meter_reading::Column::ReadingDate)
+        .all(db)
+        .await
+}
+
+/// GET master meter reading by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<master_meter_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    master_meter_reading::Entity::find_by_id(id).one(conn).await
+}
+
+/// CREATE a master meter reading
+pub async fn create<C>(
+    conn: &C,
+    item: master_meter_reading::ActiveModel,
+) -> Result<master_meter_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// DELETE a master meter reading
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<master_meter_reading::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    match get_by_id(conn, id).await? {
+        Some(model) => {
+            model.clone().delete(conn).await?;
+            Ok(Some(model))
+        }
+        None => Ok(None),
+    }
+}
@@ -2,4 +2,19 @@ pub mod room_repo;
 pub mod tenant_repo;
 pub mod electricity_reading_repo;
 pub mod additional_charge_repo;
-pub mod bill_repo;
\ No newline at end of file
+pub mod bill_repo;
+pub mod attachment_repo;
+pub mod bill_template_repo;
+pub mod notification_outbox_repo;
+pub mod webhook_endpoint_repo;
+pub mod webhook_delivery_repo;
+pub mod audit_log_repo;
+pub mod bill_note_repo;
+pub mod charge_type_repo;
+pub mod recurring_charge_repo;
+pub mod bill_discount_repo;
+pub mod report_repo;
+pub mod tenancy_repo;
+pub mod master_meter_reading_repo;
+pub mod property_repo;
+pub mod admin_user_repo;
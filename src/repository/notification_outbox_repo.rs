@@ -0,0 +1,195 @@
+use crate::entities::notification_outbox::{self, STATUS_FAILED, STATUS_PENDING, STATUS_SENT};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, Set, Statement, Value,
+};
+
+/// CREATE an outbox message
+pub async fn create<C>(
+    conn: &C,
+    item: notification_outbox::ActiveModel,
+) -> Result<notification_outbox::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// CLAIM pending messages on the given channels that are due for (re)delivery.
+/// Their next attempt moves to `lease_until` in the same statement, so another worker
+/// skips them until this one marks them sent or failed (or dies and the lease runs out).
+pub async fn claim_due(
+    db: &DatabaseConnection,
+    channels: &[&str],
+    now: NaiveDateTime,
+    lease_until: NaiveDateTime,
+    limit: u64,
+) -> Result<Vec<notification_outbox::Model>, DbErr> {
+    if channels.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders: Vec<String> = (0..channels.len()).map(|i| format!("${}", i + 4)).collect();
+    let sql = format!(
+        r#"UPDATE notification_outbox SET next_attempt_at = $1
+        WHERE id IN (
+            SELECT id FROM notification_outbox
+            WHERE status = $2 AND next_attempt_at <= $3 AND channel IN ({})
+            ORDER BY next_attempt_at
+            LIMIT {}
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *"#,
+        placeholders.join(", "),
+        limit
+    );
+    let mut values: Vec<Value> = vec![lease_until.into(), STATUS_PENDING.into(), now.into()];
+    values.extend(channels.iter().map(|c| Value::from(*c)));
+
+    let mut claimed = notification_outbox::Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(DatabaseBackend::Postgres, &sql, values))
+        .all(db)
+        .await?;
+    claimed.sort_by_key(|m| m.id);
+    Ok(claimed)
+}
+
+/// Whether a message for this bill event was already queued
+pub async fn exists_for_bill_event<C>(conn: &C, bill_id: i32, event: &str) -> Result<bool, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let count = notification_outbox::Entity::find()
+        .filter(notification_outbox::Column::BillId.eq(bill_id))
+        .filter(notification_outbox::Column::Event.eq(event))
+        .count(conn)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Mark a message as delivered, keeping the rendered content
+pub async fn mark_sent(
+    db: &DatabaseConnection,
+    item: notification_outbox::Model,
+    subject: String,
+    body: String,
+) -> Result<notification_outbox::Model, DbErr> {
+    let now = chrono::Utc::now().naive_utc();
+    let attempts = item.attempts + 1;
+    let mut am: notification_outbox::ActiveModel = item.into();
+    am.status = Set(STATUS_SENT.to_string());
+    am.attempts = Set(attempts);
+    am.subject = Set(Some(subject));
+    am.body = Set(Some(body));
+    am.last_error = Set(None);
+    am.sent_at = Set(Some(now));
+    am.update(db).await
+}
+
+/// Record a failed attempt; the message is retried at `next_attempt_at` unless `give_up` is set
+pub async fn mark_failed(
+    db: &DatabaseConnection,
+    item: notification_outbox::Model,
+    error: String,
+    next_attempt_at: NaiveDateTime,
+    give_up: bool,
+) -> Result<notification_outbox::Model, DbErr> {
+    let attempts = item.attempts + 1;
+    let mut am: notification_outbox::ActiveModel = item.into();
+    am.status = Set(if give_up { STATUS_FAILED } else { STATUS_PENDING }.to_string());
+    am.attempts = Set(attempts);
+    am.last_error = Set(Some(error));
+    am.next_attempt_at = Set(next_attempt_at);
+    am.update(db).await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+    use chrono::{Duration, Utc};
+
+    fn new_message(event: &str, next_attempt_at: NaiveDateTime) -> notification_outbox::ActiveModel {
+        notification_outbox::ActiveModel {
+            event: Set(event.to_string()),
+            channel: Set("email".into()),
+            recipient: Set("tenant@example.com".into()),
+            next_attempt_at: Set(next_attempt_at),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_claim_due() {
+        let db = get_test_db().await;
+        reset_table(&db, "notification_outbox").await;
+
+        let now = Utc::now().naive_utc();
+        create(&db, new_message("bill.created", now - Duration::minutes(1))).await.unwrap();
+        create(&db, new_message("bill.created", now + Duration::hours(1))).await.unwrap();
+
+        let lease = now + Duration::minutes(5);
+        let other_channels = claim_due(&db, &["sms", "chat"], now, lease, 10).await.unwrap();
+        assert!(other_channels.is_empty());
+
+        let due = claim_due(&db, &["email"], now, lease, 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].status, STATUS_PENDING);
+        assert!(due[0].next_attempt_at > now);
+
+        // Claimed: a second worker polling at the same time gets nothing
+        assert!(claim_due(&db, &["email"], now, lease, 10).await.unwrap().is_empty());
+        // Until the lease runs out
+        assert_eq!(claim_due(&db, &["email"], lease, lease, 10).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mark_sent() {
+        let db = get_test_db().await;
+        reset_table(&db, "notification_outbox").await;
+
+        let now = Utc::now().naive_utc();
+        let msg = create(&db, new_message("bill.paid", now)).await.unwrap();
+
+        let sent = mark_sent(&db, msg, "Subject".into(), "Body".into()).await.unwrap();
+        assert_eq!(sent.status, STATUS_SENT);
+        assert_eq!(sent.attempts, 1);
+        assert!(sent.sent_at.is_some());
+
+        let later = now + Duration::minutes(1);
+        assert!(claim_due(&db, &["email"], later, later, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mark_failed_and_retry() {
+        let db = get_test_db().await;
+        reset_table(&db, "notification_outbox").await;
+
+        let now = Utc::now().naive_utc();
+        let msg = create(&db, new_message("bill.reminder", now)).await.unwrap();
+
+        let retry_at = now + Duration::minutes(5);
+        let failed = mark_failed(&db, msg, "connection refused".into(), retry_at, false)
+            .await
+            .unwrap();
+        assert_eq!(failed.status, STATUS_PENDING);
+        assert_eq!(failed.attempts, 1);
+
+        assert!(claim_due(&db, &["email"], now, now, 10).await.unwrap().is_empty());
+        let claimed = claim_due(&db, &["email"], retry_at, retry_at, 10).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+
+        let failed = claimed.into_iter().next().unwrap();
+        let gave_up = mark_failed(&db, failed, "still down".into(), retry_at, true).await.unwrap();
+        assert_eq!(gave_up.status, STATUS_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_exists_for_bill_event() {
+        let db = get_test_db().await;
+        reset_table(&db, "notification_outbox").await;
+
+        assert!(!exists_for_bill_event(&db, 1, "bill.reminder").await.unwrap());
+    }
+}
@@ -0,0 +1,226 @@
+use crate::entities::{property, room};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DbErr, EntityTrait, FromQueryResult,
+    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, Statement,
+};
+
+/// A row whose property decides who may see it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PropertyOwner {
+    Room(i32),
+    Tenant(i32),
+    /// Through the reading's room
+    Reading(i32),
+    /// Through the room of the reading it was raised on
+    Bill(i32),
+    MasterReading(i32),
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PropertyIdRow {
+    property_id: i32,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TenantIdRow {
+    tenant_id: i32,
+}
+
+/// GET properties, all of them when `ids` is `None`
+pub async fn get_all<C>(conn: &C, ids: Option<&[i32]>) -> Result<Vec<property::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut query = property::Entity::find().order_by_asc(property::Column::Id);
+    if let Some(ids) = ids {
+        query = query.filter(property::Column::Id.is_in(ids.to_vec()));
+    }
+    query.all(conn).await
+}
+
+/// GET property by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<property::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    property::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET the oldest property, which rooms go to when none is named
+pub async fn get_default<C>(conn: &C) -> Result<Option<property::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    property::Entity::find().order_by_asc(property::Column::Id).one(conn).await
+}
+
+/// GET the property a row belongs to; `None` if the row does not exist
+pub async fn property_id_of<C>(conn: &C, owner: PropertyOwner) -> Result<Option<i32>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let (sql, id) = match owner {
+        PropertyOwner::Room(id) => ("SELECT property_id FROM room WHERE id = $1", id),
+        PropertyOwner::Tenant(id) => ("SELECT property_id FROM tenant WHERE id = $1", id),
+        PropertyOwner::Reading(id) => (
+            "SELECT r.property_id FROM electricity_reading er JOIN room r ON r.id = er.room_id WHERE er.id = $1",
+            id,
+        ),
+        PropertyOwner::Bill(id) => (
+            "SELECT r.property_id FROM bill b
+             JOIN electricity_reading er ON er.id = b.reading_id
+             JOIN room r ON r.id = er.room_id
+             WHERE b.id = $1",
+            id,
+        ),
+        PropertyOwner::MasterReading(id) => ("SELECT property_id FROM master_meter_reading WHERE id = $1", id),
+    };
+    let row = PropertyIdRow::find_by_statement(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        sql,
+        vec![id.into()],
+    ))
+    .one(conn)
+    .await?;
+    Ok(row.map(|r| r.property_id))
+}
+
+/// GET the tenant a tenant record, reading or bill was raised for; rooms and master readings have none
+pub async fn tenant_id_of<C>(conn: &C, owner: PropertyOwner) -> Result<Option<i32>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let (sql, id) = match owner {
+        PropertyOwner::Tenant(id) => ("SELECT id AS tenant_id FROM tenant WHERE id = $1", id),
+        PropertyOwner::Reading(id) => ("SELECT tenant_id FROM electricity_reading WHERE id = $1", id),
+        PropertyOwner::Bill(id) => ("SELECT tenant_id FROM bill WHERE id = $1", id),
+        PropertyOwner::Room(_) | PropertyOwner::MasterReading(_) => return Ok(None),
+    };
+    let row = TenantIdRow::find_by_statement(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        sql,
+        vec![id.into()],
+    ))
+    .one(conn)
+    .await?;
+    Ok(row.map(|r| r.tenant_id))
+}
+
+/// GET the property a row belongs to, settings included
+pub async fn get_for<C>(conn: &C, owner: PropertyOwner) -> Result<Option<property::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    match property_id_of(conn, owner).await? {
+        Some(id) => get_by_id(conn, id).await,
+        None => Ok(None),
+    }
+}
+
+/// COUNT the rooms of a property, trashed ones included
+pub async fn count_rooms<C>(conn: &C, id: i32) -> Result<u64, DbErr>
+where
+    C: ConnectionTrait,
+{
+    room::Entity::find().filter(room::Column::PropertyId.eq(id)).count(conn).await
+}
+
+/// CREATE a property
+pub async fn create<C>(conn: &C, item: property::ActiveModel) -> Result<property::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// UPDATE a property
+pub async fn update<C>(conn: &C, id: i32, mut item: property::ActiveModel) -> Result<property::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.update(conn).await
+}
+
+/// DELETE a property; fails while rooms still belong to it
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<property::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    match get_by_id(conn, id).await? {
+        Some(model) => {
+            model.clone().delete(conn).await?;
+            Ok(Some(model))
+        }
+        None => Ok(None),
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{bill, tenant};
+    use crate::test_utils::{add_reading, add_tenant, get_test_db, reset_rooms};
+
+    #[tokio::test]
+    async fn test_property_follows_room() {
+        let db = get_test_db().await;
+        reset_rooms(&db).await;
+
+        let annex = create(&db, property::ActiveModel { name: Set("Annex (repo test)".into()), ..Default::default() })
+            .await
+            .unwrap();
+        let main = get_default(&db).await.unwrap().unwrap();
+        let mut rooms = Vec::new();
+        for property_id in [main.id, annex.id] {
+            rooms.push(
+                room::ActiveModel {
+                    property_id: Set(property_id),
+                    name: Set("101".into()),
+                    rent: Set(1000),
+                    ..Default::default()
+                }
+                .insert(&db)
+                .await
+                .unwrap(),
+            );
+        }
+        let tenant = add_tenant(&db, &rooms[1], "Ana").await;
+        assert_eq!(tenant.property_id, annex.id);
+
+        let reading = add_reading(&db, &tenant, 0, 5).await;
+        let bill = bill::ActiveModel {
+            reading_id: Set(reading.id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(1000),
+            electric_charges: Set(0),
+            total_amount: Set(1000),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(property_id_of(&db, PropertyOwner::Bill(bill.id)).await.unwrap(), Some(annex.id));
+        assert_eq!(property_id_of(&db, PropertyOwner::Room(rooms[0].id)).await.unwrap(), Some(main.id));
+        assert_eq!(property_id_of(&db, PropertyOwner::Reading(0)).await.unwrap(), None);
+
+        // Tenants are checked by ownership instead
+        assert_eq!(tenant_id_of(&db, PropertyOwner::Bill(bill.id)).await.unwrap(), Some(tenant.id));
+        assert_eq!(tenant_id_of(&db, PropertyOwner::Reading(reading.id)).await.unwrap(), Some(tenant.id));
+        assert_eq!(tenant_id_of(&db, PropertyOwner::Tenant(tenant.id)).await.unwrap(), Some(tenant.id));
+        assert_eq!(tenant_id_of(&db, PropertyOwner::Room(rooms[1].id)).await.unwrap(), None);
+
+        // Moving the tenant to the main building moves them with it
+        let moved = tenant::ActiveModel { id: Set(tenant.id), room_id: Set(rooms[0].id), ..Default::default() }
+            .update(&db)
+            .await
+            .unwrap();
+        assert_eq!(moved.property_id, main.id);
+
+        reset_rooms(&db).await;
+        assert_eq!(count_rooms(&db, annex.id).await.unwrap(), 0);
+        assert!(delete(&db, annex.id).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::entities::recurring_charge;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+/// GET recurring charges, optionally only those of a tenant or a room
+pub async fn get_all(
+    db: &DatabaseConnection,
+    tenant_id: Option<i32>,
+    room_id: Option<i32>,
+) -> Result<Vec<recurring_charge::Model>, DbErr> {
+    let mut query = recurring_charge::Entity::find();
+    if let Some(tenant_id) = tenant_id {
+        query = query.filter(recurring_charge::Column::TenantId.eq(tenant_id));
+    }
+    if let Some(room_id) = room_id {
+        query = query.filter(recurring_charge::Column::RoomId.eq(room_id));
+    }
+    query.order_by_asc(recurring_charge::Column::Id).all(db).await
+}
+
+/// GET recurring charge by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<recurring_charge::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    recurring_charge::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET active recurring charges that apply to a tenant, directly or through their room
+pub async fn get_active_for<C>(
+    conn: &C,
+    tenant_id: i32,
+    room_id: i32,
+) -> Result<Vec<recurring_charge::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    recurring_charge::Entity::find()
+        .filter(recurring_charge::Column::IsActive.eq(true))
+        .filter(
+            Condition::any()
+                .add(recurring_charge::Column::TenantId.eq(tenant_id))
+                .add(recurring_charge::Column::RoomId.eq(room_id)),
+        )
+        .order_by_asc(recurring_charge::Column::Id)
+        .all(conn)
+        .await
+}
+
+/// CREATE a recurring charge
+pub async fn create<C>(conn: &C, item: recurring_charge::ActiveModel) -> Result<recurring_charge::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// UPDATE a recurring charge
+pub async fn update<C>(
+    conn: &C,
+    id: i32,
+    mut item: recurring_charge::ActiveModel,
+) -> Result<recurring_charge::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.update(conn).await
+}
+
+/// DELETE a recurring charge; bills it was already added to keep their lines
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<recurring_charge::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = recurring_charge::Entity::find_by_id(id).one(conn).await? {
+        let am: recurring_charge::ActiveModel = model.clone().into();
+        am.delete(conn).await.map(|_| Some(model))
+    } else {
+        Ok(None)
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::charge_type_repo;
+    use crate::test_utils::{add_room, add_tenant, get_test_db, reset_rooms};
+
+    #[tokio::test]
+    async fn test_get_active_for_tenant_and_room() {
+        let db = get_test_db().await;
+        reset_rooms(&db).await;
+
+        let room = add_room(&db, "Recurring Room").await;
+        let tenant = add_tenant(&db, &room, "Recurring Tenant").await;
+
+        let water = charge_type_repo::get_by_code(&db, "water").await.unwrap().unwrap();
+        let parking = charge_type_repo::get_by_code(&db, "parking").await.unwrap().unwrap();
+        let new_charge = |charge_type_id, tenant_id, room_id, is_active| recurring_charge::ActiveModel {
+            charge_type_id: Set(charge_type_id),
+            tenant_id: Set(tenant_id),
+            room_id: Set(room_id),
+            amount: Set(Some(15000)),
+            is_active: Set(is_active),
+            ..Default::default()
+        };
+
+        let by_room = create(&db, new_charge(water.id, None, Some(room.id), true)).await.unwrap();
+        let by_tenant = create(&db, new_charge(parking.id, Some(tenant.id), None, true)).await.unwrap();
+        create(&db, new_charge(parking.id, Some(tenant.id), None, false)).await.unwrap();
+
+        let active = get_active_for(&db, tenant.id, room.id).await.unwrap();
+        assert_eq!(active, vec![by_room, by_tenant.clone()]);
+
+        assert_eq!(get_all(&db, Some(tenant.id), None).await.unwrap().len(), 2);
+        assert_eq!(delete(&db, by_tenant.id).await.unwrap(), Some(by_tenant));
+    }
+}
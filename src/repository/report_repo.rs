@@ -0,0 +1,490 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DbErr, FromQueryResult, Statement, Value};
+
+// Every report counts issued and paid bills that are not in the trash. `$1`/`$2` are the
+// optional inclusive start and exclusive end of the period; NULL leaves that side open.
+// `properties` limits a report to rooms of those properties, all of them when `None`.
+
+/// Amounts billed, adjusted by notes and collected, per calendar month and currency
+#[derive(Debug, FromQueryResult)]
+pub struct MonthlyIncomeRow {
+    pub month: NaiveDate,
+    pub currency: String,
+    pub bills: i64,
+    pub billed: i64,
+    pub adjustments: i64,
+    pub collected: i64,
+}
+
+/// What a tenant still owes, split by the age of the bill it is owed on
+#[derive(Debug, FromQueryResult)]
+pub struct ReceivableRow {
+    pub tenant_id: i32,
+    pub tenant_name: String,
+    pub currency: String,
+    pub bills: i64,
+    pub days_0_30: i64,
+    pub days_31_60: i64,
+    pub days_over_60: i64,
+    pub total: i64,
+}
+
+/// Billed amounts per room, split into rent, electricity, other charges and discounts
+#[derive(Debug, FromQueryResult)]
+pub struct RoomRevenueRow {
+    pub room_id: i32,
+    pub room_name: String,
+    pub currency: String,
+    pub bills: i64,
+    pub rent: i64,
+    pub electricity: i64,
+    pub other_charges: i64,
+    pub discounts: i64,
+    pub total: i64,
+}
+
+/// Electricity billed next to the kWh it was billed for, per month and currency
+#[derive(Debug, FromQueryResult)]
+pub struct ElectricityRow {
+    pub month: NaiveDate,
+    pub currency: String,
+    pub readings: i64,
+    pub consumption: i64,
+    pub revenue: i64,
+}
+
+/// Whose consumption a series follows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConsumptionGroup {
+    Room,
+    Tenant,
+}
+
+/// kWh read for one room or tenant in one month
+#[derive(Debug, FromQueryResult)]
+pub struct ConsumptionRow {
+    pub month: NaiveDate,
+    pub key_id: i32,
+    pub key_name: String,
+    pub readings: i64,
+    pub consumption: i64,
+}
+
+/// Sum of room readings next to the master meter, per month
+#[derive(Debug, FromQueryResult)]
+pub struct BuildingConsumptionRow {
+    pub month: NaiveDate,
+    pub rooms: i64,
+    pub master: Option<i64>,
+}
+
+fn period(from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Vec<Value> {
+    vec![from.into(), to.into()]
+}
+
+/// Property ids as a `1,2,3` list, bound as text since arrays are not enabled; NULL for all
+fn properties_value(properties: Option<&[i32]>) -> Value {
+    properties
+        .map(|ids| ids.iter().map(i32::to_string).collect::<Vec<_>>().join(","))
+        .into()
+}
+
+/// `column` is one of the properties in parameter `$n`
+fn in_properties(column: &str, n: usize) -> String {
+    format!("(${n}::text IS NULL OR {column} = ANY(string_to_array(${n}::text, ',')::int[]))")
+}
+
+/// Bill `b` was raised on a reading of a room of the properties in parameter `$n`
+fn bill_in_properties(n: usize) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM electricity_reading pr JOIN room pr_room ON pr_room.id = pr.room_id
+                 WHERE pr.id = b.reading_id AND {})",
+        in_properties("pr_room.property_id", n)
+    )
+}
+
+async fn query<C, T>(conn: &C, sql: &str, values: Vec<Value>) -> Result<Vec<T>, DbErr>
+where
+    C: ConnectionTrait,
+    T: FromQueryResult,
+{
+    T::find_by_statement(Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, values))
+        .all(conn)
+        .await
+}
+
+/// Bills count towards the month they were issued, notes the month they were written
+/// and payments the month the bill was marked paid.
+pub async fn monthly_income<C>(
+    conn: &C,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    properties: Option<&[i32]>,
+) -> Result<Vec<MonthlyIncomeRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut values = period(from, to);
+    values.push(properties_value(properties));
+    query(
+        conn,
+        &format!(
+            "SELECT date_trunc('month', at)::date AS month, currency,
+                COUNT(*) FILTER (WHERE kind = 'bill') AS bills,
+                SUM(billed)::bigint AS billed,
+                SUM(adjustment)::bigint AS adjustments,
+                SUM(collected)::bigint AS collected
+         FROM (
+             SELECT 'bill' AS kind, b.issued_at AS at, b.currency, b.total_amount AS billed, 0::bigint AS adjustment, 0::bigint AS collected
+             FROM bill b
+             WHERE b.status IN ('issued', 'paid') AND b.deleted_at IS NULL
+               AND ($1::timestamp IS NULL OR b.issued_at >= $1) AND ($2::timestamp IS NULL OR b.issued_at < $2)
+               AND {bills}
+             UNION ALL
+             SELECT 'note', n.created_at, b.currency, 0, CASE WHEN n.kind = 'credit' THEN -n.amount ELSE n.amount END, 0
+             FROM bill_note n JOIN bill b ON b.id = n.bill_id
+             WHERE b.status IN ('issued', 'paid') AND b.deleted_at IS NULL
+               AND ($1::timestamp IS NULL OR n.created_at >= $1) AND ($2::timestamp IS NULL OR n.created_at < $2)
+               AND {bills}
+             UNION ALL
+             SELECT 'payment', b.paid_at, b.currency, 0, 0, COALESCE(b.amount_paid, b.total_amount)
+             FROM bill b
+             WHERE b.status = 'paid' AND b.deleted_at IS NULL AND b.paid_at IS NOT NULL
+               AND ($1::timestamp IS NULL OR b.paid_at >= $1) AND ($2::timestamp IS NULL OR b.paid_at < $2)
+               AND {bills}
+         ) entries
+         GROUP BY 1, currency
+         ORDER BY 1, currency",
+            bills = bill_in_properties(3)
+        ),
+        values,
+    )
+    .await
+}
+
+/// Unpaid totals plus notes on every bill, aged by days between issue and `as_of`.
+/// Tenants who owe nothing are left out.
+pub async fn receivables<C>(
+    conn: &C,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    as_of: NaiveDateTime,
+    properties: Option<&[i32]>,
+) -> Result<Vec<ReceivableRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut values = period(from, to);
+    values.push(as_of.into());
+    values.push(properties_value(properties));
+    query(
+        conn,
+        &format!(
+            "SELECT o.tenant_id, t.name AS tenant_name, o.currency,
+                COUNT(*) FILTER (WHERE o.outstanding <> 0) AS bills,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE o.age <= 30), 0)::bigint AS days_0_30,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE o.age BETWEEN 31 AND 60), 0)::bigint AS days_31_60,
+                COALESCE(SUM(o.outstanding) FILTER (WHERE o.age > 60), 0)::bigint AS days_over_60,
+                SUM(o.outstanding)::bigint AS total
+         FROM (
+             SELECT b.tenant_id, b.currency,
+                    ($3::timestamp::date - b.issued_at::date) AS age,
+                    b.total_amount - COALESCE(b.amount_paid, 0)
+                      + COALESCE((SELECT SUM(CASE WHEN n.kind = 'credit' THEN -n.amount ELSE n.amount END)
+                                  FROM bill_note n WHERE n.bill_id = b.id), 0) AS outstanding
+             FROM bill b
+             WHERE b.status IN ('issued', 'paid') AND b.deleted_at IS NULL
+               AND ($1::timestamp IS NULL OR b.issued_at >= $1) AND ($2::timestamp IS NULL OR b.issued_at < $2)
+               AND b.issued_at <= $3
+               AND {bills}
+         ) o
+         JOIN tenant t ON t.id = o.tenant_id
+         GROUP BY o.tenant_id, t.name, o.currency
+         HAVING SUM(o.outstanding) <> 0
+         ORDER BY total DESC, o.tenant_id",
+            bills = bill_in_properties(4)
+        ),
+        values,
+    )
+    .await
+}
+
+/// Bills are tied to a room through their meter reading
+pub async fn revenue_by_room<C>(
+    conn: &C,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    properties: Option<&[i32]>,
+) -> Result<Vec<RoomRevenueRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut values = period(from, to);
+    values.push(properties_value(properties));
+    query(
+        conn,
+        &format!(
+            "SELECT r.id AS room_id, r.name AS room_name, b.currency,
+                COUNT(*) AS bills,
+                SUM(b.room_charges)::bigint AS rent,
+                SUM(b.electric_charges)::bigint AS electricity,
+                SUM(b.total_amount + b.discount_amount - b.room_charges - b.electric_charges)::bigint AS other_charges,
+                SUM(b.discount_amount)::bigint AS discounts,
+                SUM(b.total_amount)::bigint AS total
+         FROM bill b
+         JOIN electricity_reading er ON er.id = b.reading_id
+         JOIN room r ON r.id = er.room_id
+         WHERE b.status IN ('issued', 'paid') AND b.deleted_at IS NULL
+           AND ($1::timestamp IS NULL OR b.issued_at >= $1) AND ($2::timestamp IS NULL OR b.issued_at < $2)
+           AND {rooms}
+         GROUP BY r.id, r.name, b.currency
+         ORDER BY total DESC, r.id",
+            rooms = in_properties("r.property_id", 3)
+        ),
+        values,
+    )
+    .await
+}
+
+/// Consumption comes from the reading each bill was raised on
+pub async fn electricity_by_month<C>(
+    conn: &C,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    properties: Option<&[i32]>,
+) -> Result<Vec<ElectricityRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut values = period(from, to);
+    values.push(properties_value(properties));
+    query(
+        conn,
+        &format!(
+            "SELECT date_trunc('month', b.issued_at)::date AS month, b.currency,
+                COUNT(*) AS readings,
+                SUM(er.consumption)::bigint AS consumption,
+                SUM(b.electric_charges)::bigint AS revenue
+         FROM bill b
+         JOIN electricity_reading er ON er.id = b.reading_id
+         JOIN room r ON r.id = er.room_id
+         WHERE b.status IN ('issued', 'paid') AND b.deleted_at IS NULL
+           AND ($1::timestamp IS NULL OR b.issued_at >= $1) AND ($2::timestamp IS NULL OR b.issued_at < $2)
+           AND {rooms}
+         GROUP BY 1, b.currency
+         ORDER BY 1, b.currency",
+            rooms = in_properties("r.property_id", 3)
+        ),
+        values,
+    )
+    .await
+}
+
+/// Readings count towards the month they were taken; `id` narrows to one room or tenant
+pub async fn consumption_by_month<C>(
+    conn: &C,
+    group: ConsumptionGroup,
+    id: Option<i32>,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    properties: Option<&[i32]>,
+) -> Result<Vec<ConsumptionRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let (table, column) = match group {
+        ConsumptionGroup::Room => ("room", "room_id"),
+        ConsumptionGroup::Tenant => ("tenant", "tenant_id"),
+    };
+    let mut values = period(from, to);
+    values.push(id.into());
+    values.push(properties_value(properties));
+    let rooms = in_properties("k.property_id", 4);
+    query(
+        conn,
+        &format!(
+            "SELECT date_trunc('month', er.created_at)::date AS month, k.id AS key_id, k.name AS key_name,
+                    COUNT(*) AS readings,
+                    SUM(er.consumption)::bigint AS consumption
+             FROM electricity_reading er
+             JOIN {table} k ON k.id = er.{column}
+             WHERE er.deleted_at IS NULL
+               AND ($1::timestamp IS NULL OR er.created_at >= $1) AND ($2::timestamp IS NULL OR er.created_at < $2)
+               AND ($3::integer IS NULL OR k.id = $3)
+               AND {rooms}
+             GROUP BY 1, k.id, k.name
+             ORDER BY k.id, 1"
+        ),
+        values,
+    )
+    .await
+}
+
+/// Months with room readings, master meter readings or both
+pub async fn building_consumption_by_month<C>(
+    conn: &C,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    properties: Option<&[i32]>,
+) -> Result<Vec<BuildingConsumptionRow>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut values = period(from, to);
+    values.push(properties_value(properties));
+    query(
+        conn,
+        &format!(
+            "WITH rooms AS (
+             SELECT date_trunc('month', er.created_at)::date AS month, SUM(er.consumption)::bigint AS consumption
+             FROM electricity_reading er
+             JOIN room r ON r.id = er.room_id
+             WHERE er.deleted_at IS NULL
+               AND ($1::timestamp IS NULL OR er.created_at >= $1) AND ($2::timestamp IS NULL OR er.created_at < $2)
+               AND {rooms}
+             GROUP BY 1
+         ), master AS (
+             SELECT date_trunc('month', reading_date)::date AS month, SUM(consumption)::bigint AS consumption
+             FROM master_meter_reading
+             WHERE ($1::timestamp IS NULL OR reading_date >= $1) AND ($2::timestamp IS NULL OR reading_date < $2)
+               AND {master}
+             GROUP BY 1
+         )
+         SELECT COALESCE(r.month, m.month) AS month, COALESCE(r.consumption, 0) AS rooms, m.consumption AS master
+         FROM rooms r
+         FULL JOIN master m ON m.month = r.month
+         ORDER BY 1",
+            rooms = in_properties("r.property_id", 3),
+            master = in_properties("property_id", 3)
+        ),
+        values,
+    )
+    .await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{bill, bill_note, master_meter_reading, tenant};
+    use crate::test_utils::{add_reading, add_room, add_tenant, get_test_db, reset_rooms, reset_table};
+    use chrono::{Datelike, Duration, NaiveDate, Utc};
+    use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+
+    fn at(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    async fn setup_tenant(db: &DatabaseConnection, name: &str) -> tenant::Model {
+        let room = add_room(db, &format!("Room {}", name)).await;
+        add_tenant(db, &room, name).await
+    }
+
+    async fn add_bill(
+        db: &DatabaseConnection,
+        tenant: &tenant::Model,
+        status: &str,
+        issued_at: NaiveDateTime,
+        paid_at: Option<NaiveDateTime>,
+        consumption: i32,
+    ) -> bill::Model {
+        let reading = add_reading(db, tenant, 0, consumption).await;
+        let total = 95_000 + consumption as i64 * 1_500 + 2_000;
+        bill::ActiveModel {
+            reading_id: Set(reading.id),
+            tenant_id: Set(tenant.id),
+            room_charges: Set(100_000),
+            electric_charges: Set(consumption as i64 * 1_500),
+            discount_amount: Set(5_000),
+            total_amount: Set(total),
+            paid: Set(status == bill::STATUS_PAID),
+            amount_paid: Set((status == bill::STATUS_PAID).then_some(total)),
+            status: Set(status.into()),
+            issued_at: Set(Some(issued_at)),
+            paid_at: Set(paid_at),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reports_aggregate_issued_and_paid_bills() {
+        let db = get_test_db().await;
+        reset_rooms(&db).await;
+        let ana = setup_tenant(&db, "Ana").await;
+        let ben = setup_tenant(&db, "Ben").await;
+
+        // Ana: September bill paid in October, October bill still open
+        let sept = add_bill(&db, &ana, bill::STATUS_PAID, at(2026, 9, 1), Some(at(2026, 10, 3)), 100).await;
+        add_bill(&db, &ana, bill::STATUS_ISSUED, at(2026, 10, 1), None, 50).await;
+        // Ben: an old open bill and a voided one that never counts
+        add_bill(&db, &ben, bill::STATUS_ISSUED, at(2026, 8, 1), None, 10).await;
+        add_bill(&db, &ben, bill::STATUS_VOID, at(2026, 10, 1), None, 10).await;
+
+        bill_note::ActiveModel {
+            bill_id: Set(sept.id),
+            tenant_id: Set(ana.id),
+            kind: Set(bill_note::KIND_DEBIT.into()),
+            amount: Set(3_000),
+            reason: Set("Late fee".into()),
+            created_by: Set("admin:root".into()),
+            created_at: Set(at(2026, 10, 5)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let income = monthly_income(&db, Some(at(2026, 9, 1)), Some(at(2026, 11, 1)), None).await.unwrap();
+        assert_eq!(income.len(), 2);
+        assert_eq!((income[0].month, income[0].bills, income[0].billed, income[0].collected), (NaiveDate::from_ymd_opt(2026, 9, 1).unwrap(), 1, 247_000, 0));
+        assert_eq!((income[1].bills, income[1].billed, income[1].adjustments, income[1].collected), (1, 172_000, 3_000, 247_000));
+
+        let as_of = at(2026, 10, 1) + Duration::days(5);
+        let owed = receivables(&db, None, None, as_of, None).await.unwrap();
+        assert_eq!(owed.len(), 2);
+        let ana_owes = owed.iter().find(|r| r.tenant_id == ana.id).unwrap();
+        assert_eq!((ana_owes.days_0_30, ana_owes.days_31_60, ana_owes.days_over_60), (172_000, 3_000, 0));
+        assert_eq!((ana_owes.bills, ana_owes.total), (2, 175_000));
+        let ben_owes = owed.iter().find(|r| r.tenant_id == ben.id).unwrap();
+        assert_eq!((ben_owes.days_over_60, ben_owes.total), (112_000, 112_000));
+
+        let rooms = revenue_by_room(&db, None, None, None).await.unwrap();
+        let ana_room = rooms.iter().find(|r| r.room_id == ana.room_id).unwrap();
+        assert_eq!((ana_room.bills, ana_room.rent, ana_room.electricity), (2, 200_000, 225_000));
+        assert_eq!((ana_room.other_charges, ana_room.discounts, ana_room.total), (4_000, 10_000, 419_000));
+        // Rooms of another property are left out
+        assert!(revenue_by_room(&db, None, None, Some(&[0])).await.unwrap().is_empty());
+        assert_eq!(monthly_income(&db, None, None, Some(&[1])).await.unwrap().len(), 3);
+        assert!(receivables(&db, None, None, as_of, Some(&[])).await.unwrap().is_empty());
+
+        let power = electricity_by_month(&db, Some(at(2026, 10, 1)), None, None).await.unwrap();
+        assert_eq!(power.len(), 1);
+        assert_eq!((power[0].readings, power[0].consumption, power[0].revenue), (1, 50, 75_000));
+
+        let by_room = consumption_by_month(&db, ConsumptionGroup::Room, Some(ben.room_id), None, None, None).await.unwrap();
+        assert_eq!(by_room.len(), 1);
+        assert_eq!((by_room[0].key_name.as_str(), by_room[0].readings, by_room[0].consumption), ("Room Ben", 2, 20));
+        let by_tenant = consumption_by_month(&db, ConsumptionGroup::Tenant, None, None, None, None).await.unwrap();
+        assert_eq!(by_tenant.iter().filter(|r| r.key_id == ana.id).map(|r| r.consumption).sum::<i64>(), 150);
+
+        reset_table(&db, "master_meter_reading").await;
+        let month = NaiveDate::from_ymd_opt(Utc::now().year(), Utc::now().month(), 1).unwrap();
+        master_meter_reading::ActiveModel {
+            property_id: Set(1),
+            reading_date: Set(Utc::now().naive_utc()),
+            prev_reading: Set(0),
+            curr_reading: Set(200),
+            consumption: Set(200),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let building = building_consumption_by_month(&db, None, None, None).await.unwrap();
+        assert_eq!(building.len(), 1);
+        assert_eq!((building[0].month, building[0].rooms, building[0].master), (month, 170, Some(200)));
+    }
+}
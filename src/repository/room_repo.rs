@@ -1,35 +1,155 @@
-use sea_orm::{DatabaseConnection, EntityTrait, ActiveModelTrait, Set, DbErr, QueryOrder};
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ActiveModelTrait, QueryFilter, Set, DbErr, QueryOrder};
+use sea_orm::sea_query::{Expr, Query, SelectStatement};
 use crate::entities::room;
+use chrono::NaiveDateTime;
 
 pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<room::Model>, DbErr> {
-    room::Entity::find()
+    get_in_properties(db, None).await
+}
+
+/// GET rooms of the given properties, all of them when `property_ids` is `None`
+pub async fn get_in_properties(db: &DatabaseConnection, property_ids: Option<&[i32]>) -> Result<Vec<room::Model>, DbErr> {
+    let mut query = room::Entity::find().filter(room::Column::DeletedAt.is_null());
+    if let Some(ids) = property_ids {
+        query = query.filter(room::Column::PropertyId.is_in(ids.to_vec()));
+    }
+    query
+        .order_by_asc(room::Column::PropertyId)
         .order_by_asc(room::Column::Name)
         .all(db)
         .await
 }
 
-pub async fn get_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<room::Model>, DbErr> {
-    room::Entity::find_by_id(id).one(db).await
+/// `SELECT id FROM room` of the given properties, for filtering rows that hang off a room
+pub fn ids_in_properties(property_ids: &[i32]) -> SelectStatement {
+    Query::select()
+        .column(room::Column::Id)
+        .from(room::Entity)
+        .and_where(room::Column::PropertyId.is_in(property_ids.to_vec()))
+        .to_owned()
+}
+
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    room::Entity::find_by_id(id)
+        .filter(room::Column::DeletedAt.is_null())
+        .one(conn)
+        .await
+}
+
+/// GET a property's rooms by name, trashed ones included since their names stay taken
+pub async fn get_by_names<C>(conn: &C, property_id: i32, names: &[String]) -> Result<Vec<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    room::Entity::find()
+        .filter(room::Column::PropertyId.eq(property_id))
+        .filter(room::Column::Name.is_in(names.iter().map(String::as_str)))
+        .all(conn)
+        .await
 }
 
-pub async fn create(db: &DatabaseConnection, item: room::ActiveModel) -> Result<room::Model, DbErr> {
-    item.insert(db).await
+pub async fn create<C>(conn: &C, item: room::ActiveModel) -> Result<room::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
 }
 
-pub async fn update(db: &DatabaseConnection, id: i32, mut item: room::ActiveModel) -> Result<room::Model, DbErr> {
+/// UPDATE a room still at `version`, bumping it; `RecordNotUpdated` if it has moved on
+pub async fn update<C>(conn: &C, id: i32, version: i32, mut item: room::ActiveModel) -> Result<room::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
     item.id = Set(id);
-    item.update(db).await
+    item.version = Set(version + 1);
+    room::Entity::update(item)
+        .filter(room::Column::Version.eq(version))
+        .filter(room::Column::DeletedAt.is_null())
+        .exec(conn)
+        .await
+}
+
+/// Move a room to the trash
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_by_id(conn, id).await? {
+        let mut am: room::ActiveModel = model.into();
+        am.deleted_at = Set(Some(chrono::Utc::now().naive_utc()));
+        am.update(conn).await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// GET rooms changed after `since`, trashed ones included so clients can drop them
+pub async fn get_changed_since(db: &DatabaseConnection, since: NaiveDateTime) -> Result<Vec<room::Model>, DbErr> {
+    room::Entity::find()
+        .filter(room::Column::UpdatedAt.gt(since))
+        .order_by_asc(room::Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// GET rooms in the trash, most recently deleted first
+pub async fn get_deleted(db: &DatabaseConnection) -> Result<Vec<room::Model>, DbErr> {
+    room::Entity::find()
+        .filter(room::Column::DeletedAt.is_not_null())
+        .order_by_desc(room::Column::DeletedAt)
+        .all(db)
+        .await
 }
 
-pub async fn delete(db: &DatabaseConnection, id: i32) -> Result<Option<room::Model>, DbErr> {
-    if let Some(model) = room::Entity::find_by_id(id).one(db).await? {
-        let am: room::ActiveModel = model.clone().into();
-        am.delete(db).await.map(|_| Some(model))
+/// GET a room in the trash by ID
+pub async fn get_deleted_by_id<C>(conn: &C, id: i32) -> Result<Option<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    room::Entity::find_by_id(id)
+        .filter(room::Column::DeletedAt.is_not_null())
+        .one(conn)
+        .await
+}
+
+/// Take a room out of the trash
+pub async fn restore<C>(conn: &C, id: i32) -> Result<Option<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_deleted_by_id(conn, id).await? {
+        let mut am: room::ActiveModel = model.into();
+        am.deleted_at = Set(None);
+        am.update(conn).await.map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// Permanently remove rooms deleted before `cutoff` that no tenant or reading still references
+pub async fn purge_deleted_before<C>(conn: &C, cutoff: NaiveDateTime) -> Result<Vec<room::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let purgeable = room::Entity::find()
+        .filter(room::Column::DeletedAt.lt(cutoff))
+        .filter(Expr::cust("NOT EXISTS (SELECT 1 FROM tenant WHERE tenant.room_id = room.id)"))
+        .filter(Expr::cust(
+            "NOT EXISTS (SELECT 1 FROM electricity_reading WHERE electricity_reading.room_id = room.id)",
+        ))
+        .all(conn)
+        .await?;
+
+    room::Entity::delete_many()
+        .filter(room::Column::Id.is_in(purgeable.iter().map(|r| r.id)))
+        .exec(conn)
+        .await?;
+    Ok(purgeable)
+}
+
 // ---------------------- INLINE TESTS ----------------------
 #[cfg(test)]
 mod tests {
@@ -41,6 +161,7 @@ mod tests {
         reset_table(&db, "room").await;
 
         let new_room = room::ActiveModel {
+            property_id: Set(1),
             name: Set("Unit Test Room".into()),
             rent: Set(1000),
             ..Default::default()
@@ -62,6 +183,7 @@ mod tests {
         reset_table(&db, "room").await;
 
         let new_room = room::ActiveModel {
+            property_id: Set(1),
             name: Set("Old Name".into()),
             rent: Set(500),
             ..Default::default()
@@ -73,9 +195,14 @@ mod tests {
         updated_room.name = Set("New Name".into());
         updated_room.rent = Set(1500);
 
-        let updated = update(&db, created.id, updated_room).await.unwrap();
+        let updated = update(&db, created.id, created.version, updated_room.clone()).await.unwrap();
         assert_eq!(updated.name, "New Name");
         assert_eq!(updated.rent, 1500);
+        assert_eq!(updated.version, created.version + 1);
+
+        // A writer still holding the old version is turned away
+        let stale = update(&db, created.id, created.version, updated_room).await;
+        assert!(matches!(stale, Err(DbErr::RecordNotUpdated)));
     }
 
     #[tokio::test]
@@ -84,6 +211,7 @@ mod tests {
         reset_table(&db, "room").await;
 
         let new_room = room::ActiveModel {
+            property_id: Set(1),
             name: Set("Delete Me".into()),
             rent: Set(100),
             ..Default::default()
@@ -96,4 +224,96 @@ mod tests {
         let should_be_none = get_by_id(&db, created.id).await.unwrap();
         assert!(should_be_none.is_none());
     }
+
+    #[tokio::test]
+    async fn test_restore_room_from_trash() {
+        let db = get_test_db().await;
+        reset_table(&db, "room").await;
+
+        let created = create(&db, room::ActiveModel {
+            property_id: Set(1),
+            name: Set("Trash Me".into()),
+            rent: Set(100),
+            ..Default::default()
+        }).await.unwrap();
+        delete(&db, created.id).await.unwrap();
+
+        // Deleted rooms are hidden from default queries but listed in the trash
+        assert!(get_all(&db).await.unwrap().is_empty());
+        let trashed = get_deleted(&db).await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert!(trashed[0].deleted_at.is_some());
+
+        let restored = restore(&db, created.id).await.unwrap().unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert!(get_by_id(&db, created.id).await.unwrap().is_some());
+
+        // Restoring a room that is not in the trash is a no-op
+        assert!(restore(&db, created.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_purge_deleted_rooms() {
+        let db = get_test_db().await;
+        reset_table(&db, "room").await;
+
+        let kept = create(&db, room::ActiveModel {
+            property_id: Set(1),
+            name: Set("Active".into()),
+            rent: Set(100),
+            ..Default::default()
+        }).await.unwrap();
+        let trashed = create(&db, room::ActiveModel {
+            property_id: Set(1),
+            name: Set("Trashed".into()),
+            rent: Set(100),
+            ..Default::default()
+        }).await.unwrap();
+        delete(&db, trashed.id).await.unwrap();
+
+        let deleted_at = get_deleted(&db).await.unwrap()[0].deleted_at.unwrap();
+
+        // Nothing is purged before the retention window has passed
+        let purged = purge_deleted_before(&db, deleted_at).await.unwrap();
+        assert!(purged.is_empty());
+
+        let purged = purge_deleted_before(&db, deleted_at + chrono::Duration::seconds(1)).await.unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, trashed.id);
+        assert!(get_deleted(&db).await.unwrap().is_empty());
+        assert!(get_by_id(&db, kept.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_bumps_updated_at_for_sync() {
+        let db = get_test_db().await;
+        reset_table(&db, "room").await;
+
+        let created = create(&db, room::ActiveModel {
+            property_id: Set(1),
+            name: Set("Synced".into()),
+            rent: Set(100),
+            ..Default::default()
+        }).await.unwrap();
+        let untouched = create(&db, room::ActiveModel {
+            property_id: Set(1),
+            name: Set("Untouched".into()),
+            rent: Set(100),
+            ..Default::default()
+        }).await.unwrap();
+        let since = created.updated_at.max(untouched.updated_at);
+
+        let mut changes: room::ActiveModel = created.clone().into();
+        changes.rent = Set(200);
+        let updated = update(&db, created.id, created.version, changes).await.unwrap();
+        assert!(updated.updated_at > since);
+
+        // Trashing a row counts as a change too
+        delete(&db, untouched.id).await.unwrap();
+
+        let changed = get_changed_since(&db, since).await.unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0].id, created.id);
+        assert!(changed[1].deleted_at.is_some());
+    }
 }
@@ -0,0 +1,80 @@
+use crate::entities::tenancy;
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+/// CREATE a tenancy
+pub async fn create<C>(conn: &C, item: tenancy::ActiveModel) -> Result<tenancy::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// GET the tenancy a tenant has not ended yet, if any
+pub async fn get_open_for_tenant<C>(conn: &C, tenant_id: i32) -> Result<Option<tenancy::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    tenancy::Entity::find()
+        .filter(tenancy::Column::TenantId.eq(tenant_id))
+        .filter(tenancy::Column::EndDate.is_null())
+        .order_by_desc(tenancy::Column::StartDate)
+        .one(conn)
+        .await
+}
+
+/// UPDATE the start or end of a tenancy
+pub async fn update<C>(conn: &C, id: i32, item: tenancy::ActiveModel) -> Result<tenancy::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let mut active = item;
+    active.id = Set(id);
+    active.update(conn).await
+}
+
+/// GET tenancies that have not ended by `at`, including ones that start later
+pub async fn get_current(db: &DatabaseConnection, at: NaiveDateTime) -> Result<Vec<tenancy::Model>, DbErr> {
+    tenancy::Entity::find()
+        .filter(
+            Condition::any()
+                .add(tenancy::Column::EndDate.is_null())
+                .add(tenancy::Column::EndDate.gt(at)),
+        )
+        .order_by_asc(tenancy::Column::StartDate)
+        .all(db)
+        .await
+}
+
+/// GET tenancies overlapping `[from, to)`, oldest first
+pub async fn get_overlapping(
+    db: &DatabaseConnection,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<tenancy::Model>, DbErr> {
+    tenancy::Entity::find()
+        .filter(tenancy::Column::StartDate.lt(to))
+        .filter(
+            Condition::any()
+                .add(tenancy::Column::EndDate.is_null())
+                .add(tenancy::Column::EndDate.gt(from)),
+        )
+        .order_by_asc(tenancy::Column::StartDate)
+        .all(db)
+        .await
+}
+
+/// GET all tenancies of a tenant, oldest first
+pub async fn get_all_by_tenant_id<C>(conn: &C, tenant_id: i32) -> Result<Vec<tenancy::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    tenancy::Entity::find()
+        .filter(tenancy::Column::TenantId.eq(tenant_id))
+        .order_by_asc(tenancy::Column::StartDate)
+        .all(conn)
+        .await
+}
@@ -1,42 +1,202 @@
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Select, SelectModel, Selector, Set};
+use sea_orm::sea_query::Expr;
 use crate::entities::tenant;
+use chrono::NaiveDateTime;
+
+/// Filters shared by the tenant list and the roster export
+#[derive(Debug, Default, Clone)]
+pub struct TenantFilter {
+    pub room_id: Option<i32>,
+    pub is_active: Option<bool>,
+    /// Only tenants of these properties; `None` for all
+    pub property_ids: Option<Vec<i32>>,
+}
+
+/// A tenant with their room's name, for the roster export
+#[derive(Debug, FromQueryResult)]
+pub struct TenantExportRow {
+    pub id: i32,
+    pub name: String,
+    pub room_id: i32,
+    pub room_name: Option<String>,
+    pub is_active: bool,
+    pub join_date: NaiveDateTime,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub notification_channels: String,
+}
+
+/// Tenants matching `filter`, by name
+fn filtered(filter: &TenantFilter) -> Select<tenant::Entity> {
+    let mut query = tenant::Entity::find().filter(tenant::Column::DeletedAt.is_null());
+    if let Some(room_id) = filter.room_id {
+        query = query.filter(tenant::Column::RoomId.eq(room_id));
+    }
+    if let Some(is_active) = filter.is_active {
+        query = query.filter(tenant::Column::IsActive.eq(is_active));
+    }
+    if let Some(ids) = &filter.property_ids {
+        query = query.filter(tenant::Column::PropertyId.is_in(ids.clone()));
+    }
+    query.order_by_asc(tenant::Column::Name)
+}
+
+/// GET tenants matching `filter`
+pub async fn get_filtered(db: &DatabaseConnection, filter: &TenantFilter) -> Result<Vec<tenant::Model>, DbErr> {
+    filtered(filter).all(db).await
+}
+
+/// Tenants matching `filter` as export rows; `stream` it rather than loading every row
+pub fn export_rows(filter: &TenantFilter) -> Selector<SelectModel<TenantExportRow>> {
+    filtered(filter)
+        .column_as(Expr::cust("(SELECT r.name FROM room r WHERE r.id = tenant.room_id)"), "room_name")
+        .into_model::<TenantExportRow>()
+}
 
 pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<tenant::Model>, DbErr> {
     tenant::Entity::find()
+        .filter(tenant::Column::DeletedAt.is_null())
         .order_by_asc(tenant::Column::Name)
         .all(db)
         .await
 }
 
-pub async fn get_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<tenant::Model>, DbErr> {
-    tenant::Entity::find_by_id(id).one(db).await
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    tenant::Entity::find_by_id(id)
+        .filter(tenant::Column::DeletedAt.is_null())
+        .one(conn)
+        .await
 }
 
-pub async fn get_by_name(db: &DatabaseConnection, name: &str) -> Result<Option<tenant::Model>, DbErr> {
-    tenant::Entity::find()
+/// GET tenants by name; names are only unique within a property
+pub async fn get_all_by_name(
+    db: &DatabaseConnection,
+    name: &str,
+    property_id: Option<i32>,
+) -> Result<Vec<tenant::Model>, DbErr> {
+    let mut query = tenant::Entity::find()
         .filter(tenant::Column::Name.eq(name))
-        .one(db)
+        .filter(tenant::Column::DeletedAt.is_null());
+    if let Some(property_id) = property_id {
+        query = query.filter(tenant::Column::PropertyId.eq(property_id));
+    }
+    query.order_by_asc(tenant::Column::PropertyId).all(db).await
+}
+
+/// GET a property's tenants by name, trashed ones included since their names stay taken
+pub async fn get_by_names<C>(conn: &C, property_id: i32, names: &[String]) -> Result<Vec<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    tenant::Entity::find()
+        .filter(tenant::Column::PropertyId.eq(property_id))
+        .filter(tenant::Column::Name.is_in(names.iter().map(String::as_str)))
+        .all(conn)
         .await
 }
 
-pub async fn create(db: &DatabaseConnection, item: tenant::ActiveModel) -> Result<tenant::Model, DbErr> {
-    item.insert(db).await
+pub async fn create<C>(conn: &C, item: tenant::ActiveModel) -> Result<tenant::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
 }
 
-pub async fn update(db: &DatabaseConnection, id: i32, mut item: tenant::ActiveModel) -> Result<tenant::Model, DbErr> {
+/// UPDATE a tenant still at `version`, bumping it; `RecordNotUpdated` if it has moved on
+pub async fn update<C>(conn: &C, id: i32, version: i32, mut item: tenant::ActiveModel) -> Result<tenant::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
     item.id = Set(id);
-    item.update(db).await
+    item.version = Set(version + 1);
+    tenant::Entity::update(item)
+        .filter(tenant::Column::Version.eq(version))
+        .filter(tenant::Column::DeletedAt.is_null())
+        .exec(conn)
+        .await
 }
 
-pub async fn delete(db: &DatabaseConnection, id: i32) -> Result<Option<tenant::Model>, DbErr> {
-    if let Some(model) = tenant::Entity::find_by_id(id).one(db).await? {
-        let am: tenant::ActiveModel = model.clone().into();
-        am.delete(db).await.map(|_| Some(model))
+/// Move a tenant to the trash
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_by_id(conn, id).await? {
+        let mut am: tenant::ActiveModel = model.into();
+        am.deleted_at = Set(Some(chrono::Utc::now().naive_utc()));
+        am.update(conn).await.map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// GET tenants changed after `since`, trashed ones included so clients can drop them
+pub async fn get_changed_since(db: &DatabaseConnection, since: NaiveDateTime) -> Result<Vec<tenant::Model>, DbErr> {
+    tenant::Entity::find()
+        .filter(tenant::Column::UpdatedAt.gt(since))
+        .order_by_asc(tenant::Column::UpdatedAt)
+        .all(db)
+        .await
+}
+
+/// GET tenants in the trash, most recently deleted first
+pub async fn get_deleted(db: &DatabaseConnection) -> Result<Vec<tenant::Model>, DbErr> {
+    tenant::Entity::find()
+        .filter(tenant::Column::DeletedAt.is_not_null())
+        .order_by_desc(tenant::Column::DeletedAt)
+        .all(db)
+        .await
+}
+
+/// GET a tenant in the trash by ID
+pub async fn get_deleted_by_id<C>(conn: &C, id: i32) -> Result<Option<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    tenant::Entity::find_by_id(id)
+        .filter(tenant::Column::DeletedAt.is_not_null())
+        .one(conn)
+        .await
+}
+
+/// Take a tenant out of the trash
+pub async fn restore<C>(conn: &C, id: i32) -> Result<Option<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = get_deleted_by_id(conn, id).await? {
+        let mut am: tenant::ActiveModel = model.into();
+        am.deleted_at = Set(None);
+        am.update(conn).await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Permanently remove tenants deleted before `cutoff` that no reading or bill still references
+pub async fn purge_deleted_before<C>(conn: &C, cutoff: NaiveDateTime) -> Result<Vec<tenant::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let purgeable = tenant::Entity::find()
+        .filter(tenant::Column::DeletedAt.lt(cutoff))
+        .filter(Expr::cust(
+            "NOT EXISTS (SELECT 1 FROM electricity_reading WHERE electricity_reading.tenant_id = tenant.id)",
+        ))
+        .filter(Expr::cust("NOT EXISTS (SELECT 1 FROM bill WHERE bill.tenant_id = tenant.id)"))
+        .all(conn)
+        .await?;
+
+    tenant::Entity::delete_many()
+        .filter(tenant::Column::Id.is_in(purgeable.iter().map(|t| t.id)))
+        .exec(conn)
+        .await?;
+    Ok(purgeable)
+}
+
 // ---------------------- INLINE TESTS ----------------------
 #[cfg(test)]
 mod tests {
@@ -74,8 +234,9 @@ mod tests {
         assert_eq!(fetched.name, created.name);
 
         // Fetch by name
-        let fetched_by_name = get_by_name(&db, "John Doe").await.unwrap().unwrap();
-        assert_eq!(fetched_by_name.id, created.id);
+        let fetched_by_name = get_all_by_name(&db, "John Doe", None).await.unwrap();
+        assert_eq!(fetched_by_name[0].id, created.id);
+        assert!(get_all_by_name(&db, "John Doe", Some(created.property_id + 1)).await.unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -89,7 +250,7 @@ mod tests {
         let mut updated_tenant: tenant::ActiveModel = created.clone().into();
         updated_tenant.name = Set("Jane Smith".into());
 
-        let updated = update(&db, created.id, updated_tenant).await.unwrap();
+        let updated = update(&db, created.id, created.version, updated_tenant).await.unwrap();
         assert_eq!(updated.name, "Jane Smith");
     }
 
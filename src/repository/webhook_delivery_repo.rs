@@ -0,0 +1,198 @@
+use crate::entities::webhook_delivery::{self, STATUS_DELIVERED, STATUS_FAILED, STATUS_PENDING};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, Statement, Value,
+};
+
+/// CREATE a delivery
+pub async fn create<C>(
+    conn: &C,
+    item: webhook_delivery::ActiveModel,
+) -> Result<webhook_delivery::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// GET delivery by ID
+pub async fn get_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<webhook_delivery::Model>, DbErr> {
+    webhook_delivery::Entity::find_by_id(id).one(db).await
+}
+
+/// GET the most recent deliveries for an endpoint
+pub async fn get_all_by_endpoint(
+    db: &DatabaseConnection,
+    endpoint_id: i32,
+    limit: u64,
+) -> Result<Vec<webhook_delivery::Model>, DbErr> {
+    webhook_delivery::Entity::find()
+        .filter(webhook_delivery::Column::EndpointId.eq(endpoint_id))
+        .order_by_desc(webhook_delivery::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+/// CLAIM pending deliveries that are due for (re)delivery. Their next attempt moves to
+/// `lease_until` in the same statement, so another worker skips them until this one marks
+/// them delivered or failed (or dies and the lease runs out).
+pub async fn claim_due(
+    db: &DatabaseConnection,
+    now: NaiveDateTime,
+    lease_until: NaiveDateTime,
+    limit: u64,
+) -> Result<Vec<webhook_delivery::Model>, DbErr> {
+    let sql = format!(
+        r#"UPDATE webhook_delivery SET next_attempt_at = $1
+        WHERE id IN (
+            SELECT id FROM webhook_delivery
+            WHERE status = $2 AND next_attempt_at <= $3
+            ORDER BY next_attempt_at
+            LIMIT {}
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *"#,
+        limit
+    );
+    let values: Vec<Value> = vec![lease_until.into(), STATUS_PENDING.into(), now.into()];
+
+    let mut claimed = webhook_delivery::Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(DatabaseBackend::Postgres, &sql, values))
+        .all(db)
+        .await?;
+    claimed.sort_by_key(|m| m.id);
+    Ok(claimed)
+}
+
+/// Mark a delivery as accepted by the endpoint
+pub async fn mark_delivered(
+    db: &DatabaseConnection,
+    item: webhook_delivery::Model,
+    response_status: i32,
+) -> Result<webhook_delivery::Model, DbErr> {
+    let now = chrono::Utc::now().naive_utc();
+    let attempts = item.attempts + 1;
+    let mut am: webhook_delivery::ActiveModel = item.into();
+    am.status = Set(STATUS_DELIVERED.to_string());
+    am.attempts = Set(attempts);
+    am.response_status = Set(Some(response_status));
+    am.last_error = Set(None);
+    am.delivered_at = Set(Some(now));
+    am.update(db).await
+}
+
+/// Record a failed attempt; the delivery is retried at `next_attempt_at` unless `give_up` is set
+pub async fn mark_failed(
+    db: &DatabaseConnection,
+    item: webhook_delivery::Model,
+    response_status: Option<i32>,
+    error: String,
+    next_attempt_at: NaiveDateTime,
+    give_up: bool,
+) -> Result<webhook_delivery::Model, DbErr> {
+    let attempts = item.attempts + 1;
+    let mut am: webhook_delivery::ActiveModel = item.into();
+    am.status = Set(if give_up { STATUS_FAILED } else { STATUS_PENDING }.to_string());
+    am.attempts = Set(attempts);
+    am.response_status = Set(response_status);
+    am.last_error = Set(Some(error));
+    am.next_attempt_at = Set(next_attempt_at);
+    am.update(db).await
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::webhook_endpoint;
+    use crate::test_utils::{get_test_db, reset_table};
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    async fn setup() -> (DatabaseConnection, i32) {
+        let db = get_test_db().await;
+        reset_table(&db, "webhook_endpoint").await;
+
+        let endpoint = webhook_endpoint::ActiveModel {
+            url: Set("http://localhost/hook".into()),
+            secret: Set("secret".into()),
+            events: Set("*".into()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        (db, endpoint.id)
+    }
+
+    fn new_delivery(endpoint_id: i32, next_attempt_at: NaiveDateTime) -> webhook_delivery::ActiveModel {
+        webhook_delivery::ActiveModel {
+            endpoint_id: Set(endpoint_id),
+            event: Set("bill.created".into()),
+            payload: Set(json!({ "event": "bill.created" })),
+            next_attempt_at: Set(next_attempt_at),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_due_and_mark_delivered() {
+        let (db, endpoint_id) = setup().await;
+        let now = Utc::now().naive_utc();
+        let lease = now + Duration::minutes(5);
+
+        create(&db, new_delivery(endpoint_id, now)).await.unwrap();
+        create(&db, new_delivery(endpoint_id, now + Duration::hours(1))).await.unwrap();
+
+        let due = claim_due(&db, now, lease, 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(due[0].next_attempt_at > now);
+        // Claimed: a second worker polling at the same time gets nothing
+        assert!(claim_due(&db, now, lease, 10).await.unwrap().is_empty());
+
+        let delivered = mark_delivered(&db, due[0].clone(), 200).await.unwrap();
+        assert_eq!(delivered.status, STATUS_DELIVERED);
+        assert_eq!(delivered.response_status, Some(200));
+        assert!(claim_due(&db, lease, lease, 10).await.unwrap().is_empty());
+        assert_eq!(get_all_by_endpoint(&db, endpoint_id, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mark_failed_and_give_up() {
+        let (db, endpoint_id) = setup().await;
+        let now = Utc::now().naive_utc();
+
+        let delivery = create(&db, new_delivery(endpoint_id, now)).await.unwrap();
+        let retry_at = now + Duration::minutes(2);
+        let failed = mark_failed(&db, delivery, Some(500), "server error".into(), retry_at, false)
+            .await
+            .unwrap();
+        assert_eq!(failed.status, STATUS_PENDING);
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(claim_due(&db, retry_at, retry_at, 10).await.unwrap().len(), 1);
+
+        let gave_up = mark_failed(&db, failed, None, "timeout".into(), retry_at, true).await.unwrap();
+        assert_eq!(gave_up.status, STATUS_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_claims_are_disjoint() {
+        let (db, endpoint_id) = setup().await;
+        let now = Utc::now().naive_utc();
+        let lease = now + Duration::minutes(5);
+        for _ in 0..10 {
+            create(&db, new_delivery(endpoint_id, now)).await.unwrap();
+        }
+
+        let (a, b) = tokio::join!(claim_due(&db, now, lease, 10), claim_due(&db, now, lease, 10));
+        let (a, b) = (a.unwrap(), b.unwrap());
+        let mut ids: Vec<i32> = a.iter().chain(b.iter()).map(|d| d.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), a.len() + b.len());
+        assert_eq!(ids.len(), 10);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::entities::webhook_endpoint;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+/// GET all webhook endpoints
+pub async fn get_all(db: &DatabaseConnection) -> Result<Vec<webhook_endpoint::Model>, DbErr> {
+    webhook_endpoint::Entity::find()
+        .order_by_asc(webhook_endpoint::Column::Id)
+        .all(db)
+        .await
+}
+
+/// GET webhook endpoint by ID
+pub async fn get_by_id<C>(conn: &C, id: i32) -> Result<Option<webhook_endpoint::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    webhook_endpoint::Entity::find_by_id(id).one(conn).await
+}
+
+/// GET active endpoints subscribed to an event
+pub async fn get_active_for_event<C>(conn: &C, event: &str) -> Result<Vec<webhook_endpoint::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let endpoints = webhook_endpoint::Entity::find()
+        .filter(webhook_endpoint::Column::IsActive.eq(true))
+        .all(conn)
+        .await?;
+    Ok(endpoints.into_iter().filter(|e| e.is_subscribed(event)).collect())
+}
+
+/// CREATE a webhook endpoint
+pub async fn create<C>(
+    conn: &C,
+    item: webhook_endpoint::ActiveModel,
+) -> Result<webhook_endpoint::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.insert(conn).await
+}
+
+/// UPDATE a webhook endpoint
+pub async fn update<C>(
+    conn: &C,
+    id: i32,
+    mut item: webhook_endpoint::ActiveModel,
+) -> Result<webhook_endpoint::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.id = Set(id);
+    item.update(conn).await
+}
+
+/// DELETE a webhook endpoint along with its delivery log
+pub async fn delete<C>(conn: &C, id: i32) -> Result<Option<webhook_endpoint::Model>, DbErr>
+where
+    C: ConnectionTrait,
+{
+    if let Some(model) = webhook_endpoint::Entity::find_by_id(id).one(conn).await? {
+        let am: webhook_endpoint::ActiveModel = model.clone().into();
+        am.delete(conn).await.map(|_| Some(model))
+    } else {
+        Ok(None)
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+
+    fn new_endpoint(events: &str, is_active: bool) -> webhook_endpoint::ActiveModel {
+        webhook_endpoint::ActiveModel {
+            url: Set("http://localhost/hook".into()),
+            secret: Set("secret".into()),
+            events: Set(events.into()),
+            is_active: Set(is_active),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_active_for_event() {
+        let db = get_test_db().await;
+        reset_table(&db, "webhook_endpoint").await;
+
+        create(&db, new_endpoint("bill.created,bill.paid", true)).await.unwrap();
+        create(&db, new_endpoint("*", true)).await.unwrap();
+        create(&db, new_endpoint("bill.created", false)).await.unwrap();
+
+        assert_eq!(get_active_for_event(&db, "bill.created").await.unwrap().len(), 2);
+        assert_eq!(get_active_for_event(&db, "reading.created").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete() {
+        let db = get_test_db().await;
+        reset_table(&db, "webhook_endpoint").await;
+
+        let endpoint = create(&db, new_endpoint("bill.created", true)).await.unwrap();
+        let am = webhook_endpoint::ActiveModel {
+            is_active: Set(false),
+            ..Default::default()
+        };
+        let updated = update(&db, endpoint.id, am).await.unwrap();
+        assert!(!updated.is_active);
+        assert_eq!(updated.url, endpoint.url);
+
+        assert!(delete(&db, endpoint.id).await.unwrap().is_some());
+        assert!(get_by_id(&db, endpoint.id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,11 @@
+use crate::handlers::admin_user_handler::{
+    create_admin_user, delete_admin_user, get_admin_users, update_admin_user,
+};
+use axum::Router;
+use axum::routing::{get, put};
+
+pub fn admin_user_routes() -> Router {
+    Router::new()
+        .route("/", get(get_admin_users).post(create_admin_user))
+        .route("/:id", put(update_admin_user).delete(delete_admin_user))
+}
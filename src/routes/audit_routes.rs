@@ -0,0 +1,7 @@
+use crate::handlers::audit_handler::get_audit_log;
+use axum::Router;
+use axum::routing::get;
+
+pub fn audit_routes() -> Router {
+    Router::new().route("/", get(get_audit_log))
+}
@@ -1,15 +1,36 @@
-use axum::{extract::DefaultBodyLimit, routing::{delete, get, post, put}, Router};
+use axum::{extract::DefaultBodyLimit, middleware::from_fn, routing::{delete, get, post, put}, Router};
+use crate::middleware::jwt::require_admin;
+use crate::middleware::property_access::guard_bills;
+use crate::services::upload_validation::UploadLimits;
 use crate::{handlers::bill_handler::{
-    create_bill_handler, delete_bill, get_bill_by_tenant, get_bills, get_bills_by_tenant, update_bill_json_handler, update_bill_multipart_handler
+    add_bill_note_handler, create_bill_handler, delete_bill, generate_invoice_handler, generate_receipt_handler, get_bill_by_tenant, get_bills, get_bills_by_tenant, get_tenant_balance, issue_bill_handler, pay_bill_handler, render_bill_handler, update_bill_json_handler, update_bill_multipart_handler, void_bill_handler
 }};
 
-pub fn bill_routes() -> Router {
+pub fn bill_routes(limits: &UploadLimits) -> Router {
+    let upload_limit = limits.max_request_bytes();
+
+    // Tenants read their own bills; writing, moving and correcting bills is for admins,
+    // turned away before `guard_bills` looks the bill up
+    let admin = Router::new()
+        .route("/", post(create_bill_handler)) 
+        .route("/:id", put(update_bill_json_handler))
+        .route("/:id/upload", put(update_bill_multipart_handler).route_layer(DefaultBodyLimit::max(upload_limit)))
+        .route("/:id", delete(delete_bill)) 
+        .route("/:id/issue", post(issue_bill_handler))
+        .route("/:id/pay", post(pay_bill_handler).route_layer(DefaultBodyLimit::max(upload_limit)))
+        .route("/:id/void", post(void_bill_handler))
+        .route("/:id/notes", post(add_bill_note_handler))
+        .route("/:id/invoice", post(generate_invoice_handler))
+        .route("/:id/receipt", post(generate_receipt_handler))
+        .route_layer(from_fn(guard_bills))
+        .route_layer(from_fn(require_admin));
+
     Router::new()
         .route("/", get(get_bills))
         .route("/:tenant_id/bill", get(get_bill_by_tenant))
         .route("/:tenant_id/bills", get(get_bills_by_tenant))
-        .route("/", post(create_bill_handler)) 
-        .route("/:id", put(update_bill_json_handler))
-        .route("/:id/upload", put(update_bill_multipart_handler).route_layer(DefaultBodyLimit::max(10485760)))
-        .route("/:id", delete(delete_bill)) 
+        .route("/:tenant_id/balance", get(get_tenant_balance))
+        .route("/:id/render", get(render_bill_handler))
+        .route_layer(from_fn(guard_bills))
+        .merge(admin)
 }
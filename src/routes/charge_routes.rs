@@ -0,0 +1,18 @@
+use crate::handlers::charge_handler::{
+    create_charge_type, create_recurring_charge, delete_recurring_charge, get_charge_types,
+    get_recurring_charges, retire_charge_type, update_charge_type, update_recurring_charge,
+};
+use axum::Router;
+use axum::routing::{get, put};
+
+pub fn charge_type_routes() -> Router {
+    Router::new()
+        .route("/", get(get_charge_types).post(create_charge_type))
+        .route("/:id", put(update_charge_type).delete(retire_charge_type))
+}
+
+pub fn recurring_charge_routes() -> Router {
+    Router::new()
+        .route("/", get(get_recurring_charges).post(create_recurring_charge))
+        .route("/:id", put(update_recurring_charge).delete(delete_recurring_charge))
+}
@@ -1,14 +1,32 @@
 use crate::handlers::electricity_reading_handler::{
-    create_reading, delete_reading, get_reading, get_readings, update_reading,
+    create_reading, create_reading_batch, delete_reading, get_reading, get_reading_photos, get_readings,
+    update_reading, upload_reading_photo,
 };
+use crate::services::upload_validation::UploadLimits;
+use crate::middleware::jwt::require_admin;
+use crate::middleware::property_access::guard_readings;
 use axum::Router;
+use axum::middleware::from_fn;
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{delete, get, post, put};
 
-pub fn electricity_reading_routes() -> Router {
+pub fn electricity_reading_routes(limits: &UploadLimits) -> Router {
+    let upload_limit = limits.max_request_bytes();
+
     Router::new()
         .route("/", get(get_readings))
         .route("/:id", get(get_reading))
         .route("/", post(create_reading))
+        .route("/batch", post(create_reading_batch))
+        .route("/:id/photos", get(get_reading_photos))
+        // Photos are the evidence behind a reading, so only admins add them
+        .route(
+            "/:id/photos",
+            post(upload_reading_photo)
+                .route_layer(DefaultBodyLimit::max(upload_limit))
+                .route_layer(from_fn(require_admin)),
+        )
         .route("/:id", put(update_reading))
         .route("/:id", delete(delete_reading))
+        .route_layer(from_fn(guard_readings))
 }
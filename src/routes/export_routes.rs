@@ -0,0 +1,11 @@
+use crate::handlers::export_handler::{export_bills, export_payments, export_readings, export_tenants};
+use axum::Router;
+use axum::routing::get;
+
+pub fn export_routes() -> Router {
+    Router::new()
+        .route("/bills", get(export_bills))
+        .route("/payments", get(export_payments))
+        .route("/readings", get(export_readings))
+        .route("/tenants", get(export_tenants))
+}
@@ -0,0 +1,7 @@
+use crate::handlers::import_handler::import_csv;
+use axum::Router;
+use axum::routing::post;
+
+pub fn import_routes() -> Router {
+    Router::new().route("/:kind", post(import_csv))
+}
@@ -0,0 +1,11 @@
+use crate::handlers::master_meter_handler::{
+    create_master_reading, delete_master_reading, get_master_readings,
+};
+use axum::Router;
+use axum::routing::{delete, get};
+
+pub fn master_meter_routes() -> Router {
+    Router::new()
+        .route("/", get(get_master_readings).post(create_master_reading))
+        .route("/:id", delete(delete_master_reading))
+}
@@ -1,6 +1,172 @@
+use crate::config::Config;
+use crate::middleware::jwt::{require_auth, require_superadmin};
+use axum::{middleware::from_fn, response::Json, routing::get, Router};
+
 pub mod auth_routes;
 pub mod signed_url_routes;
 pub mod room_routes;
 pub mod tenant_routes;
 pub mod electricity_reading_routes;
-pub mod bill_routes;
\ No newline at end of file
+pub mod bill_routes;
+pub mod template_routes;
+pub mod webhook_routes;
+pub mod audit_routes;
+pub mod trash_routes;
+pub mod sync_routes;
+pub mod charge_routes;
+pub mod report_routes;
+pub mod master_meter_routes;
+pub mod export_routes;
+pub mod import_routes;
+pub mod property_routes;
+pub mod admin_user_routes;
+
+/// Every route of the API; the database, R2 client and config are added as layers by the caller
+pub fn api_routes(config: &Config) -> Router {
+    // Helper to apply JWT auth to a router; it also keeps tenants out of `/api/admin`
+    let protected = |router: Router| router.route_layer(from_fn(require_auth));
+    // Settings shared by every property are left to the admin from the environment
+    let superadmin = |router: Router| protected(router.route_layer(from_fn(require_superadmin)));
+
+    Router::new()
+        // Public routes
+        .nest("/api/auth", auth_routes::auth_routes())
+        .route("/", get(|| async { "API is up" }))
+        .route("/health", get(|| async { Json(serde_json::json!({ "status": "ok" })) }))
+
+        // Protected routes
+        .nest("/api/signed-urls", protected(signed_url_routes::signed_url_routes()))
+        .nest("/api/rooms", protected(room_routes::room_routes()))
+        .nest("/api/tenants", protected(tenant_routes::tenant_routes()))
+        .nest("/api/electricity-readings", protected(electricity_reading_routes::electricity_reading_routes(&config.upload)))
+        .nest("/api/bills", protected(bill_routes::bill_routes(&config.upload)))
+        .nest("/api/admin/templates", superadmin(template_routes::template_routes()))
+        .nest("/api/admin/webhooks", superadmin(webhook_routes::webhook_routes()))
+        .nest("/api/admin/audit-log", superadmin(audit_routes::audit_routes()))
+        .nest("/api/admin/trash", superadmin(trash_routes::trash_routes()))
+        .nest("/api/admin/sync", superadmin(sync_routes::sync_routes()))
+        .nest("/api/admin/charge-types", superadmin(charge_routes::charge_type_routes()))
+        .nest("/api/admin/recurring-charges", superadmin(charge_routes::recurring_charge_routes()))
+        .nest("/api/admin/reports", protected(report_routes::report_routes()))
+        .nest("/api/admin/master-meter", protected(master_meter_routes::master_meter_routes()))
+        .nest("/api/admin/exports", protected(export_routes::export_routes()))
+        .nest("/api/admin/imports", protected(import_routes::import_routes()))
+        .nest("/api/admin/properties", protected(property_routes::property_routes()))
+        .nest("/api/admin/users", protected(admin_user_routes::admin_user_routes()))
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::jwt::Claims;
+    use crate::test_utils::test_config;
+    use axum::{Extension, body::Body, http::{Request, StatusCode}};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    /// Requests a tenant token is refused before any lookup: admin mounts stop it at
+    /// `require_auth`, admin-only routes on shared mounts at `require_admin`
+    const ADMIN_ONLY: &[(&str, &str)] = &[
+        ("POST", "/api/bills"),
+        ("PUT", "/api/bills/1"),
+        ("PUT", "/api/bills/1/upload"),
+        ("DELETE", "/api/bills/1"),
+        ("POST", "/api/bills/1/issue"),
+        ("POST", "/api/bills/1/pay"),
+        ("POST", "/api/bills/1/void"),
+        ("POST", "/api/bills/1/notes"),
+        ("POST", "/api/bills/1/invoice"),
+        ("POST", "/api/bills/1/receipt"),
+        ("GET", "/api/admin/reports/charges"),
+        ("GET", "/api/admin/reports/income"),
+        ("GET", "/api/admin/reports/receivables"),
+        ("GET", "/api/admin/reports/rooms"),
+        ("GET", "/api/admin/reports/electricity"),
+        ("GET", "/api/admin/reports/occupancy"),
+        ("GET", "/api/admin/reports/consumption?by=room&id=1"),
+        ("GET", "/api/admin/reports/consumption/building"),
+        ("GET", "/api/admin/reports/consumption/anomalies"),
+        ("GET", "/api/admin/master-meter"),
+        ("POST", "/api/admin/master-meter"),
+        ("DELETE", "/api/admin/master-meter/1"),
+        ("GET", "/api/admin/exports/bills?format=csv"),
+        ("GET", "/api/admin/exports/payments?format=xlsx"),
+        ("GET", "/api/admin/exports/readings"),
+        ("GET", "/api/admin/exports/tenants"),
+        ("POST", "/api/admin/imports/rooms"),
+        ("POST", "/api/admin/imports/tenants?dry_run=true"),
+        ("POST", "/api/admin/imports/readings"),
+        ("GET", "/api/admin/properties"),
+        ("GET", "/api/admin/properties/1"),
+        ("POST", "/api/admin/properties"),
+        ("GET", "/api/admin/users"),
+        ("DELETE", "/api/admin/users/1"),
+        ("GET", "/api/admin/templates"),
+        ("GET", "/api/admin/webhooks"),
+        ("GET", "/api/admin/audit-log"),
+        ("GET", "/api/admin/trash"),
+        ("GET", "/api/admin/sync"),
+        ("GET", "/api/admin/charge-types"),
+        ("GET", "/api/admin/recurring-charges"),
+    ];
+
+    fn token(role: &str, id: Option<i32>) -> String {
+        let claims = Claims { id, name: Some("x".into()), role: Some(role.into()), properties: None, exp: 4_000_000_000 };
+        let key = EncodingKey::from_secret(test_config().auth.jwt_secret.expose().as_bytes());
+        encode(&Header::default(), &claims, &key).unwrap()
+    }
+
+    async fn status(method: &str, path: &str, token: &str) -> StatusCode {
+        send(api_routes(&test_config()), method, path, token).await
+    }
+
+    async fn send(app: Router, method: &str, path: &str, token: &str) -> StatusCode {
+        let app = app.layer(Extension(Arc::new(test_config())));
+        let req = Request::builder()
+            .method(method)
+            .uri(path)
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_reject_tenants() {
+        let tenant = token("tenant", Some(1));
+        let admin = token("admin", None);
+        for (method, path) in ADMIN_ONLY {
+            assert_eq!(status(method, path, &tenant).await, StatusCode::FORBIDDEN, "{} {}", method, path);
+            // No database in this test, so an admin gets as far as the handler's extractors
+            assert_ne!(status(method, path, &admin).await, StatusCode::FORBIDDEN, "{} {}", method, path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_only_through_attachments() {
+        let tenant = token("tenant", Some(1));
+        for path in ["/api/signed-urls/receipts/Ana/1.png", "/api/signed-urls/payments/1"] {
+            assert_eq!(status("GET", path, &tenant).await, StatusCode::NOT_FOUND, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reading_photos_upload_is_admin_only() {
+        use crate::test_utils::{get_test_db, seed_reading};
+
+        let db = get_test_db().await;
+        let (_, owner, reading) = seed_reading(&db).await;
+
+        let app = api_routes(&test_config()).layer(Extension(db));
+        let path = format!("/api/electricity-readings/{}/photos", reading.id);
+        let tenant = token("tenant", Some(owner.id));
+        let stranger = token("tenant", Some(owner.id + 1));
+        assert_eq!(send(app.clone(), "POST", &path, &tenant).await, StatusCode::FORBIDDEN);
+        assert_eq!(send(app.clone(), "GET", &path, &tenant).await, StatusCode::OK);
+        assert_eq!(send(app.clone(), "GET", &path, &stranger).await, StatusCode::FORBIDDEN);
+        // Past the admin check; there is no storage client here, so it stops at the extractors
+        assert_ne!(send(app, "POST", &path, &token("admin", None)).await, StatusCode::FORBIDDEN);
+    }
+}
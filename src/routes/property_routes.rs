@@ -0,0 +1,11 @@
+use crate::handlers::property_handler::{
+    create_property, delete_property, get_properties, get_property, update_property,
+};
+use axum::Router;
+use axum::routing::get;
+
+pub fn property_routes() -> Router {
+    Router::new()
+        .route("/", get(get_properties).post(create_property))
+        .route("/:id", get(get_property).put(update_property).delete(delete_property))
+}
@@ -0,0 +1,20 @@
+use crate::handlers::report_handler::{
+    get_anomaly_report, get_building_consumption_report, get_charge_report,
+    get_consumption_report, get_electricity_report, get_income_report, get_occupancy_report,
+    get_receivables_report, get_room_revenue_report,
+};
+use axum::Router;
+use axum::routing::get;
+
+pub fn report_routes() -> Router {
+    Router::new()
+        .route("/charges", get(get_charge_report))
+        .route("/income", get(get_income_report))
+        .route("/receivables", get(get_receivables_report))
+        .route("/rooms", get(get_room_revenue_report))
+        .route("/electricity", get(get_electricity_report))
+        .route("/occupancy", get(get_occupancy_report))
+        .route("/consumption", get(get_consumption_report))
+        .route("/consumption/building", get(get_building_consumption_report))
+        .route("/consumption/anomalies", get(get_anomaly_report))
+}
@@ -1,5 +1,7 @@
 use crate::handlers::room_handler::{create_room, delete_room, get_room, get_rooms, update_room};
+use crate::middleware::property_access::guard_rooms;
 use axum::Router;
+use axum::middleware::from_fn;
 use axum::routing::{delete, get, post, put};
 
 pub fn room_routes() -> Router {
@@ -9,4 +11,5 @@ pub fn room_routes() -> Router {
         .route("/", post(create_room))
         .route("/:id", put(update_room))
         .route("/:id", delete(delete_room))
+        .route_layer(from_fn(guard_rooms))
 }
@@ -2,19 +2,13 @@ use axum::{
     routing::get,
     Router,
 };
-use crate::handlers::signed_url_handler::{
-    get_receipt_signed_url_handler,
-    get_payment_signed_url_handler,
-};
+use crate::handlers::signed_url_handler::get_attachment_signed_url_handler;
 
+/// Files are reached through their attachment rows, which say who may see them
 pub fn signed_url_routes() -> Router {
     Router::new()
         .route(
-            "/receipts/:tenant_name/:filename",
-            get(get_receipt_signed_url_handler),
-        )
-        .route(
-            "/payments/:filename",
-            get(get_payment_signed_url_handler),
+            "/attachments/:id",
+            get(get_attachment_signed_url_handler),
         )
 }
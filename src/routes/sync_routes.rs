@@ -0,0 +1,7 @@
+use crate::handlers::sync_handler::get_changes;
+use axum::Router;
+use axum::routing::get;
+
+pub fn sync_routes() -> Router {
+    Router::new().route("/", get(get_changes))
+}
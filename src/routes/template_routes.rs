@@ -0,0 +1,10 @@
+use crate::handlers::template_handler::{delete_template, get_templates, save_template};
+use axum::Router;
+use axum::routing::{delete, get, put};
+
+pub fn template_routes() -> Router {
+    Router::new()
+        .route("/", get(get_templates))
+        .route("/:format/:locale", put(save_template))
+        .route("/:format/:locale", delete(delete_template))
+}
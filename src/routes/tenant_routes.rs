@@ -1,7 +1,9 @@
 use crate::handlers::tenant_handler::{
     create_tenant, delete_tenant, get_tenant, get_tenant_by_name, get_tenants, update_tenant,
 };
+use crate::middleware::property_access::guard_tenants;
 use axum::Router;
+use axum::middleware::from_fn;
 use axum::routing::{delete, get, post, put};
 
 pub fn tenant_routes() -> Router {
@@ -12,4 +14,5 @@ pub fn tenant_routes() -> Router {
         .route("/", post(create_tenant))
         .route("/:id", put(update_tenant))
         .route("/:id", delete(delete_tenant))
+        .route_layer(from_fn(guard_tenants))
 }
@@ -0,0 +1,10 @@
+use crate::handlers::trash_handler::{get_trash, purge_trash, restore_from_trash};
+use axum::Router;
+use axum::routing::{get, post};
+
+pub fn trash_routes() -> Router {
+    Router::new()
+        .route("/", get(get_trash))
+        .route("/purge", post(purge_trash))
+        .route("/:entity/:id/restore", post(restore_from_trash))
+}
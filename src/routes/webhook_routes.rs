@@ -0,0 +1,14 @@
+use crate::handlers::webhook_handler::{
+    create_webhook, delete_webhook, get_webhook_deliveries, get_webhooks, replay_webhook_delivery,
+    update_webhook,
+};
+use axum::Router;
+use axum::routing::{get, post, put};
+
+pub fn webhook_routes() -> Router {
+    Router::new()
+        .route("/", get(get_webhooks).post(create_webhook))
+        .route("/:id", put(update_webhook).delete(delete_webhook))
+        .route("/:id/deliveries", get(get_webhook_deliveries))
+        .route("/deliveries/:id/replay", post(replay_webhook_delivery))
+}
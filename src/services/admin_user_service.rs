@@ -0,0 +1,238 @@
+use crate::entities::admin_user;
+use crate::repository::{admin_user_repo, property_repo};
+use crate::services::versioning::WriteError;
+use crate::services::{audit_service, map_txn_err, password};
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use serde::Serialize;
+use tracing::{error, info, instrument, warn};
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// An admin user with the properties they manage
+#[derive(Debug, Serialize)]
+pub struct AdminUserWithProperties {
+    #[serde(flatten)]
+    pub user: admin_user::Model,
+    pub property_ids: Vec<i32>,
+}
+
+/// Changes to an admin user; `None` leaves a field as it is
+#[derive(Debug, Default)]
+pub struct AdminUserChanges {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub property_ids: Option<Vec<i32>>,
+}
+
+async fn with_properties<C>(conn: &C, user: admin_user::Model) -> Result<AdminUserWithProperties, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let property_ids = admin_user_repo::get_property_ids(conn, user.id).await?;
+    Ok(AdminUserWithProperties { user, property_ids })
+}
+
+/// `reserved` is the username of the admin from the configuration
+async fn validate<C>(
+    conn: &C,
+    changes: &AdminUserChanges,
+    id: Option<i32>,
+    reserved: Option<&str>,
+) -> Result<(), WriteError>
+where
+    C: ConnectionTrait,
+{
+    if let Some(username) = &changes.username {
+        if username.trim().is_empty() {
+            return Err(WriteError::Invalid("username is required".into()));
+        }
+        if reserved == Some(username.as_str()) {
+            return Err(WriteError::Invalid("username is reserved".into()));
+        }
+        if admin_user_repo::get_by_username(conn, username).await?.is_some_and(|u| Some(u.id) != id) {
+            return Err(WriteError::InvalidState("username is taken".into()));
+        }
+    }
+    if changes.password.as_ref().is_some_and(|p| p.chars().count() < MIN_PASSWORD_LEN) {
+        return Err(WriteError::Invalid(format!("password must be at least {} characters", MIN_PASSWORD_LEN)));
+    }
+    for &property_id in changes.property_ids.iter().flatten() {
+        if property_repo::get_by_id(conn, property_id).await?.is_none() {
+            return Err(WriteError::Invalid(format!("property {} does not exist", property_id)));
+        }
+    }
+    Ok(())
+}
+
+/// GET all admin users
+pub async fn get_admin_users(db: &DatabaseConnection) -> Result<Vec<AdminUserWithProperties>, DbErr> {
+    let mut users = Vec::new();
+    for user in admin_user_repo::get_all(db).await? {
+        users.push(with_properties(db, user).await?);
+    }
+    Ok(users)
+}
+
+/// The admin user whose password matches, with the properties they manage
+pub async fn authenticate(
+    db: &DatabaseConnection,
+    username: &str,
+    password: &str,
+) -> Result<Option<AdminUserWithProperties>, DbErr> {
+    match admin_user_repo::get_by_username(db, username).await? {
+        Some(user) if password::verify(password, &user.password_hash) => Ok(Some(with_properties(db, user).await?)),
+        _ => Ok(None),
+    }
+}
+
+/// CREATE an admin user; username and password are required
+#[instrument(skip_all)]
+pub async fn create_admin_user(
+    db: &DatabaseConnection,
+    actor: &str,
+    reserved: Option<&str>,
+    changes: AdminUserChanges,
+) -> Result<AdminUserWithProperties, WriteError> {
+    let (Some(username), Some(plain)) = (changes.username.clone(), changes.password.as_deref()) else {
+        return Err(WriteError::Invalid("username and password are required".into()));
+    };
+    let password_hash = password::hash(plain);
+    let actor = actor.to_string();
+    let reserved = reserved.map(str::to_string);
+    let result = map_txn_err(
+        db.transaction::<_, AdminUserWithProperties, WriteError>(|txn| {
+            Box::pin(async move {
+                validate(txn, &changes, None, reserved.as_deref()).await?;
+                let item = admin_user::ActiveModel {
+                    username: Set(username),
+                    password_hash: Set(password_hash),
+                    ..Default::default()
+                };
+                let created = admin_user_repo::create(txn, item).await?;
+                admin_user_repo::set_property_ids(txn, created.id, changes.property_ids.as_deref().unwrap_or_default())
+                    .await?;
+                let created = with_properties(txn, created).await?;
+                audit_service::record_create(txn, &actor, "admin_user", created.user.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(u) => info!(user_id = u.user.id, username = %u.user.username, "created admin user"),
+        Err(err) => error!(error = ?err, "error creating admin user"),
+    }
+    result
+}
+
+/// UPDATE an admin user's name, password or properties
+#[instrument(skip_all)]
+pub async fn update_admin_user(
+    db: &DatabaseConnection,
+    actor: &str,
+    reserved: Option<&str>,
+    id: i32,
+    changes: AdminUserChanges,
+) -> Result<AdminUserWithProperties, WriteError> {
+    let password_hash = changes.password.as_deref().map(password::hash);
+    let actor = actor.to_string();
+    let reserved = reserved.map(str::to_string);
+    let result = map_txn_err(
+        db.transaction::<_, AdminUserWithProperties, WriteError>(|txn| {
+            Box::pin(async move {
+                let before = admin_user_repo::get_by_id(txn, id).await?.ok_or(WriteError::NotFound)?;
+                let before = with_properties(txn, before).await?;
+                validate(txn, &changes, Some(id), reserved.as_deref()).await?;
+
+                let updated = if changes.username.is_none() && password_hash.is_none() {
+                    before.user.clone()
+                } else {
+                    let item = admin_user::ActiveModel {
+                        username: changes.username.map(Set).unwrap_or_default(),
+                        password_hash: password_hash.map(Set).unwrap_or_default(),
+                        ..Default::default()
+                    };
+                    admin_user_repo::update(txn, id, item).await?
+                };
+                if let Some(property_ids) = &changes.property_ids {
+                    admin_user_repo::set_property_ids(txn, id, property_ids).await?;
+                }
+                let updated = with_properties(txn, updated).await?;
+                audit_service::record_update(txn, &actor, "admin_user", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(u) => info!(user_id = u.user.id, "updated admin user"),
+        Err(err) => error!(user_id = id, error = ?err, "error updating admin user"),
+    }
+    result
+}
+
+/// DELETE an admin user
+#[instrument(skip_all)]
+pub async fn delete_admin_user(db: &DatabaseConnection, actor: &str, id: i32) -> Result<Option<admin_user::Model>, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<admin_user::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let Some(before) = admin_user_repo::get_by_id(txn, id).await? else {
+                    return Ok(None);
+                };
+                let before = with_properties(txn, before).await?;
+                let deleted = admin_user_repo::delete(txn, id).await?;
+                audit_service::record_delete(txn, &actor, "admin_user", id, &before).await?;
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(Some(u)) => info!(user_id = u.id, username = %u.username, "deleted admin user"),
+        Ok(None) => warn!(user_id = id, "admin user not found"),
+        Err(err) => error!(user_id = id, error = ?err, "error deleting admin user"),
+    }
+    result
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+
+    #[tokio::test]
+    async fn test_admin_user_lifecycle() {
+        let db = get_test_db().await;
+        reset_table(&db, "admin_user").await;
+
+        let changes = AdminUserChanges {
+            username: Some("manager".into()),
+            password: Some("correct horse".into()),
+            property_ids: Some(vec![1]),
+        };
+        let created = create_admin_user(&db, "admin:root", Some("root"), changes).await.unwrap();
+        assert_eq!(created.property_ids, vec![1]);
+        assert!(serde_json::to_value(&created).unwrap().get("password_hash").is_none());
+
+        let user = authenticate(&db, "manager", "correct horse").await.unwrap().unwrap();
+        assert_eq!(user.property_ids, vec![1]);
+        assert!(authenticate(&db, "manager", "wrong horse").await.unwrap().is_none());
+
+        let taken = AdminUserChanges { username: Some("manager".into()), password: Some("another one".into()), ..Default::default() };
+        assert!(matches!(create_admin_user(&db, "admin:root", Some("root"), taken).await, Err(WriteError::InvalidState(_))));
+        let reserved = AdminUserChanges { username: Some("root".into()), ..Default::default() };
+        assert!(matches!(update_admin_user(&db, "admin:root", Some("root"), created.user.id, reserved).await, Err(WriteError::Invalid(_))));
+        let unknown = AdminUserChanges { property_ids: Some(vec![999]), ..Default::default() };
+        assert!(matches!(update_admin_user(&db, "admin:root", None, created.user.id, unknown).await, Err(WriteError::Invalid(_))));
+
+        let cleared = AdminUserChanges { property_ids: Some(vec![]), ..Default::default() };
+        let updated = update_admin_user(&db, "admin:root", None, created.user.id, cleared).await.unwrap();
+        assert!(updated.property_ids.is_empty());
+
+        assert!(delete_admin_user(&db, "admin:root", created.user.id).await.unwrap().is_some());
+        assert!(get_admin_users(&db).await.unwrap().is_empty());
+    }
+}
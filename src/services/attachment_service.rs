@@ -0,0 +1,239 @@
+use crate::entities::attachment::{self, OWNER_BILL, OWNER_READING, OWNER_TENANT};
+use crate::middleware::jwt::Claims;
+use crate::repository::{attachment_repo, bill_repo, electricity_reading_repo};
+use crate::services::audit_service::{self, SYSTEM_ACTOR};
+use crate::services::r2_service::{self, R2Config};
+use axum::body::Bytes;
+use chrono::Utc;
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use sha2::{Digest, Sha256};
+use tracing::{debug, error, info, instrument, warn};
+
+#[derive(Debug)]
+pub enum AttachmentError {
+    Storage(String),
+    Db(DbErr),
+}
+
+impl From<DbErr> for AttachmentError {
+    fn from(err: DbErr) -> Self {
+        AttachmentError::Db(err)
+    }
+}
+
+/// File to be stored and recorded as an attachment
+pub struct NewAttachment {
+    pub object_key: String,
+    pub owner_type: String,
+    pub owner_id: i32,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub bytes: Bytes,
+}
+
+/// File uploaded to R2 whose attachment row has not been written yet
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub object_key: String,
+    pub owner_type: String,
+    pub owner_id: i32,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: String,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// A fresh object key under `prefix`, e.g. `receipts/bills/12/1760000000000-9f2c0e1a7b3d4c55.jpg`; the
+/// random part keeps two uploads in the same millisecond from overwriting each other
+pub fn object_key(prefix: &str, extension: &str) -> String {
+    format!("{}/{}-{:016x}.{}", prefix, Utc::now().timestamp_millis(), rand::random::<u64>(), extension)
+}
+
+/// Upload a file to R2 without recording it, so no transaction is held open for the upload.
+/// The caller records it with `record_attachment` or removes it again with `discard`
+#[instrument(skip_all)]
+pub async fn store_file(r2: &R2Config, file: NewAttachment) -> Result<StoredFile, AttachmentError> {
+    let stored = StoredFile {
+        size_bytes: file.bytes.len() as i64,
+        checksum: sha256_hex(&file.bytes),
+        object_key: file.object_key,
+        owner_type: file.owner_type,
+        owner_id: file.owner_id,
+        file_name: file.file_name,
+        content_type: file.content_type,
+    };
+    r2_service::upload_file(r2, file.bytes, &stored.object_key, &stored.content_type)
+        .await
+        .map_err(|err| AttachmentError::Storage(err.to_string()))?;
+    Ok(stored)
+}
+
+/// Write the attachment row for a stored file, inside the caller's transaction when there is one
+pub async fn record_attachment<C>(
+    conn: &C,
+    file: &StoredFile,
+    uploaded_by: Option<String>,
+) -> Result<attachment::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let actor = uploaded_by.clone().unwrap_or_else(|| SYSTEM_ACTOR.to_string());
+    let item = attachment::ActiveModel {
+        object_key: Set(file.object_key.clone()),
+        owner_type: Set(file.owner_type.clone()),
+        owner_id: Set(file.owner_id),
+        file_name: Set(file.file_name.clone()),
+        content_type: Set(file.content_type.clone()),
+        size_bytes: Set(file.size_bytes),
+        checksum: Set(file.checksum.clone()),
+        uploaded_by: Set(uploaded_by),
+        ..Default::default()
+    };
+    let created = attachment_repo::create(conn, item).await?;
+    audit_service::record_create(conn, &actor, "attachment", created.id, &created).await?;
+    Ok(created)
+}
+
+/// Remove a stored file whose row was never written; failures are only logged
+pub async fn discard(r2: &R2Config, file: &StoredFile) {
+    if let Err(err) = r2_service::delete_file(r2, &file.object_key).await {
+        warn!(key = %file.object_key, error = %err, "could not remove unrecorded upload");
+    }
+}
+
+/// Upload a file to R2 and record it in the attachment table. The row is only written once
+/// the upload has succeeded, and the object is removed again if the row cannot be written
+#[instrument(skip_all)]
+pub async fn upload_attachment(
+    db: &DatabaseConnection,
+    r2: &R2Config,
+    file: NewAttachment,
+    uploaded_by: Option<String>,
+) -> Result<attachment::Model, AttachmentError> {
+    let result = async {
+        let stored = store_file(r2, file).await?;
+        let recorded = async {
+            let txn = db.begin().await?;
+            let created = record_attachment(&txn, &stored, uploaded_by).await?;
+            txn.commit().await?;
+            Ok::<_, DbErr>(created)
+        }
+        .await;
+        if recorded.is_err() {
+            discard(r2, &stored).await;
+        }
+        Ok(recorded?)
+    }
+    .await;
+    match &result {
+        Ok(a) => info!(attachment_id = a.id, key = %a.object_key, "stored attachment"),
+        Err(err) => error!(error = ?err, "error storing attachment"),
+    }
+
+    result
+}
+
+/// Get attachment by ID
+#[instrument(skip_all)]
+pub async fn get_attachment_by_id(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Option<attachment::Model>, DbErr> {
+    let result = attachment_repo::get_by_id(db, id).await;
+    match &result {
+        Ok(Some(a)) => debug!(attachment_id = a.id, "found attachment"),
+        Ok(None) => warn!(attachment_id = id, "attachment not found"),
+        Err(err) => error!(attachment_id = id, error = ?err, "error fetching attachment"),
+    }
+    result
+}
+
+/// Get all attachments for a bill
+pub async fn get_bill_attachments(
+    db: &DatabaseConnection,
+    bill_id: i32,
+) -> Result<Vec<attachment::Model>, DbErr> {
+    attachment_repo::get_all_by_owner(db, OWNER_BILL, bill_id).await
+}
+
+/// Get all photos for a reading
+pub async fn get_reading_photos(
+    db: &DatabaseConnection,
+    reading_id: i32,
+) -> Result<Vec<attachment::Model>, DbErr> {
+    attachment_repo::get_all_by_owner(db, OWNER_READING, reading_id).await
+}
+
+/// Admins can view any attachment; tenants only those belonging to them
+pub async fn can_view(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    item: &attachment::Model,
+) -> Result<bool, DbErr> {
+    if claims.is_admin() {
+        return Ok(true);
+    }
+
+    let Some(tenant_id) = claims.id else {
+        return Ok(false);
+    };
+
+    match item.owner_type.as_str() {
+        OWNER_TENANT => Ok(item.owner_id == tenant_id),
+        OWNER_BILL => Ok(bill_repo::get_by_id(db, item.owner_id)
+            .await?
+            .is_some_and(|b| b.tenant_id == tenant_id)),
+        OWNER_READING => Ok(electricity_reading_repo::get_by_id(db, item.owner_id)
+            .await?
+            .is_some_and(|r| r.tenant_id == tenant_id)),
+        _ => Ok(false),
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table, seed_reading};
+
+    fn tenant_claims(id: i32) -> Claims {
+        Claims { id: Some(id), name: None, role: Some("tenant".into()), properties: None, exp: 0 }
+    }
+
+    #[test]
+    fn test_object_keys_do_not_repeat() {
+        let a = object_key("receipts/bills/7", "jpg");
+        let b = object_key("receipts/bills/7", "jpg");
+        assert!(a.starts_with("receipts/bills/7/") && a.ends_with(".jpg"));
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_sees_only_own_reading_photos() {
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+        let (_, tenant, reading) = seed_reading(&db).await;
+        let photo = attachment_repo::create(
+            &db,
+            attachment::ActiveModel {
+                object_key: Set(format!("readings/{}/1.jpg", reading.id)),
+                owner_type: Set(OWNER_READING.into()),
+                owner_id: Set(reading.id),
+                content_type: Set("image/jpeg".into()),
+                size_bytes: Set(10),
+                checksum: Set("abc".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(get_reading_photos(&db, reading.id).await.unwrap(), vec![photo.clone()]);
+        assert!(can_view(&db, &tenant_claims(tenant.id), &photo).await.unwrap());
+        assert!(!can_view(&db, &tenant_claims(tenant.id + 1), &photo).await.unwrap());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::entities::audit_log::{
+    self, ACTION_CREATE, ACTION_DELETE, ACTION_PURGE, ACTION_RESTORE, ACTION_UPDATE,
+};
+use crate::repository::audit_log_repo::{self, AuditLogFilter};
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use tracing::{debug, error, instrument};
+
+/// Actor recorded for changes made by background jobs
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Fields left out of diffs because they change on every write
+const IGNORED_FIELDS: &[&str] = &["updated_at"];
+
+fn to_object<T: Serialize>(value: Option<&T>) -> Map<String, Value> {
+    match value.map(serde_json::to_value) {
+        Some(Ok(Value::Object(map))) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Field-level diff: `{ field: { "before": .., "after": .. } }` for every field that differs
+pub fn diff(before: &Map<String, Value>, after: &Map<String, Value>) -> Value {
+    let mut changes = Map::new();
+
+    for key in before.keys().chain(after.keys()) {
+        if IGNORED_FIELDS.contains(&key.as_str()) || changes.contains_key(key) {
+            continue;
+        }
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(key.clone(), json!({ "before": old, "after": new }));
+        }
+    }
+
+    Value::Object(changes)
+}
+
+async fn record<C, T>(
+    conn: &C,
+    actor: &str,
+    entity: &str,
+    entity_id: i32,
+    action: &str,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    let item = audit_log::ActiveModel {
+        actor: Set(actor.to_string()),
+        entity: Set(entity.to_string()),
+        entity_id: Set(entity_id),
+        action: Set(action.to_string()),
+        changes: Set(diff(&to_object(before), &to_object(after))),
+        ..Default::default()
+    };
+    audit_log_repo::create(conn, item).await?;
+    Ok(())
+}
+
+/// Record a created row; call inside the transaction that inserts it
+pub async fn record_create<C, T>(conn: &C, actor: &str, entity: &str, entity_id: i32, after: &T) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    record(conn, actor, entity, entity_id, ACTION_CREATE, None, Some(after)).await
+}
+
+/// Record an updated row; call inside the transaction that updates it
+pub async fn record_update<C, T>(
+    conn: &C,
+    actor: &str,
+    entity: &str,
+    entity_id: i32,
+    before: &T,
+    after: &T,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    record(conn, actor, entity, entity_id, ACTION_UPDATE, Some(before), Some(after)).await
+}
+
+/// Record a deleted row; call inside the transaction that deletes it
+pub async fn record_delete<C, T>(conn: &C, actor: &str, entity: &str, entity_id: i32, before: &T) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    record(conn, actor, entity, entity_id, ACTION_DELETE, Some(before), None).await
+}
+
+/// Record a row taken out of the trash
+pub async fn record_restore<C, T>(
+    conn: &C,
+    actor: &str,
+    entity: &str,
+    entity_id: i32,
+    before: &T,
+    after: &T,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    record(conn, actor, entity, entity_id, ACTION_RESTORE, Some(before), Some(after)).await
+}
+
+/// Record a row permanently removed from the trash
+pub async fn record_purge<C, T>(conn: &C, actor: &str, entity: &str, entity_id: i32, before: &T) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    record(conn, actor, entity, entity_id, ACTION_PURGE, Some(before), None).await
+}
+
+/// Query the audit log, newest first
+#[instrument(skip_all)]
+pub async fn get_audit_logs(
+    db: &DatabaseConnection,
+    filter: &AuditLogFilter,
+    limit: u64,
+    offset: u64,
+) -> Result<Vec<audit_log::Model>, DbErr> {
+    let result = audit_log_repo::find(db, filter, limit, offset).await;
+    match &result {
+        Ok(list) => debug!(count = list.len(), "fetched audit entries"),
+        Err(err) => error!(error = ?err, "error fetching audit entries"),
+    }
+    result
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_diff_only_changed_fields() {
+        let before = object(json!({ "id": 1, "paid": false, "total_amount": 100, "updated_at": "a" }));
+        let after = object(json!({ "id": 1, "paid": true, "total_amount": 100, "updated_at": "b" }));
+
+        assert_eq!(diff(&before, &after), json!({ "paid": { "before": false, "after": true } }));
+    }
+
+    #[test]
+    fn test_diff_create_and_delete() {
+        let row = object(json!({ "id": 4, "name": "Room A" }));
+
+        assert_eq!(
+            diff(&Map::new(), &row),
+            json!({
+                "id": { "before": null, "after": 4 },
+                "name": { "before": null, "after": "Room A" },
+            })
+        );
+        assert_eq!(diff(&row, &Map::new())["name"], json!({ "before": "Room A", "after": null }));
+    }
+}
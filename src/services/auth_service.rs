@@ -3,18 +3,17 @@ use sea_orm::DatabaseConnection;
 use serde::{Serialize, Deserialize};
 use chrono::Utc;
 
-use crate::services::tenant_service;
+use crate::config::AuthConfig;
+use crate::services::{admin_user_service, tenant_service};
 use crate::entities::tenant::Model as Tenant;
 
-fn jwt_secret() -> String {
-    std::env::var("JWT_SECRET").unwrap_or_else(|_| "secret".into())
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub id: Option<i32>,
     pub name: Option<String>,
     pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Vec<i32>>,
     pub exp: usize,
 }
 
@@ -22,57 +21,79 @@ pub struct Claims {
 pub enum AuthError {
     InvalidCredentials,
     TenantNotFound,
+    /// Tenants in several properties share the name
+    TenantAmbiguous,
     TokenMissing,
     TokenInvalid,
     Other(String),
 }
 
-/// Admin login
-pub async fn admin_login(username: &str, password: &str) -> Result<String, AuthError> {
-    let admin_username = std::env::var("ADMIN_USERNAME").unwrap_or_default();
-    let admin_password = std::env::var("ADMIN_PASSWORD").unwrap_or_default();
-
-    if username != admin_username || password != admin_password {
-        return Err(AuthError::InvalidCredentials);
-    }
+/// Admin login: the `ADMIN_USERNAME` account sees every property,
+/// admin users from the database only the ones assigned to them
+pub async fn admin_login(
+    db: &DatabaseConnection,
+    config: &AuthConfig,
+    username: &str,
+    password: &str,
+) -> Result<String, AuthError> {
+    let (id, properties) = if let Some(admin) = config.admin.as_ref().filter(|a| a.username == username) {
+        if password != admin.password.expose() {
+            return Err(AuthError::InvalidCredentials);
+        }
+        (None, None)
+    } else {
+        let user = admin_user_service::authenticate(db, username, password)
+            .await
+            .map_err(|e| AuthError::Other(e.to_string()))?
+            .ok_or(AuthError::InvalidCredentials)?;
+        (Some(user.user.id), Some(user.property_ids))
+    };
 
     let claims = Claims {
-        id: None,
+        id,
         name: Some(username.to_string()),
         role: Some("admin".to_string()),
+        properties,
         exp: (Utc::now().timestamp() + 3600) as usize, // 1 hour
     };
 
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(jwt_secret().as_bytes()),
+        &EncodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
     )
     .map_err(|e| AuthError::Other(e.to_string()))
 }
 
-/// Tenant login
+/// Tenant login; `property_id` tells apart tenants of different properties with the same name
 pub async fn tenant_login(
     db: &DatabaseConnection,
+    config: &AuthConfig,
     name: &str,
+    property_id: Option<i32>,
 ) -> Result<(String, Tenant), AuthError> {
-    let tenant_opt = tenant_service::get_tenant_by_name(db, name)
+    let mut tenants = tenant_service::get_tenants_by_name(db, name, property_id)
         .await
         .map_err(|e| AuthError::Other(e.to_string()))?;
 
-    let tenant = tenant_opt.ok_or(AuthError::TenantNotFound)?;
+    let tenant = match tenants.len() {
+        0 => return Err(AuthError::TenantNotFound),
+        1 => tenants.remove(0),
+        _ => return Err(AuthError::TenantAmbiguous),
+    };
 
     let claims = Claims {
         id: Some(tenant.id),
         name: Some(tenant.name.clone()),
         role: None,
+        properties: None,
         exp: (Utc::now().timestamp() + 1200) as usize, // 20 minutes
     };
 
     let token = encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(jwt_secret().as_bytes()),
+        &EncodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
     )
     .map_err(|e| AuthError::Other(e.to_string()))?;
 
@@ -80,14 +101,14 @@ pub async fn tenant_login(
 }
 
 /// Validate JWT token 
-pub fn validate_token(auth_header: Option<&str>) -> Result<Claims, AuthError> {
+pub fn validate_token(config: &AuthConfig, auth_header: Option<&str>) -> Result<Claims, AuthError> {
     let token = auth_header
         .and_then(|h| h.strip_prefix("Bearer "))
         .ok_or(AuthError::TokenMissing)?;
 
     decode::<Claims>(
         token,
-        &DecodingKey::from_secret(jwt_secret().as_bytes()),
+        &DecodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
         &Validation::new(Algorithm::HS256),
     )
     .map(|data| data.claims)
@@ -1,57 +1,281 @@
 use crate::{
-    entities::{additional_charge, bill, electricity_reading},
-    repository::{additional_charge_repo, bill_repo, electricity_reading_repo},
+    entities::{additional_charge, attachment, bill, bill_discount, bill_note, charge_type, electricity_reading, tenant},
+    money::{self, Currency, Money, MoneyError},
+    repository::{
+        additional_charge_repo, attachment_repo, bill_discount_repo, bill_note_repo, bill_repo::{self, BillFilter},
+        charge_type_repo, electricity_reading_repo, recurring_charge_repo, tenant_repo,
+    },
+    services::attachment_service::{self, StoredFile},
+    services::notification_service::{self, EVENT_BILL_CREATED, EVENT_BILL_PAID},
+    services::webhook_service::{self, EVENT_BILL_ISSUED, EVENT_BILL_UPDATED, EVENT_BILL_VOIDED},
+    services::versioning::{self, WriteError},
+    services::{audit_service, map_txn_err},
 };
 use sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DatabaseTransaction, DbErr, Set, TransactionError,
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, Set,
     TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::info;
 
 #[derive(Debug, Serialize)]
 pub struct BillWithChargesAndReading {
     pub bill: bill::Model,
     pub additional_charges: Vec<additional_charge::Model>,
+    /// Discounts in the order they were applied
+    pub discounts: Vec<bill_discount::Model>,
     pub reading: Option<electricity_reading::Model>,
+    /// Meter photos taken for the reading
+    pub reading_photos: Vec<attachment::Model>,
+    pub attachments: Vec<attachment::Model>,
+    /// Credit and debit notes issued against the bill
+    pub notes: Vec<bill_note::Model>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl BillWithChargesAndReading {
+    /// Bill total after credit and debit notes
+    pub fn adjusted_total(&self) -> Result<Money, MoneyError> {
+        let notes = Money::sum(self.bill.currency(), self.notes.iter().map(|n| n.signed_amount()))?;
+        self.bill.total().checked_add(notes)
+    }
+
+    /// Still owed after notes and payment; below zero when a paid bill was credited and
+    /// the tenant is owed a refund
+    pub fn outstanding(&self) -> Result<Money, MoneyError> {
+        self.adjusted_total()?.checked_sub(self.bill.amount_paid())
+    }
+}
+
+/// A requested bill line. With a `charge_type_id`, the amount and description
+/// default to the catalog entry's; without one both are required.
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct AdditionalChargeInput {
-    pub amount: i32,
+    #[serde(default)]
+    pub charge_type_id: Option<i32>,
+    #[serde(default, with = "money::decimal_option")]
+    pub amount: Option<i64>,
+    #[serde(default)]
     pub description: String,
 }
 
+/// A bill line with its catalog defaults applied
+#[derive(Debug, Clone, PartialEq)]
+struct ChargeLine {
+    charge_type_id: Option<i32>,
+    amount: i64,
+    description: String,
+}
+
+/// A requested discount; `approved_by` defaults to whoever saves the bill
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscountInput {
+    /// `fixed` or `percent`
+    pub kind: String,
+    /// An amount for `fixed`, a percentage for `percent` (`"12.5"` is 12.5%)
+    #[serde(with = "money::decimal")]
+    pub value: i64,
+    pub reason: String,
+    #[serde(default)]
+    pub approved_by: Option<String>,
+}
+
+/// A discount with the amount it takes off the bill
+#[derive(Debug, Clone, PartialEq)]
+struct DiscountLine {
+    kind: String,
+    value: i64,
+    amount: i64,
+    reason: String,
+    approved_by: Option<String>,
+}
+
+/// What `calculate_total` works out for a bill
+#[derive(Debug, PartialEq)]
+struct BillTotals {
+    discount: Money,
+    total: Money,
+    /// In the order they were applied
+    discounts: Vec<DiscountLine>,
+}
+
+/// Amounts are minor units of `currency`
 #[derive(Debug, Clone)]
 pub struct BillInput {
     pub tenant_id: i32,
     pub reading_id: i32,
-    pub room_charges: i32,
-    pub electric_charges: i32,
+    pub currency: Currency,
+    pub room_charges: i64,
+    pub electric_charges: i64,
     pub additional_charges: Vec<AdditionalChargeInput>,
+    pub discounts: Vec<DiscountInput>,
     pub receipt_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct BillNoteInput {
+    pub kind: String,
+    #[serde(with = "money::decimal")]
+    pub amount: i64,
+    pub reason: String,
+}
+
+/// What a tenant owes across issued and paid bills, after notes
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TenantBalance {
+    pub tenant_id: i32,
+    pub currency: String,
+    #[serde(with = "money::decimal")]
+    pub billed: i64,
+    #[serde(with = "money::decimal")]
+    pub debits: i64,
+    #[serde(with = "money::decimal")]
+    pub credits: i64,
+    #[serde(with = "money::decimal")]
+    pub paid: i64,
+    #[serde(with = "money::decimal")]
+    pub balance: i64,
+}
+
 // ---------- helpers ----------
-fn calculate_total(room: i32, electric: i32, charges: &[AdditionalChargeInput]) -> i32 {
-    room + electric + charges.iter().map(|c| c.amount).sum::<i32>()
+/// 100% in hundredths of a percent
+const FULL_PERCENT: i64 = 10_000;
+
+fn validate_discount(d: &DiscountInput) -> Result<(), WriteError> {
+    let max = if d.kind == bill_discount::KIND_PERCENT { FULL_PERCENT } else { i64::MAX };
+    if d.kind != bill_discount::KIND_FIXED && d.kind != bill_discount::KIND_PERCENT {
+        return Err(WriteError::Invalid("discount kind must be 'fixed' or 'percent'".into()));
+    }
+    if d.value <= 0 || d.value > max {
+        return Err(WriteError::Invalid("discount value must be positive and at most 100%".into()));
+    }
+    if d.reason.trim().is_empty() {
+        return Err(WriteError::Invalid("discount reason is required".into()));
+    }
+    Ok(())
+}
+
+/// Work out a bill's total in a fixed order:
+/// 1. gross = rent + electricity + additional charges, none of them negative
+/// 2. percentage discounts, each taken from the gross and rounded half up to the minor unit
+/// 3. fixed discounts
+///
+/// The total may not go below zero.
+fn calculate_total(input: &BillInput, lines: &[ChargeLine]) -> Result<BillTotals, WriteError> {
+    let amounts: Vec<i64> = [input.room_charges, input.electric_charges]
+        .into_iter()
+        .chain(lines.iter().map(|c| c.amount))
+        .collect();
+    if amounts.iter().any(|a| *a < 0) {
+        return Err(WriteError::Invalid("charges cannot be negative; add a discount instead".into()));
+    }
+    let gross = Money::sum(input.currency, amounts)?;
+
+    let percent = input.discounts.iter().filter(|d| d.kind == bill_discount::KIND_PERCENT);
+    let fixed = input.discounts.iter().filter(|d| d.kind != bill_discount::KIND_PERCENT);
+    let mut discounts = Vec::with_capacity(input.discounts.len());
+    for d in percent.chain(fixed) {
+        validate_discount(d)?;
+        let amount = if d.kind == bill_discount::KIND_PERCENT {
+            let share = (gross.minor() as i128 * d.value as i128 + (FULL_PERCENT / 2) as i128) / FULL_PERCENT as i128;
+            i64::try_from(share).map_err(|_| MoneyError::Overflow)?
+        } else {
+            d.value
+        };
+        discounts.push(DiscountLine {
+            kind: d.kind.clone(),
+            value: d.value,
+            amount,
+            reason: d.reason.trim().to_string(),
+            approved_by: d.approved_by.clone().filter(|a| !a.trim().is_empty()),
+        });
+    }
+
+    let discount = Money::sum(input.currency, discounts.iter().map(|d| d.amount))?;
+    let total = gross.checked_sub(discount)?;
+    if total.minor() < 0 {
+        return Err(WriteError::Invalid(format!("discounts of {} exceed the bill's {}", discount, gross)));
+    }
+
+    Ok(BillTotals { discount, total, discounts })
 }
 
-fn determine_paid_status(receipt_url: &Option<String>) -> bool {
-    receipt_url.is_some()
+/// Fill in catalog defaults for the requested charges
+fn apply_catalog(
+    charges: &[AdditionalChargeInput],
+    catalog: &[charge_type::Model],
+) -> Result<Vec<ChargeLine>, WriteError> {
+    charges
+        .iter()
+        .map(|c| {
+            let charge_type = match c.charge_type_id {
+                Some(id) => Some(
+                    catalog
+                        .iter()
+                        .find(|t| t.id == id && t.is_active)
+                        .ok_or_else(|| WriteError::Invalid(format!("charge type {} does not exist or is retired", id)))?,
+                ),
+                None => None,
+            };
+            let amount = c
+                .amount
+                .or(charge_type.map(|t| t.default_amount))
+                .ok_or_else(|| WriteError::Invalid("amount is required for uncategorized charges".into()))?;
+            let description = match (c.description.trim(), charge_type) {
+                ("", Some(t)) => t.name.clone(),
+                ("", None) => return Err(WriteError::Invalid("description is required for uncategorized charges".into())),
+                (text, _) => text.to_string(),
+            };
+            Ok(ChargeLine { charge_type_id: c.charge_type_id, amount, description })
+        })
+        .collect()
 }
 
-fn build_bill_active_model(input: &BillInput) -> bill::ActiveModel {
+async fn resolve_charges<C>(conn: &C, charges: &[AdditionalChargeInput]) -> Result<Vec<ChargeLine>, WriteError>
+where
+    C: ConnectionTrait,
+{
+    let ids = charges.iter().filter_map(|c| c.charge_type_id).collect();
+    let catalog = charge_type_repo::get_by_ids(conn, ids).await?;
+    apply_catalog(charges, &catalog)
+}
+
+/// Recurring charges of the tenant and their room, skipping any type the bill already has
+async fn recurring_lines<C>(conn: &C, tenant: &tenant::Model, existing: &[ChargeLine]) -> Result<Vec<ChargeLine>, WriteError>
+where
+    C: ConnectionTrait,
+{
+    let recurring = recurring_charge_repo::get_active_for(conn, tenant.id, tenant.room_id).await?;
+    let catalog = charge_type_repo::get_by_ids(conn, recurring.iter().map(|r| r.charge_type_id).collect()).await?;
+    let mut seen: Vec<i32> = existing.iter().filter_map(|c| c.charge_type_id).collect();
+
+    let mut lines = Vec::new();
+    for r in recurring {
+        let Some(charge_type) = catalog.iter().find(|t| t.id == r.charge_type_id && t.is_active) else {
+            continue;
+        };
+        if seen.contains(&charge_type.id) {
+            continue;
+        }
+        seen.push(charge_type.id);
+        lines.push(ChargeLine {
+            charge_type_id: Some(charge_type.id),
+            amount: r.amount.unwrap_or(charge_type.default_amount),
+            description: r.description.filter(|d| !d.trim().is_empty()).unwrap_or_else(|| charge_type.name.clone()),
+        });
+    }
+    Ok(lines)
+}
+
+fn build_bill_active_model(input: &BillInput, totals: &BillTotals) -> bill::ActiveModel {
     bill::ActiveModel {
         tenant_id: Set(input.tenant_id),
         reading_id: Set(input.reading_id),
+        currency: Set(input.currency.code().to_string()),
         room_charges: Set(input.room_charges),
         electric_charges: Set(input.electric_charges),
-        total_amount: Set(calculate_total(
-            input.room_charges,
-            input.electric_charges,
-            &input.additional_charges,
-        )),
-        paid: Set(determine_paid_status(&input.receipt_url)),
+        discount_amount: Set(totals.discount.minor()),
+        total_amount: Set(totals.total.minor()),
         receipt_url: Set(input.receipt_url.clone()),
         ..Default::default()
     }
@@ -59,12 +283,13 @@ fn build_bill_active_model(input: &BillInput) -> bill::ActiveModel {
 
 fn build_charge_models(
     bill_id: i32,
-    charges: &[AdditionalChargeInput],
+    charges: &[ChargeLine],
 ) -> Vec<additional_charge::ActiveModel> {
     charges
         .iter()
         .map(|c| additional_charge::ActiveModel {
             bill_id: Set(bill_id),
+            charge_type_id: Set(c.charge_type_id),
             amount: Set(c.amount),
             description: Set(c.description.clone()),
             ..Default::default()
@@ -75,7 +300,7 @@ fn build_charge_models(
 async fn insert_charges(
     txn: &DatabaseTransaction,
     bill_id: i32,
-    charges: &[AdditionalChargeInput],
+    charges: &[ChargeLine],
 ) -> Result<(), DbErr> {
     for charge in build_charge_models(bill_id, charges) {
         additional_charge_repo::create(txn, charge).await?;
@@ -83,50 +308,182 @@ async fn insert_charges(
     Ok(())
 }
 
-fn map_txn_err<T>(res: Result<T, TransactionError<DbErr>>) -> Result<T, DbErr> {
-    res.map_err(|e| match e {
-        TransactionError::Connection(err) => err,
-        TransactionError::Transaction(err) => err,
+async fn insert_discounts(
+    txn: &DatabaseTransaction,
+    bill_id: i32,
+    discounts: &[DiscountLine],
+    actor: &str,
+) -> Result<(), DbErr> {
+    for (position, d) in discounts.iter().enumerate() {
+        let item = bill_discount::ActiveModel {
+            bill_id: Set(bill_id),
+            kind: Set(d.kind.clone()),
+            value: Set(d.value),
+            amount: Set(d.amount),
+            reason: Set(d.reason.clone()),
+            approved_by: Set(d.approved_by.clone().unwrap_or_else(|| actor.to_string())),
+            position: Set(position as i32),
+            ..Default::default()
+        };
+        bill_discount_repo::create(txn, item).await?;
+    }
+    Ok(())
+}
+
+async fn load_details<C>(conn: &C, bill_model: bill::Model) -> Result<BillWithChargesAndReading, DbErr>
+where
+    C: ConnectionTrait + TransactionTrait,
+{
+    let charges = additional_charge_repo::get_all_by_bill_id(conn, bill_model.id).await?;
+    let discounts = bill_discount_repo::get_all_by_bill_id(conn, bill_model.id).await?;
+    let reading = electricity_reading_repo::get_by_id(conn, bill_model.reading_id).await?;
+    let reading_photos = match &reading {
+        Some(r) => attachment_repo::get_all_by_owner(conn, attachment::OWNER_READING, r.id).await?,
+        None => Vec::new(),
+    };
+    let attachments =
+        attachment_repo::get_all_by_owner(conn, attachment::OWNER_BILL, bill_model.id).await?;
+    let notes = bill_note_repo::get_all_by_bill_id(conn, bill_model.id).await?;
+
+    Ok(BillWithChargesAndReading {
+        bill: bill_model,
+        additional_charges: charges,
+        discounts,
+        reading,
+        reading_photos,
+        attachments,
+        notes,
     })
 }
 
+/// Load a bill for a state change, checking its version and current status
+async fn get_for_transition(
+    txn: &DatabaseTransaction,
+    id: i32,
+    expected_version: Option<i32>,
+    allowed: &[&str],
+    action: &str,
+) -> Result<bill::Model, WriteError> {
+    let existing = bill_repo::get_by_id_for_update(txn, id).await?.ok_or(WriteError::NotFound)?;
+    versioning::check_version(expected_version, existing.version)?;
+    if !allowed.contains(&existing.status.as_str()) {
+        return Err(WriteError::InvalidState(format!(
+            "cannot {} a bill that is {}",
+            action, existing.status
+        )));
+    }
+    Ok(existing)
+}
+
+/// Sum issued and paid bills and their notes; drafts and void bills are not owed.
+/// Bills in more than one currency cannot be netted and are rejected.
+pub fn calculate_balance(
+    tenant_id: i32,
+    default_currency: Currency,
+    bills: &[bill::Model],
+    notes: &[bill_note::Model],
+) -> Result<TenantBalance, MoneyError> {
+    let owed: Vec<&bill::Model> = bills
+        .iter()
+        .filter(|b| b.status == bill::STATUS_ISSUED || b.status == bill::STATUS_PAID)
+        .collect();
+    let currency = owed.first().map(|b| b.currency()).unwrap_or(default_currency);
+    let notes: Vec<&bill_note::Model> = notes
+        .iter()
+        .filter(|n| owed.iter().any(|b| b.id == n.bill_id))
+        .collect();
+
+    let mut billed = Money::zero(currency);
+    let mut paid = Money::zero(currency);
+    for b in &owed {
+        billed = billed.checked_add(b.total())?;
+        paid = paid.checked_add(b.amount_paid())?;
+    }
+    let credits = Money::sum(currency, notes.iter().filter(|n| n.kind == bill_note::KIND_CREDIT).map(|n| n.amount))?;
+    let debits = Money::sum(currency, notes.iter().filter(|n| n.kind != bill_note::KIND_CREDIT).map(|n| n.amount))?;
+    let balance = billed.checked_add(debits)?.checked_sub(credits)?.checked_sub(paid)?;
+
+    Ok(TenantBalance {
+        tenant_id,
+        currency: currency.code().to_string(),
+        billed: billed.minor(),
+        debits: debits.minor(),
+        credits: credits.minor(),
+        paid: paid.minor(),
+        balance: balance.minor(),
+    })
+}
+
+/// Bill fields plus its charges and discounts, as recorded in the audit log
+fn audit_snapshot(details: &BillWithChargesAndReading) -> serde_json::Value {
+    let mut value = json!(details.bill);
+    value["additional_charges"] = details
+        .additional_charges
+        .iter()
+        .map(|c| {
+            json!({
+                "charge_type_id": c.charge_type_id,
+                "amount": money::to_decimal_string(c.amount),
+                "description": c.description,
+            })
+        })
+        .collect();
+    value["discounts"] = details
+        .discounts
+        .iter()
+        .map(|d| {
+            json!({
+                "kind": d.kind,
+                "value": money::to_decimal_string(d.value),
+                "amount": money::to_decimal_string(d.amount),
+                "reason": d.reason,
+                "approved_by": d.approved_by,
+            })
+        })
+        .collect();
+    value
+}
+
 // ---------- public methods ----------
 
 /// GET all bills with charges and reading
 pub async fn get_all_bills_with_details(
     db: &DatabaseConnection,
+    filter: &BillFilter,
 ) -> Result<Vec<BillWithChargesAndReading>, DbErr> {
-    let bills = bill_repo::get_all(db).await?;
+    let bills = bill_repo::get_filtered(db, filter).await?;
     let mut result = Vec::with_capacity(bills.len());
 
     for bill_model in bills {
-        let charges = additional_charge_repo::get_all_by_bill_id(db, bill_model.id).await?;
-        let reading = electricity_reading_repo::get_by_id(db, bill_model.reading_id).await?;
-
-        result.push(BillWithChargesAndReading {
-            bill: bill_model,
-            additional_charges: charges,
-            reading,
-        });
+        result.push(load_details(db, bill_model).await?);
     }
 
     Ok(result)
 }
 
+/// GET a single bill
+pub async fn get_bill_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<bill::Model>, DbErr> {
+    bill_repo::get_by_id(db, id).await
+}
+
+/// GET a single bill with charges and reading
+pub async fn get_bill_with_details(
+    db: &DatabaseConnection,
+    id: i32,
+) -> Result<Option<BillWithChargesAndReading>, DbErr> {
+    match bill_repo::get_by_id(db, id).await? {
+        Some(bill_model) => Ok(Some(load_details(db, bill_model).await?)),
+        None => Ok(None),
+    }
+}
+
 /// GET the most recent bill for a tenant with charges and reading
 pub async fn get_tenant_bill_with_details(
     db: &DatabaseConnection,
     tenant_id: i32,
 ) -> Result<Option<BillWithChargesAndReading>, DbErr> {
     if let Some(bill_model) = bill_repo::get_latest_by_tenant_id(db, tenant_id).await? {
-        let charges = additional_charge_repo::get_all_by_bill_id(db, bill_model.id).await?;
-        let reading = electricity_reading_repo::get_by_id(db, bill_model.reading_id).await?;
-
-        Ok(Some(BillWithChargesAndReading {
-            bill: bill_model,
-            additional_charges: charges,
-            reading,
-        }))
+        Ok(Some(load_details(db, bill_model).await?))
     } else {
         Ok(None)
     }
@@ -141,109 +498,318 @@ pub async fn get_all_bills_for_tenant(
     let mut result = Vec::with_capacity(bills.len());
 
     for bill_model in bills {
-        let charges = additional_charge_repo::get_all_by_bill_id(db, bill_model.id).await?;
-        let reading = electricity_reading_repo::get_by_id(db, bill_model.reading_id).await?;
-
-        result.push(BillWithChargesAndReading {
-            bill: bill_model,
-            additional_charges: charges,
-            reading,
-        });
+        result.push(load_details(db, bill_model).await?);
     }
 
     Ok(result)
 }
 
 
-// CREATE a new bill
+/// GET a tenant's balance across issued and paid bills
+/// `default_currency` is reported for a tenant with nothing owed yet
+pub async fn get_tenant_balance(
+    db: &DatabaseConnection,
+    tenant_id: i32,
+    default_currency: Currency,
+) -> Result<TenantBalance, WriteError> {
+    let bills = bill_repo::get_all_by_tenant_id(db, tenant_id).await?;
+    let notes = bill_note_repo::get_all_by_tenant_id(db, tenant_id).await?;
+    Ok(calculate_balance(tenant_id, default_currency, &bills, &notes)?)
+}
+
+// CREATE a new draft bill, adding the tenant's recurring charges
 pub async fn create_bill(
     db: &DatabaseConnection,
+    actor: &str,
     input: BillInput,
-) -> Result<BillWithChargesAndReading, DbErr> {
+) -> Result<BillWithChargesAndReading, WriteError> {
     map_txn_err(
-        db.transaction::<_, BillWithChargesAndReading, DbErr>(|txn| {
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
             let input = input.clone();
+            let actor = actor.to_string();
             Box::pin(async move {
-                let bill_model = build_bill_active_model(&input).insert(txn).await?;
-                insert_charges(txn, bill_model.id, &input.additional_charges).await?;
-
-                let charges = additional_charge_repo::get_all_by_bill_id(txn, bill_model.id).await?;
-                let reading =
-                    electricity_reading_repo::get_by_id(txn, bill_model.reading_id).await?;
-
-                println!(
-                    "✅ Created bill id={} with {} charges",
-                    bill_model.id,
-                    charges.len()
-                );
-
-                Ok(BillWithChargesAndReading {
-                    bill: bill_model,
-                    additional_charges: charges,
-                    reading,
-                })
+                let tenant = tenant_repo::get_by_id(txn, input.tenant_id)
+                    .await?
+                    .ok_or_else(|| WriteError::Invalid(format!("tenant {} does not exist", input.tenant_id)))?;
+                let mut lines = resolve_charges(txn, &input.additional_charges).await?;
+                let recurring = recurring_lines(txn, &tenant, &lines).await?;
+                lines.extend(recurring);
+
+                let totals = calculate_total(&input, &lines)?;
+                let mut bill_am = build_bill_active_model(&input, &totals);
+                bill_am.status = Set(bill::STATUS_DRAFT.to_string());
+                bill_am.paid = Set(false);
+                let bill_model = bill_am.insert(txn).await?;
+                insert_charges(txn, bill_model.id, &lines).await?;
+                insert_discounts(txn, bill_model.id, &totals.discounts, &actor).await?;
+
+                let details = load_details(txn, bill_model).await?;
+                audit_service::record_create(txn, &actor, "bill", details.bill.id, &audit_snapshot(&details)).await?;
+                webhook_service::enqueue(txn, EVENT_BILL_CREATED, &details).await?;
+
+                info!(bill_id = details.bill.id, charges = details.additional_charges.len(), "created draft bill");
+
+                Ok(details)
             })
         })
         .await,
     )
 }
 
-// UPDATE a bill
+// UPDATE a draft bill if it is still at `expected_version`. An uploaded `receipt` is recorded
+// in the same transaction, so a rejected update leaves no attachment row behind
 pub async fn update_bill(
     db: &DatabaseConnection,
+    actor: &str,
     id: i32,
+    expected_version: Option<i32>,
     input: BillInput,
-) -> Result<BillWithChargesAndReading, DbErr> {
+    receipt: Option<StoredFile>,
+) -> Result<BillWithChargesAndReading, WriteError> {
     map_txn_err(
-        db.transaction::<_, BillWithChargesAndReading, DbErr>(|txn| {
-            let input = input.clone();
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
+            let mut input = input.clone();
+            let actor = actor.to_string();
             Box::pin(async move {
-                let mut bill_am = build_bill_active_model(&input);
-                bill_am.id = Set(id);
-                let updated_bill = bill_am.update(txn).await?;
+                let existing = get_for_transition(txn, id, expected_version, &[bill::STATUS_DRAFT], "edit").await?;
+                if let Some(receipt) = &receipt {
+                    let attachment = attachment_service::record_attachment(txn, receipt, Some(actor.clone())).await?;
+                    input.receipt_url = Some(attachment.object_key);
+                }
+                let lines = resolve_charges(txn, &input.additional_charges).await?;
+                let totals = calculate_total(&input, &lines)?;
+                let changes = build_bill_active_model(&input, &totals);
+                let version = existing.version;
+                let before = audit_snapshot(&load_details(txn, existing).await?);
+
+                let updated_bill = bill_repo::update(txn, id, version, changes)
+                    .await
+                    .map_err(versioning::map_guarded_update_err)?;
 
                 additional_charge_repo::delete_many_by_bill_id(txn, updated_bill.id).await?;
-                insert_charges(txn, updated_bill.id, &input.additional_charges).await?;
-
-                let charges =
-                    additional_charge_repo::get_all_by_bill_id(txn, updated_bill.id).await?;
-                let reading =
-                    electricity_reading_repo::get_by_id(txn, updated_bill.reading_id).await?;
-
-                println!(
-                    "✅ Updated bill id={} with {} charges",
-                    updated_bill.id,
-                    charges.len()
-                );
-
-                Ok(BillWithChargesAndReading {
-                    bill: updated_bill,
-                    additional_charges: charges,
-                    reading,
+                insert_charges(txn, updated_bill.id, &lines).await?;
+                bill_discount_repo::delete_many_by_bill_id(txn, updated_bill.id).await?;
+                insert_discounts(txn, updated_bill.id, &totals.discounts, &actor).await?;
+
+                let details = load_details(txn, updated_bill).await?;
+                audit_service::record_update(txn, &actor, "bill", id, &before, &audit_snapshot(&details)).await?;
+                webhook_service::enqueue(txn, EVENT_BILL_UPDATED, &details).await?;
+
+                info!(bill_id = details.bill.id, charges = details.additional_charges.len(), "updated bill");
+
+                Ok(details)
+            })
+        })
+        .await,
+    )
+}
+
+/// Apply a status change to a bill loaded by `get_for_transition`, recording it
+async fn transition(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    existing: bill::Model,
+    changes: bill::ActiveModel,
+) -> Result<BillWithChargesAndReading, WriteError> {
+    let id = existing.id;
+    let version = existing.version;
+    let before = audit_snapshot(&load_details(txn, existing).await?);
+
+    let updated_bill = bill_repo::update(txn, id, version, changes)
+        .await
+        .map_err(versioning::map_guarded_update_err)?;
+
+    let details = load_details(txn, updated_bill).await?;
+    audit_service::record_update(txn, actor, "bill", id, &before, &audit_snapshot(&details)).await?;
+    Ok(details)
+}
+
+// ISSUE a draft bill; it is locked from here on and the tenant is notified
+pub async fn issue_bill(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    expected_version: Option<i32>,
+) -> Result<BillWithChargesAndReading, WriteError> {
+    let actor = actor.to_string();
+    map_txn_err(
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
+            Box::pin(async move {
+                let existing = get_for_transition(txn, id, expected_version, &[bill::STATUS_DRAFT], "issue").await?;
+                let details = transition(txn, &actor, existing, bill::ActiveModel {
+                    status: Set(bill::STATUS_ISSUED.to_string()),
+                    issued_at: Set(Some(chrono::Utc::now().naive_utc())),
+                    ..Default::default()
+                })
+                .await?;
+
+                notification_service::enqueue_bill_event(txn, EVENT_BILL_CREATED, &details.bill).await?;
+                webhook_service::enqueue(txn, EVENT_BILL_ISSUED, &details).await?;
+
+                info!(bill_id = id, "issued bill");
+                Ok(details)
+            })
+        })
+        .await,
+    )
+}
+
+// PAY an issued bill, optionally recording an uploaded receipt in the same transaction
+pub async fn pay_bill(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    expected_version: Option<i32>,
+    receipt: Option<StoredFile>,
+) -> Result<BillWithChargesAndReading, WriteError> {
+    let actor = actor.to_string();
+    map_txn_err(
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
+            Box::pin(async move {
+                let existing = get_for_transition(txn, id, expected_version, &[bill::STATUS_ISSUED], "pay").await?;
+                let receipt_url = match &receipt {
+                    Some(receipt) => Some(attachment_service::record_attachment(txn, receipt, Some(actor.clone())).await?.object_key),
+                    None => existing.receipt_url.clone(),
+                };
+                // Settles the bill as it stands, notes included
+                let amount_paid = load_details(txn, existing.clone()).await?.adjusted_total()?;
+                let details = transition(txn, &actor, existing, bill::ActiveModel {
+                    status: Set(bill::STATUS_PAID.to_string()),
+                    paid: Set(true),
+                    amount_paid: Set(Some(amount_paid.minor())),
+                    paid_at: Set(Some(chrono::Utc::now().naive_utc())),
+                    receipt_url: Set(receipt_url),
+                    ..Default::default()
+                })
+                .await?;
+
+                notification_service::enqueue_bill_event(txn, EVENT_BILL_PAID, &details.bill).await?;
+                webhook_service::enqueue(txn, EVENT_BILL_UPDATED, &details).await?;
+                webhook_service::enqueue(txn, EVENT_BILL_PAID, &details).await?;
+
+                info!(bill_id = id, "marked bill as paid");
+                Ok(details)
+            })
+        })
+        .await,
+    )
+}
+
+// VOID an issued bill that should never have been sent; paid bills are corrected with notes instead
+pub async fn void_bill(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    expected_version: Option<i32>,
+) -> Result<BillWithChargesAndReading, WriteError> {
+    let actor = actor.to_string();
+    map_txn_err(
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
+            Box::pin(async move {
+                let existing = get_for_transition(txn, id, expected_version, &[bill::STATUS_ISSUED], "void").await?;
+                let details = transition(txn, &actor, existing, bill::ActiveModel {
+                    status: Set(bill::STATUS_VOID.to_string()),
+                    ..Default::default()
+                })
+                .await?;
+
+                webhook_service::enqueue(txn, EVENT_BILL_VOIDED, &details).await?;
+
+                info!(bill_id = id, "voided bill");
+                Ok(details)
+            })
+        })
+        .await,
+    )
+}
+
+// ADD a credit or debit note to an issued or paid bill. Credits can take a bill down to zero;
+// on a paid bill that is a refund of what was paid, owed back to the tenant.
+pub async fn add_bill_note(
+    db: &DatabaseConnection,
+    actor: &str,
+    bill_id: i32,
+    input: BillNoteInput,
+) -> Result<BillWithChargesAndReading, WriteError> {
+    if input.kind != bill_note::KIND_CREDIT && input.kind != bill_note::KIND_DEBIT {
+        return Err(WriteError::Invalid("kind must be 'credit' or 'debit'".into()));
+    }
+    if input.amount <= 0 {
+        return Err(WriteError::Invalid("amount must be positive".into()));
+    }
+    if input.reason.trim().is_empty() {
+        return Err(WriteError::Invalid("reason is required".into()));
+    }
+
+    let actor = actor.to_string();
+    map_txn_err(
+        db.transaction::<_, BillWithChargesAndReading, WriteError>(|txn| {
+            Box::pin(async move {
+                let existing = get_for_transition(
+                    txn,
+                    bill_id,
+                    None,
+                    &[bill::STATUS_ISSUED, bill::STATUS_PAID],
+                    "add a note to",
+                )
+                .await?;
+
+                let note = bill_note_repo::create(txn, bill_note::ActiveModel {
+                    bill_id: Set(bill_id),
+                    tenant_id: Set(existing.tenant_id),
+                    kind: Set(input.kind),
+                    amount: Set(input.amount),
+                    reason: Set(input.reason.trim().to_string()),
+                    created_by: Set(actor.clone()),
+                    ..Default::default()
                 })
+                .await?;
+                audit_service::record_create(txn, &actor, "bill_note", note.id, &note).await?;
+
+                let paid = existing.paid;
+                let details = load_details(txn, existing).await?;
+                // The transaction rolls the note back
+                let left = details.adjusted_total()?.minor();
+                if left < 0 {
+                    let what = if paid { "paid for" } else { "outstanding on" };
+                    return Err(WriteError::Invalid(format!(
+                        "credit exceeds the {} {} the bill",
+                        money::to_decimal_string(left + note.amount),
+                        what
+                    )));
+                }
+                webhook_service::enqueue(txn, EVENT_BILL_UPDATED, &details).await?;
+
+                info!(note_id = note.id, kind = %note.kind, bill_id, "added note to bill");
+                Ok(details)
             })
         })
         .await,
     )
 }
 
-// Delete bill and additional charges
-pub async fn delete_bill_with_charges(
+// Move a draft bill to the trash if it is still at `expected_version`; charges are kept until it is purged
+pub async fn delete_bill(
     db: &DatabaseConnection,
+    actor: &str,
     bill_id: i32,
-) -> Result<Option<bill::Model>, DbErr> {
+    expected_version: Option<i32>,
+) -> Result<Option<bill::Model>, WriteError> {
+    let actor = actor.to_string();
     map_txn_err(
-        db.transaction::<_, Option<bill::Model>, DbErr>(|txn| {
+        db.transaction::<_, Option<bill::Model>, WriteError>(|txn| {
             Box::pin(async move {
-                let deleted_charges =
-                    additional_charge_repo::delete_many_by_bill_id(txn, bill_id).await?;
+                if bill_repo::get_by_id(txn, bill_id).await?.is_none() {
+                    return Ok(None);
+                }
+                let existing = get_for_transition(txn, bill_id, expected_version, &[bill::STATUS_DRAFT], "delete").await?;
+                let before = audit_snapshot(&load_details(txn, existing).await?);
+                audit_service::record_delete(txn, &actor, "bill", bill_id, &before).await?;
+
                 let deleted_bill = bill_repo::delete(txn, bill_id).await?;
 
                 if let Some(bill) = &deleted_bill {
-                    println!(
-                        "✅ Deleted bill id={} with {} charges",
-                        bill.id, deleted_charges
-                    );
+                    info!(bill_id = bill.id, "moved bill to the trash");
                 }
 
                 Ok(deleted_bill)
@@ -252,3 +818,325 @@ pub async fn delete_bill_with_charges(
         .await,
     )
 }
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn sample_bill(id: i32, status: &str, total: i64) -> bill::Model {
+        let now = Utc::now().naive_utc();
+        bill::Model {
+            id,
+            reading_id: id,
+            tenant_id: 1,
+            room_charges: total,
+            electric_charges: 0,
+            discount_amount: 0,
+            total_amount: total,
+            currency: "PHP".into(),
+            receipt_url: None,
+            paid: status == bill::STATUS_PAID,
+            amount_paid: (status == bill::STATUS_PAID).then_some(total),
+            status: status.into(),
+            issued_at: Some(now),
+            paid_at: None,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            version: 1,
+        }
+    }
+
+    fn sample_note(bill_id: i32, kind: &str, amount: i64) -> bill_note::Model {
+        let now = Utc::now().naive_utc();
+        bill_note::Model {
+            id: bill_id * 10,
+            bill_id,
+            tenant_id: 1,
+            kind: kind.into(),
+            amount,
+            reason: "Correction".into(),
+            created_by: "admin:root".into(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_calculate_balance() {
+        let bills = vec![
+            sample_bill(1, bill::STATUS_PAID, 5000),
+            sample_bill(2, bill::STATUS_ISSUED, 6000),
+            sample_bill(3, bill::STATUS_DRAFT, 7000),
+            sample_bill(4, bill::STATUS_VOID, 8000),
+        ];
+        let notes = vec![
+            sample_note(1, bill_note::KIND_CREDIT, 500),
+            sample_note(2, bill_note::KIND_DEBIT, 250),
+            // Notes on bills that are not owed are ignored
+            sample_note(4, bill_note::KIND_CREDIT, 1000),
+        ];
+
+        let balance = calculate_balance(1, Currency::PHP, &bills, &notes).unwrap();
+        assert_eq!(
+            balance,
+            TenantBalance {
+                tenant_id: 1,
+                currency: "PHP".into(),
+                billed: 11000,
+                debits: 250,
+                credits: 500,
+                paid: 5000,
+                balance: 5750,
+            }
+        );
+        assert_eq!(serde_json::to_value(&balance).unwrap()["balance"], "57.50");
+    }
+
+    #[test]
+    fn test_money_checks() {
+        let input = BillInput {
+            tenant_id: 1,
+            reading_id: 1,
+            currency: Currency::PHP,
+            room_charges: 500000,
+            electric_charges: 125050,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        };
+        let lines = vec![ChargeLine { charge_type_id: None, amount: 30000, description: "Water".into() }];
+        assert_eq!(calculate_total(&input, &lines).unwrap().total.minor(), 655050);
+
+        let overflowing = BillInput { room_charges: i64::MAX, ..input };
+        assert!(matches!(calculate_total(&overflowing, &lines), Err(WriteError::Invalid(_))));
+
+        let mut usd = sample_bill(5, bill::STATUS_ISSUED, 100);
+        usd.currency = "USD".into();
+        let bills = vec![sample_bill(1, bill::STATUS_ISSUED, 100), usd];
+        assert!(matches!(calculate_balance(1, Currency::PHP, &bills, &[]), Err(MoneyError::CurrencyMismatch(..))));
+    }
+
+    #[test]
+    fn test_apply_catalog() {
+        let now = Utc::now().naive_utc();
+        let charge_type = |id, code: &str, is_active| charge_type::Model {
+            id,
+            code: code.into(),
+            name: code.to_uppercase(),
+            default_amount: 20000,
+            is_active,
+            created_at: now,
+            updated_at: now,
+        };
+        let catalog = vec![charge_type(1, "water", true), charge_type(2, "cable", false)];
+
+        let lines = apply_catalog(
+            &[
+                AdditionalChargeInput { charge_type_id: Some(1), ..Default::default() },
+                AdditionalChargeInput { charge_type_id: Some(1), amount: Some(5000), description: "Water (June)".into() },
+                AdditionalChargeInput { charge_type_id: None, amount: Some(300), description: "Key copy".into() },
+            ],
+            &catalog,
+        )
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                ChargeLine { charge_type_id: Some(1), amount: 20000, description: "WATER".into() },
+                ChargeLine { charge_type_id: Some(1), amount: 5000, description: "Water (June)".into() },
+                ChargeLine { charge_type_id: None, amount: 300, description: "Key copy".into() },
+            ]
+        );
+
+        let retired = AdditionalChargeInput { charge_type_id: Some(2), ..Default::default() };
+        assert!(matches!(apply_catalog(&[retired], &catalog), Err(WriteError::Invalid(_))));
+        let bare = AdditionalChargeInput { description: "Misc".into(), ..Default::default() };
+        assert!(matches!(apply_catalog(&[bare], &catalog), Err(WriteError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_calculate_total_with_discounts() {
+        let discount = |kind: &str, value, reason: &str| DiscountInput {
+            kind: kind.into(),
+            value,
+            reason: reason.into(),
+            approved_by: None,
+        };
+        let mut input = BillInput {
+            tenant_id: 1,
+            reading_id: 1,
+            currency: Currency::PHP,
+            room_charges: 500000,
+            electric_charges: 100050,
+            additional_charges: vec![],
+            // Fixed first in the request, but percentages are always applied first
+            discounts: vec![discount("fixed", 10000, "Loyalty"), discount("percent", 1250, "Promo")],
+            receipt_url: None,
+        };
+
+        let totals = calculate_total(&input, &[]).unwrap();
+        // 12.5% of 6,000.50 is 750.0625, rounded to 750.06
+        assert_eq!(totals.discounts.iter().map(|d| d.amount).collect::<Vec<_>>(), vec![75006, 10000]);
+        assert_eq!(totals.discounts[0].reason, "Promo");
+        assert_eq!(totals.discount.minor(), 85006);
+        assert_eq!(totals.total.minor(), 515044);
+
+        input.discounts = vec![discount("percent", 10000, "Waived"), discount("fixed", 1, "Extra")];
+        assert!(matches!(calculate_total(&input, &[]), Err(WriteError::Invalid(_))));
+
+        input.discounts = vec![discount("percent", 10001, "Too much")];
+        assert!(matches!(calculate_total(&input, &[]), Err(WriteError::Invalid(_))));
+
+        input.discounts = vec![discount("fixed", 100, " ")];
+        assert!(matches!(calculate_total(&input, &[]), Err(WriteError::Invalid(_))));
+
+        input.discounts = vec![];
+        let negative = vec![ChargeLine { charge_type_id: None, amount: -500, description: "Rebate".into() }];
+        assert!(matches!(calculate_total(&input, &negative), Err(WriteError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_credit_note_cannot_exceed_outstanding() {
+        use crate::test_utils::{get_test_db, seed_reading};
+
+        let db = get_test_db().await;
+        let (_, tenant, reading) = seed_reading(&db).await;
+        let input = BillInput {
+            tenant_id: tenant.id,
+            reading_id: reading.id,
+            currency: Currency::PHP,
+            room_charges: 500_000,
+            electric_charges: 0,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        };
+        let bill = create_bill(&db, "admin:root", input).await.unwrap().bill;
+        issue_bill(&db, "admin:root", bill.id, None).await.unwrap();
+
+        let credit = |amount| BillNoteInput { kind: bill_note::KIND_CREDIT.into(), amount, reason: "Goodwill".into() };
+        let details = add_bill_note(&db, "admin:root", bill.id, credit(300_000)).await.unwrap();
+        assert_eq!(details.outstanding().unwrap().minor(), 200_000);
+
+        let over = add_bill_note(&db, "admin:root", bill.id, credit(200_001)).await;
+        assert!(matches!(over, Err(WriteError::Invalid(msg)) if msg.contains("2000.00")));
+        // Rolled back: exactly the remainder still fits
+        let details = add_bill_note(&db, "admin:root", bill.id, credit(200_000)).await.unwrap();
+        assert_eq!(details.outstanding().unwrap().minor(), 0);
+        assert_eq!(details.notes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_payment_settles_the_bill_after_notes() {
+        use crate::test_utils::{add_reading, get_test_db, seed_reading};
+
+        let db = get_test_db().await;
+        let (_, tenant, reading) = seed_reading(&db).await;
+        let draft = |reading_id| BillInput {
+            tenant_id: tenant.id,
+            reading_id,
+            currency: Currency::PHP,
+            room_charges: 100_000,
+            electric_charges: 0,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        };
+        let credit = |amount| BillNoteInput { kind: bill_note::KIND_CREDIT.into(), amount, reason: "Goodwill".into() };
+
+        let bill = create_bill(&db, "admin:root", draft(reading.id)).await.unwrap().bill;
+        issue_bill(&db, "admin:root", bill.id, None).await.unwrap();
+        add_bill_note(&db, "admin:root", bill.id, credit(20_000)).await.unwrap();
+        let paid = pay_bill(&db, "admin:root", bill.id, None, None).await.unwrap();
+        assert_eq!(paid.bill.amount_paid, Some(80_000));
+        assert_eq!(paid.outstanding().unwrap().minor(), 0);
+        assert_eq!(get_tenant_balance(&db, tenant.id, Currency::PHP).await.unwrap().balance, 0);
+
+        // A credit on a paid bill refunds part of the payment, up to all of it
+        let refunded = add_bill_note(&db, "admin:root", bill.id, credit(30_000)).await.unwrap();
+        assert_eq!(refunded.outstanding().unwrap().minor(), -30_000);
+        assert_eq!(get_tenant_balance(&db, tenant.id, Currency::PHP).await.unwrap().balance, -30_000);
+        let over = add_bill_note(&db, "admin:root", bill.id, credit(50_001)).await;
+        assert!(matches!(over, Err(WriteError::Invalid(msg)) if msg.contains("500.00 paid for")));
+
+        // A credit waits for one still being written elsewhere, then counts it
+        let reading = add_reading(&db, &tenant, 150, 200).await;
+        let bill = create_bill(&db, "admin:root", draft(reading.id)).await.unwrap().bill;
+        issue_bill(&db, "admin:root", bill.id, None).await.unwrap();
+        let txn = db.begin().await.unwrap();
+        bill_note_repo::create(&txn, bill_note::ActiveModel {
+            bill_id: Set(bill.id),
+            tenant_id: Set(tenant.id),
+            kind: Set(bill_note::KIND_CREDIT.into()),
+            amount: Set(60_000),
+            reason: Set("Goodwill".into()),
+            created_by: Set("admin:root".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let second = tokio::spawn({
+            let db = db.clone();
+            async move { add_bill_note(&db, "admin:root", bill.id, credit(60_000)).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(!second.is_finished());
+        txn.commit().await.unwrap();
+        assert!(matches!(second.await.unwrap(), Err(WriteError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_receipt_is_recorded_with_the_payment() {
+        use crate::test_utils::{get_test_db, reset_table, seed_reading};
+
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+        let (_, tenant, reading) = seed_reading(&db).await;
+        let input = BillInput {
+            tenant_id: tenant.id,
+            reading_id: reading.id,
+            currency: Currency::PHP,
+            room_charges: 100_000,
+            electric_charges: 0,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        };
+        let bill = create_bill(&db, "admin:root", input).await.unwrap().bill;
+        let receipt = StoredFile {
+            object_key: format!("receipts/bills/{}/1.jpg", bill.id),
+            owner_type: attachment::OWNER_BILL.into(),
+            owner_id: bill.id,
+            file_name: Some("receipt.jpg".into()),
+            content_type: "image/jpeg".into(),
+            size_bytes: 10,
+            checksum: "abc".into(),
+        };
+
+        // A rejected payment leaves no attachment row behind
+        let early = pay_bill(&db, "admin:root", bill.id, None, Some(receipt.clone())).await;
+        assert!(matches!(early, Err(WriteError::InvalidState(_))));
+        let stale = update_bill(&db, "admin:root", bill.id, Some(bill.version + 1), BillInput {
+            tenant_id: tenant.id,
+            reading_id: reading.id,
+            currency: Currency::PHP,
+            room_charges: 100_000,
+            electric_charges: 0,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        }, Some(receipt.clone()))
+        .await;
+        assert!(matches!(stale, Err(WriteError::VersionMismatch)));
+        assert!(attachment_repo::get_all_by_owner(&db, attachment::OWNER_BILL, bill.id).await.unwrap().is_empty());
+
+        issue_bill(&db, "admin:root", bill.id, None).await.unwrap();
+        let paid = pay_bill(&db, "admin:root", bill.id, None, Some(receipt.clone())).await.unwrap();
+        assert_eq!(paid.bill.receipt_url.as_deref(), Some(receipt.object_key.as_str()));
+        assert_eq!(paid.attachments.len(), 1);
+        assert_eq!(paid.attachments[0].object_key, receipt.object_key);
+    }
+}
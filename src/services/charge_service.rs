@@ -0,0 +1,263 @@
+use crate::entities::{charge_type, recurring_charge};
+use crate::money;
+use crate::repository::{additional_charge_repo, bill_discount_repo, charge_type_repo, recurring_charge_repo};
+use crate::services::{audit_service, map_txn_err};
+use chrono::NaiveDateTime;
+use sea_orm::{DatabaseConnection, DbErr, Set, TransactionTrait};
+use serde::Serialize;
+use tracing::{error, info, instrument, warn};
+
+/// Report key for charges without a catalog category
+pub const UNCATEGORIZED: &str = "uncategorized";
+/// Report key for discount lines, whose totals are negative
+pub const DISCOUNTS: &str = "discounts";
+
+/// Charges of one category and currency in a reporting period
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ChargeCategoryTotal {
+    pub charge_type_id: Option<i32>,
+    pub code: String,
+    pub name: String,
+    pub currency: String,
+    pub count: i64,
+    #[serde(with = "money::decimal")]
+    pub total: i64,
+}
+
+// ---------- charge catalog ----------
+
+/// GET the charge catalog
+pub async fn get_charge_types(db: &DatabaseConnection) -> Result<Vec<charge_type::Model>, DbErr> {
+    charge_type_repo::get_all(db).await
+}
+
+/// CREATE a charge type
+#[instrument(skip_all)]
+pub async fn create_charge_type(
+    db: &DatabaseConnection,
+    actor: &str,
+    item: charge_type::ActiveModel,
+) -> Result<charge_type::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, charge_type::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let created = charge_type_repo::create(txn, item).await?;
+                audit_service::record_create(txn, &actor, "charge_type", created.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(t) => info!(charge_type_id = t.id, code = %t.code, "created charge type"),
+        Err(err) => error!(error = ?err, "error creating charge type"),
+    }
+    result
+}
+
+/// UPDATE a charge type; `DbErr::RecordNotUpdated` when it does not exist
+#[instrument(skip_all)]
+pub async fn update_charge_type(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    item: charge_type::ActiveModel,
+) -> Result<charge_type::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, charge_type::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = charge_type_repo::get_by_id(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                let updated = charge_type_repo::update(txn, id, item).await?;
+                audit_service::record_update(txn, &actor, "charge_type", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(t) => info!(charge_type_id = t.id, "updated charge type"),
+        Err(err) => error!(charge_type_id = id, error = ?err, "error updating charge type"),
+    }
+    result
+}
+
+/// Retire a charge type: past bills keep their category, new bills and recurring charges skip it
+pub async fn retire_charge_type(db: &DatabaseConnection, actor: &str, id: i32) -> Result<charge_type::Model, DbErr> {
+    let changes = charge_type::ActiveModel {
+        is_active: Set(false),
+        ..Default::default()
+    };
+    update_charge_type(db, actor, id, changes).await
+}
+
+// ---------- recurring charges ----------
+
+/// GET recurring charges, optionally only those of a tenant or a room
+pub async fn get_recurring_charges(
+    db: &DatabaseConnection,
+    tenant_id: Option<i32>,
+    room_id: Option<i32>,
+) -> Result<Vec<recurring_charge::Model>, DbErr> {
+    recurring_charge_repo::get_all(db, tenant_id, room_id).await
+}
+
+/// CREATE a recurring charge
+#[instrument(skip_all)]
+pub async fn create_recurring_charge(
+    db: &DatabaseConnection,
+    actor: &str,
+    item: recurring_charge::ActiveModel,
+) -> Result<recurring_charge::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, recurring_charge::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let created = recurring_charge_repo::create(txn, item).await?;
+                audit_service::record_create(txn, &actor, "recurring_charge", created.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(r) => info!(charge_id = r.id, "created recurring charge"),
+        Err(err) => error!(error = ?err, "error creating recurring charge"),
+    }
+    result
+}
+
+/// UPDATE a recurring charge; `DbErr::RecordNotUpdated` when it does not exist
+#[instrument(skip_all)]
+pub async fn update_recurring_charge(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    item: recurring_charge::ActiveModel,
+) -> Result<recurring_charge::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, recurring_charge::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = recurring_charge_repo::get_by_id(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                let updated = recurring_charge_repo::update(txn, id, item).await?;
+                audit_service::record_update(txn, &actor, "recurring_charge", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(r) => info!(charge_id = r.id, "updated recurring charge"),
+        Err(err) => error!(charge_id = id, error = ?err, "error updating recurring charge"),
+    }
+    result
+}
+
+/// DELETE a recurring charge
+#[instrument(skip_all)]
+pub async fn delete_recurring_charge(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+) -> Result<Option<recurring_charge::Model>, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<recurring_charge::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let deleted = recurring_charge_repo::delete(txn, id).await?;
+                if let Some(r) = &deleted {
+                    audit_service::record_delete(txn, &actor, "recurring_charge", id, r).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(Some(r)) => info!(charge_id = r.id, "deleted recurring charge"),
+        Ok(None) => warn!(charge_id = id, "recurring charge not found"),
+        Err(err) => error!(charge_id = id, error = ?err, "error deleting recurring charge"),
+    }
+    result
+}
+
+// ---------- reporting ----------
+
+/// Label report rows with their catalog entry, largest totals first
+fn label_category_totals(
+    rows: Vec<additional_charge_repo::CategoryTotalRow>,
+    catalog: &[charge_type::Model],
+) -> Vec<ChargeCategoryTotal> {
+    let mut totals: Vec<ChargeCategoryTotal> = rows
+        .into_iter()
+        .map(|row| {
+            let charge_type = row.charge_type_id.and_then(|id| catalog.iter().find(|t| t.id == id));
+            ChargeCategoryTotal {
+                charge_type_id: row.charge_type_id,
+                code: charge_type.map_or(UNCATEGORIZED.into(), |t| t.code.clone()),
+                name: charge_type.map_or("Uncategorized".into(), |t| t.name.clone()),
+                currency: row.currency,
+                count: row.count,
+                total: row.total,
+            }
+        })
+        .collect();
+    totals.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.code.cmp(&b.code)));
+    totals
+}
+
+/// Additional charges on issued and paid bills in `[from, to)`, grouped by category,
+/// followed by the discounts given on those bills
+pub async fn get_category_totals(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<ChargeCategoryTotal>, DbErr> {
+    let rows = additional_charge_repo::sum_by_category(db, from, to, property_ids).await?;
+    let catalog = charge_type_repo::get_all(db).await?;
+    let mut totals = label_category_totals(rows, &catalog);
+
+    for row in bill_discount_repo::sum_by_currency(db, from, to, property_ids).await? {
+        totals.push(ChargeCategoryTotal {
+            charge_type_id: None,
+            code: DISCOUNTS.into(),
+            name: "Discounts".into(),
+            currency: row.currency,
+            count: row.count,
+            total: -row.total,
+        });
+    }
+    Ok(totals)
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use additional_charge_repo::CategoryTotalRow;
+    use chrono::Utc;
+
+    #[test]
+    fn test_label_category_totals() {
+        let now = Utc::now().naive_utc();
+        let catalog = vec![charge_type::Model {
+            id: 1,
+            code: "water".into(),
+            name: "Water".into(),
+            default_amount: 0,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }];
+        let row = |charge_type_id, total| CategoryTotalRow { charge_type_id, currency: "PHP".into(), count: 1, total };
+
+        let totals = label_category_totals(vec![row(None, 500), row(Some(1), 9000)], &catalog);
+        assert_eq!(totals[0].code, "water");
+        assert_eq!(totals[0].name, "Water");
+        assert_eq!(totals[1].code, UNCATEGORIZED);
+        assert_eq!(serde_json::to_value(&totals[1]).unwrap()["total"], "5.00");
+    }
+}
@@ -0,0 +1,333 @@
+use crate::entities::{electricity_reading, master_meter_reading};
+use crate::repository::report_repo::{self, BuildingConsumptionRow, ConsumptionGroup, ConsumptionRow};
+use crate::repository::{electricity_reading_repo, master_meter_reading_repo};
+use crate::services::electricity_reading_service::calculate_consumption;
+use crate::services::{audit_service, map_txn_err};
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, Set, TransactionTrait};
+use serde::Serialize;
+use tracing::{debug, error, info, instrument, warn};
+
+/// How many of a room's previous readings make up its baseline
+pub const BASELINE_READINGS: u64 = 6;
+/// Fewer readings than this are not enough to judge a new one
+const MIN_BASELINE_READINGS: usize = 3;
+/// Readings within this many standard deviations of the baseline are normal...
+const MAX_SIGMAS: f64 = 3.0;
+/// ...and so are readings within this share of it, so a very steady room is not flagged for small changes
+const MIN_RELATIVE_DEVIATION: f64 = 0.5;
+
+/// One month of a consumption series with its comparisons
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConsumptionPoint {
+    pub month: NaiveDate,
+    pub readings: i64,
+    /// kWh
+    pub consumption: i64,
+    pub previous_month: Option<i64>,
+    /// Change from the previous month, in percent
+    pub month_over_month: Option<f64>,
+    pub same_month_last_year: Option<i64>,
+    /// Change from the same month a year earlier, in percent
+    pub year_over_year: Option<f64>,
+}
+
+/// Monthly consumption of one room or tenant
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConsumptionSeries {
+    pub id: i32,
+    pub name: String,
+    pub total: i64,
+    pub points: Vec<ConsumptionPoint>,
+}
+
+/// What the rooms' meters add up to against the utility's master meter in one month
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BuildingConsumption {
+    pub month: NaiveDate,
+    pub rooms: i64,
+    /// Absent when no master meter reading was recorded for the month
+    pub master: Option<i64>,
+    /// `master - rooms`: common areas, losses or unmetered use
+    pub unaccounted: Option<i64>,
+    /// `unaccounted / master`, in percent
+    pub unaccounted_share: Option<f64>,
+}
+
+/// Why a reading with `consumption` stands out from the room's `baseline`, if it does.
+/// A reading below the previous one is always suspicious; otherwise it is compared with
+/// the mean of the baseline once there are enough readings to have one.
+pub fn detect_anomaly(consumption: i32, baseline: &[i32]) -> Option<String> {
+    if consumption < 0 {
+        return Some(format!("negative consumption of {} kWh; the meter went backwards", consumption));
+    }
+    let baseline: Vec<f64> = baseline.iter().filter(|c| **c >= 0).map(|c| *c as f64).collect();
+    if baseline.len() < MIN_BASELINE_READINGS {
+        return None;
+    }
+
+    let n = baseline.len() as f64;
+    let mean = baseline.iter().sum::<f64>() / n;
+    let std_dev = (baseline.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if mean <= 0.0 {
+        return None;
+    }
+    let deviation = (consumption as f64 - mean).abs();
+    if deviation <= (MAX_SIGMAS * std_dev).max(MIN_RELATIVE_DEVIATION * mean) {
+        return None;
+    }
+
+    if consumption == 0 {
+        Some(format!("no consumption against a baseline of {:.0} kWh", mean))
+    } else {
+        Some(format!(
+            "{} kWh is {:.1}x the room's baseline of {:.0} kWh",
+            consumption,
+            consumption as f64 / mean,
+            mean
+        ))
+    }
+}
+
+/// Compare a reading with the room's previous ones and store the verdict
+#[instrument(skip_all)]
+pub async fn flag_reading<C>(conn: &C, reading: electricity_reading::Model) -> Result<electricity_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let baseline: Vec<i32> = electricity_reading_repo::get_recent_for_room(
+        conn,
+        reading.room_id,
+        reading.created_at,
+        reading.id,
+        BASELINE_READINGS,
+    )
+    .await?
+    .iter()
+    .map(|r| r.consumption)
+    .collect();
+
+    let reason = detect_anomaly(reading.consumption, &baseline);
+    if reason == reading.flag_reason {
+        return Ok(reading);
+    }
+    if let Some(reason) = &reason {
+        warn!(reading_id = reading.id, room_id = reading.room_id, %reason, "flagged reading");
+    }
+    electricity_reading_repo::set_flag(conn, reading.id, reason).await
+}
+
+fn percent_change(from: i64, to: i64) -> Option<f64> {
+    (from != 0).then(|| ((to - from) as f64 / from as f64 * 1_000.0).round() / 10.0)
+}
+
+/// Group monthly rows into series and compare each month with the one before and the same
+/// month a year earlier. Rows before `first_month` only feed those comparisons.
+pub fn build_series(rows: Vec<ConsumptionRow>, first_month: Option<NaiveDate>) -> Vec<ConsumptionSeries> {
+    let mut series: Vec<(ConsumptionSeries, Vec<ConsumptionRow>)> = Vec::new();
+    for row in rows {
+        match series.iter_mut().find(|(s, _)| s.id == row.key_id) {
+            Some((_, rows)) => rows.push(row),
+            None => series.push((
+                ConsumptionSeries { id: row.key_id, name: row.key_name.clone(), total: 0, points: Vec::new() },
+                vec![row],
+            )),
+        }
+    }
+
+    series
+        .into_iter()
+        .map(|(mut s, rows)| {
+            let in_month = |month: NaiveDate| rows.iter().find(|r| r.month == month).map(|r| r.consumption);
+            for row in rows.iter().filter(|r| first_month.is_none_or(|first| r.month >= first)) {
+                let previous_month = in_month(row.month - Months::new(1));
+                let same_month_last_year = in_month(row.month - Months::new(12));
+                s.total += row.consumption;
+                s.points.push(ConsumptionPoint {
+                    month: row.month,
+                    readings: row.readings,
+                    consumption: row.consumption,
+                    previous_month,
+                    month_over_month: previous_month.and_then(|p| percent_change(p, row.consumption)),
+                    same_month_last_year,
+                    year_over_year: same_month_last_year.and_then(|p| percent_change(p, row.consumption)),
+                });
+            }
+            s
+        })
+        .filter(|s| !s.points.is_empty())
+        .collect()
+}
+
+impl From<BuildingConsumptionRow> for BuildingConsumption {
+    fn from(row: BuildingConsumptionRow) -> Self {
+        let unaccounted = row.master.map(|master| master - row.rooms);
+        BuildingConsumption {
+            month: row.month,
+            rooms: row.rooms,
+            master: row.master,
+            unaccounted,
+            unaccounted_share: row.master.zip(unaccounted).and_then(|(master, u)| {
+                (master != 0).then(|| (u as f64 / master as f64 * 1_000.0).round() / 10.0)
+            }),
+        }
+    }
+}
+
+/// Monthly consumption per room or tenant between `from` and `to`
+pub async fn get_consumption_series(
+    db: &DatabaseConnection,
+    group: ConsumptionGroup,
+    id: Option<i32>,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<ConsumptionSeries>, DbErr> {
+    // A year of history before `from` so its months have something to compare with
+    let first_month = from.map(|f| f.date().with_day(1).unwrap_or(f.date()));
+    let lookback = first_month.map(|m| NaiveDateTime::from(m - Months::new(12)));
+    let rows = report_repo::consumption_by_month(db, group, id, lookback, to, property_ids).await?;
+    Ok(build_series(rows, first_month))
+}
+
+pub async fn get_building_consumption(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<BuildingConsumption>, DbErr> {
+    let rows = report_repo::building_consumption_by_month(db, from, to, property_ids).await?;
+    Ok(rows.into_iter().map(BuildingConsumption::from).collect())
+}
+
+#[instrument(skip_all)]
+pub async fn get_flagged_readings(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    let result = electricity_reading_repo::get_flagged(db, from, to, property_ids).await;
+    match &result {
+        Ok(list) => debug!(count = list.len(), "fetched flagged readings"),
+        Err(err) => error!(error = ?err, "error fetching flagged readings"),
+    }
+    result
+}
+
+pub async fn get_master_readings(
+    db: &DatabaseConnection,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<master_meter_reading::Model>, DbErr> {
+    master_meter_reading_repo::get_all(db, property_ids).await
+}
+
+/// Record a master meter reading; consumption is derived as for room readings
+#[instrument(skip_all)]
+pub async fn create_master_reading(
+    db: &DatabaseConnection,
+    actor: &str,
+    mut item: master_meter_reading::ActiveModel,
+) -> Result<master_meter_reading::Model, DbErr> {
+    item.consumption = Set(calculate_consumption(item.prev_reading.clone(), item.curr_reading.clone()));
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, master_meter_reading::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let created = master_meter_reading_repo::create(txn, item).await?;
+                audit_service::record_create(txn, &actor, "master_meter_reading", created.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(r) => info!(master_reading_id = r.id, consumption = %r.consumption, "created master reading"),
+        Err(err) => error!(error = ?err, "error creating master reading"),
+    }
+    result
+}
+
+#[instrument(skip_all)]
+pub async fn delete_master_reading(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+) -> Result<Option<master_meter_reading::Model>, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<master_meter_reading::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let deleted = master_meter_reading_repo::delete(txn, id).await?;
+                if let Some(r) = &deleted {
+                    audit_service::record_delete(txn, &actor, "master_meter_reading", id, r).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(Some(_)) => info!(master_reading_id = id, "deleted master reading"),
+        Ok(None) => warn!(master_reading_id = id, "master reading not found"),
+        Err(err) => error!(master_reading_id = id, error = ?err, "error deleting master reading"),
+    }
+    result
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn row(key_id: i32, month: NaiveDate, consumption: i64) -> ConsumptionRow {
+        ConsumptionRow { month, key_id, key_name: format!("Room {}", key_id), readings: 1, consumption }
+    }
+
+    #[test]
+    fn test_detect_anomaly() {
+        let steady = [100, 110, 95, 105, 100];
+        assert_eq!(detect_anomaly(120, &steady), None);
+        assert_eq!(detect_anomaly(60, &steady), None);
+        assert_eq!(
+            detect_anomaly(320, &steady).as_deref(),
+            Some("320 kWh is 3.1x the room's baseline of 102 kWh")
+        );
+        assert_eq!(detect_anomaly(0, &steady).as_deref(), Some("no consumption against a baseline of 102 kWh"));
+        assert!(detect_anomaly(-5, &[]).unwrap().contains("went backwards"));
+        // Too little history to judge
+        assert_eq!(detect_anomaly(900, &[100, 100]), None);
+        // A room that swings a lot is given more room
+        assert_eq!(detect_anomaly(260, &[50, 250, 60, 240]), None);
+    }
+
+    #[test]
+    fn test_build_series_compares_months_and_years() {
+        let rows = vec![
+            row(1, month(2025, 10), 80),
+            row(1, month(2026, 9), 100),
+            row(1, month(2026, 10), 120),
+            row(2, month(2025, 10), 50),
+        ];
+        let series = build_series(rows, Some(month(2026, 1)));
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].total, 220);
+
+        let october = &series[0].points[1];
+        assert_eq!((october.previous_month, october.month_over_month), (Some(100), Some(20.0)));
+        assert_eq!((october.same_month_last_year, october.year_over_year), (Some(80), Some(50.0)));
+        assert_eq!(series[0].points[0].previous_month, None);
+    }
+
+    #[test]
+    fn test_building_consumption_unaccounted() {
+        let row = BuildingConsumption::from(BuildingConsumptionRow { month: month(2026, 10), rooms: 900, master: Some(1_000) });
+        assert_eq!((row.unaccounted, row.unaccounted_share), (Some(100), Some(10.0)));
+        let row = BuildingConsumption::from(BuildingConsumptionRow { month: month(2026, 10), rooms: 900, master: None });
+        assert_eq!((row.unaccounted, row.unaccounted_share), (None, None));
+    }
+}
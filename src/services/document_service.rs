@@ -0,0 +1,490 @@
+use crate::entities::attachment::{self, OWNER_BILL};
+use crate::entities::{bill_discount, bill_note, property, room, tenant};
+use crate::money::{self, Money, MoneyError};
+use crate::repository::{property_repo, room_repo, tenant_repo};
+use crate::services::attachment_service::{self, AttachmentError, NewAttachment};
+use crate::services::bill_service::{self, BillWithChargesAndReading};
+use crate::services::r2_service::R2Config;
+use crate::services::template_service::Branding;
+use axum::body::Bytes;
+use chrono::Utc;
+use printpdf::{BuiltinFont, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point};
+use sea_orm::{DatabaseConnection, DbErr};
+use tracing::{error, info, instrument};
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 7.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Invoice,
+    Receipt,
+}
+
+impl DocumentKind {
+    fn title(&self) -> &'static str {
+        match self {
+            DocumentKind::Invoice => "INVOICE",
+            DocumentKind::Receipt => "OFFICIAL RECEIPT",
+        }
+    }
+
+    fn slug(&self) -> &'static str {
+        match self {
+            DocumentKind::Invoice => "invoice",
+            DocumentKind::Receipt => "receipt",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DocumentError {
+    BillNotFound,
+    BillNotPaid,
+    Render(String),
+    Attachment(AttachmentError),
+    Db(DbErr),
+}
+
+impl From<DbErr> for DocumentError {
+    fn from(err: DbErr) -> Self {
+        DocumentError::Db(err)
+    }
+}
+
+/// A stored bill document
+pub struct BillDocument {
+    pub attachment: attachment::Model,
+    /// False when the document generated earlier for the same bill was returned
+    pub created: bool,
+}
+
+/// Everything printed on a bill document
+pub struct BillDocumentData {
+    pub details: BillWithChargesAndReading,
+    pub tenant: Option<tenant::Model>,
+    pub room: Option<room::Model>,
+    /// The room's property, whose branding heads the document
+    pub property: Option<property::Model>,
+}
+
+impl BillDocumentData {
+    pub fn money(&self, minor: i64) -> Money {
+        Money::new(minor, self.details.bill.currency())
+    }
+
+    pub fn amount_paid(&self) -> Money {
+        self.details.bill.amount_paid()
+    }
+
+    /// Outstanding after payment, credit notes and debit notes
+    pub fn balance(&self) -> Result<Money, MoneyError> {
+        self.details.outstanding()
+    }
+
+    /// Electricity rate per kWh in major units, derived from the charge and consumption
+    pub fn electric_rate(&self) -> Option<f64> {
+        self.details
+            .reading
+            .as_ref()
+            .filter(|r| r.consumption > 0)
+            .map(|r| self.money(self.details.bill.electric_charges).to_major_f64() / r.consumption as f64)
+    }
+}
+
+/// `PHP 1,234.50`; the built-in PDF fonts have no peso sign, so the ISO code is used
+pub fn format_amount(amount: Money) -> String {
+    let minor = amount.minor().unsigned_abs();
+    let digits = (minor / 100).to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if amount.minor() < 0 { "-" } else { "" };
+    format!("{}{} {}.{:02}", sign, amount.currency(), grouped, minor % 100)
+}
+
+/// Load the bill together with its tenant, room and property
+pub async fn load_document_data(
+    db: &DatabaseConnection,
+    bill_id: i32,
+) -> Result<Option<BillDocumentData>, DbErr> {
+    let Some(details) = bill_service::get_bill_with_details(db, bill_id).await? else {
+        return Ok(None);
+    };
+
+    let tenant = tenant_repo::get_by_id(db, details.bill.tenant_id).await?;
+    let room_id = details
+        .reading
+        .as_ref()
+        .map(|r| r.room_id)
+        .or(tenant.as_ref().map(|t| t.room_id));
+    let room = match room_id {
+        Some(id) => room_repo::get_by_id(db, id).await?,
+        None => None,
+    };
+    let property = match &room {
+        Some(room) => property_repo::get_by_id(db, room.property_id).await?,
+        None => None,
+    };
+
+    Ok(Some(BillDocumentData { details, tenant, room, property }))
+}
+
+struct PdfWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl PdfWriter<'_> {
+    /// Continue on a new page once the next line would fall into the bottom margin
+    fn ensure_room(&mut self) {
+        if self.y < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn text(&mut self, text: &str, size: f32, bold: bool) {
+        self.ensure_room();
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(MARGIN), Mm(self.y), font);
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn row(&mut self, label: &str, value: &str, bold: bool) {
+        self.ensure_room();
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(label, 11.0, Mm(MARGIN), Mm(self.y), font);
+        self.layer.use_text(value, 11.0, Mm(PAGE_WIDTH - MARGIN - 50.0), Mm(self.y), font);
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn rule(&mut self) {
+        self.ensure_room();
+        let line = Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y + 4.0)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y + 4.0)), false),
+            ],
+            is_closed: false,
+        };
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(line);
+        self.y -= LINE_HEIGHT / 2.0;
+    }
+
+    fn gap(&mut self) {
+        self.y -= LINE_HEIGHT / 2.0;
+    }
+}
+
+/// Render a bill invoice or official receipt as PDF bytes, headed by the property's
+/// name or, without one, the configured brand
+pub fn render_bill_pdf(kind: DocumentKind, data: &BillDocumentData, branding: &Branding) -> Result<Vec<u8>, DocumentError> {
+    let bill = &data.details.bill;
+    let title = format!("{} #{}", kind.title(), bill.id);
+    let balance = data.balance().map_err(|e| DocumentError::Render(e.to_string()))?;
+
+    let (doc, page, layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    let regular = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| DocumentError::Render(e.to_string()))?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| DocumentError::Render(e.to_string()))?;
+
+    let mut w = PdfWriter {
+        doc: &doc,
+        layer: doc.get_page(page).get_layer(layer),
+        regular,
+        bold,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    w.text(&Branding::for_property(branding, data.property.as_ref()).name, 18.0, true);
+    w.text(&title, 14.0, true);
+    w.text(&format!("Issued: {}", Utc::now().format("%Y-%m-%d")), 10.0, false);
+    w.gap();
+
+    let tenant_name = data.tenant.as_ref().map(|t| t.name.as_str()).unwrap_or("-");
+    let room_name = data.room.as_ref().map(|r| r.name.as_str()).unwrap_or("-");
+    w.row("Tenant", tenant_name, false);
+    w.row("Room", room_name, false);
+    if let Some(reading) = &data.details.reading {
+        w.row("Period", &reading.created_at.format("%B %Y").to_string(), false);
+    }
+    w.gap();
+
+    if let Some(reading) = &data.details.reading {
+        w.text("Electricity", 12.0, true);
+        w.row("Previous reading", &reading.prev_reading.to_string(), false);
+        w.row("Current reading", &reading.curr_reading.to_string(), false);
+        w.row("Consumption", &format!("{} kWh", reading.consumption), false);
+        if let Some(rate) = data.electric_rate() {
+            w.row("Rate", &format!("{} {:.2} / kWh", bill.currency(), rate), false);
+        }
+        w.gap();
+    }
+
+    w.text("Charges", 12.0, true);
+    w.row("Room rent", &format_amount(data.money(bill.room_charges)), false);
+    w.row("Electricity", &format_amount(data.money(bill.electric_charges)), false);
+    for charge in &data.details.additional_charges {
+        w.row(&charge.description, &format_amount(data.money(charge.amount)), false);
+    }
+    for discount in &data.details.discounts {
+        let label = if discount.kind == bill_discount::KIND_PERCENT {
+            format!("Discount {}%: {}", money::to_decimal_string(discount.value), discount.reason)
+        } else {
+            format!("Discount: {}", discount.reason)
+        };
+        w.row(&label, &format_amount(data.money(-discount.amount)), false);
+    }
+    w.rule();
+    w.row("Total", &format_amount(bill.total()), true);
+    for note in &data.details.notes {
+        let label = format!("{} note: {}", if note.kind == bill_note::KIND_CREDIT { "Credit" } else { "Debit" }, note.reason);
+        w.row(&label, &format_amount(data.money(note.signed_amount())), false);
+    }
+    w.row("Payments", &format_amount(data.amount_paid()), false);
+    w.row("Balance", &format_amount(balance), true);
+
+    if kind == DocumentKind::Receipt {
+        w.gap();
+        w.text(
+            &format!("Received with thanks the amount of {}.", format_amount(data.amount_paid())),
+            11.0,
+            false,
+        );
+    }
+
+    doc.save_to_bytes().map_err(|e| DocumentError::Render(e.to_string()))
+}
+
+/// File name of a bill document. Edits bump the bill's version and notes are only ever added,
+/// so the name changes exactly when what the document prints does
+fn document_file_name(kind: DocumentKind, details: &BillWithChargesAndReading) -> String {
+    format!("{}-{}-v{}-n{}.pdf", kind.slug(), details.bill.id, details.bill.version, details.notes.len())
+}
+
+/// Render a bill document, store it in R2 and record it as a bill attachment. A bill that has
+/// not changed since its last document of this kind gets that document back instead
+#[instrument(skip_all)]
+pub async fn generate_bill_document(
+    db: &DatabaseConnection,
+    r2: &R2Config,
+    bill_id: i32,
+    kind: DocumentKind,
+    branding: &Branding,
+    generated_by: Option<String>,
+) -> Result<BillDocument, DocumentError> {
+    let data = load_document_data(db, bill_id)
+        .await?
+        .ok_or(DocumentError::BillNotFound)?;
+
+    if kind == DocumentKind::Receipt && !data.details.bill.paid {
+        return Err(DocumentError::BillNotPaid);
+    }
+
+    let file_name = document_file_name(kind, &data.details);
+    if let Some(existing) = data.details.attachments.iter().find(|a| a.file_name.as_deref() == Some(file_name.as_str())) {
+        info!(bill_id, kind = kind.slug(), attachment_id = existing.id, "bill document is up to date");
+        return Ok(BillDocument { attachment: existing.clone(), created: false });
+    }
+
+    let bytes = render_bill_pdf(kind, &data, branding)?;
+
+    let result = attachment_service::upload_attachment(
+        db,
+        r2,
+        NewAttachment {
+            object_key: attachment_service::object_key(&format!("documents/bills/{}", bill_id), "pdf"),
+            owner_type: OWNER_BILL.to_string(),
+            owner_id: bill_id,
+            file_name: Some(file_name),
+            content_type: "application/pdf".to_string(),
+            bytes: Bytes::from(bytes),
+        },
+        generated_by,
+    )
+    .await
+    .map(|attachment| BillDocument { attachment, created: true })
+    .map_err(DocumentError::Attachment);
+
+    match &result {
+        Ok(doc) => info!(bill_id, kind = kind.slug(), attachment_id = doc.attachment.id, "stored bill document"),
+        Err(err) => error!(bill_id, kind = kind.slug(), error = ?err, "error generating bill document"),
+    }
+
+    result
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{additional_charge, bill, electricity_reading};
+    use crate::money::Currency;
+
+    fn sample_data(paid: bool) -> BillDocumentData {
+        let now = Utc::now().naive_utc();
+        BillDocumentData {
+            details: BillWithChargesAndReading {
+                bill: bill::Model {
+                    id: 7,
+                    reading_id: 3,
+                    tenant_id: 2,
+                    room_charges: 500000,
+                    electric_charges: 120000,
+                    discount_amount: 0,
+                    total_amount: 650000,
+                    currency: "PHP".into(),
+                    receipt_url: None,
+                    paid,
+                    amount_paid: paid.then_some(650000),
+                    status: if paid { bill::STATUS_PAID } else { bill::STATUS_ISSUED }.into(),
+                    issued_at: Some(now),
+                    paid_at: None,
+                    created_at: now,
+                    updated_at: now,
+                    deleted_at: None,
+                    version: 1,
+                },
+                additional_charges: vec![additional_charge::Model {
+                    id: 1,
+                    bill_id: 7,
+                    amount: 30000,
+                    description: "Water".into(),
+                    charge_type_id: None,
+                    created_at: now,
+                    updated_at: now,
+                }],
+                discounts: vec![],
+                reading: Some(electricity_reading::Model {
+                    id: 3,
+                    tenant_id: 2,
+                    room_id: 1,
+                    prev_reading: 100,
+                    curr_reading: 200,
+                    consumption: 100,
+                    flagged: false,
+                    flag_reason: None,
+                    created_at: now,
+                    updated_at: now,
+                    deleted_at: None,
+                }),
+                reading_photos: vec![],
+                attachments: vec![],
+                notes: vec![],
+            },
+            tenant: None,
+            room: None,
+            property: None,
+        }
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let php = |minor| Money::new(minor, Currency::PHP);
+        assert_eq!(format_amount(php(0)), "PHP 0.00");
+        assert_eq!(format_amount(php(123456705)), "PHP 1,234,567.05");
+        assert_eq!(format_amount(php(-50050)), "-PHP 500.50");
+        assert_eq!(format_amount(Money::new(1999, Currency::parse("USD").unwrap())), "USD 19.99");
+    }
+
+    #[test]
+    fn test_balance_and_rate() {
+        let unpaid = sample_data(false);
+        assert_eq!(unpaid.balance().unwrap().minor(), 650000);
+        assert_eq!(unpaid.electric_rate(), Some(12.0));
+
+        let paid = sample_data(true);
+        assert_eq!(paid.amount_paid().minor(), 650000);
+        assert_eq!(paid.balance().unwrap().minor(), 0);
+    }
+
+    fn page_count(pdf: &[u8]) -> usize {
+        pdf.windows(b"/Type/Page/".len()).filter(|w| *w == b"/Type/Page/").count()
+    }
+
+    #[test]
+    fn test_render_bill_pdf() {
+        let bytes = render_bill_pdf(DocumentKind::Invoice, &sample_data(false), &Branding::default()).unwrap();
+        assert!(bytes.starts_with(b"%PDF-"));
+        assert_eq!(page_count(&bytes), 1);
+    }
+
+    #[test]
+    fn test_long_bills_continue_on_new_pages() {
+        let mut data = sample_data(false);
+        let charge = data.details.additional_charges[0].clone();
+        data.details.additional_charges = (0..80)
+            .map(|i| additional_charge::Model { id: i, description: format!("Charge {}", i), ..charge.clone() })
+            .collect();
+        let bytes = render_bill_pdf(DocumentKind::Invoice, &data, &Branding::default()).unwrap();
+        assert!(page_count(&bytes) >= 3);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_bill_gets_its_document_back() {
+        use crate::repository::attachment_repo;
+        use crate::services::bill_service::{BillInput, BillNoteInput};
+        use crate::services::r2_service;
+        use crate::test_utils::{get_test_db, reset_table, seed_reading, test_config};
+        use sea_orm::Set;
+
+        let db = get_test_db().await;
+        reset_table(&db, "attachment").await;
+        let (_, tenant, reading) = seed_reading(&db).await;
+        let input = BillInput {
+            tenant_id: tenant.id,
+            reading_id: reading.id,
+            currency: Currency::PHP,
+            room_charges: 100_000,
+            electric_charges: 0,
+            additional_charges: vec![],
+            discounts: vec![],
+            receipt_url: None,
+        };
+        let bill = bill_service::create_bill(&db, "admin:root", input).await.unwrap().bill;
+        let issued = bill_service::issue_bill(&db, "admin:root", bill.id, None).await.unwrap();
+
+        let file_name = document_file_name(DocumentKind::Invoice, &issued);
+        let earlier = attachment_repo::create(&db, attachment::ActiveModel {
+            object_key: Set(format!("documents/bills/{}/1.pdf", bill.id)),
+            owner_type: Set(OWNER_BILL.into()),
+            owner_id: Set(bill.id),
+            file_name: Set(Some(file_name.clone())),
+            content_type: Set("application/pdf".into()),
+            size_bytes: Set(10),
+            checksum: Set("abc".into()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        // Found without touching R2
+        let r2 = r2_service::init_r2(&test_config().r2).await;
+        let doc = generate_bill_document(&db, &r2, bill.id, DocumentKind::Invoice, &Branding::default(), None)
+            .await
+            .unwrap();
+        assert!(!doc.created);
+        assert_eq!(doc.attachment, earlier);
+
+        // A note changes what the invoice prints, so it needs a new one
+        let note = BillNoteInput { kind: bill_note::KIND_CREDIT.into(), amount: 1_000, reason: "Goodwill".into() };
+        let noted = bill_service::add_bill_note(&db, "admin:root", bill.id, note).await.unwrap();
+        assert_ne!(document_file_name(DocumentKind::Invoice, &noted), file_name);
+        assert_ne!(document_file_name(DocumentKind::Receipt, &issued), file_name);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::entities::electricity_reading;
-use crate::repository::electricity_reading_repo;
-use sea_orm::{ActiveValue::Set, DatabaseConnection, DbErr};
+use crate::repository::electricity_reading_repo::{self, ReadingFilter};
+use crate::services::webhook_service::{self, EVENT_READING_CREATED};
+use crate::services::{audit_service, consumption_service, map_txn_err};
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use tracing::{debug, error, info, instrument, warn};
 
 fn value_or_zero(v: sea_orm::ActiveValue<i32>) -> i32 {
     if let sea_orm::ActiveValue::Set(x) = v { x } else { 0 }
@@ -10,77 +13,135 @@ pub fn calculate_consumption(prev: sea_orm::ActiveValue<i32>, curr: sea_orm::Act
     value_or_zero(curr) - value_or_zero(prev)
 }
 
-/// GET all readings
-pub async fn get_all_readings(db: &DatabaseConnection) -> Result<Vec<electricity_reading::Model>, DbErr> {
-    let result = electricity_reading_repo::get_all(db).await;
+/// GET all readings matching `filter`
+#[instrument(skip_all)]
+pub async fn get_all_readings(
+    db: &DatabaseConnection,
+    filter: &ReadingFilter,
+) -> Result<Vec<electricity_reading::Model>, DbErr> {
+    let result = electricity_reading_repo::get_filtered(db, filter).await;
     if let Ok(list) = &result {
-        println!("✅ get_all_readings: fetched {} readings", list.len());
+        debug!(count = list.len(), "fetched readings");
     } else if let Err(err) = &result {
-        eprintln!("❌ get_all_readings: error: {:?}", err);
+        error!(error = ?err, "error fetching readings");
     }
     result
 }
 
 /// GET reading by ID
+#[instrument(skip_all)]
 pub async fn get_reading_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<electricity_reading::Model>, DbErr> {
     let result = electricity_reading_repo::get_by_id(db, id).await;
     match &result {
-        Ok(Some(r)) => println!("✅ get_reading_by_id: found id={}", r.id),
-        Ok(None) => println!("⚠️ get_reading_by_id: id={} not found", id),
-        Err(err) => eprintln!("❌ get_reading_by_id: error id={}: {:?}", id, err),
+        Ok(Some(r)) => debug!(reading_id = r.id, "found reading"),
+        Ok(None) => warn!(reading_id = id, "reading not found"),
+        Err(err) => error!(reading_id = id, error = ?err, "error fetching reading"),
     }
     result
 }
 
+/// Insert a reading, flag it, audit it and announce it, inside the caller's transaction
+pub async fn insert_reading<C>(
+    conn: &C,
+    actor: &str,
+    mut item: electricity_reading::ActiveModel,
+) -> Result<electricity_reading::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    item.consumption = Set(calculate_consumption(item.prev_reading.clone(), item.curr_reading.clone()));
+    let reading = electricity_reading_repo::create(conn, item).await?;
+    let reading = consumption_service::flag_reading(conn, reading).await?;
+    audit_service::record_create(conn, actor, "electricity_reading", reading.id, &reading).await?;
+    webhook_service::enqueue(conn, EVENT_READING_CREATED, &reading).await?;
+    Ok(reading)
+}
+
 /// CREATE reading
+#[instrument(skip_all)]
 pub async fn create_reading(
     db: &DatabaseConnection,
-    mut item: electricity_reading::ActiveModel,
+    actor: &str,
+    item: electricity_reading::ActiveModel,
 ) -> Result<electricity_reading::Model, DbErr> {
-    item.consumption = Set(calculate_consumption(item.prev_reading.clone(), item.curr_reading.clone()));
-
-    let result = electricity_reading_repo::create(db, item).await;
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, electricity_reading::Model, DbErr>(|txn| {
+            Box::pin(async move { insert_reading(txn, &actor, item).await })
+        })
+        .await,
+    );
 
     if let Ok(ref r) = result {
-        println!("✅ create_reading: created id={}", r.id);
+        info!(reading_id = r.id, "created reading");
     } else if let Err(err) = &result {
-        eprintln!("❌ create_reading: error: {:?}", err);
+        error!(error = ?err, "error creating reading");
     }
 
     result
 }
 
 /// UPDATE reading
+#[instrument(skip_all)]
 pub async fn update_reading(
     db: &DatabaseConnection,
+    actor: &str,
     id: i32,
     mut item: electricity_reading::ActiveModel,
 ) -> Result<electricity_reading::Model, DbErr> {
     item.id = Set(id);
     item.consumption = Set(calculate_consumption(item.prev_reading.clone(), item.curr_reading.clone()));
 
-    let result = electricity_reading_repo::update(db, item).await;
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, electricity_reading::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = electricity_reading_repo::get_by_id(txn, id)
+                    .await?
+                    .ok_or(DbErr::RecordNotUpdated)?;
+                let updated = electricity_reading_repo::update(txn, item).await?;
+                let updated = consumption_service::flag_reading(txn, updated).await?;
+                audit_service::record_update(txn, &actor, "electricity_reading", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
 
     if let Ok(ref r) = result {
-        println!("✅ update_reading: updated id={}", r.id);
+        info!(reading_id = r.id, "updated reading");
     } else if let Err(err) = &result {
-        eprintln!("❌ update_reading: error id={}: {:?}", id, err);
+        error!(reading_id = id, error = ?err, "error updating reading");
     }
 
     result
 }
 
 /// DELETE reading
+#[instrument(skip_all)]
 pub async fn delete_reading(
     db: &DatabaseConnection,
+    actor: &str,
     id: i32,
 ) -> Result<Option<electricity_reading::Model>, DbErr> {
-    let result = electricity_reading_repo::delete(db, id).await;
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<electricity_reading::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let deleted = electricity_reading_repo::delete(txn, id).await?;
+                if let Some(r) = &deleted {
+                    audit_service::record_delete(txn, &actor, "electricity_reading", id, r).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
 
     match &result {
-        Ok(Some(r)) => println!("✅ delete_reading: deleted id={}", r.id),
-        Ok(None) => println!("⚠️ delete_reading: id={} not found", id),
-        Err(err) => eprintln!("❌ delete_reading: error id={}: {:?}", id, err),
+        Ok(Some(r)) => info!(reading_id = r.id, "deleted reading"),
+        Ok(None) => warn!(reading_id = id, "reading not found"),
+        Err(err) => error!(reading_id = id, error = ?err, "error deleting reading"),
     }
 
     result
@@ -0,0 +1,152 @@
+use crate::config::{SmtpSecurity, SmtpSettings};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Outgoing email over SMTP
+#[derive(Clone)]
+pub struct EmailSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailSender {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, String> {
+        let builder = match settings.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                    .map_err(|e| e.to_string())?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+                .map_err(|e| e.to_string())?,
+        };
+
+        let builder = match (&settings.username, &settings.password) {
+            (Some(user), Some(pass)) => {
+                builder.credentials(Credentials::new(user.clone(), pass.expose().to_string()))
+            }
+            _ => builder,
+        };
+
+        Ok(EmailSender {
+            transport: builder.port(settings.port).build(),
+            from: settings.from.parse().map_err(|e| format!("invalid SMTP_FROM: {}", e))?,
+        })
+    }
+
+    /// Send a plain text email, with an optional HTML alternative
+    pub async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        text: String,
+        html: Option<String>,
+    ) -> Result<(), String> {
+        let to: Mailbox = to.parse().map_err(|e| format!("invalid recipient: {}", e))?;
+        let builder = Message::builder().from(self.from.clone()).to(to).subject(subject);
+
+        let message = match html {
+            Some(html) => builder.multipart(MultiPart::alternative_plain_html(text, html)),
+            None => builder.singlepart(SinglePart::plain(text)),
+        }
+        .map_err(|e| e.to_string())?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Minimal SMTP sink that accepts one message and returns its DATA section
+    async fn smtp_sink() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let mut data = String::new();
+            let mut in_data = false;
+
+            write.write_all(b"220 sink ready\r\n").await.unwrap();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        write.write_all(b"250 queued\r\n").await.unwrap();
+                    } else {
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    continue;
+                }
+
+                let cmd = line.to_ascii_uppercase();
+                if cmd.starts_with("EHLO") {
+                    write.write_all(b"250 sink\r\n").await.unwrap();
+                } else if cmd.starts_with("DATA") {
+                    in_data = true;
+                    write.write_all(b"354 go ahead\r\n").await.unwrap();
+                } else if cmd.starts_with("QUIT") {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    write.write_all(b"250 ok\r\n").await.unwrap();
+                }
+            }
+            data
+        });
+
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_send_to_local_sink() {
+        let (port, sink) = smtp_sink().await;
+        let sender = EmailSender::new(&SmtpSettings {
+            host: "127.0.0.1".into(),
+            port,
+            username: None,
+            password: None,
+            from: "Billing <billing@example.com>".into(),
+            security: SmtpSecurity::None,
+        })
+        .unwrap();
+
+        sender
+            .send("tenant@example.com", "New bill #1", "Your bill is ready".into(), None)
+            .await
+            .unwrap();
+        drop(sender);
+
+        let data = sink.await.unwrap();
+        assert!(data.contains("Subject: New bill #1"));
+        assert!(data.contains("Your bill is ready"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_from_address() {
+        let result = EmailSender::new(&SmtpSettings {
+            host: "127.0.0.1".into(),
+            port: 25,
+            username: None,
+            password: None,
+            from: "not an address".into(),
+            security: SmtpSecurity::None,
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use crate::money;
+use crate::repository::bill_repo::{self, BillExportRow, BillFilter};
+use crate::repository::electricity_reading_repo::{self, ReadingExportRow, ReadingFilter};
+use crate::repository::tenant_repo::{self, TenantExportRow, TenantFilter};
+use axum::body::Bytes;
+use chrono::NaiveDateTime;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use sea_orm::{DatabaseConnection, DbErr};
+use std::fmt;
+use tokio::sync::mpsc;
+use tracing::{error, info, instrument};
+
+/// Rows per CSV chunk sent to the client
+const CSV_CHUNK_ROWS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<ExportFormat> {
+        match value {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// What to export, with the same filters as the matching list endpoint
+#[derive(Debug, Clone)]
+pub enum Export {
+    Bills(BillFilter),
+    Payments(BillFilter),
+    Readings(ReadingFilter),
+    Tenants(TenantFilter),
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Db(DbErr),
+    Csv(csv::Error),
+    Xlsx(XlsxError),
+    /// The client went away mid-download
+    Disconnected,
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Db(err) => write!(f, "database error: {}", err),
+            ExportError::Csv(err) => write!(f, "csv error: {}", err),
+            ExportError::Xlsx(err) => write!(f, "xlsx error: {}", err),
+            ExportError::Disconnected => write!(f, "client disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<DbErr> for ExportError {
+    fn from(err: DbErr) -> Self {
+        ExportError::Db(err)
+    }
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(err: csv::Error) -> Self {
+        ExportError::Csv(err)
+    }
+}
+
+impl From<XlsxError> for ExportError {
+    fn from(err: XlsxError) -> Self {
+        ExportError::Xlsx(err)
+    }
+}
+
+/// One spreadsheet cell; amounts stay numbers in XLSX and become decimal strings in CSV
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    Amount(i64),
+    DateTime(NaiveDateTime),
+    Bool(bool),
+    Empty,
+}
+
+impl Cell {
+    fn text(value: Option<String>) -> Cell {
+        value.map_or(Cell::Empty, Cell::Text)
+    }
+
+    fn date(value: Option<NaiveDateTime>) -> Cell {
+        value.map_or(Cell::Empty, Cell::DateTime)
+    }
+
+    fn to_csv(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Int(n) => n.to_string(),
+            Cell::Amount(minor) => money::to_decimal_string(*minor),
+            Cell::DateTime(at) => at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            Cell::Bool(b) => b.to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+const BILL_HEADERS: &[&str] = &[
+    "bill_id", "tenant_id", "tenant", "room", "status", "currency", "issued_at", "paid_at",
+    "room_charges", "electric_charges", "additional_charges", "charges", "discounts", "total", "receipt_url",
+];
+const READING_HEADERS: &[&str] = &[
+    "reading_id", "tenant_id", "tenant", "room_id", "room", "prev_reading", "curr_reading",
+    "consumption_kwh", "flagged", "flag_reason", "taken_at",
+];
+const TENANT_HEADERS: &[&str] = &[
+    "tenant_id", "name", "room_id", "room", "is_active", "join_date", "email", "phone_number",
+    "notification_channels",
+];
+
+/// Additional charges as `Water 150.00; Internet 500.00`, and their sum
+fn flatten_charges(charges: Option<&serde_json::Value>) -> (String, i64) {
+    let lines: Vec<(String, i64)> = charges
+        .and_then(|c| c.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .map(|c| {
+                    let description = c["description"].as_str().unwrap_or_default().to_string();
+                    (description, c["amount"].as_i64().unwrap_or(0))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let text = lines
+        .iter()
+        .map(|(description, amount)| format!("{} {}", description, money::to_decimal_string(*amount)))
+        .collect::<Vec<_>>()
+        .join("; ");
+    (text, lines.iter().map(|(_, amount)| amount).sum())
+}
+
+pub fn bill_cells(row: BillExportRow) -> Vec<Cell> {
+    let (charges, additional) = flatten_charges(row.charges.as_ref());
+    vec![
+        Cell::Int(row.id as i64),
+        Cell::Int(row.tenant_id as i64),
+        Cell::text(row.tenant_name),
+        Cell::text(row.room_name),
+        Cell::Text(row.status),
+        Cell::Text(row.currency),
+        Cell::date(row.issued_at),
+        Cell::date(row.paid_at),
+        Cell::Amount(row.room_charges),
+        Cell::Amount(row.electric_charges),
+        Cell::Amount(additional),
+        Cell::Text(charges),
+        Cell::Amount(row.discount_amount),
+        Cell::Amount(row.total_amount),
+        Cell::text(row.receipt_url),
+    ]
+}
+
+fn reading_cells(row: ReadingExportRow) -> Vec<Cell> {
+    vec![
+        Cell::Int(row.id as i64),
+        Cell::Int(row.tenant_id as i64),
+        Cell::text(row.tenant_name),
+        Cell::Int(row.room_id as i64),
+        Cell::text(row.room_name),
+        Cell::Int(row.prev_reading as i64),
+        Cell::Int(row.curr_reading as i64),
+        Cell::Int(row.consumption as i64),
+        Cell::Bool(row.flagged),
+        Cell::text(row.flag_reason),
+        Cell::DateTime(row.created_at),
+    ]
+}
+
+fn tenant_cells(row: TenantExportRow) -> Vec<Cell> {
+    vec![
+        Cell::Int(row.id as i64),
+        Cell::Text(row.name),
+        Cell::Int(row.room_id as i64),
+        Cell::text(row.room_name),
+        Cell::Bool(row.is_active),
+        Cell::DateTime(row.join_date),
+        Cell::text(row.email),
+        Cell::text(row.phone_number),
+        Cell::Text(row.notification_channels),
+    ]
+}
+
+impl Export {
+    /// Used for the file and sheet names
+    pub fn name(&self) -> &'static str {
+        match self {
+            Export::Bills(_) => "bills",
+            Export::Payments(_) => "payments",
+            Export::Readings(_) => "readings",
+            Export::Tenants(_) => "tenants",
+        }
+    }
+
+    fn headers(&self) -> &'static [&'static str] {
+        match self {
+            Export::Bills(_) | Export::Payments(_) => BILL_HEADERS,
+            Export::Readings(_) => READING_HEADERS,
+            Export::Tenants(_) => TENANT_HEADERS,
+        }
+    }
+
+    /// Rows as the database hands them over, never the whole table at once
+    async fn rows<'a>(&self, db: &'a DatabaseConnection) -> Result<BoxStream<'a, Result<Vec<Cell>, DbErr>>, DbErr> {
+        Ok(match self {
+            Export::Bills(filter) => {
+                bill_repo::export_rows(filter).stream(db).await?.map(|r| r.map(bill_cells)).boxed()
+            }
+            Export::Payments(filter) => {
+                bill_repo::payment_rows(filter).stream(db).await?.map(|r| r.map(bill_cells)).boxed()
+            }
+            Export::Readings(filter) => {
+                electricity_reading_repo::export_rows(filter).stream(db).await?.map(|r| r.map(reading_cells)).boxed()
+            }
+            Export::Tenants(filter) => {
+                tenant_repo::export_rows(filter).stream(db).await?.map(|r| r.map(tenant_cells)).boxed()
+            }
+        })
+    }
+}
+
+fn csv_chunk<I>(records: I) -> Result<Vec<u8>, ExportError>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    for record in records {
+        writer.write_record(&record)?;
+    }
+    writer.into_inner().map_err(|e| ExportError::Csv(e.into_error().into()))
+}
+
+async fn write_csv(
+    db: &DatabaseConnection,
+    export: &Export,
+    tx: &mpsc::Sender<Result<Bytes, std::io::Error>>,
+) -> Result<usize, ExportError> {
+    let mut rows = export.rows(db).await?;
+    let header = csv_chunk([export.headers().iter().map(|h| h.to_string()).collect()])?;
+    tx.send(Ok(header.into())).await.map_err(|_| ExportError::Disconnected)?;
+
+    let mut count = 0;
+    let mut pending = Vec::with_capacity(CSV_CHUNK_ROWS);
+    while let Some(row) = rows.next().await {
+        pending.push(row?.iter().map(Cell::to_csv).collect());
+        count += 1;
+        if pending.len() == CSV_CHUNK_ROWS {
+            let chunk = csv_chunk(pending.drain(..))?;
+            tx.send(Ok(chunk.into())).await.map_err(|_| ExportError::Disconnected)?;
+        }
+    }
+    if !pending.is_empty() {
+        let chunk = csv_chunk(pending)?;
+        tx.send(Ok(chunk.into())).await.map_err(|_| ExportError::Disconnected)?;
+    }
+    Ok(count)
+}
+
+/// CSV body that is written while rows arrive from the database. An error after the
+/// first chunk can only cut the download short, so it ends the stream with an error.
+#[instrument(skip_all)]
+pub fn stream_csv(db: DatabaseConnection, export: Export) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    let (tx, rx) = mpsc::channel(4);
+    tokio::spawn(async move {
+        match write_csv(&db, &export, &tx).await {
+            Ok(count) => info!(export = export.name(), rows = count, "exported CSV"),
+            Err(err) => {
+                error!(export = export.name(), error = %err, "CSV export failed");
+                let _ = tx.send(Err(std::io::Error::other(err.to_string()))).await;
+            }
+        }
+    });
+    stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) })
+}
+
+/// XLSX workbook with one sheet. Rows are streamed from the database into a
+/// constant-memory worksheet, which keeps them in a temp file until the zip is written.
+#[instrument(skip_all)]
+pub async fn build_xlsx(db: &DatabaseConnection, export: &Export) -> Result<Vec<u8>, ExportError> {
+    let bold = Format::new().set_bold();
+    let amount = Format::new().set_num_format("#,##0.00");
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet_with_constant_memory();
+    sheet.set_name(export.name())?;
+    for (col, header) in export.headers().iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, &bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    let mut rows = export.rows(db).await?;
+    let mut row_num: u32 = 0;
+    while let Some(row) = rows.next().await {
+        row_num += 1;
+        for (col, cell) in row?.into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(s) => sheet.write_string(row_num, col, s).map(|_| ())?,
+                Cell::Int(n) => sheet.write_number(row_num, col, n as f64).map(|_| ())?,
+                Cell::Amount(minor) => {
+                    sheet.write_number_with_format(row_num, col, money::to_major_f64(minor), &amount).map(|_| ())?
+                }
+                Cell::DateTime(at) => sheet.write_datetime_with_format(row_num, col, at, &datetime).map(|_| ())?,
+                Cell::Bool(b) => sheet.write_boolean(row_num, col, b).map(|_| ())?,
+                Cell::Empty => {}
+            }
+        }
+    }
+
+    let buffer = workbook.save_to_buffer()?;
+    info!(export = export.name(), rows = row_num, bytes = buffer.len(), "exported XLSX");
+    Ok(buffer)
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_charges() {
+        let charges = json!([
+            { "description": "Water", "amount": 15000 },
+            { "description": "Internet", "amount": 50050 }
+        ]);
+        assert_eq!(flatten_charges(Some(&charges)), ("Water 150.00; Internet 500.50".to_string(), 65050));
+        assert_eq!(flatten_charges(None), (String::new(), 0));
+    }
+
+    #[test]
+    fn test_csv_chunk_quotes_and_formats_cells() {
+        let cells = [
+            Cell::Text("Cruz, Ana".into()),
+            Cell::Amount(123450),
+            Cell::DateTime(chrono::NaiveDate::from_ymd_opt(2026, 10, 1).unwrap().and_hms_opt(8, 30, 0).unwrap()),
+            Cell::Bool(true),
+            Cell::Empty,
+        ];
+        let chunk = csv_chunk([cells.iter().map(Cell::to_csv).collect()]).unwrap();
+        assert_eq!(String::from_utf8(chunk).unwrap(), "\"Cruz, Ana\",1234.50,2026-10-01 08:30:00,true,\n");
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!(ExportFormat::parse("csv"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("xlsx").map(|f| f.extension()), Some("xlsx"));
+        assert_eq!(ExportFormat::parse("pdf"), None);
+    }
+}
@@ -0,0 +1,525 @@
+use crate::entities::{electricity_reading, room, tenant};
+use crate::money::{self, Currency};
+use crate::repository::{electricity_reading_repo, room_repo, tenant_repo};
+use crate::services::{electricity_reading_service, room_service, tenant_service};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use csv::StringRecord;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tracing::{info, instrument, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportKind {
+    Rooms,
+    Tenants,
+    Readings,
+}
+
+impl ImportKind {
+    pub fn parse(value: &str) -> Option<ImportKind> {
+        match value {
+            "rooms" => Some(ImportKind::Rooms),
+            "tenants" => Some(ImportKind::Tenants),
+            "readings" => Some(ImportKind::Readings),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportKind::Rooms => "rooms",
+            ImportKind::Tenants => "tenants",
+            ImportKind::Readings => "readings",
+        }
+    }
+
+    /// Columns every file must have; others are optional
+    pub fn required_columns(&self) -> &'static [&'static str] {
+        match self {
+            ImportKind::Rooms => &["name", "rent"],
+            ImportKind::Tenants => &["name", "room", "join_date"],
+            ImportKind::Readings => &["tenant", "curr_reading"],
+        }
+    }
+}
+
+/// A problem with one line of the file; `line` counts the header as line 1
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RowError {
+    pub line: u64,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub kind: &'static str,
+    pub dry_run: bool,
+    /// Data rows read, header excluded
+    pub rows: usize,
+    /// Rows written, or that would be written on a clean dry run
+    pub created: usize,
+    /// Only true when the rows are saved; any error or a dry run rolls everything back
+    pub committed: bool,
+    pub errors: Vec<RowError>,
+}
+
+/// One data row, looked up by header name
+struct Row<'a> {
+    line: u64,
+    headers: &'a HashMap<String, usize>,
+    record: StringRecord,
+}
+
+impl Row<'_> {
+    /// The trimmed value, `None` when the column is missing or blank
+    fn get(&self, column: &str) -> Option<&str> {
+        let index = *self.headers.get(column)?;
+        self.record.get(index).map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    fn error(&self, column: &str, message: impl Into<String>) -> RowError {
+        RowError { line: self.line, column: Some(column.to_string()), message: message.into() }
+    }
+
+    fn required(&self, column: &str, errors: &mut Vec<RowError>) -> Option<String> {
+        let value = self.get(column).map(str::to_string);
+        if value.is_none() {
+            errors.push(self.error(column, "is required"));
+        }
+        value
+    }
+
+    fn parse<T: FromStr>(&self, column: &str, errors: &mut Vec<RowError>) -> Option<T> {
+        let value = self.get(column)?;
+        let parsed = value.parse().ok();
+        if parsed.is_none() {
+            errors.push(self.error(column, format!("'{}' is not a valid number", value)));
+        }
+        parsed
+    }
+
+    fn flag(&self, column: &str, errors: &mut Vec<RowError>) -> Option<bool> {
+        let value = self.get(column)?;
+        match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(true),
+            "false" | "no" | "n" | "0" => Some(false),
+            _ => {
+                errors.push(self.error(column, format!("'{}' is not true or false", value)));
+                None
+            }
+        }
+    }
+
+    fn datetime(&self, column: &str, errors: &mut Vec<RowError>) -> Option<NaiveDateTime> {
+        let value = self.get(column)?;
+        let parsed = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+            .ok()
+            .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0));
+        if parsed.is_none() {
+            errors.push(self.error(column, format!("'{}' is not a date (YYYY-MM-DD)", value)));
+        }
+        parsed
+    }
+}
+
+/// Split the file into rows, reporting unreadable lines and missing columns
+fn read_rows(
+    data: &[u8],
+    kind: ImportKind,
+    headers: &mut HashMap<String, usize>,
+    errors: &mut Vec<RowError>,
+) -> Vec<(u64, StringRecord)> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+    match reader.headers() {
+        Ok(record) => {
+            for (index, name) in record.iter().enumerate() {
+                headers.insert(name.trim().to_ascii_lowercase(), index);
+            }
+        }
+        Err(err) => {
+            errors.push(RowError { line: 1, column: None, message: format!("unreadable header: {}", err) });
+            return Vec::new();
+        }
+    }
+    for column in kind.required_columns() {
+        if !headers.contains_key(*column) {
+            errors.push(RowError { line: 1, column: Some(column.to_string()), message: "column is missing".into() });
+        }
+    }
+
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        match result {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or_default();
+                if record.iter().any(|v| !v.trim().is_empty()) {
+                    rows.push((line, record));
+                }
+            }
+            Err(err) => {
+                let line = err.position().map(|p| p.line()).unwrap_or_default();
+                errors.push(RowError { line, column: None, message: err.to_string() });
+            }
+        }
+    }
+    rows
+}
+
+/// Rows checked and ready to insert, each with its line for error reporting
+enum Prepared {
+    Rooms(Vec<(u64, room::ActiveModel)>),
+    Tenants(Vec<(u64, tenant::ActiveModel)>),
+    Readings(Vec<(u64, electricity_reading::ActiveModel)>),
+}
+
+async fn prepare_rooms<C>(
+    conn: &C,
+    property_id: i32,
+    default_currency: Currency,
+    rows: &[Row<'_>],
+    errors: &mut Vec<RowError>,
+) -> Result<Prepared, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let names: Vec<String> = rows.iter().filter_map(|r| r.get("name").map(str::to_string)).collect();
+    let taken: HashSet<String> = room_repo::get_by_names(conn, property_id, &names).await?.into_iter().map(|r| r.name).collect();
+    let mut seen = HashSet::new();
+    let mut prepared = Vec::new();
+
+    for row in rows {
+        let before = errors.len();
+        let name = row.required("name", errors);
+        if let Some(name) = &name {
+            if taken.contains(name) {
+                errors.push(row.error("name", format!("room '{}' already exists", name)));
+            } else if !seen.insert(name.clone()) {
+                errors.push(row.error("name", format!("room '{}' appears more than once", name)));
+            }
+        }
+        let rent = row.required("rent", errors).and_then(|value| match money::parse_decimal(&value) {
+            Ok(minor) if minor >= 0 => Some(minor),
+            Ok(_) => {
+                errors.push(row.error("rent", "must not be negative"));
+                None
+            }
+            Err(err) => {
+                errors.push(row.error("rent", err.to_string()));
+                None
+            }
+        });
+        let currency = match row.get("currency") {
+            None => Some(default_currency),
+            Some(code) => {
+                let currency = Currency::parse(code);
+                if currency.is_none() {
+                    errors.push(row.error("currency", format!("unsupported currency '{}'", code)));
+                }
+                currency
+            }
+        };
+        let under_maintenance = row.flag("under_maintenance", errors);
+
+        if let (Some(name), Some(rent), Some(currency), true) = (name, rent, currency, errors.len() == before) {
+            prepared.push((
+                row.line,
+                room::ActiveModel {
+                    property_id: Set(property_id),
+                    name: Set(name),
+                    rent: Set(rent),
+                    currency: Set(currency.code().to_string()),
+                    under_maintenance: Set(under_maintenance.unwrap_or(false)),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    Ok(Prepared::Rooms(prepared))
+}
+
+async fn prepare_tenants<C>(
+    conn: &C,
+    property_id: i32,
+    rows: &[Row<'_>],
+    errors: &mut Vec<RowError>,
+) -> Result<Prepared, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let names: Vec<String> = rows.iter().filter_map(|r| r.get("name").map(str::to_string)).collect();
+    let taken: HashSet<String> = tenant_repo::get_by_names(conn, property_id, &names).await?.into_iter().map(|t| t.name).collect();
+    let room_names: Vec<String> = rows.iter().filter_map(|r| r.get("room").map(str::to_string)).collect();
+    let rooms: HashMap<String, room::Model> = room_repo::get_by_names(conn, property_id, &room_names)
+        .await?
+        .into_iter()
+        .filter(|r| r.deleted_at.is_none())
+        .map(|r| (r.name.clone(), r))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut prepared = Vec::new();
+
+    for row in rows {
+        let before = errors.len();
+        let name = row.required("name", errors);
+        if let Some(name) = &name {
+            if taken.contains(name) {
+                errors.push(row.error("name", format!("tenant '{}' already exists", name)));
+            } else if !seen.insert(name.clone()) {
+                errors.push(row.error("name", format!("tenant '{}' appears more than once", name)));
+            }
+        }
+        let room = row.required("room", errors).and_then(|room_name| {
+            let room = rooms.get(&room_name);
+            if room.is_none() {
+                errors.push(row.error("room", format!("no room named '{}'", room_name)));
+            }
+            room
+        });
+        let join_date = row.required("join_date", errors).and(row.datetime("join_date", errors));
+        let is_active = row.flag("is_active", errors);
+
+        if let (Some(name), Some(room), Some(join_date), true) = (name, room, join_date, errors.len() == before) {
+            prepared.push((
+                row.line,
+                tenant::ActiveModel {
+                    name: Set(name),
+                    room_id: Set(room.id),
+                    join_date: Set(join_date),
+                    is_active: Set(is_active.unwrap_or(true)),
+                    email: Set(row.get("email").map(str::to_string)),
+                    phone_number: Set(row.get("phone_number").map(str::to_string)),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    Ok(Prepared::Tenants(prepared))
+}
+
+async fn prepare_readings<C>(
+    conn: &C,
+    property_id: i32,
+    rows: &[Row<'_>],
+    errors: &mut Vec<RowError>,
+) -> Result<Prepared, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let names: Vec<String> = rows.iter().filter_map(|r| r.get("tenant").map(str::to_string)).collect();
+    let tenants: HashMap<String, tenant::Model> = tenant_repo::get_by_names(conn, property_id, &names)
+        .await?
+        .into_iter()
+        .filter(|t| t.deleted_at.is_none())
+        .map(|t| (t.name.clone(), t))
+        .collect();
+    let now = Utc::now().naive_utc();
+    // Last known meter value per room, so `prev_reading` can be left out
+    let mut last_reading: HashMap<i32, i32> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut prepared = Vec::new();
+
+    for row in rows {
+        let before = errors.len();
+        let tenant = row.required("tenant", errors).and_then(|name| {
+            let tenant = tenants.get(&name);
+            if tenant.is_none() {
+                errors.push(row.error("tenant", format!("no tenant named '{}'", name)));
+            } else if !seen.insert(name.clone()) {
+                errors.push(row.error("tenant", format!("tenant '{}' appears more than once", name)));
+            }
+            tenant
+        });
+        let curr = row.required("curr_reading", errors).and(row.parse::<i32>("curr_reading", errors));
+        let prev = row.parse::<i32>("prev_reading", errors);
+        let Some(tenant) = tenant else { continue };
+
+        let prev = match prev {
+            Some(prev) => Some(prev),
+            None if row.get("prev_reading").is_some() => None,
+            None => match last_reading.get(&tenant.room_id) {
+                Some(value) => Some(*value),
+                None => Some(
+                    electricity_reading_repo::get_recent_for_room(conn, tenant.room_id, now, 0, 1)
+                        .await?
+                        .first()
+                        .map(|r| r.curr_reading)
+                        .unwrap_or(0),
+                ),
+            },
+        };
+        if let (Some(prev), Some(curr)) = (prev, curr) {
+            if prev < 0 {
+                errors.push(row.error("prev_reading", "must not be negative"));
+            }
+            if curr < prev {
+                errors.push(row.error("curr_reading", format!("{} is below the previous reading {}", curr, prev)));
+            }
+            if errors.len() == before {
+                last_reading.insert(tenant.room_id, curr);
+                prepared.push((
+                    row.line,
+                    electricity_reading::ActiveModel {
+                        tenant_id: Set(tenant.id),
+                        room_id: Set(tenant.room_id),
+                        prev_reading: Set(prev),
+                        curr_reading: Set(curr),
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+    }
+    Ok(Prepared::Readings(prepared))
+}
+
+/// Insert prepared rows in order, stopping at the first database error
+async fn insert_all<C>(conn: &C, actor: &str, prepared: Prepared) -> Result<usize, RowError>
+where
+    C: ConnectionTrait,
+{
+    let failed = |line: u64, err: DbErr| RowError { line, column: None, message: err.to_string() };
+    let mut created = 0;
+    match prepared {
+        Prepared::Rooms(rows) => {
+            for (line, item) in rows {
+                room_service::insert_room(conn, actor, item).await.map_err(|e| failed(line, e))?;
+                created += 1;
+            }
+        }
+        Prepared::Tenants(rows) => {
+            for (line, item) in rows {
+                tenant_service::insert_tenant(conn, actor, item).await.map_err(|e| failed(line, e))?;
+                created += 1;
+            }
+        }
+        Prepared::Readings(rows) => {
+            for (line, item) in rows {
+                electricity_reading_service::insert_reading(conn, actor, item).await.map_err(|e| failed(line, e))?;
+                created += 1;
+            }
+        }
+    }
+    Ok(created)
+}
+
+/// Validate every row of a CSV file and insert them all in one transaction.
+/// Nothing is saved when any row fails or on a dry run, which still goes
+/// through the inserts so database constraints are checked too. Names are
+/// looked up, and rooms created, in `property_id`; rooms without a currency get
+/// `default_currency`.
+#[instrument(skip_all)]
+pub async fn import_csv(
+    db: &DatabaseConnection,
+    actor: &str,
+    property_id: i32,
+    default_currency: Currency,
+    kind: ImportKind,
+    data: &[u8],
+    dry_run: bool,
+) -> Result<ImportReport, DbErr> {
+    let mut errors = Vec::new();
+    let mut headers = HashMap::new();
+    let records = read_rows(data, kind, &mut headers, &mut errors);
+    let rows: Vec<Row> = records
+        .into_iter()
+        .map(|(line, record)| Row { line, headers: &headers, record })
+        .collect();
+
+    let txn = db.begin().await?;
+    let prepared = match kind {
+        ImportKind::Rooms => prepare_rooms(&txn, property_id, default_currency, &rows, &mut errors).await?,
+        ImportKind::Tenants => prepare_tenants(&txn, property_id, &rows, &mut errors).await?,
+        ImportKind::Readings => prepare_readings(&txn, property_id, &rows, &mut errors).await?,
+    };
+
+    let mut created = 0;
+    if errors.is_empty() {
+        match insert_all(&txn, actor, prepared).await {
+            Ok(count) => created = count,
+            Err(err) => errors.push(err),
+        }
+    }
+    errors.sort_by_key(|e| e.line);
+
+    let committed = errors.is_empty() && !dry_run;
+    if committed {
+        txn.commit().await?;
+    } else {
+        txn.rollback().await?;
+    }
+
+    let report = ImportReport { kind: kind.name(), dry_run, rows: rows.len(), created, committed, errors };
+    if report.errors.is_empty() {
+        info!(kind = %report.kind, created = report.created, committed, "imported rows");
+    } else {
+        warn!(kind = %report.kind, errors = report.errors.len(), "import rejected");
+    }
+    Ok(report)
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{get_test_db, reset_table};
+
+    async fn reset_tables(db: &DatabaseConnection) {
+        reset_table(db, "electricity_reading").await;
+        reset_table(db, "tenancy").await;
+        reset_table(db, "tenant").await;
+        reset_table(db, "room").await;
+    }
+
+    #[test]
+    fn test_read_rows_reports_missing_columns() {
+        let mut headers = HashMap::new();
+        let mut errors = Vec::new();
+        let rows = read_rows(b"Name,currency\nA,PHP\n,\n", ImportKind::Rooms, &mut headers, &mut errors);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(headers.get("name"), Some(&0));
+        assert_eq!(errors, vec![RowError { line: 1, column: Some("rent".into()), message: "column is missing".into() }]);
+    }
+
+    #[tokio::test]
+    async fn test_import_is_all_or_nothing() {
+        let db = get_test_db().await;
+        reset_tables(&db).await;
+
+        let rooms = b"name,rent,currency\nA1,4500.50,\nA2,-1,PHP\nA1,3000,XXX\n";
+        let report = import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Rooms, rooms, false).await.unwrap();
+        assert!(!report.committed);
+        assert_eq!((report.rows, report.created), (3, 0));
+        let lines: Vec<_> = report.errors.iter().map(|e| (e.line, e.column.as_deref())).collect();
+        assert_eq!(lines, vec![(3, Some("rent")), (4, Some("name")), (4, Some("currency"))]);
+        assert!(room_repo::get_all(&db).await.unwrap().is_empty());
+
+        let rooms = b"name,rent\nA1,4500.50\nA2,3000\n";
+        let dry = import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Rooms, rooms, true).await.unwrap();
+        assert!(dry.errors.is_empty() && !dry.committed);
+        assert_eq!(dry.created, 2);
+        assert!(room_repo::get_all(&db).await.unwrap().is_empty());
+
+        let report = import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Rooms, rooms, false).await.unwrap();
+        assert!(report.committed);
+        assert_eq!(room_repo::get_all(&db).await.unwrap()[0].rent, 450_050);
+
+        let tenants = b"name,room,join_date,is_active\nAna,A1,2026-10-01,yes\nBen,B9,2026-10-01,\n";
+        let report = import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Tenants, tenants, false).await.unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].message, "no room named 'B9'");
+
+        let tenants = b"name,room,join_date\nAna,A1,2026-10-01\n";
+        assert!(import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Tenants, tenants, false).await.unwrap().committed);
+
+        let readings = b"tenant,prev_reading,curr_reading\nAna,,120\n";
+        assert!(import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Readings, readings, false).await.unwrap().committed);
+        let readings = b"tenant,curr_reading\nAna,100\n";
+        let report = import_csv(&db, "admin:root", 1, Currency::PHP, ImportKind::Readings, readings, false).await.unwrap();
+        assert_eq!(report.errors[0].message, "100 is below the previous reading 120");
+    }
+}
@@ -3,4 +3,36 @@ pub mod room_service;
 pub mod tenant_service;
 pub mod electricity_reading_service;
 pub mod bill_service;
-pub mod r2_service;
\ No newline at end of file
+pub mod r2_service;
+pub mod attachment_service;
+pub mod upload_validation;
+pub mod document_service;
+pub mod template_service;
+pub mod email_service;
+pub mod notification_service;
+pub mod notification_channel;
+pub mod webhook_service;
+pub mod audit_service;
+pub mod trash_service;
+pub mod versioning;
+pub mod sync_service;
+pub mod charge_service;
+pub mod report_service;
+pub mod occupancy_service;
+pub mod consumption_service;
+pub mod export_service;
+
+use sea_orm::{DbErr, TransactionError};
+
+/// Flatten a transaction error into the error raised inside it
+pub(crate) fn map_txn_err<T, E: std::error::Error + From<DbErr>>(res: Result<T, TransactionError<E>>) -> Result<T, E> {
+    res.map_err(|e| match e {
+        TransactionError::Connection(err) => err.into(),
+        TransactionError::Transaction(err) => err,
+    })
+}
+pub mod import_service;
+pub mod reading_batch_service;
+pub mod password;
+pub mod property_service;
+pub mod admin_user_service;
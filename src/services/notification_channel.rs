@@ -0,0 +1,255 @@
+use crate::config::NotificationConfig;
+use crate::services::email_service::EmailSender;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+
+pub const CHANNEL_EMAIL: &str = "email";
+pub const CHANNEL_SMS: &str = "sms";
+pub const CHANNEL_CHAT: &str = "chat";
+
+pub const ALL_CHANNELS: &[&str] = &[CHANNEL_EMAIL, CHANNEL_SMS, CHANNEL_CHAT];
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A rendered notification ready for delivery
+#[derive(Debug, Clone)]
+pub struct OutgoingMessage {
+    pub recipient: String,
+    pub subject: String,
+    /// Full plain text body, used by email
+    pub text: String,
+    pub html: Option<String>,
+    /// Short text for SMS and chat
+    pub short_text: String,
+}
+
+/// A way of delivering notifications to tenants
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, message: &OutgoingMessage) -> Result<(), String>;
+}
+
+#[async_trait]
+impl NotificationChannel for EmailSender {
+    fn name(&self) -> &'static str {
+        CHANNEL_EMAIL
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> Result<(), String> {
+        EmailSender::send(
+            self,
+            &message.recipient,
+            &message.subject,
+            message.text.clone(),
+            message.html.clone(),
+        )
+        .await
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+async fn check_response(res: Result<reqwest::Response, reqwest::Error>) -> Result<(), String> {
+    // The URL can carry a chat token, keep it out of stored errors and logs
+    let res = res.map_err(|e| e.without_url().to_string())?;
+    let status = res.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        let body = res.text().await.unwrap_or_default();
+        Err(format!("gateway returned {}: {}", status, body))
+    }
+}
+
+/// SMS delivery through an HTTP gateway.
+/// Posts `{ "to", "from", "message" }` as JSON with a bearer API key.
+pub struct SmsGatewayChannel {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    sender_id: Option<String>,
+}
+
+impl SmsGatewayChannel {
+    pub fn new(url: String, api_key: Option<String>, sender_id: Option<String>) -> Self {
+        SmsGatewayChannel { client: http_client(), url, api_key, sender_id }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SmsGatewayChannel {
+    fn name(&self) -> &'static str {
+        CHANNEL_SMS
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> Result<(), String> {
+        let mut req = self.client.post(&self.url).json(&json!({
+            "to": message.recipient,
+            "from": self.sender_id,
+            "message": message.short_text,
+        }));
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+        check_response(req.send().await).await
+    }
+}
+
+/// Chat delivery through a generic webhook.
+/// Posts `{ "chat_id", "text" }` as JSON.
+pub struct ChatWebhookChannel {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl ChatWebhookChannel {
+    pub fn new(url: String) -> Self {
+        ChatWebhookChannel { client: http_client(), url }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for ChatWebhookChannel {
+    fn name(&self) -> &'static str {
+        CHANNEL_CHAT
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> Result<(), String> {
+        let req = self.client.post(&self.url).json(&json!({
+            "chat_id": message.recipient,
+            "text": message.short_text,
+        }));
+        check_response(req.send().await).await
+    }
+}
+
+/// Channels configured for this server, keyed by name
+#[derive(Clone, Default)]
+pub struct ChannelRegistry {
+    channels: HashMap<&'static str, Arc<dyn NotificationChannel>>,
+}
+
+impl ChannelRegistry {
+    pub fn register(&mut self, channel: Arc<dyn NotificationChannel>) {
+        self.channels.insert(channel.name(), channel);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn NotificationChannel>> {
+        self.channels.get(name)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.channels.keys().copied().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Build the channels set up in the `[smtp]`, `[sms]` and `[chat]` config sections
+    pub fn new(config: &NotificationConfig) -> Self {
+        let mut registry = ChannelRegistry::default();
+
+        if let Some(settings) = &config.smtp {
+            match EmailSender::new(settings) {
+                Ok(sender) => registry.register(Arc::new(sender)),
+                Err(err) => error!(error = %err, "email channel could not be created"),
+            }
+        }
+        if let Some(sms) = &config.sms {
+            registry.register(Arc::new(SmsGatewayChannel::new(
+                sms.gateway_url.clone(),
+                sms.api_key.as_ref().map(|key| key.expose().to_string()),
+                sms.sender_id.clone(),
+            )));
+        }
+        if let Some(url) = &config.chat_webhook_url {
+            registry.register(Arc::new(ChatWebhookChannel::new(url.expose().to_string())));
+        }
+
+        registry
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, extract::State, http::{HeaderMap, StatusCode}, routing::post};
+    use tokio::sync::mpsc;
+
+    /// Local mock HTTP server that forwards each request body and auth header
+    async fn mock_server(status: StatusCode) -> (String, mpsc::UnboundedReceiver<(Option<String>, serde_json::Value)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route(
+                "/send",
+                post(
+                    move |State(tx): State<mpsc::UnboundedSender<_>>,
+                          headers: HeaderMap,
+                          Json(body): Json<serde_json::Value>| async move {
+                        let auth = headers
+                            .get("authorization")
+                            .and_then(|h| h.to_str().ok())
+                            .map(str::to_string);
+                        tx.send((auth, body)).unwrap();
+                        status
+                    },
+                ),
+            )
+            .with_state(tx);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}/send", addr), rx)
+    }
+
+    fn message(recipient: &str) -> OutgoingMessage {
+        OutgoingMessage {
+            recipient: recipient.into(),
+            subject: "Bill #1".into(),
+            text: "Full text".into(),
+            html: None,
+            short_text: "Your bill #1 is ready".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sms_gateway_posts_message() {
+        let (url, mut rx) = mock_server(StatusCode::OK).await;
+        let channel = SmsGatewayChannel::new(url, Some("key123".into()), Some("M18".into()));
+
+        channel.send(&message("+639171234567")).await.unwrap();
+
+        let (auth, body) = rx.recv().await.unwrap();
+        assert_eq!(auth.as_deref(), Some("Bearer key123"));
+        assert_eq!(body["to"], "+639171234567");
+        assert_eq!(body["from"], "M18");
+        assert_eq!(body["message"], "Your bill #1 is ready");
+    }
+
+    #[tokio::test]
+    async fn test_chat_webhook_reports_gateway_errors() {
+        let (url, mut rx) = mock_server(StatusCode::BAD_GATEWAY).await;
+        let channel = ChatWebhookChannel::new(url);
+
+        let result = channel.send(&message("chat-42")).await;
+        assert!(result.unwrap_err().contains("502"));
+
+        let (_, body) = rx.recv().await.unwrap();
+        assert_eq!(body["chat_id"], "chat-42");
+    }
+}
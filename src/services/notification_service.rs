@@ -0,0 +1,324 @@
+use crate::config::{BillingConfig, Config, SharedConfig};
+use crate::entities::{bill, notification_outbox, property, tenant};
+use crate::repository::property_repo::{self, PropertyOwner};
+use crate::repository::{bill_repo, notification_outbox_repo, tenant_repo};
+use crate::services::notification_channel::{
+    CHANNEL_CHAT, CHANNEL_EMAIL, CHANNEL_SMS, ChannelRegistry, OutgoingMessage,
+};
+use crate::services::template_service::{self, Branding, Locale, RenderFormat};
+use chrono::{Duration, NaiveDateTime, Utc};
+use minijinja::Environment;
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr};
+use serde_json::json;
+use tracing::{error, info, instrument, warn};
+
+pub const EVENT_BILL_CREATED: &str = "bill.created";
+pub const EVENT_BILL_REMINDER: &str = "bill.reminder";
+pub const EVENT_BILL_OVERDUE: &str = "bill.overdue";
+pub const EVENT_BILL_PAID: &str = "bill.paid";
+
+/// Message templates per event: (event, subject, intro for email, short text for SMS and chat)
+const MESSAGE_TEMPLATES: &[(&str, &str, &str, &str)] = &[
+    (
+        EVENT_BILL_CREATED,
+        "{{ brand }}: new bill #{{ bill_id }}",
+        "A new bill has been issued. Please pay by {{ due_date }}.",
+        "{{ brand }}: your bill #{{ bill_id }} of {{ total }} is ready. Please pay by {{ due_date }}.",
+    ),
+    (
+        EVENT_BILL_REMINDER,
+        "{{ brand }}: bill #{{ bill_id }} is due soon",
+        "This is a reminder that your bill is due on {{ due_date }}.",
+        "{{ brand }}: reminder, bill #{{ bill_id }} of {{ total }} is due on {{ due_date }}.",
+    ),
+    (
+        EVENT_BILL_OVERDUE,
+        "{{ brand }}: bill #{{ bill_id }} is overdue",
+        "Your bill was due on {{ due_date }} and is still unpaid. Please settle it as soon as possible.",
+        "{{ brand }}: bill #{{ bill_id }} of {{ total }} was due on {{ due_date }} and is now overdue.",
+    ),
+    (
+        EVENT_BILL_PAID,
+        "{{ brand }}: payment received for bill #{{ bill_id }}",
+        "Thank you, we have received your payment.",
+        "{{ brand }}: payment of {{ total }} for bill #{{ bill_id }} received. Thank you!",
+    ),
+];
+
+const MAX_ATTEMPTS: i32 = 8;
+const BATCH_SIZE: u64 = 20;
+/// How long a claimed batch is hidden from other workers while it is being sent
+const CLAIM_LEASE_MINUTES: i64 = 10;
+
+/// Bills are due the property's `bill_due_days` (else `default_days`) days after they are issued
+pub fn due_date(bill: &bill::Model, property: Option<&property::Model>, default_days: i64) -> NaiveDateTime {
+    let days = property
+        .and_then(|p| p.bill_due_days)
+        .map(i64::from)
+        .unwrap_or(default_days);
+    bill.issued_at.unwrap_or(bill.created_at) + Duration::days(days)
+}
+
+/// Exponential backoff: 1, 2, 4, ... minutes, capped at 6 hours
+pub fn next_retry_at(now: NaiveDateTime, attempts: i32) -> NaiveDateTime {
+    let minutes = 2i64.saturating_pow(attempts.clamp(0, 16) as u32).min(6 * 60);
+    now + Duration::minutes(minutes)
+}
+
+/// Tenant address for a channel, if the tenant has one
+fn recipient_for(tenant: &tenant::Model, channel: &str) -> Option<String> {
+    let value = match channel {
+        CHANNEL_EMAIL => tenant.email.as_ref(),
+        CHANNEL_SMS => tenant.phone_number.as_ref(),
+        CHANNEL_CHAT => tenant.chat_id.as_ref(),
+        _ => None,
+    };
+    value.filter(|v| !v.is_empty()).cloned()
+}
+
+/// Queue a notification on each of the tenant's preferred channels; call inside the transaction that changes the bill
+#[instrument(skip_all)]
+pub async fn enqueue_bill_event<C>(conn: &C, event: &str, bill: &bill::Model) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    let Some(tenant) = tenant_repo::get_by_id(conn, bill.tenant_id).await? else {
+        return Ok(());
+    };
+
+    for channel in tenant.channels() {
+        let Some(recipient) = recipient_for(&tenant, channel) else {
+            warn!(tenant_id = tenant.id, %channel, %event, "tenant has no address for channel, skipping");
+            continue;
+        };
+
+        let item = notification_outbox::ActiveModel {
+            event: Set(event.to_string()),
+            channel: Set(channel.to_string()),
+            recipient: Set(recipient),
+            tenant_id: Set(Some(tenant.id)),
+            bill_id: Set(Some(bill.id)),
+            next_attempt_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+
+        let msg = notification_outbox_repo::create(conn, item).await?;
+        info!(message_id = msg.id, bill_id = bill.id, %event, %channel, "queued message");
+    }
+
+    Ok(())
+}
+
+/// Queue reminders for unpaid bills that fall due within `reminder_days_before` days,
+/// and overdue notices for unpaid bills past their due date
+pub async fn enqueue_due_reminders(db: &DatabaseConnection, billing: &BillingConfig) -> Result<usize, DbErr> {
+    let now = Utc::now().naive_utc();
+    let horizon = now + Duration::days(billing.reminder_days_before);
+    let mut queued = 0;
+
+    for bill in bill_repo::get_unpaid(db).await? {
+        let property = property_repo::get_for(db, PropertyOwner::Bill(bill.id)).await?;
+        let due = due_date(&bill, property.as_ref(), billing.due_days);
+        let event = if due < now {
+            EVENT_BILL_OVERDUE
+        } else if due <= horizon {
+            EVENT_BILL_REMINDER
+        } else {
+            continue;
+        };
+        if notification_outbox_repo::exists_for_bill_event(db, bill.id, event).await? {
+            continue;
+        }
+        enqueue_bill_event(db, event, &bill).await?;
+        queued += 1;
+    }
+
+    Ok(queued)
+}
+
+/// Render the subject, email intro and short text for an event
+fn render_message(
+    event: &str,
+    bill: &bill::Model,
+    brand: &str,
+    due: NaiveDateTime,
+) -> Result<(String, String, String), String> {
+    let Some((_, subject, intro, short)) = MESSAGE_TEMPLATES.iter().find(|(e, ..)| *e == event) else {
+        return Err(format!("unknown event '{}'", event));
+    };
+
+    let ctx = json!({
+        "brand": brand,
+        "bill_id": bill.id,
+        "total": Locale::get("en").format_currency(bill.total()),
+        "due_date": due.format("%B %-d, %Y").to_string(),
+    });
+    let env = Environment::new();
+    let render = |source: &str| env.render_str(source, &ctx).map_err(|e| e.to_string());
+
+    Ok((render(subject)?, render(intro)?, render(short)?))
+}
+
+/// Build the outgoing message for an outbox row
+async fn compose(
+    db: &DatabaseConnection,
+    config: &Config,
+    msg: &notification_outbox::Model,
+) -> Result<OutgoingMessage, String> {
+    let Some(bill_id) = msg.bill_id else {
+        return Err("message has no bill".into());
+    };
+    let bill = bill_repo::get_by_id(db, bill_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("bill no longer exists")?;
+    let property = property_repo::get_for(db, PropertyOwner::Bill(bill.id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let brand = Branding::for_property(&config.templates.branding, property.as_ref()).name;
+    let due = due_date(&bill, property.as_ref(), config.billing.due_days);
+
+    let (subject, intro, short_text) = render_message(&msg.event, &bill, &brand, due)?;
+
+    // Only email carries the full rendered bill
+    let (text, html) = if msg.channel == CHANNEL_EMAIL {
+        let text = template_service::render_bill(db, &config.templates, bill.id, RenderFormat::Text, "en")
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let html = template_service::render_bill(db, &config.templates, bill.id, RenderFormat::Html, "en")
+            .await
+            .ok();
+        (format!("{}\n\n{}", intro, text), html)
+    } else {
+        (short_text.clone(), None)
+    };
+
+    Ok(OutgoingMessage { recipient: msg.recipient.clone(), subject, text, html, short_text })
+}
+
+/// Deliver due outbox messages on the configured channels, rescheduling failures with backoff
+#[instrument(skip_all)]
+pub async fn process_outbox(
+    db: &DatabaseConnection,
+    config: &Config,
+    channels: &ChannelRegistry,
+) -> Result<usize, DbErr> {
+    let now = Utc::now().naive_utc();
+    let mut sent = 0;
+
+    let lease_until = now + Duration::minutes(CLAIM_LEASE_MINUTES);
+    let due = notification_outbox_repo::claim_due(db, &channels.names(), now, lease_until, BATCH_SIZE).await?;
+    for msg in due {
+        let result = match (channels.get(&msg.channel), compose(db, config, &msg).await) {
+            (Some(channel), Ok(out)) => channel.send(&out).await.map(|_| (out.subject, out.text)),
+            (None, _) => Err(format!("channel '{}' is not configured", msg.channel)),
+            (_, Err(err)) => Err(err),
+        };
+
+        match result {
+            Ok((subject, text)) => {
+                let id = msg.id;
+                notification_outbox_repo::mark_sent(db, msg, subject, text).await?;
+                info!(message_id = id, "sent message");
+                sent += 1;
+            }
+            Err(err) => {
+                let give_up = msg.attempts + 1 >= MAX_ATTEMPTS;
+                error!(message_id = msg.id, attempt = msg.attempts + 1, error = %err, "message attempt failed");
+                let retry_at = next_retry_at(now, msg.attempts);
+                notification_outbox_repo::mark_failed(db, msg, err, retry_at, give_up).await?;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Run the reminder scan and outbox delivery every `poll_secs` seconds
+pub fn spawn_worker(db: DatabaseConnection, config: SharedConfig) {
+    let channels = ChannelRegistry::new(&config.notifications);
+    // Reminders are queued either way, ready for when a channel is configured
+    if channels.is_empty() {
+        warn!("no notification channels configured, notifications are queued but not sent");
+    } else {
+        info!(channels = %channels.names().join(", "), "notification worker started");
+    }
+    let interval = std::time::Duration::from_secs(config.notifications.poll_secs);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(err) = enqueue_due_reminders(&db, &config.billing).await {
+                error!(error = ?err, "error enqueueing due reminders");
+            }
+            if let Err(err) = process_outbox(&db, &config, &channels).await {
+                error!(error = ?err, "error processing outbox");
+            }
+        }
+    });
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_retry_backoff() {
+        let now = Utc::now().naive_utc();
+        assert_eq!(next_retry_at(now, 0) - now, Duration::minutes(1));
+        assert_eq!(next_retry_at(now, 3) - now, Duration::minutes(8));
+        assert_eq!(next_retry_at(now, 30) - now, Duration::hours(6));
+    }
+
+    #[test]
+    fn test_render_message_templates() {
+        let now = Utc::now().naive_utc();
+        let bill = bill::Model {
+            id: 7,
+            reading_id: 1,
+            tenant_id: 1,
+            room_charges: 500000,
+            electric_charges: 125000,
+            discount_amount: 0,
+            total_amount: 625000,
+            currency: "PHP".into(),
+            receipt_url: None,
+            paid: false,
+            amount_paid: None,
+            status: bill::STATUS_ISSUED.into(),
+            issued_at: Some(now),
+            paid_at: None,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            version: 1,
+        };
+
+        let due = due_date(&bill, None, 7);
+        for (event, ..) in MESSAGE_TEMPLATES {
+            let (subject, _, short) = render_message(event, &bill, "M18", due).unwrap();
+            assert!(subject.starts_with("M18: "));
+            assert!(short.contains("#7"));
+        }
+
+        let (_, _, short) = render_message(EVENT_BILL_OVERDUE, &bill, "M18", due).unwrap();
+        assert!(short.contains("₱6,250.00"));
+        assert!(render_message("bill.unknown", &bill, "M18", due).is_err());
+
+        let annex = property::Model {
+            id: 2,
+            name: "Annex".into(),
+            address: None,
+            contact: None,
+            logo_url: None,
+            accent_color: None,
+            electric_rate: None,
+            bill_due_days: Some(15),
+            created_at: now,
+            updated_at: now,
+        };
+        assert_eq!(due_date(&bill, Some(&annex), 7) - now, Duration::days(15));
+    }
+}
@@ -0,0 +1,411 @@
+use crate::entities::{room, tenancy, tenant};
+use crate::money;
+use crate::repository::{room_repo, tenancy_repo};
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, Set};
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, instrument};
+
+pub const STATUS_VACANT: &str = "vacant";
+pub const STATUS_OCCUPIED: &str = "occupied";
+/// A tenant has signed on but not moved in yet
+pub const STATUS_RESERVED: &str = "reserved";
+pub const STATUS_MAINTENANCE: &str = "maintenance";
+
+/// Room as returned by the room list, with its computed status
+#[derive(Debug, Serialize)]
+pub struct RoomWithStatus {
+    #[serde(flatten)]
+    pub room: room::Model,
+    pub status: &'static str,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RoomOccupancy {
+    pub room_id: i32,
+    pub room_name: String,
+    pub status: &'static str,
+    pub currency: String,
+    /// Days the room existed within the period
+    pub days: i64,
+    pub occupied_days: i64,
+    pub vacant_days: i64,
+    /// `occupied_days / days`; absent when the room did not exist in the period
+    pub occupancy_rate: Option<f64>,
+    /// Separate stretches without a tenant
+    pub vacancies: usize,
+    pub average_vacancy_days: Option<f64>,
+    /// Vacant days priced at the current rent
+    #[serde(with = "money::decimal")]
+    pub lost_rent: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MonthlyOccupancy {
+    pub month: NaiveDate,
+    pub room_days: i64,
+    pub occupied_days: i64,
+    pub occupancy_rate: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LostRent {
+    pub currency: String,
+    #[serde(with = "money::decimal")]
+    pub total: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OccupancyReport {
+    pub from: NaiveDate,
+    /// Exclusive
+    pub to: NaiveDate,
+    pub occupancy_rate: Option<f64>,
+    pub average_vacancy_days: Option<f64>,
+    pub lost_rent: Vec<LostRent>,
+    pub months: Vec<MonthlyOccupancy>,
+    pub rooms: Vec<RoomOccupancy>,
+}
+
+/// Keep a tenant's tenancy history in step with the tenant row. Called after every
+/// write to a tenant: moving rooms closes the old tenancy and opens a new one, and
+/// deactivating or trashing the tenant closes it.
+pub async fn sync_tenancy<C>(conn: &C, tenant: &tenant::Model, now: NaiveDateTime) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    let lives_here = tenant.is_active && tenant.deleted_at.is_none();
+    let open = tenancy_repo::get_open_for_tenant(conn, tenant.id).await?;
+
+    if let Some(open) = &open {
+        if lives_here && open.room_id == tenant.room_id {
+            // Still the first tenancy: a corrected join date moves its start
+            if open.start_date != tenant.join_date && tenancy_repo::get_all_by_tenant_id(conn, tenant.id).await?.len() == 1 {
+                tenancy_repo::update(conn, open.id, tenancy::ActiveModel {
+                    start_date: Set(tenant.join_date),
+                    ..Default::default()
+                })
+                .await?;
+            }
+            return Ok(());
+        }
+        tenancy_repo::update(conn, open.id, tenancy::ActiveModel {
+            end_date: Set(Some(now.max(open.start_date))),
+            ..Default::default()
+        })
+        .await?;
+    }
+
+    if lives_here {
+        let first = open.is_none() && tenancy_repo::get_all_by_tenant_id(conn, tenant.id).await?.is_empty();
+        let start_date = if first { tenant.join_date } else { now.max(tenant.join_date) };
+        tenancy_repo::create(conn, tenancy::ActiveModel {
+            tenant_id: Set(tenant.id),
+            room_id: Set(tenant.room_id),
+            start_date: Set(start_date),
+            ..Default::default()
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Maintenance wins, then a tenant living there, then one due to move in
+pub fn room_status(room: &room::Model, tenancies: &[&tenancy::Model], now: NaiveDateTime) -> &'static str {
+    let current = |t: &&&tenancy::Model| t.room_id == room.id && t.end_date.is_none_or(|end| end > now);
+    if room.under_maintenance {
+        STATUS_MAINTENANCE
+    } else if tenancies.iter().filter(current).any(|t| t.start_date <= now) {
+        STATUS_OCCUPIED
+    } else if tenancies.iter().filter(current).any(|t| t.start_date > now) {
+        STATUS_RESERVED
+    } else {
+        STATUS_VACANT
+    }
+}
+
+/// Rooms of the given properties (all when `None`) with their status right now
+pub async fn get_rooms_with_status(
+    db: &DatabaseConnection,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<RoomWithStatus>, DbErr> {
+    let now = chrono::Utc::now().naive_utc();
+    let rooms = room_repo::get_in_properties(db, property_ids).await?;
+    let tenancies = tenancy_repo::get_current(db, now).await?;
+    let tenancies: Vec<&tenancy::Model> = tenancies.iter().collect();
+
+    Ok(rooms
+        .into_iter()
+        .map(|room| RoomWithStatus {
+            status: room_status(&room, &tenancies, now),
+            room,
+        })
+        .collect())
+}
+
+/// Occupied days of one room in `[from, to)` as sorted, non-overlapping date ranges
+fn occupied_ranges(tenancies: &[&tenancy::Model], from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut ranges: Vec<(NaiveDate, NaiveDate)> = tenancies
+        .iter()
+        .map(|t| (t.start_date.date().max(from), t.end_date.map_or(to, |end| end.date()).min(to)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    ranges.sort();
+
+    let mut merged: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn days_within(ranges: &[(NaiveDate, NaiveDate)], from: NaiveDate, to: NaiveDate) -> i64 {
+    ranges
+        .iter()
+        .map(|(start, end)| ((*end).min(to) - (*start).max(from)).num_days().max(0))
+        .sum()
+}
+
+fn rate(part: i64, whole: i64) -> Option<f64> {
+    (whole > 0).then(|| (part as f64 / whole as f64 * 10_000.0).round() / 10_000.0)
+}
+
+fn average(days: &[i64]) -> Option<f64> {
+    (!days.is_empty()).then(|| (days.iter().sum::<i64>() as f64 / days.len() as f64 * 10.0).round() / 10.0)
+}
+
+/// Monthly rent spread over a 365-day year, rounded half up
+pub fn lost_rent(monthly_rent: i64, vacant_days: i64) -> i64 {
+    let owed = monthly_rent as i128 * vacant_days as i128 * 12;
+    ((owed * 2 + 365) / (365 * 2)) as i64
+}
+
+/// Occupancy of every room over `[from, to)`, from tenancy history. Rooms count from the
+/// day they were created, so a new room does not drag the rate down for earlier months.
+pub fn build_report(
+    rooms: &[room::Model],
+    tenancies: &[tenancy::Model],
+    from: NaiveDate,
+    to: NaiveDate,
+    now: NaiveDateTime,
+) -> OccupancyReport {
+    let mut month_starts = Vec::new();
+    let mut month = from.with_day(1).unwrap_or(from);
+    while month < to {
+        month_starts.push(month);
+        month = month + Months::new(1);
+    }
+    let mut months: Vec<MonthlyOccupancy> = month_starts
+        .iter()
+        .map(|month| MonthlyOccupancy { month: *month, room_days: 0, occupied_days: 0, occupancy_rate: None })
+        .collect();
+
+    let mut report_rooms = Vec::new();
+    let mut vacancy_lengths = Vec::new();
+    let mut lost: HashMap<String, i64> = HashMap::new();
+    let all: Vec<&tenancy::Model> = tenancies.iter().collect();
+
+    for room in rooms {
+        let by_room: Vec<&tenancy::Model> = tenancies.iter().filter(|t| t.room_id == room.id).collect();
+        let start = from.max(room.created_at.date());
+        let end = to.max(start);
+        let occupied = occupied_ranges(&by_room, start, end);
+
+        let mut gaps = Vec::new();
+        let mut cursor = start;
+        for (s, e) in occupied.iter().copied().chain(std::iter::once((end, end))) {
+            if s > cursor {
+                gaps.push((s - cursor).num_days());
+            }
+            cursor = cursor.max(e);
+        }
+
+        for m in months.iter_mut() {
+            let month_end = m.month + Months::new(1);
+            let (m_start, m_end) = (m.month.max(start), month_end.min(end));
+            m.room_days += (m_end - m_start).num_days().max(0);
+            m.occupied_days += days_within(&occupied, m_start, m_end);
+        }
+
+        let days = (end - start).num_days();
+        let occupied_days = days_within(&occupied, start, end);
+        let vacant_days = days - occupied_days;
+        let lost_rent = lost_rent(room.rent, vacant_days);
+        *lost.entry(room.currency.clone()).or_default() += lost_rent;
+
+        report_rooms.push(RoomOccupancy {
+            room_id: room.id,
+            room_name: room.name.clone(),
+            status: room_status(room, &all, now),
+            currency: room.currency.clone(),
+            days,
+            occupied_days,
+            vacant_days,
+            occupancy_rate: rate(occupied_days, days),
+            vacancies: gaps.len(),
+            average_vacancy_days: average(&gaps),
+            lost_rent,
+        });
+        vacancy_lengths.extend(gaps);
+    }
+
+    for m in months.iter_mut() {
+        m.occupancy_rate = rate(m.occupied_days, m.room_days);
+    }
+    let mut lost_rent: Vec<LostRent> = lost.into_iter().map(|(currency, total)| LostRent { currency, total }).collect();
+    lost_rent.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+    OccupancyReport {
+        from,
+        to,
+        occupancy_rate: rate(
+            report_rooms.iter().map(|r| r.occupied_days).sum(),
+            report_rooms.iter().map(|r| r.days).sum(),
+        ),
+        average_vacancy_days: average(&vacancy_lengths),
+        lost_rent,
+        months,
+        rooms: report_rooms,
+    }
+}
+
+/// Occupancy between `from` and `to`; the last 12 months up to now by default
+#[instrument(skip_all)]
+pub async fn get_occupancy_report(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<OccupancyReport, DbErr> {
+    let now = chrono::Utc::now().naive_utc();
+    let to = to.unwrap_or(now).date();
+    let from = from.map(|f| f.date()).unwrap_or(to - Months::new(12));
+    let rooms = room_repo::get_in_properties(db, property_ids).await?;
+    let tenancies = tenancy_repo::get_overlapping(db, from.into(), to.into()).await?;
+
+    let report = build_report(&rooms, &tenancies, from, to, now);
+    info!(rooms = report.rooms.len(), %from, %to, rate = ?report.occupancy_rate, "built occupancy report");
+    Ok(report)
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::tenant_repo;
+    use crate::test_utils::{add_room, get_test_db, reset_rooms};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn sample_room(id: i32, rent: i64, maintenance: bool) -> room::Model {
+        let created = date(2026, 1, 1).into();
+        room::Model {
+            id,
+            property_id: 1,
+            name: format!("Room {}", id),
+            rent,
+            currency: "PHP".into(),
+            under_maintenance: maintenance,
+            created_at: created,
+            updated_at: created,
+            deleted_at: None,
+            version: 1,
+        }
+    }
+
+    fn sample_tenancy(room_id: i32, start: NaiveDate, end: Option<NaiveDate>) -> tenancy::Model {
+        tenancy::Model {
+            id: 0,
+            tenant_id: 1,
+            room_id,
+            start_date: start.into(),
+            end_date: end.map(Into::into),
+            created_at: start.into(),
+            updated_at: start.into(),
+        }
+    }
+
+    #[test]
+    fn test_room_status_precedence() {
+        let now = date(2026, 10, 19).into();
+        let past = sample_tenancy(1, date(2026, 1, 1), Some(date(2026, 5, 1)));
+        let living = sample_tenancy(1, date(2026, 6, 1), None);
+        let future = sample_tenancy(1, date(2026, 11, 1), None);
+
+        assert_eq!(room_status(&sample_room(1, 0, false), &[&past], now), STATUS_VACANT);
+        assert_eq!(room_status(&sample_room(1, 0, false), &[&past, &future], now), STATUS_RESERVED);
+        assert_eq!(room_status(&sample_room(1, 0, false), &[&future, &living], now), STATUS_OCCUPIED);
+        assert_eq!(room_status(&sample_room(1, 0, true), &[&living], now), STATUS_MAINTENANCE);
+        assert_eq!(room_status(&sample_room(2, 0, false), &[&living], now), STATUS_VACANT);
+    }
+
+    #[test]
+    fn test_build_report_counts_vacancies_and_lost_rent() {
+        let rooms = vec![sample_room(1, 365_000, false), sample_room(2, 100_000, false)];
+        let tenancies = vec![
+            // Room 1: empty for January and for 10 days in March
+            sample_tenancy(1, date(2026, 2, 1), Some(date(2026, 3, 1))),
+            sample_tenancy(1, date(2026, 3, 11), None),
+            // Overlapping stays are not double counted
+            sample_tenancy(1, date(2026, 3, 20), Some(date(2026, 3, 25))),
+            // Room 2: occupied throughout
+            sample_tenancy(2, date(2025, 6, 1), None),
+        ];
+
+        let report = build_report(&rooms, &tenancies, date(2026, 1, 1), date(2026, 4, 1), date(2026, 10, 19).into());
+        let room1 = &report.rooms[0];
+        assert_eq!((room1.days, room1.occupied_days, room1.vacant_days), (90, 49, 41));
+        assert_eq!((room1.vacancies, room1.average_vacancy_days), (2, Some(20.5)));
+        assert_eq!(room1.lost_rent, 41 * 12_000);
+        assert_eq!(room1.status, STATUS_OCCUPIED);
+        assert_eq!(report.rooms[1].occupancy_rate, Some(1.0));
+
+        assert_eq!(report.months.len(), 3);
+        assert_eq!((report.months[0].room_days, report.months[0].occupied_days), (62, 31));
+        assert_eq!(report.months[0].occupancy_rate, Some(0.5));
+        assert_eq!(report.occupancy_rate, Some(((139.0 / 180.0) * 10_000.0_f64).round() / 10_000.0));
+        assert_eq!(report.lost_rent, vec![LostRent { currency: "PHP".into(), total: 492_000 }]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_tenancy_follows_moves_and_move_out() {
+        let db = get_test_db().await;
+        reset_rooms(&db).await;
+
+        let mut room_ids = Vec::new();
+        for name in ["Sync A", "Sync B"] {
+            room_ids.push(add_room(&db, name).await.id);
+        }
+        let joined: NaiveDateTime = date(2026, 1, 1).into();
+        let tenant = tenant_repo::create(&db, tenant::ActiveModel {
+            name: Set("Sync Tenant".into()),
+            room_id: Set(room_ids[0]),
+            is_active: Set(true),
+            join_date: Set(joined),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        sync_tenancy(&db, &tenant, date(2026, 1, 1).into()).await.unwrap();
+        sync_tenancy(&db, &tenant, date(2026, 2, 1).into()).await.unwrap();
+        let moved = tenant::Model { room_id: room_ids[1], ..tenant.clone() };
+        sync_tenancy(&db, &moved, date(2026, 3, 1).into()).await.unwrap();
+        let left = tenant::Model { is_active: false, ..moved };
+        sync_tenancy(&db, &left, date(2026, 4, 1).into()).await.unwrap();
+
+        let history = tenancy_repo::get_all_by_tenant_id(&db, tenant.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].room_id, history[0].start_date), (room_ids[0], joined));
+        assert_eq!(history[0].end_date, Some(date(2026, 3, 1).into()));
+        assert_eq!((history[1].room_id, history[1].start_date), (room_ids[1], date(2026, 3, 1).into()));
+        assert_eq!(history[1].end_date, Some(date(2026, 4, 1).into()));
+        assert!(tenancy_repo::get_open_for_tenant(&db, tenant.id).await.unwrap().is_none());
+    }
+}
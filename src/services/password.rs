@@ -0,0 +1,85 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+const SCHEME: &str = "pbkdf2_sha256";
+const ITERATIONS: u32 = 310_000;
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// PBKDF2-HMAC-SHA256 (RFC 8018) producing `out.len()` bytes
+fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let prf = Hmac::<Sha256>::new_from_slice(password).expect("HMAC accepts any key length");
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut block = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            block.iter_mut().zip(u.iter()).for_each(|(b, x)| *b ^= x);
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+/// Hash a password for storage as `pbkdf2_sha256$<iterations>$<salt hex>$<hash hex>`
+pub fn hash(password: &str) -> String {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut out = [0u8; HASH_LEN];
+    pbkdf2(password.as_bytes(), &salt, ITERATIONS, &mut out);
+    format!("{}${}${}${}", SCHEME, ITERATIONS, hex::encode(salt), hex::encode(out))
+}
+
+/// Check a password against a stored hash; malformed hashes never match
+pub fn verify(password: &str, stored: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    let [SCHEME, iterations, salt, expected] = parts.as_slice() else {
+        return false;
+    };
+    let (Ok(iterations), Ok(salt), Ok(expected)) = (iterations.parse::<u32>(), hex::decode(salt), hex::decode(expected))
+    else {
+        return false;
+    };
+    if iterations == 0 || expected.is_empty() {
+        return false;
+    }
+
+    let mut out = vec![0u8; expected.len()];
+    pbkdf2(password.as_bytes(), &salt, iterations, &mut out);
+    // Compare every byte so the time taken does not leak how much matched
+    out.iter().zip(expected.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pbkdf2_vectors() {
+        let mut out = [0u8; 32];
+        pbkdf2(b"password", b"salt", 1, &mut out);
+        assert_eq!(hex::encode(out), "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+        pbkdf2(b"password", b"salt", 2, &mut out);
+        assert_eq!(hex::encode(out), "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43");
+    }
+
+    #[test]
+    fn test_hash_and_verify() {
+        let stored = format!("{}$2${}${}", SCHEME, hex::encode("salt"), "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43");
+        assert!(verify("password", &stored));
+        assert!(!verify("Password", &stored));
+        assert!(!verify("password", "plain"));
+        assert!(!verify("password", "md5$2$00$00"));
+
+        let stored = hash("s3cret");
+        assert!(stored.starts_with("pbkdf2_sha256$310000$"));
+        assert!(verify("s3cret", &stored));
+        assert_ne!(stored, hash("s3cret"));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::entities::property;
+use crate::middleware::jwt::Claims;
+use crate::repository::property_repo::{self, PropertyOwner};
+use crate::services::versioning::WriteError;
+use crate::services::{audit_service, map_txn_err};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use tracing::{error, info, instrument};
+
+/// Properties a caller may see and change
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyScope {
+    All,
+    Only(Vec<i32>),
+}
+
+impl PropertyScope {
+    /// Admin users from the database are limited to their assigned properties; tenants
+    /// get none, and reach their own rows only where a handler checks ownership
+    pub fn for_claims(claims: &Claims) -> Self {
+        match (claims.is_admin(), &claims.properties) {
+            (true, None) => PropertyScope::All,
+            (true, Some(ids)) => PropertyScope::Only(ids.clone()),
+            (false, _) => PropertyScope::Only(Vec::new()),
+        }
+    }
+
+    /// Narrow to one property when the caller asks for it; `None` if it is out of scope
+    pub fn narrow(self, property_id: Option<i32>) -> Option<Self> {
+        match property_id {
+            None => Some(self),
+            Some(id) if self.allows(id) => Some(PropertyScope::Only(vec![id])),
+            Some(_) => None,
+        }
+    }
+
+    pub fn allows(&self, property_id: i32) -> bool {
+        match self {
+            PropertyScope::All => true,
+            PropertyScope::Only(ids) => ids.contains(&property_id),
+        }
+    }
+
+    /// `None` when every property is in scope
+    pub fn ids(&self) -> Option<&[i32]> {
+        match self {
+            PropertyScope::All => None,
+            PropertyScope::Only(ids) => Some(ids),
+        }
+    }
+}
+
+/// GET the properties in scope
+pub async fn get_properties(db: &DatabaseConnection, scope: &PropertyScope) -> Result<Vec<property::Model>, DbErr> {
+    property_repo::get_all(db, scope.ids()).await
+}
+
+/// GET property by ID
+pub async fn get_property(db: &DatabaseConnection, id: i32) -> Result<Option<property::Model>, DbErr> {
+    property_repo::get_by_id(db, id).await
+}
+
+/// The property new rooms and imports go to when none is named: the oldest one in scope
+pub async fn default_property_id(db: &DatabaseConnection, scope: &PropertyScope) -> Result<Option<i32>, DbErr> {
+    match scope.ids() {
+        Some(ids) => Ok(ids.iter().min().copied()),
+        None => Ok(property_repo::get_default(db).await?.map(|p| p.id)),
+    }
+}
+
+/// Whether a row is in scope; `None` if the row does not exist
+pub async fn owner_in_scope(
+    db: &DatabaseConnection,
+    scope: &PropertyScope,
+    owner: PropertyOwner,
+) -> Result<Option<bool>, DbErr> {
+    if *scope == PropertyScope::All {
+        return Ok(Some(true));
+    }
+    Ok(property_repo::property_id_of(db, owner).await?.map(|id| scope.allows(id)))
+}
+
+/// Whether a tenant may reach a row: their own record and the readings and bills raised for
+/// them, never rooms or master readings. `None` when the row does not exist
+pub async fn owned_by_tenant(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    owner: PropertyOwner,
+) -> Result<Option<bool>, DbErr> {
+    if let PropertyOwner::Room(_) | PropertyOwner::MasterReading(_) = owner {
+        return Ok(Some(false));
+    }
+    Ok(property_repo::tenant_id_of(db, owner).await?.map(|id| claims.id == Some(id)))
+}
+
+/// CREATE a property
+#[instrument(skip_all)]
+pub async fn create_property(
+    db: &DatabaseConnection,
+    actor: &str,
+    item: property::ActiveModel,
+) -> Result<property::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, property::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let created = property_repo::create(txn, item).await?;
+                audit_service::record_create(txn, &actor, "property", created.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(p) => info!(property_id = p.id, name = %p.name, "created property"),
+        Err(err) => error!(error = ?err, "error creating property"),
+    }
+    result
+}
+
+/// UPDATE a property; `DbErr::RecordNotUpdated` when it does not exist
+#[instrument(skip_all)]
+pub async fn update_property(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    item: property::ActiveModel,
+) -> Result<property::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, property::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = property_repo::get_by_id(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                let updated = property_repo::update(txn, id, item).await?;
+                audit_service::record_update(txn, &actor, "property", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(p) => info!(property_id = p.id, "updated property"),
+        Err(err) => error!(property_id = id, error = ?err, "error updating property"),
+    }
+    result
+}
+
+/// DELETE a property that no longer has rooms, trashed ones included
+#[instrument(skip_all)]
+pub async fn delete_property(db: &DatabaseConnection, actor: &str, id: i32) -> Result<property::Model, WriteError> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, property::Model, WriteError>(|txn| {
+            Box::pin(async move {
+                if property_repo::count_rooms(txn, id).await? > 0 {
+                    return Err(WriteError::InvalidState("property still has rooms".into()));
+                }
+                let deleted = property_repo::delete(txn, id).await?.ok_or(WriteError::NotFound)?;
+                audit_service::record_delete(txn, &actor, "property", id, &deleted).await?;
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(p) => info!(property_id = p.id, name = %p.name, "deleted property"),
+        Err(err) => error!(property_id = id, error = ?err, "error deleting property"),
+    }
+    result
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(role: &str, properties: Option<Vec<i32>>) -> Claims {
+        Claims { id: None, name: Some("x".into()), role: Some(role.into()), properties, exp: 0 }
+    }
+
+    #[test]
+    fn test_scope_for_claims() {
+        assert_eq!(PropertyScope::for_claims(&claims("admin", None)), PropertyScope::All);
+        let limited = PropertyScope::for_claims(&claims("admin", Some(vec![2, 3])));
+        assert_eq!(limited.ids(), Some(&[2, 3][..]));
+        assert!(limited.allows(3) && !limited.allows(1));
+
+        assert_eq!(limited.clone().narrow(None), Some(limited.clone()));
+        assert_eq!(limited.clone().narrow(Some(2)), Some(PropertyScope::Only(vec![2])));
+        assert_eq!(limited.narrow(Some(1)), None);
+        assert_eq!(PropertyScope::All.narrow(Some(1)), Some(PropertyScope::Only(vec![1])));
+
+        let tenant = PropertyScope::for_claims(&claims("tenant", None));
+        assert_eq!(tenant.ids(), Some(&[][..]));
+        assert_eq!(tenant.narrow(Some(1)), None);
+    }
+}
@@ -1,13 +1,16 @@
 use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::delete_object::DeleteObjectError;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use aws_sdk_s3::operation::put_object::PutObjectError;
 use aws_sdk_s3::{
     Client, config::Credentials, presigning::PresigningConfig, primitives::ByteStream,
 };
 use aws_types::region::Region;
+use crate::config::R2Settings;
 use axum::body::Bytes;
-use std::env;
 use std::time::{Duration, SystemTime};
+use crate::middleware::logging::url_origin;
+use tracing::{debug, info, instrument};
 
 /// Shared R2 client data
 #[derive(Clone)]
@@ -17,40 +20,40 @@ pub struct R2Config {
 }
 
 /// Initialize the R2 client once
-pub async fn init_r2() -> R2Config {
-    let endpoint = env::var("R2_ENDPOINT").expect("R2_ENDPOINT missing");
-    let bucket = env::var("R2_BUCKET_NAME").expect("R2_BUCKET_NAME missing");
-    let access_key = env::var("R2_ACCESS_KEY_ID").expect("R2_ACCESS_KEY_ID missing");
-    let secret_key = env::var("R2_SECRET_ACCESS_KEY").expect("R2_SECRET_ACCESS_KEY missing");
-
-    println!(
-        "🔑 Initializing R2 client for bucket '{}' at endpoint '{}'",
-        bucket, endpoint
+pub async fn init_r2(settings: &R2Settings) -> R2Config {
+    info!(bucket = %settings.bucket_name, endpoint = url_origin(&settings.endpoint), "initializing R2 client");
+
+    let creds = Credentials::new(
+        settings.access_key_id.expose(),
+        settings.secret_access_key.expose(),
+        None,
+        None,
+        "r2",
     );
-
-    let creds = Credentials::new(&access_key, &secret_key, None, None, "r2");
     let config = aws_sdk_s3::config::Builder::new()
         .region(Region::new("auto"))
         .credentials_provider(creds)
-        .endpoint_url(endpoint)
+        .endpoint_url(&settings.endpoint)
         .build();
 
     let client = Client::from_conf(config);
 
-    println!("✅ R2 client initialized successfully");
+    info!("R2 client initialized");
 
     R2Config {
         client,
-        bucket,
+        bucket: settings.bucket_name.clone(),
     }
 }
 
+#[instrument(skip(r2, bytes))]
 pub async fn upload_file(
     r2: &R2Config,
     bytes: Bytes,
     key: &str,
     mime_type: &str,
 ) -> Result<String, SdkError<PutObjectError>> {
+    let byte_len = bytes.len();
     let byte_stream = ByteStream::from(bytes);
 
     // Upload to R2
@@ -63,28 +66,32 @@ pub async fn upload_file(
         .send()
         .await?;
 
-    let last_segment = key
-        .rsplit('/')
-        .next()
-        .unwrap_or(key)
-        .to_string();
+    info!(size = byte_len, "uploaded file to R2");
 
-    println!("✅ File uploaded to R2: {}", last_segment);
+    Ok(key.to_string())
+}
 
-    Ok(last_segment)
+#[instrument(skip(r2))]
+pub async fn delete_file(r2: &R2Config, key: &str) -> Result<(), SdkError<DeleteObjectError>> {
+    r2.client
+        .delete_object()
+        .bucket(&r2.bucket)
+        .key(key)
+        .send()
+        .await?;
+
+    info!("deleted file from R2");
+
+    Ok(())
 }
 
-/// Generate a signed URL
+/// Generate a signed URL; the URL itself is a credential and never logged
+#[instrument(skip(r2))]
 pub async fn get_signed_url(
     r2: &R2Config,
     key: &str,
     expires_secs: u64,
 ) -> Result<String, SdkError<GetObjectError>> {
-    println!(
-        "🔑 Generating signed URL for '{}', expires in {} seconds",
-        key, expires_secs
-    );
-
     let presign_config = PresigningConfig::builder()
         .expires_in(Duration::from_secs(expires_secs))
         .start_time(SystemTime::now())
@@ -99,8 +106,7 @@ pub async fn get_signed_url(
         .presigned(presign_config)
         .await?;
 
-    let url = get_obj.uri().to_string();
-    println!("✅ Signed URL generated: {}", url);
+    debug!("generated signed URL");
 
-    Ok(url)
+    Ok(get_obj.uri().to_string())
 }
@@ -0,0 +1,286 @@
+use crate::entities::{electricity_reading, room, tenant};
+use crate::money::{self, Currency};
+use crate::repository::electricity_reading_repo;
+use crate::repository::{property_repo, room_repo};
+use crate::repository::tenant_repo::{self, TenantFilter};
+use crate::services::bill_service::{self, BillInput};
+use crate::services::electricity_reading_service;
+use crate::services::versioning::WriteError;
+use chrono::Utc;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::{info, instrument};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchReadingItem {
+    pub room_id: i32,
+    /// Defaults to the room's only active tenant
+    pub tenant_id: Option<i32>,
+    /// Defaults to the room's last reading, or 0 for a new meter
+    pub prev_reading: Option<i32>,
+    pub curr_reading: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchReadingInput {
+    pub readings: Vec<BatchReadingItem>,
+    /// Create a draft bill for every reading saved
+    #[serde(default)]
+    pub generate_bills: bool,
+    /// Price per kWh for `generate_bills`; defaults to each room's property rate
+    #[serde(default, with = "money::decimal_option")]
+    pub rate: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    /// Position in the request
+    pub index: usize,
+    pub room_id: i32,
+    pub reading: Option<electricity_reading::Model>,
+    pub bill_id: Option<i32>,
+    /// Why the reading was not saved
+    pub error: Option<String>,
+    /// Why the bill was not created; the reading is kept
+    pub bill_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub created: usize,
+    pub failed: usize,
+    pub bills: usize,
+    pub results: Vec<BatchItemResult>,
+}
+
+/// The tenant a reading is charged to: the given one if they live in the room,
+/// otherwise the room's only active tenant
+async fn resolve_tenant(
+    db: &DatabaseConnection,
+    room: &room::Model,
+    tenant_id: Option<i32>,
+) -> Result<Result<tenant::Model, String>, DbErr> {
+    if let Some(id) = tenant_id {
+        return Ok(match tenant_repo::get_by_id(db, id).await? {
+            Some(t) if t.room_id == room.id => Ok(t),
+            Some(_) => Err(format!("tenant {} does not live in room {}", id, room.name)),
+            None => Err(format!("tenant {} does not exist", id)),
+        });
+    }
+
+    let filter = TenantFilter { room_id: Some(room.id), is_active: Some(true), ..Default::default() };
+    let mut tenants = tenant_repo::get_filtered(db, &filter).await?;
+    Ok(match tenants.len() {
+        1 => Ok(tenants.remove(0)),
+        0 => Err(format!("room {} has no active tenant", room.name)),
+        n => Err(format!("room {} has {} active tenants; give tenant_id", room.name, n)),
+    })
+}
+
+/// Check one item and build its reading, with the previous value filled in
+async fn prepare(
+    db: &DatabaseConnection,
+    item: &BatchReadingItem,
+) -> Result<Result<(room::Model, electricity_reading::ActiveModel), String>, DbErr> {
+    let Some(room) = room_repo::get_by_id(db, item.room_id).await? else {
+        return Ok(Err(format!("room {} does not exist", item.room_id)));
+    };
+    let tenant = match resolve_tenant(db, &room, item.tenant_id).await? {
+        Ok(tenant) => tenant,
+        Err(msg) => return Ok(Err(msg)),
+    };
+    let prev = match item.prev_reading {
+        Some(prev) => prev,
+        None => electricity_reading_repo::get_recent_for_room(db, room.id, Utc::now().naive_utc(), 0, 1)
+            .await?
+            .first()
+            .map(|r| r.curr_reading)
+            .unwrap_or(0),
+    };
+    if prev < 0 {
+        return Ok(Err("prev_reading must not be negative".into()));
+    }
+    if item.curr_reading < prev {
+        return Ok(Err(format!("curr_reading {} is below the previous reading {}", item.curr_reading, prev)));
+    }
+
+    let reading = electricity_reading::ActiveModel {
+        tenant_id: Set(tenant.id),
+        room_id: Set(room.id),
+        prev_reading: Set(prev),
+        curr_reading: Set(item.curr_reading),
+        ..Default::default()
+    };
+    Ok(Ok((room, reading)))
+}
+
+/// Draft bill for a fresh reading: the room's rent plus consumption at `rate`
+async fn bill_reading(
+    db: &DatabaseConnection,
+    actor: &str,
+    room: &room::Model,
+    reading: &electricity_reading::Model,
+    rate: i64,
+) -> Result<i32, WriteError> {
+    let electric_charges = (reading.consumption as i64)
+        .checked_mul(rate)
+        .ok_or_else(|| WriteError::Invalid("electric charges overflow".into()))?;
+    // Checked when the room was saved
+    let currency = Currency::parse(&room.currency)
+        .ok_or_else(|| WriteError::Invalid(format!("room {} has unsupported currency '{}'", room.name, room.currency)))?;
+    let input = BillInput {
+        tenant_id: reading.tenant_id,
+        reading_id: reading.id,
+        currency,
+        room_charges: room.rent,
+        electric_charges,
+        additional_charges: Vec::new(),
+        discounts: Vec::new(),
+        receipt_url: None,
+    };
+    bill_service::create_bill(db, actor, input).await.map(|details| details.bill.id)
+}
+
+/// Save many readings at once. Each item stands alone: a bad item is reported
+/// and skipped without holding up the rest.
+#[instrument(skip_all)]
+pub async fn create_batch(
+    db: &DatabaseConnection,
+    actor: &str,
+    input: BatchReadingInput,
+) -> Result<BatchResult, WriteError> {
+    if input.rate.is_some_and(|rate| rate < 0) {
+        return Err(WriteError::Invalid("rate must not be negative".into()));
+    }
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(input.readings.len());
+    for (index, item) in input.readings.iter().enumerate() {
+        let mut result = BatchItemResult {
+            index,
+            room_id: item.room_id,
+            reading: None,
+            bill_id: None,
+            error: None,
+            bill_error: None,
+        };
+
+        let prepared = if seen.insert(item.room_id) {
+            prepare(db, item).await?
+        } else {
+            Err(format!("room {} appears more than once in the batch", item.room_id))
+        };
+        match prepared {
+            Ok((room, active_model)) => match electricity_reading_service::create_reading(db, actor, active_model).await {
+                Ok(reading) => {
+                    if input.generate_bills {
+                        let rate = match input.rate {
+                            Some(rate) => Some(rate),
+                            None => property_repo::get_by_id(db, room.property_id)
+                                .await?
+                                .and_then(|p| p.electric_rate),
+                        };
+                        let billed = match rate {
+                            Some(rate) => bill_reading(db, actor, &room, &reading, rate).await,
+                            None => Err(WriteError::Invalid(format!(
+                                "no rate given and room {}'s property has no electric rate",
+                                room.name
+                            ))),
+                        };
+                        match billed {
+                            Ok(bill_id) => result.bill_id = Some(bill_id),
+                            Err(err) => result.bill_error = Some(err.to_string()),
+                        }
+                    }
+                    result.reading = Some(reading);
+                }
+                Err(err) => result.error = Some(err.to_string()),
+            },
+            Err(msg) => result.error = Some(msg),
+        }
+        results.push(result);
+    }
+
+    let created = results.iter().filter(|r| r.reading.is_some()).count();
+    let bills = results.iter().filter(|r| r.bill_id.is_some()).count();
+    let failed = results.len() - created;
+    info!(created, failed, bills, "saved reading batch");
+
+    Ok(BatchResult { created, failed, bills, results })
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::property;
+    use crate::repository::bill_repo;
+    use crate::test_utils::{self, add_tenant, get_test_db, reset_rooms};
+
+    async fn add_room(db: &DatabaseConnection, name: &str, tenants: usize) -> room::Model {
+        let room = test_utils::add_room(db, name).await;
+        for i in 0..tenants {
+            add_tenant(db, &room, &format!("{} tenant {}", name, i)).await;
+        }
+        room
+    }
+
+    #[tokio::test]
+    async fn test_batch_fills_previous_and_bills_each_reading() {
+        let db = get_test_db().await;
+        reset_rooms(&db).await;
+
+        let a = add_room(&db, "A", 1).await;
+        let b = add_room(&db, "B", 2).await;
+        let c = add_room(&db, "C", 1).await;
+        let first = BatchReadingItem { room_id: a.id, tenant_id: None, prev_reading: Some(100), curr_reading: 150 };
+        let input = BatchReadingInput { readings: vec![first], generate_bills: false, rate: None };
+        assert_eq!(create_batch(&db, "admin:root", input).await.unwrap().created, 1);
+
+        let input = BatchReadingInput {
+            readings: vec![
+                BatchReadingItem { room_id: a.id, tenant_id: None, prev_reading: None, curr_reading: 190 },
+                BatchReadingItem { room_id: b.id, tenant_id: None, prev_reading: None, curr_reading: 10 },
+                BatchReadingItem { room_id: c.id, tenant_id: None, prev_reading: Some(50), curr_reading: 40 },
+                BatchReadingItem { room_id: a.id, tenant_id: None, prev_reading: None, curr_reading: 200 },
+            ],
+            generate_bills: true,
+            rate: Some(1_250),
+        };
+        let result = create_batch(&db, "admin:root", input).await.unwrap();
+        assert_eq!((result.created, result.failed, result.bills), (1, 3, 1));
+
+        let reading = result.results[0].reading.as_ref().unwrap();
+        assert_eq!((reading.prev_reading, reading.consumption), (150, 40));
+        let bill = bill_repo::get_by_id(&db, result.results[0].bill_id.unwrap()).await.unwrap().unwrap();
+        assert_eq!((bill.room_charges, bill.electric_charges), (a.rent, 50_000));
+
+        assert_eq!(result.results[1].error.as_deref(), Some("room B has 2 active tenants; give tenant_id"));
+        assert_eq!(result.results[2].error.as_deref(), Some("curr_reading 40 is below the previous reading 50"));
+        assert!(result.results[3].error.as_deref().unwrap().contains("more than once"));
+
+        let input = BatchReadingInput { readings: vec![], generate_bills: true, rate: Some(-1) };
+        assert!(matches!(create_batch(&db, "admin:root", input).await, Err(WriteError::Invalid(_))));
+
+        // Without a rate the property's tariff applies
+        property_repo::update(&db, a.property_id, property::ActiveModel {
+            electric_rate: Set(Some(1_000)),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let item = BatchReadingItem { room_id: a.id, tenant_id: None, prev_reading: None, curr_reading: 200 };
+        let input = BatchReadingInput { readings: vec![item], generate_bills: true, rate: None };
+        let result = create_batch(&db, "admin:root", input).await.unwrap();
+        let bill = bill_repo::get_by_id(&db, result.results[0].bill_id.unwrap()).await.unwrap().unwrap();
+        assert_eq!(bill.electric_charges, 10_000);
+        property_repo::update(&db, a.property_id, property::ActiveModel {
+            electric_rate: Set(None),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+}
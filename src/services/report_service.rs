@@ -0,0 +1,221 @@
+use crate::money;
+use crate::repository::report_repo::{self, ElectricityRow, MonthlyIncomeRow, ReceivableRow, RoomRevenueRow};
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+
+/// Billed vs. collected in one month and currency
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MonthlyIncome {
+    pub month: NaiveDate,
+    pub currency: String,
+    pub bills: i64,
+    #[serde(with = "money::decimal")]
+    pub billed: i64,
+    /// Debit notes less credit notes written in the month
+    #[serde(with = "money::decimal")]
+    pub adjustments: i64,
+    #[serde(with = "money::decimal")]
+    pub collected: i64,
+    /// `billed + adjustments - collected`
+    #[serde(with = "money::decimal")]
+    pub net: i64,
+}
+
+/// Outstanding balance of one tenant in one currency, by age of the bill
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Receivable {
+    pub tenant_id: i32,
+    pub tenant_name: String,
+    pub currency: String,
+    pub bills: i64,
+    #[serde(rename = "0_30", with = "money::decimal")]
+    pub days_0_30: i64,
+    #[serde(rename = "31_60", with = "money::decimal")]
+    pub days_31_60: i64,
+    #[serde(rename = "60_plus", with = "money::decimal")]
+    pub days_over_60: i64,
+    #[serde(with = "money::decimal")]
+    pub total: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReceivablesReport {
+    pub as_of: NaiveDateTime,
+    pub tenants: Vec<Receivable>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RoomRevenue {
+    pub room_id: i32,
+    pub room_name: String,
+    pub currency: String,
+    pub bills: i64,
+    #[serde(with = "money::decimal")]
+    pub rent: i64,
+    #[serde(with = "money::decimal")]
+    pub electricity: i64,
+    #[serde(with = "money::decimal")]
+    pub other_charges: i64,
+    #[serde(with = "money::decimal")]
+    pub discounts: i64,
+    #[serde(with = "money::decimal")]
+    pub total: i64,
+}
+
+/// Electricity billed against kWh consumed in one month and currency
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ElectricityRevenue {
+    pub month: NaiveDate,
+    pub currency: String,
+    pub readings: i64,
+    /// kWh
+    pub consumption: i64,
+    #[serde(with = "money::decimal")]
+    pub revenue: i64,
+    /// Revenue per kWh rounded to the minor unit; absent when nothing was consumed
+    #[serde(with = "money::decimal_option")]
+    pub average_rate: Option<i64>,
+}
+
+impl From<MonthlyIncomeRow> for MonthlyIncome {
+    fn from(row: MonthlyIncomeRow) -> Self {
+        MonthlyIncome {
+            net: row.billed + row.adjustments - row.collected,
+            month: row.month,
+            currency: row.currency,
+            bills: row.bills,
+            billed: row.billed,
+            adjustments: row.adjustments,
+            collected: row.collected,
+        }
+    }
+}
+
+impl From<ReceivableRow> for Receivable {
+    fn from(row: ReceivableRow) -> Self {
+        Receivable {
+            tenant_id: row.tenant_id,
+            tenant_name: row.tenant_name,
+            currency: row.currency,
+            bills: row.bills,
+            days_0_30: row.days_0_30,
+            days_31_60: row.days_31_60,
+            days_over_60: row.days_over_60,
+            total: row.total,
+        }
+    }
+}
+
+impl From<RoomRevenueRow> for RoomRevenue {
+    fn from(row: RoomRevenueRow) -> Self {
+        RoomRevenue {
+            room_id: row.room_id,
+            room_name: row.room_name,
+            currency: row.currency,
+            bills: row.bills,
+            rent: row.rent,
+            electricity: row.electricity,
+            other_charges: row.other_charges,
+            discounts: row.discounts,
+            total: row.total,
+        }
+    }
+}
+
+impl From<ElectricityRow> for ElectricityRevenue {
+    fn from(row: ElectricityRow) -> Self {
+        ElectricityRevenue {
+            average_rate: average_rate(row.revenue, row.consumption),
+            month: row.month,
+            currency: row.currency,
+            readings: row.readings,
+            consumption: row.consumption,
+            revenue: row.revenue,
+        }
+    }
+}
+
+/// `revenue / consumption`, rounded half up
+pub fn average_rate(revenue: i64, consumption: i64) -> Option<i64> {
+    if consumption <= 0 {
+        return None;
+    }
+    let (revenue, consumption) = (revenue as i128, consumption as i128);
+    Some(((revenue * 2 + consumption) / (consumption * 2)) as i64)
+}
+
+pub async fn get_monthly_income(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<MonthlyIncome>, DbErr> {
+    let rows = report_repo::monthly_income(db, from, to, property_ids).await?;
+    Ok(rows.into_iter().map(MonthlyIncome::from).collect())
+}
+
+/// Aged as of the end of the period, or now when it is open-ended
+pub async fn get_receivables(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<ReceivablesReport, DbErr> {
+    let as_of = to.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let rows = report_repo::receivables(db, from, to, as_of, property_ids).await?;
+    Ok(ReceivablesReport {
+        as_of,
+        tenants: rows.into_iter().map(Receivable::from).collect(),
+    })
+}
+
+pub async fn get_revenue_by_room(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<RoomRevenue>, DbErr> {
+    let rows = report_repo::revenue_by_room(db, from, to, property_ids).await?;
+    Ok(rows.into_iter().map(RoomRevenue::from).collect())
+}
+
+pub async fn get_electricity_revenue(
+    db: &DatabaseConnection,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+    property_ids: Option<&[i32]>,
+) -> Result<Vec<ElectricityRevenue>, DbErr> {
+    let rows = report_repo::electricity_by_month(db, from, to, property_ids).await?;
+    Ok(rows.into_iter().map(ElectricityRevenue::from).collect())
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_rate_rounds_half_up() {
+        assert_eq!(average_rate(75_000, 50), Some(1_500));
+        assert_eq!(average_rate(1_000, 3), Some(333));
+        assert_eq!(average_rate(1_000, 6), Some(167));
+        assert_eq!(average_rate(1_000, 0), None);
+    }
+
+    #[test]
+    fn test_monthly_income_nets_collections() {
+        let income = MonthlyIncome::from(MonthlyIncomeRow {
+            month: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            currency: "PHP".into(),
+            bills: 2,
+            billed: 300_000,
+            adjustments: -5_000,
+            collected: 200_000,
+        });
+        assert_eq!(income.net, 95_000);
+        let json = serde_json::to_value(&income).unwrap();
+        assert_eq!(json["billed"], "3000.00");
+        assert_eq!(json["net"], "950.00");
+    }
+}
@@ -1,55 +1,121 @@
 use crate::entities::room;
 use crate::repository::room_repo;
-use sea_orm::{DatabaseConnection, DbErr};
+use crate::services::versioning::{self, WriteError};
+use crate::services::{audit_service, map_txn_err};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Get all rooms
+#[instrument(skip_all)]
 pub async fn get_all_rooms(db: &DatabaseConnection) -> Result<Vec<room::Model>, DbErr> {
     let result = room_repo::get_all(db).await;
     match &result {
-        Ok(list) => println!("✅ get_all_rooms: fetched {} rooms", list.len()),
-        Err(err) => eprintln!("❌ get_all_rooms: error fetching rooms: {:?}", err),
+        Ok(list) => debug!(count = list.len(), "fetched rooms"),
+        Err(err) => error!(error = ?err, "error fetching rooms"),
     }
     result
 }
 
 /// Get room by ID 
+#[instrument(skip_all)]
 pub async fn get_room_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<room::Model>, DbErr> {
     let result = room_repo::get_by_id(db, id).await;
     match &result {
-        Ok(Some(r)) => println!("✅ get_room_by_id: found room id={} name={}", r.id, r.name),
-        Ok(None) => println!("⚠️ get_room_by_id: room id={} not found", id),
-        Err(err) => eprintln!("❌ get_room_by_id: error fetching room id={}: {:?}", id, err),
+        Ok(Some(r)) => debug!(room_id = r.id, name = %r.name, "found room"),
+        Ok(None) => warn!(room_id = id, "room not found"),
+        Err(err) => error!(room_id = id, error = ?err, "error fetching room"),
     }
     result
 }
 
+/// Insert a room and audit it, inside the caller's transaction
+pub async fn insert_room<C>(conn: &C, actor: &str, item: room::ActiveModel) -> Result<room::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let created = room_repo::create(conn, item).await?;
+    audit_service::record_create(conn, actor, "room", created.id, &created).await?;
+    Ok(created)
+}
+
 /// Create room 
-pub async fn create_room(db: &DatabaseConnection, item: room::ActiveModel) -> Result<room::Model, DbErr> {
-    let result = room_repo::create(db, item).await;
+#[instrument(skip_all)]
+pub async fn create_room(db: &DatabaseConnection, actor: &str, item: room::ActiveModel) -> Result<room::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, room::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                insert_room(txn, &actor, item).await
+            })
+        })
+        .await,
+    );
     match &result {
-        Ok(r) => println!("✅ create_room: created room id={} name={}", r.id, r.name),
-        Err(err) => eprintln!("❌ create_room: error creating room: {:?}", err),
+        Ok(r) => info!(room_id = r.id, name = %r.name, "created room"),
+        Err(err) => error!(error = ?err, "error creating room"),
     }
     result
 }
 
-/// Update room 
-pub async fn update_room(db: &DatabaseConnection, id: i32, item: room::ActiveModel) -> Result<room::Model, DbErr> {
-    let result = room_repo::update(db, id, item).await;
+/// Update room if it is still at `expected_version`
+#[instrument(skip_all)]
+pub async fn update_room(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    expected_version: Option<i32>,
+    item: room::ActiveModel,
+) -> Result<room::Model, WriteError> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, room::Model, WriteError>(|txn| {
+            Box::pin(async move {
+                let before = room_repo::get_by_id(txn, id).await?.ok_or(WriteError::NotFound)?;
+                versioning::check_version(expected_version, before.version)?;
+                let updated = room_repo::update(txn, id, before.version, item)
+                    .await
+                    .map_err(versioning::map_guarded_update_err)?;
+                audit_service::record_update(txn, &actor, "room", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
     match &result {
-        Ok(r) => println!("✅ update_room: updated room id={} name={}", r.id, r.name),
-        Err(err) => eprintln!("❌ update_room: error updating room id={}: {:?}", id, err),
+        Ok(r) => info!(room_id = r.id, name = %r.name, "updated room"),
+        Err(err) => error!(room_id = id, error = ?err, "error updating room"),
     }
     result
 }
 
-/// Delete room 
-pub async fn delete_room(db: &DatabaseConnection, id: i32) -> Result<Option<room::Model>, DbErr> {
-    let result = room_repo::delete(db, id).await;
+/// Delete room if it is still at `expected_version`
+#[instrument(skip_all)]
+pub async fn delete_room(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    expected_version: Option<i32>,
+) -> Result<Option<room::Model>, WriteError> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<room::Model>, WriteError>(|txn| {
+            Box::pin(async move {
+                if let Some(current) = room_repo::get_by_id(txn, id).await? {
+                    versioning::check_version(expected_version, current.version)?;
+                }
+                let deleted = room_repo::delete(txn, id).await?;
+                if let Some(r) = &deleted {
+                    audit_service::record_delete(txn, &actor, "room", id, r).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
     match &result {
-        Ok(Some(r)) => println!("✅ delete_room: deleted room id={} name={}", r.id, r.name),
-        Ok(None) => println!("⚠️ delete_room: room id={} not found", id),
-        Err(err) => eprintln!("❌ delete_room: error deleting room id={}: {:?}", id, err),
+        Ok(Some(r)) => info!(room_id = r.id, name = %r.name, "deleted room"),
+        Ok(None) => warn!(room_id = id, "room not found"),
+        Err(err) => error!(room_id = id, error = ?err, "error deleting room"),
     }
     result
 }
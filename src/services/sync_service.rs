@@ -0,0 +1,171 @@
+use crate::entities::{additional_charge, bill, bill_discount, bill_note, electricity_reading, room, tenant};
+use crate::repository::{
+    additional_charge_repo, bill_discount_repo, bill_note_repo, bill_repo, electricity_reading_repo, room_repo, tenant_repo,
+};
+use crate::services::trash_service;
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+use tracing::{info, instrument};
+
+/// Rows written by transactions still open during a sync can commit with an earlier
+/// `updated_at`; handing out a cursor slightly in the past picks them up next time
+const SYNC_OVERLAP_SECS: i64 = 30;
+
+#[derive(Debug, Default, Serialize)]
+pub struct DeletedIds {
+    pub rooms: Vec<i32>,
+    pub tenants: Vec<i32>,
+    pub readings: Vec<i32>,
+    pub bills: Vec<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncChanges {
+    /// Send back as `since` on the next call; rows may repeat, so upsert by id
+    pub next_since: NaiveDateTime,
+    /// Set when this is a full snapshot and the local copy should be replaced
+    pub full: bool,
+    pub rooms: Vec<room::Model>,
+    pub tenants: Vec<tenant::Model>,
+    pub readings: Vec<electricity_reading::Model>,
+    pub bills: Vec<bill::Model>,
+    /// Complete charge lists for every bill in `bills`
+    pub additional_charges: Vec<additional_charge::Model>,
+    /// Complete discount lists for every bill in `bills`
+    pub bill_discounts: Vec<bill_discount::Model>,
+    pub bill_notes: Vec<bill_note::Model>,
+    /// Rows moved to the trash since the last sync
+    pub deleted: DeletedIds,
+}
+
+/// A full snapshot is needed on first sync, or once trashed rows the client
+/// has not heard about may already have been purged
+pub fn needs_full_sync(since: Option<NaiveDateTime>, now: NaiveDateTime, retention_days: i64) -> bool {
+    match since {
+        Some(since) => since < trash_service::retention_cutoff(now, retention_days),
+        None => true,
+    }
+}
+
+/// GET everything changed after `since`, or a full snapshot
+#[instrument(skip_all)]
+pub async fn get_changes(
+    db: &DatabaseConnection,
+    since: Option<NaiveDateTime>,
+    retention_days: i64,
+) -> Result<SyncChanges, DbErr> {
+    let now = Utc::now().naive_utc();
+    let next_since = now - Duration::seconds(SYNC_OVERLAP_SECS);
+
+    let changes = match since.filter(|_| !needs_full_sync(since, now, retention_days)) {
+        None => {
+            let bills = bill_repo::get_all(db).await?;
+            SyncChanges {
+                next_since,
+                full: true,
+                rooms: room_repo::get_all(db).await?,
+                tenants: tenant_repo::get_all(db).await?,
+                readings: electricity_reading_repo::get_all(db).await?,
+                additional_charges: additional_charge_repo::get_all_by_bill_ids(db, bills.iter().map(|b| b.id).collect()).await?,
+                bill_discounts: bill_discount_repo::get_all_by_bill_ids(db, bills.iter().map(|b| b.id).collect()).await?,
+                bills,
+                bill_notes: bill_note_repo::get_all(db).await?,
+                deleted: DeletedIds::default(),
+            }
+        }
+        Some(since) => {
+            let mut deleted = DeletedIds::default();
+
+            let (rooms, trashed): (Vec<_>, Vec<_>) =
+                room_repo::get_changed_since(db, since).await?.into_iter().partition(|r| r.deleted_at.is_none());
+            deleted.rooms = trashed.iter().map(|r| r.id).collect();
+
+            let (tenants, trashed): (Vec<_>, Vec<_>) =
+                tenant_repo::get_changed_since(db, since).await?.into_iter().partition(|t| t.deleted_at.is_none());
+            deleted.tenants = trashed.iter().map(|t| t.id).collect();
+
+            let (readings, trashed): (Vec<_>, Vec<_>) = electricity_reading_repo::get_changed_since(db, since)
+                .await?
+                .into_iter()
+                .partition(|r| r.deleted_at.is_none());
+            deleted.readings = trashed.iter().map(|r| r.id).collect();
+
+            let (bills, trashed): (Vec<_>, Vec<_>) =
+                bill_repo::get_changed_since(db, since).await?.into_iter().partition(|b| b.deleted_at.is_none());
+            deleted.bills = trashed.iter().map(|b| b.id).collect();
+
+            SyncChanges {
+                next_since,
+                full: false,
+                rooms,
+                tenants,
+                readings,
+                additional_charges: additional_charge_repo::get_all_by_bill_ids(db, bills.iter().map(|b| b.id).collect()).await?,
+                bill_discounts: bill_discount_repo::get_all_by_bill_ids(db, bills.iter().map(|b| b.id).collect()).await?,
+                bills,
+                bill_notes: bill_note_repo::get_changed_since(db, since).await?,
+                deleted,
+            }
+        }
+    };
+
+    info!(
+        full = changes.full,
+        ?since,
+        rooms = changes.rooms.len(),
+        tenants = changes.tenants.len(),
+        readings = changes.readings.len(),
+        bills = changes.bills.len(),
+        "collected changes"
+    );
+    Ok(changes)
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_full_sync() {
+        let now = Utc::now().naive_utc();
+        assert!(needs_full_sync(None, now, 30));
+        assert!(!needs_full_sync(Some(now - Duration::hours(1)), now, 30));
+        // Older than the trash retention
+        assert!(needs_full_sync(Some(now - Duration::days(45)), now, 30));
+    }
+
+    #[tokio::test]
+    async fn test_sync_sees_writes_from_other_time_zones() {
+        use crate::test_utils::{get_test_db, reset_table};
+        use sea_orm::{ActiveModelTrait, ActiveValue::Set, ConnectionTrait, TransactionTrait};
+
+        let db = get_test_db().await;
+        reset_table(&db, "room").await;
+        let since = Utc::now().naive_utc() - Duration::seconds(5);
+
+        // A session seven hours behind UTC; local timestamps would land before `since`
+        let txn = db.begin().await.unwrap();
+        txn.execute_unprepared("SET LOCAL TIME ZONE 'America/Los_Angeles'").await.unwrap();
+        let inserted = room::ActiveModel { property_id: Set(1), name: Set("TZ1".into()), rent: Set(1), ..Default::default() }
+            .insert(&txn)
+            .await
+            .unwrap();
+        let updated = room::ActiveModel { property_id: Set(1), name: Set("TZ2".into()), rent: Set(1), ..Default::default() }
+            .insert(&db)
+            .await
+            .unwrap();
+        room::ActiveModel { id: Set(updated.id), rent: Set(2), ..Default::default() }.update(&txn).await.unwrap();
+        txn.commit().await.unwrap();
+
+        let changes = get_changes(&db, Some(since), 30).await.unwrap();
+        assert!(!changes.full);
+        assert_eq!(changes.rooms.iter().map(|r| r.id).collect::<Vec<_>>(), vec![inserted.id, updated.id]);
+        let now = Utc::now().naive_utc();
+        for room in &changes.rooms {
+            assert!((now - room.created_at).num_minutes().abs() < 1);
+            assert!((now - room.updated_at).num_minutes().abs() < 1);
+        }
+    }
+}
@@ -0,0 +1,497 @@
+use crate::config::TemplateConfig;
+use crate::entities::{bill_template, property};
+use crate::money::{self, Money};
+use crate::repository::bill_template_repo;
+use crate::services::document_service::{self, BillDocumentData};
+use crate::services::{audit_service, map_txn_err};
+use minijinja::{AutoEscape, Environment, ErrorKind, Value};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde::Serialize;
+use serde_json::json;
+use tracing::{error, info, instrument};
+
+const BUILTIN_HTML: &str = include_str!("../../templates/bill.html");
+const BUILTIN_TEXT: &str = include_str!("../../templates/bill.txt");
+const DEFAULT_LOCALE: &str = "en";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    Html,
+    Text,
+}
+
+impl RenderFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "html" => Some(RenderFormat::Html),
+            "text" | "txt" => Some(RenderFormat::Text),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderFormat::Html => "html",
+            RenderFormat::Text => "text",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            RenderFormat::Html => "text/html; charset=utf-8",
+            RenderFormat::Text => "text/plain; charset=utf-8",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            RenderFormat::Html => "html",
+            RenderFormat::Text => "txt",
+        }
+    }
+
+    fn builtin(&self) -> &'static str {
+        match self {
+            RenderFormat::Html => BUILTIN_HTML,
+            RenderFormat::Text => BUILTIN_TEXT,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    BillNotFound,
+    Template(String),
+    Db(DbErr),
+}
+
+impl From<DbErr> for RenderError {
+    fn from(err: DbErr) -> Self {
+        RenderError::Db(err)
+    }
+}
+
+impl From<minijinja::Error> for RenderError {
+    fn from(err: minijinja::Error) -> Self {
+        RenderError::Template(err.to_string())
+    }
+}
+
+/// Property branding shown on rendered bills; the configured `BRAND_*` defaults fill whatever
+/// the property leaves unset
+#[derive(Debug, Clone, Serialize)]
+pub struct Branding {
+    pub name: String,
+    pub address: Option<String>,
+    pub contact: Option<String>,
+    pub logo_url: Option<String>,
+    pub accent_color: String,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Branding {
+            name: "M18 Residences".into(),
+            address: None,
+            contact: None,
+            logo_url: None,
+            accent_color: "#1f4e79".into(),
+        }
+    }
+}
+
+impl Branding {
+    pub fn for_property(defaults: &Branding, property: Option<&property::Model>) -> Self {
+        let Some(property) = property else {
+            return defaults.clone();
+        };
+        let set = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+
+        Branding {
+            name: property.name.clone(),
+            address: set(&property.address).or_else(|| defaults.address.clone()),
+            contact: set(&property.contact).or_else(|| defaults.contact.clone()),
+            logo_url: set(&property.logo_url).or_else(|| defaults.logo_url.clone()),
+            accent_color: set(&property.accent_color).unwrap_or_else(|| defaults.accent_color.clone()),
+        }
+    }
+}
+
+/// Labels and number formatting for a locale; the currency symbol comes from the bill
+pub struct Locale {
+    pub code: &'static str,
+    labels: &'static [(&'static str, &'static str)],
+    group_separator: char,
+    decimal_separator: char,
+}
+
+const EN_LABELS: &[(&str, &str)] = &[
+    ("invoice", "Statement of Account"),
+    ("tenant", "Tenant"),
+    ("room", "Room"),
+    ("period", "Period"),
+    ("status", "Status"),
+    ("paid", "Paid"),
+    ("unpaid", "Unpaid"),
+    ("electricity", "Electricity"),
+    ("previous_reading", "Previous reading"),
+    ("current_reading", "Current reading"),
+    ("consumption", "Consumption"),
+    ("rate", "Rate"),
+    ("charges", "Charges"),
+    ("room_rent", "Room rent"),
+    ("electricity_charges", "Electricity"),
+    ("discount", "Discount"),
+    ("total", "Total"),
+    ("payments", "Payments"),
+    ("balance", "Balance"),
+];
+
+const FIL_LABELS: &[(&str, &str)] = &[
+    ("invoice", "Pahayag ng Singil"),
+    ("tenant", "Nangungupahan"),
+    ("room", "Kuwarto"),
+    ("period", "Panahon"),
+    ("status", "Katayuan"),
+    ("paid", "Bayad na"),
+    ("unpaid", "Hindi pa bayad"),
+    ("electricity", "Kuryente"),
+    ("previous_reading", "Nakaraang metro"),
+    ("current_reading", "Kasalukuyang metro"),
+    ("consumption", "Nakonsumo"),
+    ("rate", "Halaga bawat kWh"),
+    ("charges", "Mga singil"),
+    ("room_rent", "Upa sa kuwarto"),
+    ("electricity_charges", "Kuryente"),
+    ("discount", "Diskwento"),
+    ("total", "Kabuuan"),
+    ("payments", "Mga bayad"),
+    ("balance", "Natitirang balanse"),
+];
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        code: "en",
+        labels: EN_LABELS,
+        group_separator: ',',
+        decimal_separator: '.',
+    },
+    Locale {
+        code: "fil",
+        labels: FIL_LABELS,
+        group_separator: ',',
+        decimal_separator: '.',
+    },
+];
+
+impl Locale {
+    /// Look up a supported locale, falling back to English
+    pub fn get(code: &str) -> &'static Locale {
+        LOCALES
+            .iter()
+            .find(|l| l.code.eq_ignore_ascii_case(code))
+            .unwrap_or(&LOCALES[0])
+    }
+
+    pub fn format_currency(&self, amount: Money) -> String {
+        let fixed = money::to_decimal_string(amount.minor());
+        let fixed = fixed.trim_start_matches('-');
+        let (whole, cents) = fixed.split_once('.').unwrap_or((fixed, "00"));
+
+        let mut grouped = String::new();
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(c);
+        }
+
+        let sign = if amount.minor() < 0 { "-" } else { "" };
+        let symbol = amount.currency().symbol();
+        format!("{}{}{}{}{}", sign, symbol, grouped, self.decimal_separator, cents)
+    }
+
+    fn labels_json(&self) -> serde_json::Value {
+        self.labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), json!(v)))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// Template source for a format and locale: database first, then `dir`, then built-in
+pub async fn load_template_source(
+    db: &DatabaseConnection,
+    dir: &str,
+    format: RenderFormat,
+    locale: &str,
+) -> Result<String, DbErr> {
+    for code in [locale, DEFAULT_LOCALE] {
+        if let Some(t) = bill_template_repo::get_by_format_and_locale(db, format.as_str(), code).await? {
+            return Ok(t.body);
+        }
+    }
+
+    for file in [
+        format!("{}/bill.{}.{}", dir, locale, format.extension()),
+        format!("{}/bill.{}", dir, format.extension()),
+    ] {
+        if let Ok(body) = tokio::fs::read_to_string(&file).await {
+            return Ok(body);
+        }
+    }
+
+    Ok(format.builtin().to_string())
+}
+
+/// Amounts reach templates as decimal strings from the models, or as plain numbers in major units
+fn template_minor_units(value: &Value) -> Result<i64, minijinja::Error> {
+    let invalid = |msg: String| minijinja::Error::new(ErrorKind::InvalidOperation, msg);
+    if let Some(text) = value.as_str() {
+        return money::parse_decimal(text).map_err(|e| invalid(e.to_string()));
+    }
+    let major = f64::try_from(value.clone()).map_err(|_| invalid(format!("{} is not an amount", value)))?;
+    Ok((major * 100.0).round() as i64)
+}
+
+/// Render bill data with the given template source
+pub fn render_with_template(
+    source: &str,
+    format: RenderFormat,
+    locale: &'static Locale,
+    branding: &Branding,
+    data: &BillDocumentData,
+) -> Result<String, RenderError> {
+    let mut env = Environment::new();
+    env.set_auto_escape_callback(move |_| match format {
+        RenderFormat::Html => AutoEscape::Html,
+        RenderFormat::Text => AutoEscape::None,
+    });
+    let currency = data.details.bill.currency();
+    env.add_filter("currency", move |amount: Value| -> Result<String, minijinja::Error> {
+        Ok(locale.format_currency(Money::new(template_minor_units(&amount)?, currency)))
+    });
+    env.add_template("bill", source)?;
+
+    let details = &data.details;
+    let balance = data.balance().map_err(|e| RenderError::Template(e.to_string()))?;
+    let ctx = json!({
+        "locale": locale.code,
+        "labels": locale.labels_json(),
+        "branding": branding,
+        "bill": details.bill,
+        "charges": details.additional_charges,
+        "discounts": details.discounts,
+        "notes": details.notes,
+        "reading": details.reading,
+        "tenant": data.tenant,
+        "room": data.room,
+        "period": details.reading.as_ref().map(|r| r.created_at.format("%B %Y").to_string()),
+        "rate": data.electric_rate(),
+        "amount_paid": money::to_decimal_string(data.amount_paid().minor()),
+        "balance": money::to_decimal_string(balance.minor()),
+    });
+
+    Ok(env.get_template("bill")?.render(ctx)?)
+}
+
+/// Render a bill as HTML or plain text
+#[instrument(skip_all)]
+pub async fn render_bill(
+    db: &DatabaseConnection,
+    templates: &TemplateConfig,
+    bill_id: i32,
+    format: RenderFormat,
+    locale: &str,
+) -> Result<String, RenderError> {
+    let data = document_service::load_document_data(db, bill_id)
+        .await?
+        .ok_or(RenderError::BillNotFound)?;
+
+    let locale = Locale::get(locale);
+    let source = load_template_source(db, &templates.dir, format, locale.code).await?;
+    let branding = Branding::for_property(&templates.branding, data.property.as_ref());
+    let result = render_with_template(&source, format, locale, &branding, &data);
+
+    if let Err(err) = &result {
+        error!(bill_id, error = ?err, "error rendering bill");
+    }
+
+    result
+}
+
+/// List templates stored in the database
+pub async fn get_all_templates(db: &DatabaseConnection) -> Result<Vec<bill_template::Model>, DbErr> {
+    bill_template_repo::get_all(db).await
+}
+
+/// Save a template after checking that it compiles
+#[instrument(skip_all)]
+pub async fn save_template(
+    db: &DatabaseConnection,
+    actor: &str,
+    format: RenderFormat,
+    locale: &str,
+    body: String,
+) -> Result<bill_template::Model, RenderError> {
+    Environment::new().template_from_str(&body)?;
+
+    let (actor, locale) = (actor.to_string(), locale.to_string());
+    let result = map_txn_err(
+        db.transaction::<_, bill_template::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = bill_template_repo::get_by_format_and_locale(txn, format.as_str(), &locale).await?;
+                let saved = bill_template_repo::upsert(txn, format.as_str(), &locale, body).await?;
+                match &before {
+                    Some(before) => {
+                        audit_service::record_update(txn, &actor, "bill_template", saved.id, before, &saved).await?
+                    }
+                    None => audit_service::record_create(txn, &actor, "bill_template", saved.id, &saved).await?,
+                }
+                Ok(saved)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(t) => info!(format = %t.format, locale = %t.locale, "saved template"),
+        Err(err) => error!(error = ?err, "error saving template"),
+    }
+
+    Ok(result?)
+}
+
+/// Delete a stored template so the on-disk default is used again
+pub async fn delete_template(
+    db: &DatabaseConnection,
+    actor: &str,
+    format: RenderFormat,
+    locale: &str,
+) -> Result<Option<bill_template::Model>, DbErr> {
+    let (actor, locale) = (actor.to_string(), locale.to_string());
+    map_txn_err(
+        db.transaction::<_, Option<bill_template::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let deleted = bill_template_repo::delete(txn, format.as_str(), &locale).await?;
+                if let Some(t) = &deleted {
+                    audit_service::record_delete(txn, &actor, "bill_template", t.id, t).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    )
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{additional_charge, bill, bill_discount};
+    use crate::money::Currency;
+    use crate::services::bill_service::BillWithChargesAndReading;
+    use chrono::Utc;
+
+    fn sample_data(description: &str) -> BillDocumentData {
+        let now = Utc::now().naive_utc();
+        BillDocumentData {
+            details: BillWithChargesAndReading {
+                bill: bill::Model {
+                    id: 4,
+                    reading_id: 1,
+                    tenant_id: 1,
+                    room_charges: 500000,
+                    electric_charges: 125000,
+                    discount_amount: 0,
+                    total_amount: 675000,
+                    currency: "PHP".into(),
+                    receipt_url: None,
+                    paid: false,
+                    amount_paid: None,
+                    status: bill::STATUS_ISSUED.into(),
+                    issued_at: Some(now),
+                    paid_at: None,
+                    created_at: now,
+                    updated_at: now,
+                    deleted_at: None,
+                    version: 1,
+                },
+                additional_charges: vec![additional_charge::Model {
+                    id: 1,
+                    bill_id: 4,
+                    amount: 50000,
+                    description: description.into(),
+                    charge_type_id: None,
+                    created_at: now,
+                    updated_at: now,
+                }],
+                discounts: vec![],
+                reading: None,
+                reading_photos: vec![],
+                attachments: vec![],
+                notes: vec![],
+            },
+            tenant: None,
+            room: None,
+            property: None,
+        }
+    }
+
+    #[test]
+    fn test_format_currency() {
+        let en = Locale::get("en");
+        assert_eq!(en.format_currency(Money::new(123456750, Currency::PHP)), "₱1,234,567.50");
+        assert_eq!(en.format_currency(Money::new(-1200, Currency::PHP)), "-₱12.00");
+        assert_eq!(en.format_currency(Money::new(1999, Currency::parse("USD").unwrap())), "$19.99");
+        assert_eq!(template_minor_units(&Value::from("12.5")).unwrap(), 1250);
+        assert_eq!(template_minor_units(&Value::from(12.345)).unwrap(), 1235);
+        assert_eq!(Locale::get("xx").code, "en");
+    }
+
+    #[test]
+    fn test_render_text_localized() {
+        let mut data = sample_data("Water");
+        let now = Utc::now().naive_utc();
+        data.details.discounts.push(bill_discount::Model {
+            id: 1,
+            bill_id: 4,
+            kind: bill_discount::KIND_PERCENT.into(),
+            value: 1000,
+            amount: 67500,
+            reason: "Promo".into(),
+            approved_by: "admin:root".into(),
+            position: 0,
+            created_at: now,
+            updated_at: now,
+        });
+        let out = render_with_template(
+            BUILTIN_TEXT,
+            RenderFormat::Text,
+            Locale::get("fil"),
+            &Branding::default(),
+            &data,
+        )
+        .unwrap();
+
+        assert!(out.contains("Kabuuan: ₱6,750.00"));
+        assert!(out.contains("Water: ₱500.00"));
+        assert!(out.contains("Diskwento 10.00% (Promo): -₱675.00"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_content() {
+        let data = sample_data("<script>x</script>");
+        let out = render_with_template(
+            BUILTIN_HTML,
+            RenderFormat::Html,
+            Locale::get("en"),
+            &Branding::default(),
+            &data,
+        )
+        .unwrap();
+
+        assert!(out.contains("&lt;script&gt;"));
+        assert!(!out.contains("<script>"));
+    }
+}
@@ -1,95 +1,164 @@
-use crate::repository::tenant_repo;
+use crate::repository::tenant_repo::{self, TenantFilter};
 use crate::entities::tenant;
-use sea_orm::{DatabaseConnection, DbErr};
+use crate::services::webhook_service::{self, EVENT_TENANT_MOVED_OUT};
+use crate::services::versioning::{self, WriteError};
+use crate::services::{audit_service, map_txn_err, occupancy_service};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use tracing::{debug, error, info, instrument, warn};
 
 
-/// Get all tenants
-pub async fn get_all_tenants(db: &DatabaseConnection) -> Result<Vec<tenant::Model>, DbErr> {
-    let result = tenant_repo::get_all(db).await;
+/// Get all tenants matching `filter`
+#[instrument(skip_all)]
+pub async fn get_all_tenants(db: &DatabaseConnection, filter: &TenantFilter) -> Result<Vec<tenant::Model>, DbErr> {
+    let result = tenant_repo::get_filtered(db, filter).await;
 
     if let Ok(list) = &result {
-        println!("✅ get_all_tenants: fetched {} tenants", list.len());
+        debug!(count = list.len(), "fetched tenants");
     } else if let Err(err) = &result {
-        eprintln!("❌ get_all_tenants: error fetching tenants: {:?}", err);
+        error!(error = ?err, "error fetching tenants");
     }
 
     result
 }
 
 /// Get tenant by ID 
+#[instrument(skip_all)]
 pub async fn get_tenant_by_id(db: &DatabaseConnection, id: i32) -> Result<Option<tenant::Model>, DbErr> {
     let result = tenant_repo::get_by_id(db, id).await;
 
     match &result {
-        Ok(Some(t)) => println!("✅ get_tenant_by_id: found tenant id={} name={}", t.id, t.name),
-        Ok(None) => println!("⚠️ get_tenant_by_id: tenant id={} not found", id),
-        Err(err) => eprintln!("❌ get_tenant_by_id: error fetching tenant id={}: {:?}", id, err),
+        Ok(Some(t)) => debug!(tenant_id = t.id, name = %t.name, "found tenant"),
+        Ok(None) => warn!(tenant_id = id, "tenant not found"),
+        Err(err) => error!(tenant_id = id, error = ?err, "error fetching tenant"),
     }
 
     result
 }
 
-/// Get tenant by name 
-pub async fn get_tenant_by_name(
+/// Get tenants by name, optionally within one property
+#[instrument(skip_all)]
+pub async fn get_tenants_by_name(
     db: &DatabaseConnection,
     name: &str,
-) -> Result<Option<tenant::Model>, DbErr> {
-    let result = tenant_repo::get_by_name(db, name).await;
+    property_id: Option<i32>,
+) -> Result<Vec<tenant::Model>, DbErr> {
+    let result = tenant_repo::get_all_by_name(db, name, property_id).await;
 
     match &result {
-        Ok(Some(t)) => println!("✅ get_tenant_by_name: found tenant id={} name={}", t.id, t.name),
-        Ok(None) => println!("⚠️ get_tenant_by_name: tenant with name '{}' not found", name),
-        Err(err) => eprintln!("❌ get_tenant_by_name: error fetching tenant by name '{}': {:?}", name, err),
+        Ok(list) if list.is_empty() => warn!(%name, "tenant not found"),
+        Ok(list) => debug!(%name, count = list.len(), "found tenants"),
+        Err(err) => error!(%name, error = ?err, "error fetching tenants by name"),
     }
 
     result
 }
 
+/// Insert a tenant, audit it and open their tenancy, inside the caller's transaction
+pub async fn insert_tenant<C>(conn: &C, actor: &str, item: tenant::ActiveModel) -> Result<tenant::Model, DbErr>
+where
+    C: ConnectionTrait,
+{
+    let created = tenant_repo::create(conn, item).await?;
+    audit_service::record_create(conn, actor, "tenant", created.id, &created).await?;
+    occupancy_service::sync_tenancy(conn, &created, chrono::Utc::now().naive_utc()).await?;
+    Ok(created)
+}
+
 /// Create tenant 
+#[instrument(skip_all)]
 pub async fn create_tenant(
     db: &DatabaseConnection,
+    actor: &str,
     item: tenant::ActiveModel,
 ) -> Result<tenant::Model, DbErr> {
-    let result = tenant_repo::create(db, item).await;
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, tenant::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                insert_tenant(txn, &actor, item).await
+            })
+        })
+        .await,
+    );
 
     if let Ok(ref t) = result {
-        println!("✅ create_tenant: created id={} name={}", t.id, t.name);
+        info!(tenant_id = t.id, name = %t.name, "created tenant");
     } else if let Err(ref err) = result {
-        eprintln!("❌ create_tenant: error creating tenant: {:?}", err);
+        error!(error = ?err, "error creating tenant");
     }
 
     result
 }
 
 
-/// Update tenant 
+/// Update tenant if it is still at `expected_version`
+#[instrument(skip_all)]
 pub async fn update_tenant(
     db: &DatabaseConnection,
+    actor: &str,
     id: i32,
+    expected_version: Option<i32>,
     item: tenant::ActiveModel,
-) -> Result<tenant::Model, DbErr> {
-    let result = tenant_repo::update(db, id, item).await;
+) -> Result<tenant::Model, WriteError> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, tenant::Model, WriteError>(|txn| {
+            Box::pin(async move {
+                let before = tenant_repo::get_by_id(txn, id).await?.ok_or(WriteError::NotFound)?;
+                versioning::check_version(expected_version, before.version)?;
+                let updated = tenant_repo::update(txn, id, before.version, item)
+                    .await
+                    .map_err(versioning::map_guarded_update_err)?;
+                audit_service::record_update(txn, &actor, "tenant", id, &before, &updated).await?;
+                occupancy_service::sync_tenancy(txn, &updated, chrono::Utc::now().naive_utc()).await?;
+                if before.is_active && !updated.is_active {
+                    webhook_service::enqueue(txn, EVENT_TENANT_MOVED_OUT, &updated).await?;
+                }
+                Ok(updated)
+            })
+        })
+        .await,
+    );
     if let Ok(t) = &result {
-        println!("✅ update_tenant: updated tenant id={} name={}", t.id, t.name);
+        info!(tenant_id = t.id, name = %t.name, "updated tenant");
     } else if let Err(err) = &result {
-        eprintln!("❌ update_tenant: error updating tenant id={}: {:?}", id, err);
+        error!(tenant_id = id, error = ?err, "error updating tenant");
     }
 
     result
 }
 
-/// Delete tenant 
+/// Delete tenant if it is still at `expected_version`
+#[instrument(skip_all)]
 pub async fn delete_tenant(
     db: &DatabaseConnection,
+    actor: &str,
     id: i32,
-) -> Result<Option<tenant::Model>, DbErr> {
-    let result = tenant_repo::delete(db, id).await;
+    expected_version: Option<i32>,
+) -> Result<Option<tenant::Model>, WriteError> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<tenant::Model>, WriteError>(|txn| {
+            Box::pin(async move {
+                if let Some(current) = tenant_repo::get_by_id(txn, id).await? {
+                    versioning::check_version(expected_version, current.version)?;
+                }
+                let deleted = tenant_repo::delete(txn, id).await?;
+                if let Some(t) = &deleted {
+                    audit_service::record_delete(txn, &actor, "tenant", id, t).await?;
+                    occupancy_service::sync_tenancy(txn, t, chrono::Utc::now().naive_utc()).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
 
     match &result {
-        Ok(Some(t)) => println!("✅ delete_tenant: deleted tenant id={} name={}", t.id, t.name),
-        Ok(None) => println!("⚠️ delete_tenant: tenant id={} not found", id),
-        Err(err) => eprintln!("❌ delete_tenant: error deleting tenant id={}: {:?}", id, err),
+        Ok(Some(t)) => info!(tenant_id = t.id, name = %t.name, "deleted tenant"),
+        Ok(None) => warn!(tenant_id = id, "tenant not found"),
+        Err(err) => error!(tenant_id = id, error = ?err, "error deleting tenant"),
     }
 
     result
-}
\ No newline at end of file
+}
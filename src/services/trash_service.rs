@@ -0,0 +1,184 @@
+use crate::config::TrashConfig;
+use crate::entities::{bill, electricity_reading, room, tenant};
+use crate::repository::{bill_repo, electricity_reading_repo, room_repo, tenant_repo};
+use crate::services::audit_service::{self, SYSTEM_ACTOR};
+use crate::services::{map_txn_err, occupancy_service};
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tracing::{error, info, instrument, warn};
+
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Entities that are soft deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashEntity {
+    Room,
+    Tenant,
+    Reading,
+    Bill,
+}
+
+impl TrashEntity {
+    /// Accepts the entity name or its plural, e.g. `bill` or `bills`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "room" | "rooms" => Some(TrashEntity::Room),
+            "tenant" | "tenants" => Some(TrashEntity::Tenant),
+            "reading" | "readings" | "electricity_reading" => Some(TrashEntity::Reading),
+            "bill" | "bills" => Some(TrashEntity::Bill),
+            _ => None,
+        }
+    }
+
+    /// Table name, as recorded in the audit log
+    pub fn table(&self) -> &'static str {
+        match self {
+            TrashEntity::Room => "room",
+            TrashEntity::Tenant => "tenant",
+            TrashEntity::Reading => "electricity_reading",
+            TrashEntity::Bill => "bill",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Trash {
+    pub rooms: Vec<room::Model>,
+    pub tenants: Vec<tenant::Model>,
+    pub readings: Vec<electricity_reading::Model>,
+    pub bills: Vec<bill::Model>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeSummary {
+    pub rooms: usize,
+    pub tenants: usize,
+    pub readings: usize,
+    pub bills: usize,
+}
+
+/// Rows in the trash are purged once they have been there `retention_days` days
+pub fn retention_cutoff(now: NaiveDateTime, retention_days: i64) -> NaiveDateTime {
+    now - Duration::days(retention_days)
+}
+
+/// GET everything currently in the trash
+pub async fn get_trash(db: &DatabaseConnection) -> Result<Trash, DbErr> {
+    Ok(Trash {
+        rooms: room_repo::get_deleted(db).await?,
+        tenants: tenant_repo::get_deleted(db).await?,
+        readings: electricity_reading_repo::get_deleted(db).await?,
+        bills: bill_repo::get_deleted(db).await?,
+    })
+}
+
+/// Take a row out of the trash, returning it as JSON
+#[instrument(skip_all)]
+pub async fn restore(
+    db: &DatabaseConnection,
+    actor: &str,
+    entity: TrashEntity,
+    id: i32,
+) -> Result<Option<Value>, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<Value>, DbErr>(|txn| {
+            Box::pin(async move {
+                let restored = match entity {
+                    TrashEntity::Room => match room_repo::get_deleted_by_id(txn, id).await? {
+                        Some(before) => {
+                            let after = room_repo::restore(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                            audit_service::record_restore(txn, &actor, entity.table(), id, &before, &after).await?;
+                            Some(json!(after))
+                        }
+                        None => None,
+                    },
+                    TrashEntity::Tenant => match tenant_repo::get_deleted_by_id(txn, id).await? {
+                        Some(before) => {
+                            let after = tenant_repo::restore(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                            audit_service::record_restore(txn, &actor, entity.table(), id, &before, &after).await?;
+                            occupancy_service::sync_tenancy(txn, &after, chrono::Utc::now().naive_utc()).await?;
+                            Some(json!(after))
+                        }
+                        None => None,
+                    },
+                    TrashEntity::Reading => match electricity_reading_repo::get_deleted_by_id(txn, id).await? {
+                        Some(before) => {
+                            let after = electricity_reading_repo::restore(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                            audit_service::record_restore(txn, &actor, entity.table(), id, &before, &after).await?;
+                            Some(json!(after))
+                        }
+                        None => None,
+                    },
+                    TrashEntity::Bill => match bill_repo::get_deleted_by_id(txn, id).await? {
+                        Some(before) => {
+                            let after = bill_repo::restore(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                            audit_service::record_restore(txn, &actor, entity.table(), id, &before, &after).await?;
+                            Some(json!(after))
+                        }
+                        None => None,
+                    },
+                };
+                Ok(restored)
+            })
+        })
+        .await,
+    );
+
+    match &result {
+        Ok(Some(_)) => info!(table = entity.table(), id, "restored from the trash"),
+        Ok(None) => warn!(table = entity.table(), id, "not in the trash"),
+        Err(err) => error!(table = entity.table(), id, error = ?err, "error restoring from the trash"),
+    }
+    result
+}
+
+/// Permanently remove rows deleted before `cutoff`.
+/// Bills go first so the readings, tenants and rooms they reference can follow in the same run.
+pub async fn purge(db: &DatabaseConnection, cutoff: NaiveDateTime) -> Result<PurgeSummary, DbErr> {
+    map_txn_err(
+        db.transaction::<_, PurgeSummary, DbErr>(|txn| {
+            Box::pin(async move {
+                let mut summary = PurgeSummary::default();
+
+                for b in bill_repo::purge_deleted_before(txn, cutoff).await? {
+                    audit_service::record_purge(txn, SYSTEM_ACTOR, "bill", b.id, &b).await?;
+                    summary.bills += 1;
+                }
+                for r in electricity_reading_repo::purge_deleted_before(txn, cutoff).await? {
+                    audit_service::record_purge(txn, SYSTEM_ACTOR, "electricity_reading", r.id, &r).await?;
+                    summary.readings += 1;
+                }
+                for t in tenant_repo::purge_deleted_before(txn, cutoff).await? {
+                    audit_service::record_purge(txn, SYSTEM_ACTOR, "tenant", t.id, &t).await?;
+                    summary.tenants += 1;
+                }
+                for r in room_repo::purge_deleted_before(txn, cutoff).await? {
+                    audit_service::record_purge(txn, SYSTEM_ACTOR, "room", r.id, &r).await?;
+                    summary.rooms += 1;
+                }
+
+                Ok(summary)
+            })
+        })
+        .await,
+    )
+}
+
+/// Purge the trash every hour
+pub fn spawn_purge_worker(db: DatabaseConnection, config: &TrashConfig) {
+    let retention_days = config.retention_days;
+    info!("trash purge worker started");
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match purge(&db, retention_cutoff(Utc::now().naive_utc(), retention_days)).await {
+                Ok(summary) => info!(?summary, "purged the trash"),
+                Err(err) => error!(error = ?err, "error purging the trash"),
+            }
+        }
+    });
+}
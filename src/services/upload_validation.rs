@@ -0,0 +1,276 @@
+use axum::body::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use std::io::Cursor;
+
+pub const DEFAULT_MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+pub const DEFAULT_MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+const JPEG_QUALITY: u8 = 90;
+
+/// File types accepted for upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Jpeg,
+    Png,
+    Webp,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCategory {
+    Image,
+    Document,
+}
+
+impl FileKind {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FileKind::Jpeg => "image/jpeg",
+            FileKind::Png => "image/png",
+            FileKind::Webp => "image/webp",
+            FileKind::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileKind::Jpeg => "jpg",
+            FileKind::Png => "png",
+            FileKind::Webp => "webp",
+            FileKind::Pdf => "pdf",
+        }
+    }
+
+    pub fn category(&self) -> FileCategory {
+        match self {
+            FileKind::Pdf => FileCategory::Document,
+            _ => FileCategory::Image,
+        }
+    }
+
+    fn matches_extension(&self, ext: &str) -> bool {
+        match self {
+            FileKind::Jpeg => matches!(ext, "jpg" | "jpeg"),
+            _ => ext == self.extension(),
+        }
+    }
+}
+
+/// Per-category size limits, from the `[upload]` config section
+#[derive(Debug, Clone, Copy)]
+pub struct UploadLimits {
+    pub max_image_bytes: usize,
+    pub max_document_bytes: usize,
+}
+
+impl UploadLimits {
+    pub fn limit_for(&self, category: FileCategory) -> usize {
+        match category {
+            FileCategory::Image => self.max_image_bytes,
+            FileCategory::Document => self.max_document_bytes,
+        }
+    }
+
+    /// Largest request body an upload route needs to accept
+    pub fn max_request_bytes(&self) -> usize {
+        self.max_image_bytes.max(self.max_document_bytes) + 1024 * 1024
+    }
+}
+
+#[derive(Debug)]
+pub enum UploadError {
+    UnsupportedType,
+    ExtensionMismatch { detected: FileKind, extension: String },
+    TooLarge { limit: usize },
+    InvalidImage(String),
+}
+
+impl UploadError {
+    pub fn message(&self) -> String {
+        match self {
+            UploadError::UnsupportedType => {
+                "Unsupported file type; allowed types are JPEG, PNG, WebP and PDF".into()
+            }
+            UploadError::ExtensionMismatch { detected, extension } => format!(
+                "File extension '.{}' does not match its content ({})",
+                extension,
+                detected.mime_type()
+            ),
+            UploadError::TooLarge { limit } => {
+                format!("File exceeds the maximum size of {} bytes", limit)
+            }
+            UploadError::InvalidImage(err) => format!("Image could not be processed: {}", err),
+        }
+    }
+}
+
+/// Upload that passed validation, with metadata stripped from images
+#[derive(Debug)]
+pub struct ValidatedUpload {
+    pub kind: FileKind,
+    pub bytes: Bytes,
+}
+
+/// Detect the file type from its magic bytes
+pub fn sniff(bytes: &[u8]) -> Option<FileKind> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(FileKind::Jpeg)
+    } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(FileKind::Png)
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(FileKind::Webp)
+    } else if bytes.starts_with(b"%PDF-") {
+        Some(FileKind::Pdf)
+    } else {
+        None
+    }
+}
+
+/// Validate an uploaded file against the allow-list and size limits
+pub async fn validate_upload(
+    file_name: &str,
+    bytes: Bytes,
+    limits: &UploadLimits,
+) -> Result<ValidatedUpload, UploadError> {
+    let kind = sniff(&bytes).ok_or(UploadError::UnsupportedType)?;
+
+    if let Some((_, ext)) = file_name.rsplit_once('.') {
+        let ext = ext.to_ascii_lowercase();
+        if !kind.matches_extension(&ext) {
+            return Err(UploadError::ExtensionMismatch { detected: kind, extension: ext });
+        }
+    }
+
+    let limit = limits.limit_for(kind.category());
+    if bytes.len() > limit {
+        return Err(UploadError::TooLarge { limit });
+    }
+
+    let bytes = match kind.category() {
+        // Decoding and re-encoding is CPU-bound; keep it off the async workers
+        FileCategory::Image => tokio::task::spawn_blocking(move || strip_metadata(kind, &bytes))
+            .await
+            .map_err(|e| UploadError::InvalidImage(e.to_string()))??,
+        FileCategory::Document => bytes,
+    };
+
+    Ok(ValidatedUpload { kind, bytes })
+}
+
+/// Re-encode an image, dropping EXIF and other metadata.
+/// The EXIF orientation is applied first so photos keep their rotation.
+fn strip_metadata(kind: FileKind, bytes: &[u8]) -> Result<Bytes, UploadError> {
+    let format = match kind {
+        FileKind::Jpeg => ImageFormat::Jpeg,
+        FileKind::Png => ImageFormat::Png,
+        FileKind::Webp => ImageFormat::WebP,
+        FileKind::Pdf => return Ok(Bytes::copy_from_slice(bytes)),
+    };
+    let invalid = |e: image::ImageError| UploadError::InvalidImage(e.to_string());
+
+    let mut decoder = ImageReader::with_format(Cursor::new(bytes), format)
+        .into_decoder()
+        .map_err(invalid)?;
+    let orientation = decoder.orientation().map_err(invalid)?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    img.apply_orientation(orientation);
+
+    let mut out = Vec::new();
+    match kind {
+        FileKind::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)),
+        FileKind::Png => img.write_with_encoder(PngEncoder::new(&mut out)),
+        _ => DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+    }
+    .map_err(invalid)?;
+
+    Ok(Bytes::from(out))
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> UploadLimits {
+        UploadLimits {
+            max_image_bytes: 1024 * 1024,
+            max_document_bytes: 64,
+        }
+    }
+
+    fn png_bytes() -> Vec<u8> {
+        let img = DynamicImage::new_rgb8(4, 4);
+        let mut out = Vec::new();
+        img.write_to(&mut Cursor::new(&mut out), ImageFormat::Png).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_sniff_allowed_types() {
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(FileKind::Jpeg));
+        assert_eq!(sniff(&png_bytes()), Some(FileKind::Png));
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 "), Some(FileKind::Webp));
+        assert_eq!(sniff(b"%PDF-1.7\n"), Some(FileKind::Pdf));
+        assert_eq!(sniff(b"MZ\x90\x00"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_mismatched_extension() {
+        let result = validate_upload("receipt.pdf", Bytes::from(png_bytes()), &limits()).await;
+        assert!(matches!(result, Err(UploadError::ExtensionMismatch { detected: FileKind::Png, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_document() {
+        let pdf = Bytes::from([b"%PDF-1.7\n".as_slice(), &[0u8; 128]].concat());
+        let result = validate_upload("receipt.pdf", pdf, &limits()).await;
+        assert!(matches!(result, Err(UploadError::TooLarge { limit: 64 })));
+    }
+
+    /// A JPEG whose EXIF block carries an orientation and a GPS latitude
+    fn jpeg_with_exif() -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        DynamicImage::new_rgb8(8, 8).write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg).unwrap();
+
+        // Little-endian TIFF: IFD0 (orientation, GPS pointer) at 8, GPS IFD (latitude ref) at 38
+        let mut tiff = b"II\x2A\x00\x08\x00\x00\x00".to_vec();
+        tiff.extend_from_slice(&[0x02, 0x00]);
+        tiff.extend_from_slice(&[0x12, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]);
+        tiff.extend_from_slice(&[0x25, 0x88, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 0x26, 0x00, 0x00, 0x00]);
+        tiff.extend_from_slice(&[0x00; 4]);
+        tiff.extend_from_slice(&[0x01, 0x00]);
+        tiff.extend_from_slice(&[0x01, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00, b'N', 0x00, 0x00, 0x00]);
+        tiff.extend_from_slice(&[0x00; 4]);
+
+        let payload = [b"Exif\0\0".as_slice(), &tiff].concat();
+        let len = (payload.len() + 2) as u16;
+        let app1 = [&[0xFF, 0xE1], len.to_be_bytes().as_slice(), &payload].concat();
+        // Right after SOI
+        jpeg.splice(2..2, app1);
+        jpeg
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[tokio::test]
+    async fn test_reencodes_images() {
+        let photo = jpeg_with_exif();
+        assert!(contains(&photo, b"Exif\0\0"));
+
+        let validated = validate_upload("meter.JPG", Bytes::from(photo), &limits()).await.unwrap();
+        assert_eq!(validated.kind, FileKind::Jpeg);
+        assert_eq!(sniff(&validated.bytes), Some(FileKind::Jpeg));
+        assert!(!contains(&validated.bytes, b"Exif\0\0"));
+        // No APP1 segment at all
+        assert!(!contains(&validated.bytes, &[0xFF, 0xE1]));
+
+        let validated = validate_upload("receipt.PNG", Bytes::from(png_bytes()), &limits()).await.unwrap();
+        assert_eq!(sniff(&validated.bytes), Some(FileKind::Png));
+    }
+}
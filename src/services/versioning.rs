@@ -0,0 +1,79 @@
+//! Optimistic concurrency for rooms, tenants and bills.
+//!
+//! Each row carries a `version` that is bumped on every update and served as the
+//! `ETag`. Writers send it back in `If-Match`; a stale version is rejected instead of
+//! silently overwriting someone else's change.
+
+use crate::money::MoneyError;
+use sea_orm::DbErr;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum WriteError {
+    NotFound,
+    /// The row changed since the caller read it
+    VersionMismatch,
+    /// The row's state does not allow this change, e.g. editing an issued bill
+    InvalidState(String),
+    /// The request itself is malformed
+    Invalid(String),
+    Db(DbErr),
+}
+
+impl From<DbErr> for WriteError {
+    fn from(err: DbErr) -> Self {
+        WriteError::Db(err)
+    }
+}
+
+/// Overflowing or mixed-currency amounts are a bad request, not a server error
+impl From<MoneyError> for WriteError {
+    fn from(err: MoneyError) -> Self {
+        WriteError::Invalid(err.to_string())
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::NotFound => write!(f, "record not found"),
+            WriteError::VersionMismatch => write!(f, "version mismatch"),
+            WriteError::InvalidState(msg) | WriteError::Invalid(msg) => write!(f, "{}", msg),
+            WriteError::Db(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+/// `expected` is `None` for unconditional writes (`If-Match: *`)
+pub fn check_version(expected: Option<i32>, current: i32) -> Result<(), WriteError> {
+    match expected {
+        Some(v) if v != current => Err(WriteError::VersionMismatch),
+        _ => Ok(()),
+    }
+}
+
+/// A guarded update that touched no row lost the race to a concurrent writer
+pub fn map_guarded_update_err(err: DbErr) -> WriteError {
+    match err {
+        DbErr::RecordNotUpdated => WriteError::VersionMismatch,
+        err => WriteError::Db(err),
+    }
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert!(check_version(Some(3), 3).is_ok());
+        assert!(check_version(None, 3).is_ok());
+        assert!(matches!(
+            check_version(Some(2), 3),
+            Err(WriteError::VersionMismatch)
+        ));
+    }
+}
@@ -0,0 +1,372 @@
+use crate::config::WebhookConfig;
+use crate::entities::{webhook_delivery, webhook_endpoint};
+use crate::middleware::logging::url_origin;
+use crate::repository::{webhook_delivery_repo, webhook_endpoint_repo};
+use crate::services::notification_service::{self, EVENT_BILL_CREATED, EVENT_BILL_PAID};
+use crate::services::{audit_service, map_txn_err};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sea_orm::{ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbErr, TransactionTrait};
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+pub const EVENT_BILL_UPDATED: &str = "bill.updated";
+pub const EVENT_BILL_ISSUED: &str = "bill.issued";
+pub const EVENT_BILL_VOIDED: &str = "bill.voided";
+pub const EVENT_READING_CREATED: &str = "reading.created";
+pub const EVENT_TENANT_MOVED_OUT: &str = "tenant.moved_out";
+
+/// Events an endpoint can subscribe to; `*` subscribes to all of them
+pub const ALL_EVENTS: &[&str] = &[
+    EVENT_BILL_CREATED,
+    EVENT_BILL_UPDATED,
+    EVENT_BILL_ISSUED,
+    EVENT_BILL_PAID,
+    EVENT_BILL_VOIDED,
+    EVENT_READING_CREATED,
+    EVENT_TENANT_MOVED_OUT,
+];
+
+const MAX_ATTEMPTS: i32 = 10;
+const BATCH_SIZE: u64 = 20;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a claimed delivery is hidden from other workers; well beyond a batch of timeouts
+const CLAIM_LEASE_MINUTES: i64 = 10;
+
+/// Whether an event name (or `*`) is valid in a subscription
+pub fn is_known_event(event: &str) -> bool {
+    event == "*" || ALL_EVENTS.contains(&event)
+}
+
+/// Random secret handed out when an endpoint is registered without one
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// `X-M18-Signature` value: HMAC-SHA256 of `{timestamp}.{body}` keyed with the endpoint secret
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Queue a delivery to every endpoint subscribed to `event`; call inside the transaction that makes the change
+pub async fn enqueue<C, T>(conn: &C, event: &str, data: &T) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    let endpoints = webhook_endpoint_repo::get_active_for_event(conn, event).await?;
+    if endpoints.is_empty() {
+        return Ok(());
+    }
+
+    let payload = json!({
+        "event": event,
+        "occurred_at": Utc::now().naive_utc(),
+        "data": data,
+    });
+
+    for endpoint in endpoints {
+        let item = webhook_delivery::ActiveModel {
+            endpoint_id: Set(endpoint.id),
+            event: Set(event.to_string()),
+            payload: Set(payload.clone()),
+            next_attempt_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        let delivery = webhook_delivery_repo::create(conn, item).await?;
+        info!(delivery_id = delivery.id, endpoint_id = endpoint.id, %event, "queued webhook");
+    }
+
+    Ok(())
+}
+
+/// POST one delivery, returning the response status code
+async fn deliver(
+    client: &reqwest::Client,
+    endpoint: &webhook_endpoint::Model,
+    delivery: &webhook_delivery::Model,
+) -> Result<i32, (Option<i32>, String)> {
+    let body = delivery.payload.to_string();
+    let timestamp = Utc::now().timestamp();
+
+    let res = client
+        .post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .header("X-M18-Event", &delivery.event)
+        .header("X-M18-Delivery", delivery.id.to_string())
+        .header("X-M18-Timestamp", timestamp.to_string())
+        .header("X-M18-Signature", sign(&endpoint.secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, e.without_url().to_string()))?;
+
+    let status = res.status().as_u16() as i32;
+    if res.status().is_success() {
+        Ok(status)
+    } else {
+        Err((Some(status), format!("endpoint returned {}", status)))
+    }
+}
+
+/// Deliver due webhook calls, rescheduling failures with backoff
+#[instrument(skip_all)]
+pub async fn process_deliveries(db: &DatabaseConnection, client: &reqwest::Client) -> Result<usize, DbErr> {
+    let now = Utc::now().naive_utc();
+    let mut delivered = 0;
+
+    let lease_until = now + chrono::Duration::minutes(CLAIM_LEASE_MINUTES);
+
+    for delivery in webhook_delivery_repo::claim_due(db, now, lease_until, BATCH_SIZE).await? {
+        let endpoint = webhook_endpoint_repo::get_by_id(db, delivery.endpoint_id).await?;
+        let result = match &endpoint {
+            Some(endpoint) if endpoint.is_active => deliver(client, endpoint, &delivery).await,
+            _ => {
+                let retry_at = notification_service::next_retry_at(now, delivery.attempts);
+                webhook_delivery_repo::mark_failed(db, delivery, None, "endpoint is disabled".into(), retry_at, true)
+                    .await?;
+                continue;
+            }
+        };
+
+        match result {
+            Ok(status) => {
+                let id = delivery.id;
+                webhook_delivery_repo::mark_delivered(db, delivery, status).await?;
+                info!(delivery_id = id, "delivered webhook");
+                delivered += 1;
+            }
+            Err((status, err)) => {
+                let give_up = delivery.attempts + 1 >= MAX_ATTEMPTS;
+                error!(delivery_id = delivery.id, attempt = delivery.attempts + 1, error = %err, "webhook attempt failed");
+                let retry_at = notification_service::next_retry_at(now, delivery.attempts);
+                webhook_delivery_repo::mark_failed(db, delivery, status, err, retry_at, give_up).await?;
+            }
+        }
+    }
+
+    Ok(delivered)
+}
+
+/// Queue a fresh copy of a past delivery; the original stays in the log
+#[instrument(skip_all)]
+pub async fn replay_delivery(db: &DatabaseConnection, id: i32) -> Result<Option<webhook_delivery::Model>, DbErr> {
+    let Some(original) = webhook_delivery_repo::get_by_id(db, id).await? else {
+        warn!(delivery_id = id, "delivery not found");
+        return Ok(None);
+    };
+
+    let item = webhook_delivery::ActiveModel {
+        endpoint_id: Set(original.endpoint_id),
+        event: Set(original.event),
+        payload: Set(original.payload),
+        next_attempt_at: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    let result = webhook_delivery_repo::create(db, item).await;
+    match &result {
+        Ok(d) => info!(delivery_id = id, replay_id = d.id, "replayed delivery"),
+        Err(err) => error!(delivery_id = id, error = ?err, "error replaying delivery"),
+    }
+
+    result.map(Some)
+}
+
+/// GET all webhook endpoints
+pub async fn get_all_endpoints(db: &DatabaseConnection) -> Result<Vec<webhook_endpoint::Model>, DbErr> {
+    webhook_endpoint_repo::get_all(db).await
+}
+
+/// CREATE a webhook endpoint
+#[instrument(skip_all)]
+pub async fn create_endpoint(
+    db: &DatabaseConnection,
+    actor: &str,
+    item: webhook_endpoint::ActiveModel,
+) -> Result<webhook_endpoint::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, webhook_endpoint::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let created = webhook_endpoint_repo::create(txn, item).await?;
+                audit_service::record_create(txn, &actor, "webhook_endpoint", created.id, &created).await?;
+                Ok(created)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(e) => info!(webhook_id = e.id, origin = url_origin(&e.url), "created webhook"),
+        Err(err) => error!(error = ?err, "error creating webhook"),
+    }
+    result
+}
+
+/// UPDATE a webhook endpoint
+#[instrument(skip_all)]
+pub async fn update_endpoint(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+    item: webhook_endpoint::ActiveModel,
+) -> Result<webhook_endpoint::Model, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, webhook_endpoint::Model, DbErr>(|txn| {
+            Box::pin(async move {
+                let before = webhook_endpoint_repo::get_by_id(txn, id).await?.ok_or(DbErr::RecordNotUpdated)?;
+                let updated = webhook_endpoint_repo::update(txn, id, item).await?;
+                audit_service::record_update(txn, &actor, "webhook_endpoint", id, &before, &updated).await?;
+                Ok(updated)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(e) => info!(webhook_id = e.id, "updated webhook"),
+        Err(err) => error!(webhook_id = id, error = ?err, "error updating webhook"),
+    }
+    result
+}
+
+/// DELETE a webhook endpoint
+#[instrument(skip_all)]
+pub async fn delete_endpoint(
+    db: &DatabaseConnection,
+    actor: &str,
+    id: i32,
+) -> Result<Option<webhook_endpoint::Model>, DbErr> {
+    let actor = actor.to_string();
+    let result = map_txn_err(
+        db.transaction::<_, Option<webhook_endpoint::Model>, DbErr>(|txn| {
+            Box::pin(async move {
+                let deleted = webhook_endpoint_repo::delete(txn, id).await?;
+                if let Some(e) = &deleted {
+                    audit_service::record_delete(txn, &actor, "webhook_endpoint", id, e).await?;
+                }
+                Ok(deleted)
+            })
+        })
+        .await,
+    );
+    match &result {
+        Ok(Some(e)) => info!(webhook_id = e.id, "deleted webhook"),
+        Ok(None) => warn!(webhook_id = id, "webhook not found"),
+        Err(err) => error!(webhook_id = id, error = ?err, "error deleting webhook"),
+    }
+    result
+}
+
+/// GET the delivery log of an endpoint, newest first
+pub async fn get_deliveries(
+    db: &DatabaseConnection,
+    endpoint_id: i32,
+    limit: u64,
+) -> Result<Vec<webhook_delivery::Model>, DbErr> {
+    webhook_delivery_repo::get_all_by_endpoint(db, endpoint_id, limit).await
+}
+
+/// Deliver queued webhooks every `poll_secs` seconds
+pub fn spawn_worker(db: DatabaseConnection, config: &WebhookConfig) {
+    let client = match reqwest::Client::builder().timeout(HTTP_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            error!(error = %err, "webhook HTTP client could not be created");
+            return;
+        }
+    };
+    let secs = config.poll_secs;
+
+    info!("webhook worker started");
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(secs));
+        loop {
+            ticker.tick().await;
+            if let Err(err) = process_deliveries(&db, &client).await {
+                error!(error = ?err, "error processing webhook deliveries");
+            }
+        }
+    });
+}
+
+// ---------------------- INLINE TESTS ----------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::webhook_delivery::{STATUS_DELIVERED, STATUS_PENDING};
+    use crate::test_utils::{get_test_db, reset_table};
+    use axum::{Router, http::HeaderMap, routing::post};
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("whsec_test", 1700000000, r#"{"event":"bill.paid"}"#),
+            "sha256=3db08993b3ed2e9027318cdb8e319b3c2ed56e11adde342e7af803bacc8a5309"
+        );
+        assert!(is_known_event("tenant.moved_out"));
+        assert!(!is_known_event("bill.deleted"));
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_and_deliver_signed_payload() {
+        let db = get_test_db().await;
+        reset_table(&db, "webhook_endpoint").await;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| async move {
+                let header = |name: &str| headers.get(name).unwrap().to_str().unwrap().to_string();
+                tx.send((header("x-m18-timestamp"), header("x-m18-signature"), body)).unwrap();
+                "ok"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        webhook_endpoint_repo::create(
+            &db,
+            webhook_endpoint::ActiveModel {
+                url: Set(format!("http://{}/hook", addr)),
+                secret: Set("whsec_test".into()),
+                events: Set(EVENT_READING_CREATED.into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        enqueue(&db, EVENT_BILL_PAID, &json!({ "id": 1 })).await.unwrap();
+        enqueue(&db, EVENT_READING_CREATED, &json!({ "id": 2 })).await.unwrap();
+
+        let delivered = process_deliveries(&db, &reqwest::Client::new()).await.unwrap();
+        assert_eq!(delivered, 1);
+
+        let (timestamp, signature, body) = rx.recv().await.unwrap();
+        assert_eq!(signature, sign("whsec_test", timestamp.parse().unwrap(), &body));
+        let payload: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["event"], EVENT_READING_CREATED);
+        assert_eq!(payload["data"]["id"], 2);
+
+        let endpoint_id = webhook_endpoint_repo::get_all(&db).await.unwrap()[0].id;
+        let log = get_deliveries(&db, endpoint_id, 10).await.unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].status, STATUS_DELIVERED);
+
+        let replayed = replay_delivery(&db, log[0].id).await.unwrap().unwrap();
+        assert_eq!(replayed.status, STATUS_PENDING);
+        assert_eq!(replayed.payload, log[0].payload);
+    }
+}
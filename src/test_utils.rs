@@ -1,4 +1,7 @@
-use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Statement};
+use crate::config::Config;
+use crate::entities::{electricity_reading, room, tenant};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseConnection, Set, Statement};
 use std::env;
 
 #[cfg(test)]
@@ -25,3 +28,80 @@ pub async fn reset_table(db: &DatabaseConnection, table_name: &str) {
         .await
         .unwrap();
 }
+
+/// Empty the rooms, tenants, readings and bills; tables referencing them go too
+#[cfg(test)]
+pub async fn reset_rooms(db: &DatabaseConnection) {
+    let sql = "TRUNCATE TABLE bill_note, bill, electricity_reading, tenant, room RESTART IDENTITY CASCADE;";
+    db.execute(Statement::from_string(db.get_database_backend(), sql.to_string()))
+        .await
+        .unwrap();
+}
+
+/// A room in the seeded default property
+#[cfg(test)]
+pub async fn add_room(db: &DatabaseConnection, name: &str) -> room::Model {
+    room::ActiveModel { property_id: Set(1), name: Set(name.into()), rent: Set(1000), ..Default::default() }
+        .insert(db)
+        .await
+        .unwrap()
+}
+
+/// An active tenant who joined today
+#[cfg(test)]
+pub async fn add_tenant(db: &DatabaseConnection, room: &room::Model, name: &str) -> tenant::Model {
+    tenant::ActiveModel {
+        name: Set(name.into()),
+        room_id: Set(room.id),
+        is_active: Set(true),
+        join_date: Set(Utc::now().naive_utc()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// A reading for the tenant's current room
+#[cfg(test)]
+pub async fn add_reading(db: &DatabaseConnection, tenant: &tenant::Model, prev: i32, curr: i32) -> electricity_reading::Model {
+    electricity_reading::ActiveModel {
+        tenant_id: Set(tenant.id),
+        room_id: Set(tenant.room_id),
+        prev_reading: Set(prev),
+        curr_reading: Set(curr),
+        consumption: Set(curr - prev),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// Start from empty tables with one room, its tenant and a 50 kWh reading, enough to bill
+#[cfg(test)]
+pub async fn seed_reading(db: &DatabaseConnection) -> (room::Model, tenant::Model, electricity_reading::Model) {
+    reset_rooms(db).await;
+    let room = add_room(db, "Test Room").await;
+    let tenant = add_tenant(db, &room, "Test Tenant").await;
+    let reading = add_reading(db, &tenant, 100, 150).await;
+    (room, tenant, reading)
+}
+
+/// A valid config that does not depend on the environment
+#[cfg(test)]
+pub fn test_config() -> Config {
+    Config::from_sources(None, |key| {
+        let value = match key {
+            "DATABASE_URL" => "postgres://localhost/m18_test",
+            "JWT_SECRET" => "test-secret",
+            "R2_ENDPOINT" => "https://r2.example.com",
+            "R2_BUCKET_NAME" => "test",
+            "R2_ACCESS_KEY_ID" => "key",
+            "R2_SECRET_ACCESS_KEY" => "secret",
+            _ => return None,
+        };
+        Some(value.to_string())
+    })
+    .unwrap()
+}